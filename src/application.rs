@@ -1,75 +1,94 @@
 use crate::channel::consumer::handler::TransactionConsumer;
 use crate::channel::consumer::ConsumerHandler;
-use crate::channel::Channel;
+use crate::channel::lease::LeaseKeeper;
+use crate::channel::webhook::WebhookNotifier;
+use crate::channel::{Channel, SenderSet};
+use crate::configs::remote::RemoteConfigRefresher;
 use crate::configs::server::ServerConfig;
+use crate::configs::validation::{validate_sqlite_db_path, ConfigErrors};
 use crate::context::Context;
 use crate::database::{init_sqlite_database, Database};
+use crate::handler::api_key::flush_usage_periodically;
+use crate::logger::init_logger;
+use crate::service::admin::handler::{
+    add_account, cancel_transaction, disable_account, enable_account, fail_transaction, list_accounts,
+    list_transactions, requeue_transaction, retire_account, update_account_tokens,
+};
+use crate::service::auth::authenticate;
+use crate::service::cors::create_cors;
 use crate::service::handshake;
+use crate::service::rate_limit::{rate_limit, RateLimiter};
+use crate::service::tls::{create_tls_config, require_admin_client_certificate, tls_on_connect};
 use crate::service::v1::handler::{chain_status, job_status, transact_v1};
-use crate::service::v2::handler::{info, transact, transaction_status};
-use actix_cors::Cors;
-use actix_web::middleware::Logger;
+use crate::service::v2::handler::{
+    chains, info, transact, transaction_status, transaction_status_batch, transaction_status_stream,
+    transactions_by_hash, transactions_by_serial_number, transactions_by_signature,
+};
+use crate::service::v2::ws::ws;
+use actix_web::middleware::{from_fn, Logger};
 use actix_web::web::{scope, Data};
-use actix_web::{http, App, HttpServer};
+use actix_web::{App, HttpServer};
 use anyhow::Result;
-use log::{info, LevelFilter};
+use log::{info, warn, LevelFilter};
 use mystiko_storage::{SqlStatementFormatter, StatementFormatter, Storage};
 use mystiko_storage_sqlite::SqliteStorage;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct ApplicationOptions<F: StatementFormatter, S: Storage> {
     pub database: Arc<Database<F, S>>,
     pub context: Arc<Context>,
     pub channel: Channel,
+    // path the server config was loaded from, reloaded on SIGHUP
+    pub server_config_path: Option<String>,
 }
 
 impl<F, S> ApplicationOptions<F, S>
 where
-    F: StatementFormatter,
-    S: Storage,
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
 {
-    pub async fn from_server_config(
-        server_config: Arc<ServerConfig>,
-    ) -> Result<ApplicationOptions<SqlStatementFormatter, SqliteStorage>> {
-        // init sqlite db connection
-        let database = Arc::new(init_sqlite_database(server_config.settings.sqlite_db_path.clone()).await?);
+    pub async fn from_database(server_config: Arc<ServerConfig>, database: Arc<Database<F, S>>) -> Result<Self> {
         // create context
-        let context = Arc::new(Context::new(server_config.clone(), database.clone()).await?);
+        let context = Arc::new(Context::new(server_config, database.clone()).await?);
         // create channel
         let channel = Channel::<TransactionConsumer>::new(context.clone()).await?;
         Ok(ApplicationOptions {
             database,
             context,
             channel,
+            server_config_path: None,
         })
     }
 }
 
+impl ApplicationOptions<SqlStatementFormatter, SqliteStorage> {
+    pub async fn from_server_config(server_config: Arc<ServerConfig>) -> Result<Self> {
+        // check the db path before sqlite turns it into a less helpful error
+        if let Some(path) = &server_config.settings.sqlite_db_path {
+            validate_sqlite_db_path(path).map_err(|error| ConfigErrors(vec![error]))?;
+        }
+        let database = Arc::new(init_sqlite_database(server_config.settings.sqlite_db_path.clone()).await?);
+        Self::from_database(server_config, database).await
+    }
+}
+
 pub async fn run_application<F, S>(options: ApplicationOptions<F, S>) -> Result<()>
 where
     F: StatementFormatter,
     S: Storage,
 {
-    let server_config = options.context.server_config.clone();
-
-    // try init logger
-    let _ = env_logger::builder()
-        .filter_module(
-            "mystiko_relayer",
-            LevelFilter::from_str(&server_config.settings.log_level)?,
-        )
-        .filter_module(
-            "mystiko_server_utils",
-            LevelFilter::from_str(&server_config.settings.log_level)?,
-        )
-        .try_init();
+    let server_config = options.context.server_config.load();
+
+    // try init logger, the level can be changed by a config reload
+    init_logger(LevelFilter::from_str(&server_config.settings.log_level)?);
 
     info!("load server config successful");
 
     let channel = options.channel;
     let consumers = channel.consumers;
-    let senders = Arc::new(channel.senders);
+    let senders = Arc::new(SenderSet::new(channel.senders));
 
     // spawn consumer
     for mut consumer in consumers {
@@ -78,29 +97,104 @@ where
         });
     }
 
+    // coordinate with the other instances sharing the database, one of them drives each account
+    let lease_keeper = if server_config.lease.enabled {
+        let keeper = Arc::new(LeaseKeeper::new(
+            options.context.clone(),
+            senders.clone(),
+            &server_config.lease,
+        ));
+        tokio::spawn(keeper.clone().run());
+        Some(keeper)
+    } else {
+        None
+    };
+
+    // post status changes of transactions to the configured webhooks
+    if server_config.webhook.enabled {
+        let notifier = Arc::new(WebhookNotifier::new(
+            server_config.webhook.clone(),
+            options.context.transaction_handler.clone(),
+            options.context.webhook_handler.clone(),
+        )?);
+        tokio::spawn(notifier.run(options.context.status_bus.subscribe_all()));
+    }
+
+    // write the api key usage counters periodically
+    if server_config.auth.enabled {
+        tokio::spawn(flush_usage_periodically(
+            options.context.api_key_handler.clone(),
+            Duration::from_millis(server_config.auth.usage_flush_interval_ms),
+        ));
+    }
+
+    // keep the cached gas and token prices fresh
+    let price_refresh_interval = Duration::from_millis(server_config.price_cache.refresh_interval_ms);
+    if server_config.price_cache.gas_price_ttl_ms > 0 {
+        tokio::spawn(options.context.gas_price_cache.clone().run(price_refresh_interval));
+    }
+    if let Some(token_price_cache) = options.context.token_price_cache.clone() {
+        tokio::spawn(token_price_cache.run(price_refresh_interval));
+    }
+
+    // refresh relayer and mystiko configs periodically
+    if let Some(interval_ms) = server_config.options.config_refresh_interval_ms {
+        info!("refresh relayer and mystiko configs every {} ms", interval_ms);
+        let refresher = RemoteConfigRefresher::new(options.context.clone(), Duration::from_millis(interval_ms));
+        tokio::spawn(refresher.run());
+    }
+
+    // reload server config on SIGHUP
+    #[cfg(unix)]
+    {
+        let reloader = crate::configs::reload::ConfigReloader::new(
+            options.server_config_path,
+            options.context.clone(),
+            senders.clone(),
+        );
+        tokio::spawn(reloader.watch());
+    }
+
     // run http server
     let host = server_config.settings.host.as_str();
     let port = &server_config.settings.port;
     let api_version = &server_config.settings.api_version;
 
     info!(
-        "Application server start at {}:{}, available api version: {:?}",
-        host, port, api_version
+        "Application server start at {}:{}, tls: {}, available api version: {:?}",
+        host,
+        port,
+        server_config.settings.tls.is_some(),
+        api_version
     );
 
-    HttpServer::new(move || {
-        // allow CORS request
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allowed_methods(vec!["GET", "POST"])
-            .allowed_header(http::header::CONTENT_TYPE);
+    // the resolver keeps serving the latest certificate files, checked periodically
+    let tls_config = match &server_config.settings.tls {
+        Some(tls) => {
+            let (tls_config, resolver) = create_tls_config(tls)?;
+            tokio::spawn(resolver.watch());
+            Some(tls_config)
+        }
+        None => None,
+    };
+
+    // shared by every worker, the buckets must see all requests
+    let rate_limiter = Arc::new(RateLimiter::new(server_config.rate_limit.clone()));
+    let cors_config = server_config.settings.cors.clone();
+
+    let server = HttpServer::new(move || {
+        let cors = create_cors(&cors_config);
         // create app
         App::new()
+            .wrap(from_fn(require_admin_client_certificate))
+            .wrap(from_fn(rate_limit))
+            .wrap(from_fn(authenticate))
             .wrap(cors)
             .wrap(Logger::default())
             .wrap(Logger::new("%a %{User-Agent}i"))
             .app_data(Data::new(options.context.clone()))
             .app_data(Data::new(senders.clone()))
+            .app_data(Data::new(rate_limiter.clone()))
             .service(handshake)
             // v1
             .service(chain_status)
@@ -108,14 +202,45 @@ where
             .service(transact_v1)
             .service(
                 scope("/api/v2")
+                    .service(chains)
                     .service(info)
                     .service(transact)
-                    .service(transaction_status),
+                    .service(transaction_status)
+                    .service(transaction_status_batch)
+                    .service(transaction_status_stream)
+                    .service(transactions_by_hash)
+                    .service(transactions_by_signature)
+                    .service(transactions_by_serial_number)
+                    .service(ws),
+            )
+            .service(
+                scope("/api/admin")
+                    .service(list_transactions)
+                    .service(requeue_transaction)
+                    .service(fail_transaction)
+                    .service(cancel_transaction)
+                    .service(list_accounts)
+                    .service(add_account)
+                    .service(enable_account)
+                    .service(disable_account)
+                    .service(update_account_tokens)
+                    .service(retire_account),
             )
     })
-    .bind((host, *port))?
-    .run()
-    .await?;
+    .on_connect(tls_on_connect);
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_021((host, *port), tls_config)?,
+        None => server.bind((host, *port))?,
+    };
+    server.run().await?;
 
+    if let Err(error) = options.context.api_key_handler.flush_usage().await {
+        warn!("flush api key usage got error: {:?}", error);
+    }
+
+    // hand the accounts over right away instead of waiting for the leases to expire
+    if let Some(keeper) = lease_keeper {
+        keeper.release_all().await;
+    }
     Ok(())
 }
@@ -0,0 +1,56 @@
+use crate::signer::AccountSigner;
+use anyhow::{bail, Result};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{TransactionRequest, TxHash, U256};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use log::info;
+use mystiko_ethers::Provider;
+use std::cmp::max;
+
+const REPLACEMENT_GAS_LIMIT: u64 = 21000;
+// nodes require at least a 10% bump to accept a replacement
+const REPLACEMENT_GAS_PRICE_PERCENT: u64 = 125;
+
+pub async fn send_cancel_transaction(
+    provider: &Provider,
+    wallets: &[AccountSigner],
+    tx_hash: TxHash,
+) -> Result<TxHash> {
+    let pending = match provider.get_transaction(tx_hash).await? {
+        Some(pending) => pending,
+        None => bail!("transaction {:?} not found on chain", tx_hash),
+    };
+    if pending.block_number.is_some() {
+        bail!("transaction {:?} has already been mined", tx_hash);
+    }
+    let wallet = match wallets.iter().find(|wallet| wallet.address() == pending.from) {
+        Some(wallet) => wallet,
+        None => bail!("no account found for transaction sender {:?}", pending.from),
+    };
+
+    let original_gas_price = pending.max_fee_per_gas.or(pending.gas_price).unwrap_or_default();
+    let gas_price = max(
+        original_gas_price * REPLACEMENT_GAS_PRICE_PERCENT / 100,
+        provider.get_gas_price().await?,
+    );
+
+    // zero-value self transfer with the same nonce
+    let tx: TypedTransaction = TransactionRequest::new()
+        .from(wallet.address())
+        .to(wallet.address())
+        .value(U256::zero())
+        .nonce(pending.nonce)
+        .gas(REPLACEMENT_GAS_LIMIT)
+        .gas_price(gas_price)
+        .chain_id(wallet.chain_id())
+        .into();
+    let signature = wallet.sign_transaction(&tx).await?;
+    let replacement = provider.send_raw_transaction(tx.rlp_signed(&signature)).await?;
+    let replacement_hash = replacement.tx_hash();
+    info!(
+        "sent replacement transaction(hash = {:?}, nonce = {}, gas_price = {}) for {:?}",
+        replacement_hash, pending.nonce, gas_price, tx_hash
+    );
+    Ok(replacement_hash)
+}
@@ -1,12 +1,15 @@
 use crate::channel::consumer::ConsumerHandler;
+use crate::channel::lease::LeaseState;
 use crate::database::transaction::Transaction as DocumentTransaction;
 use crate::error::RelayerServerError;
+use crate::handler::quote::QuoteHandlerRef;
 use crate::handler::transaction::{TransactionHandler, UpdateTransactionOptions};
-use anyhow::{bail, Result};
+use crate::price_cache::GasPriceCache;
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use ethers_core::abi::{AbiEncode, Address};
 use ethers_core::types::{Bytes, TxHash, U256};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use mystiko_abi::commitment_pool::{CommitmentPool, TransactRequest};
 use mystiko_ethers::{JsonRpcClientWrapper, Provider, ProviderWrapper, Providers};
 use mystiko_relayer_types::{TransactRequestData, TransactStatus};
@@ -19,12 +22,17 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::Receiver;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use tokio::time::sleep;
 
 const MAX_GAS_PRICE_MULTIPLIER_LEGACY: u64 = 1;
 const MAX_GAS_PRICE_MULTIPLIER_1559: u64 = 2;
 
+// the lease lapsed while a job was prepared, the job goes back to the queue of the new holder
+#[derive(Debug, thiserror::Error)]
+#[error("account lease is held by another relayer instance")]
+struct LeaseLost;
+
 pub struct TransactionConsumer<
     P: Providers = Box<dyn Providers>,
     T: TransactionHandler<Document<DocumentTransaction>> = Box<
@@ -45,6 +53,13 @@ pub struct TransactionConsumer<
     pub handler: Arc<T>,
     pub token_price: Arc<RwLock<TP>>,
     pub tx_manager: TX,
+    pub drained: Arc<Notify>,
+    pub lease: LeaseState,
+    // jobs accepted with a fee quote may cost up to this much more than their relayer fee
+    pub quote_handler: QuoteHandlerRef,
+    pub quote_risk_limit_percent: u32,
+    // the api reads the gas prices the consumer sends with
+    pub gas_price_cache: Arc<GasPriceCache>,
 }
 
 #[async_trait]
@@ -65,23 +80,59 @@ where
                 id, self.chain_id, data.spend_type
             );
 
-            let options = match self.send_tx(id.as_str(), &data).await {
-                Ok(tx_hash) => UpdateTransactionOptions::builder()
-                    .status(TransactStatus::Succeeded)
-                    .transaction_hash(tx_hash)
-                    .build(),
+            // another instance drives the account now, it loads the job from the database
+            if !self.lease.is_held() {
+                info!(
+                    "skip transaction(id = {}, chain_id = {}), the account lease is not held",
+                    id, self.chain_id
+                );
+                continue;
+            }
+
+            // claim the job so a cancelled or duplicated one is never sent twice
+            let claim = UpdateTransactionOptions::builder()
+                .status(TransactStatus::Pending)
+                .build();
+            match self
+                .handler
+                .update_by_id_if_status(id.as_str(), &[TransactStatus::Queued], &claim)
+                .await
+            {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    info!(
+                        "skip transaction(id = {}, chain_id = {}), it is no longer queued",
+                        id, self.chain_id
+                    );
+                    continue;
+                }
                 Err(err) => {
-                    error!("consume transaction error: {}", err);
-                    UpdateTransactionOptions::builder()
-                        .status(TransactStatus::Failed)
-                        .error_message(err.to_string())
-                        .build()
+                    error!("Failed to claim transaction(id = {}): {:?}", id, err);
+                    continue;
                 }
-            };
+            }
 
-            // update database
-            self.update_transaction_status(id.as_str(), options).await;
+            match self.send_tx(id.as_str(), &data).await {
+                Ok(tx_hash) => {
+                    let options = UpdateTransactionOptions::builder()
+                        .status(TransactStatus::Succeeded)
+                        .transaction_hash(tx_hash)
+                        .build();
+                    self.update_transaction_status(id.as_str(), options).await;
+                }
+                Err(err) if err.is::<LeaseLost>() => {
+                    warn!("release transaction(id = {}), {}", id, err);
+                    self.release_claimed_transaction(id.as_str()).await;
+                }
+                Err(err) => {
+                    error!("consume transaction error: {}", err);
+                    self.fail_claimed_transaction(id.as_str(), &err.to_string()).await;
+                }
+            }
         }
+
+        info!("Consumer for chain_id: {} drained, queue closed", chain_id);
+        self.drained.notify_one();
     }
 }
 
@@ -100,14 +151,23 @@ where
         let call_data = self
             .build_call_data(contract_address, &signer, &data.contract_param, &data.signature)
             .await?;
-        // get gas price
+        // get gas price, always a fresh one for sending
         let gas_price = self.tx_manager.gas_price(&signer).await?;
+        self.gas_price_cache
+            .insert(self.chain_id, self.is_tx_eip1559, gas_price)
+            .await;
         // estimate gas
         let estimate_gas = self
             .estimate_gas(contract_address, &call_data, &signer, gas_price)
             .await?;
         // validate relayer fee
         let max_gas_price = self.validate_relayer_fee(data, &estimate_gas, gas_price).await?;
+        // the job may have been cancelled or failed by an admin while it was prepared
+        self.ensure_claimed(uuid).await?;
+        // the lease may have lapsed while the job was prepared, the nonce belongs to its new holder then
+        if !self.lease.is_held() {
+            return Err(LeaseLost.into());
+        }
         // send transaction
         let tx_hash = self
             .send(contract_address, &call_data, &signer, estimate_gas, max_gas_price)
@@ -132,6 +192,28 @@ where
         self.wait_confirm(&signer, &tx_hash).await
     }
 
+    async fn ensure_claimed(&self, uuid: &str) -> Result<()> {
+        let transaction = self
+            .handler
+            .find_by_id(uuid)
+            .await
+            .map_err(|err| anyhow!("failed to query transaction(id = {}): {:?}", uuid, err))?;
+        match transaction {
+            Some(transaction)
+                if transaction.data.status == TransactStatus::Pending
+                    && transaction.data.transaction_hash.is_none() =>
+            {
+                Ok(())
+            }
+            Some(transaction) => bail!(
+                "transaction(id = {}) is {:?} now, it is not sent",
+                uuid,
+                transaction.data.status
+            ),
+            None => bail!("transaction(id = {}) no longer exists", uuid),
+        }
+    }
+
     async fn validate_relayer_fee(
         &mut self,
         data: &TransactRequestData,
@@ -155,7 +237,7 @@ where
         debug!("estimate transaction fee amount = {}", estimate_transaction_fee_amount);
 
         // swap estimate gas to asset symbol
-        let price_service = self.token_price.write().await;
+        let price_service = self.token_price.read().await;
         // swap relayer fee to main asset symbol
         debug!(
             "relayer asset symbol = {}, decimals = {} swap to main asset symbol = {} decimals = {}",
@@ -176,8 +258,14 @@ where
             asset_symbol, relayer_fee_amount, self.main_asset_symbol, relayer_fee_amount_main
         );
 
-        // relayer_fee_amount_main > estimate_transaction_fee
+        // relayer_fee_amount_main > estimate_transaction_fee, unless a fee quote still covers it
         if relayer_fee_amount_main.lt(&estimate_transaction_fee_amount) {
+            if self
+                .is_covered_by_quote(data, &relayer_fee_amount_main, &estimate_transaction_fee_amount)
+                .await
+            {
+                return Ok(gas_price);
+            }
             bail!(
                 "Relayer fee amount not enough(relayer_fee_amount_main(symbol = {},decimals = {},amount = {}) \
                 less than estimate_transaction_fee_amount(symbol = {},decimals = {},amount = {})",
@@ -216,6 +304,36 @@ where
         Ok(max_gas_price)
     }
 
+    async fn is_covered_by_quote(
+        &self,
+        data: &TransactRequestData,
+        relayer_fee_amount_main: &U256,
+        estimate_transaction_fee_amount: &U256,
+    ) -> bool {
+        let quote = match self.quote_handler.find(&data.signature).await {
+            Ok(Some(quote)) => quote,
+            Ok(None) => return false,
+            Err(err) => {
+                error!("Failed to query fee quote of signature {}: {:?}", data.signature, err);
+                return false;
+            }
+        };
+        let limit = relayer_fee_amount_main.saturating_mul(U256::from(100 + self.quote_risk_limit_percent)) / 100;
+        if estimate_transaction_fee_amount.gt(&limit) {
+            warn!(
+                "fee quote {} exceeds the risk limit: estimate_transaction_fee_amount = {}, limit = {}",
+                quote.data.quote_id, estimate_transaction_fee_amount, limit
+            );
+            return false;
+        }
+        info!(
+            "honour fee quote {}(minimum_gas_fee = {}): estimate_transaction_fee_amount = {}, \
+            relayer_fee_amount_main = {}",
+            quote.data.quote_id, quote.data.minimum_gas_fee, estimate_transaction_fee_amount, relayer_fee_amount_main
+        );
+        true
+    }
+
     async fn send(
         &mut self,
         contract_address: Address,
@@ -291,6 +409,41 @@ where
         }
     }
 
+    // a job failed by an admin in the meantime keeps the admin's reason
+    async fn fail_claimed_transaction(&self, uuid: &str, error_message: &str) {
+        let options = UpdateTransactionOptions::builder()
+            .status(TransactStatus::Failed)
+            .error_message(error_message.to_string())
+            .build();
+        match self
+            .handler
+            .update_by_id_if_status(uuid, &[TransactStatus::Pending], &options)
+            .await
+        {
+            Ok(Some(_)) => info!("Successfully update transaction(id = {}) to status Failed", uuid),
+            Ok(None) => info!("transaction(id = {}) was already settled, keep its status", uuid),
+            Err(err) => error!(
+                "Failed to update transaction(id = {}) to status Failed: {:?}",
+                uuid, err
+            ),
+        }
+    }
+
+    // nothing was sent, the job is queued again for whichever instance holds the lease
+    async fn release_claimed_transaction(&self, uuid: &str) {
+        let options = UpdateTransactionOptions::builder()
+            .status(TransactStatus::Queued)
+            .build();
+        match self.handler.update_by_id_if_claimed(uuid, u64::MAX, &options).await {
+            Ok(Some(_)) => info!("Successfully update transaction(id = {}) to status Queued", uuid),
+            Ok(None) => info!("transaction(id = {}) was already settled, keep its status", uuid),
+            Err(err) => error!(
+                "Failed to update transaction(id = {}) to status Queued: {:?}",
+                uuid, err
+            ),
+        }
+    }
+
     async fn estimate_gas(
         &mut self,
         contract_address: Address,
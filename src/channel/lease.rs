@@ -0,0 +1,291 @@
+use crate::channel::producer::ProducerHandler;
+use crate::channel::{read_senders, take_sender, update_sender, SenderSet};
+use crate::configs::lease::LeaseConfig;
+use crate::context::Context;
+use crate::database::lease::AccountLease as DocumentAccountLease;
+use crate::database::transaction::Transaction as DocumentTransaction;
+use crate::error::RelayerServerError;
+use crate::handler::account::AccountHandler;
+use crate::handler::lease::LeaseHandler;
+use crate::handler::transaction::{TransactionHandler, UpdateTransactionOptions};
+use crate::handler::types::current_timestamp;
+use crate::service::admin::REQUEUE_STUCK_AFTER_MS;
+use crate::service::find_sender;
+use actix_web::web::Data;
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::Document;
+use mystiko_types::AssetType;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub type LeaseHandlerRef = Arc<Box<dyn LeaseHandler<Document<DocumentAccountLease>, Error = RelayerServerError>>>;
+
+// whether this instance drives an account, shared by its sender and consumer
+#[derive(Debug, Clone)]
+pub struct LeaseState {
+    held_until: Arc<AtomicU64>,
+}
+
+impl LeaseState {
+    // leases are disabled, a single instance drives every account
+    pub fn always_held() -> Self {
+        LeaseState {
+            held_until: Arc::new(AtomicU64::new(u64::MAX)),
+        }
+    }
+
+    pub fn not_held() -> Self {
+        LeaseState {
+            held_until: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    // checked against the local clock, a stalled heartbeat lets the lease lapse on its own
+    pub fn is_held(&self) -> bool {
+        current_timestamp() < self.held_until.load(Ordering::Acquire)
+    }
+
+    pub fn hold_until(&self, timestamp: u64) {
+        self.held_until.store(timestamp, Ordering::Release);
+    }
+
+    pub fn lose(&self) {
+        self.held_until.store(0, Ordering::Release);
+    }
+}
+
+#[derive(Debug)]
+pub struct LeaseKeeper {
+    context: Arc<Context>,
+    senders: Arc<SenderSet>,
+    owner: String,
+    ttl_ms: u64,
+    heartbeat_interval_ms: u64,
+}
+
+impl LeaseKeeper {
+    pub fn new(context: Arc<Context>, senders: Arc<SenderSet>, config: &LeaseConfig) -> Self {
+        let owner = config
+            .instance_id
+            .clone()
+            .unwrap_or_else(|| format!("relayer-{}", hex::encode(rand::random::<[u8; 8]>())));
+        LeaseKeeper {
+            context,
+            senders,
+            owner,
+            ttl_ms: config.ttl_ms,
+            heartbeat_interval_ms: config.heartbeat_interval_ms,
+        }
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        info!("keep account leases as instance {}", self.owner);
+        let mut interval = tokio::time::interval(Duration::from_millis(self.heartbeat_interval_ms));
+        loop {
+            interval.tick().await;
+            self.heartbeat().await;
+        }
+    }
+
+    // accounts added or retired at runtime are picked up from the sender set
+    pub async fn heartbeat(&self) {
+        self.reload_accounts().await;
+        // jobs are stored per chain, each chain with a held lease is swept once
+        let mut chains: HashMap<u64, u64> = HashMap::new();
+        for (chain_id, address, lease) in self.accounts() {
+            let started_at = current_timestamp();
+            match self
+                .context
+                .lease_handler
+                .try_acquire(chain_id, &address, &self.owner, self.ttl_ms)
+                .await
+            {
+                Ok(true) => {
+                    // a new holder takes every queued job, the previous one may have left them behind
+                    let queued_before = if lease.is_held() {
+                        started_at.saturating_sub(REQUEUE_STUCK_AFTER_MS)
+                    } else {
+                        info!(
+                            "instance {} drives account {} of chain id {}",
+                            self.owner, address, chain_id
+                        );
+                        started_at
+                    };
+                    // stop one heartbeat early, before another instance may take over
+                    lease.hold_until(started_at + self.ttl_ms - self.heartbeat_interval_ms);
+                    let swept_before = chains.entry(chain_id).or_insert(queued_before);
+                    *swept_before = (*swept_before).max(queued_before);
+                }
+                Ok(false) => {
+                    if lease.is_held() {
+                        warn!(
+                            "instance {} lost account {} of chain id {}",
+                            self.owner, address, chain_id
+                        );
+                    }
+                    lease.lose();
+                }
+                Err(error) => {
+                    warn!(
+                        "renew lease of account {} of chain id {} got error: {:?}",
+                        address, chain_id, error
+                    );
+                }
+            }
+        }
+        // requeueing waits for room in the queues, the heartbeat must not
+        for (chain_id, queued_before) in chains {
+            tokio::spawn(recover_unsent_transactions(
+                self.context.clone(),
+                self.senders.clone(),
+                chain_id,
+                queued_before,
+            ));
+        }
+    }
+
+    // the admin api of another instance changes the stored accounts only, they are synced from there
+    async fn reload_accounts(&self) {
+        let accounts = match self.context.account_handler.find_all().await {
+            Ok(accounts) => accounts,
+            Err(error) => {
+                warn!("reload accounts got error: {:?}", error);
+                return;
+            }
+        };
+        for (chain_id, address, _) in self.accounts() {
+            let account = accounts.iter().find(|account| {
+                account.data.chain_id == chain_id && account.data.chain_address.eq_ignore_ascii_case(&address)
+            });
+            match account {
+                Some(account) => {
+                    update_sender(&self.senders, chain_id, &address, |sender| {
+                        sender.available = account.data.available;
+                        sender.supported_erc20_tokens = account.data.supported_erc20_tokens.clone();
+                    });
+                }
+                None => {
+                    // dropping the producer closes the queue, the consumer drains what is left and stops
+                    if take_sender(&self.senders, chain_id, &address).is_some() {
+                        info!("account {} of chain id {} was retired", address, chain_id);
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn release_all(&self) {
+        for (chain_id, address, lease) in self.accounts() {
+            lease.lose();
+            if let Err(error) = self
+                .context
+                .lease_handler
+                .release(chain_id, &address, &self.owner)
+                .await
+            {
+                warn!(
+                    "release lease of account {} of chain id {} got error: {:?}",
+                    address, chain_id, error
+                );
+            }
+        }
+    }
+
+    fn accounts(&self) -> Vec<(u64, String, LeaseState)> {
+        read_senders(&self.senders)
+            .iter()
+            .map(|sender| (sender.chain_id, sender.address.clone(), sender.lease.clone()))
+            .collect()
+    }
+}
+
+// jobs of a chain nothing sends: queued ones left alone since queued_before and claims left behind by a
+// consumer, each goes to a held sender of this instance
+pub async fn recover_unsent_transactions(
+    context: Arc<Context>,
+    senders: Arc<SenderSet>,
+    chain_id: u64,
+    queued_before: u64,
+) {
+    let claimed_before = current_timestamp().saturating_sub(REQUEUE_STUCK_AFTER_MS);
+    let transactions = match context
+        .transaction_handler
+        .find_unsent(chain_id, queued_before, claimed_before)
+        .await
+    {
+        Ok(transactions) => transactions,
+        Err(error) => {
+            warn!(
+                "find unsent transactions of chain id {} got error: {:?}",
+                chain_id, error
+            );
+            return;
+        }
+    };
+    for transaction in transactions {
+        if let Err(error) = recover_transaction(&context, &senders, &transaction, claimed_before).await {
+            warn!("recover transaction(id = {}) got error: {:?}", transaction.id, error);
+        }
+    }
+}
+
+async fn recover_transaction(
+    context: &Context,
+    senders: &Arc<SenderSet>,
+    transaction: &Document<DocumentTransaction>,
+    claimed_before: u64,
+) -> Result<()> {
+    let id = transaction.id.as_str();
+    let data = context
+        .transaction_handler
+        .find_request_by_id(id)
+        .await?
+        .ok_or_else(|| anyhow!("request of transaction(id = {}) not found", id))?;
+    let relayer_config = context.relayer_config.load();
+    let chain_config = relayer_config
+        .find_chain_config(data.chain_id)
+        .ok_or_else(|| anyhow!("chain id {} config not found in relayer config", data.chain_id))?;
+    let asset_type = if chain_config.asset_symbol().eq_ignore_ascii_case(&data.asset_symbol) {
+        AssetType::Main
+    } else {
+        AssetType::Erc20
+    };
+    // no held sender takes the asset here, the instance driving one picks the job up
+    let Some(producer) = find_sender(
+        Data::new(senders.clone()),
+        data.chain_id,
+        &data.asset_symbol,
+        asset_type,
+    ) else {
+        return Ok(());
+    };
+
+    // touched first so the next sweep leaves it alone, the claim of the consumer drops any extra copy
+    let options = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Queued)
+        .build();
+    let handler = &context.transaction_handler;
+    let touched = if transaction.data.status == TransactStatus::Queued {
+        handler
+            .update_by_id_if_status(id, &[TransactStatus::Queued], &options)
+            .await?
+    } else {
+        handler.update_by_id_if_claimed(id, claimed_before, &options).await?
+    };
+    if touched.is_some() {
+        producer.requeue(id, data).await?;
+        info!(
+            "recover transaction(id = {}) of chain id {}",
+            id, transaction.data.chain_id
+        );
+    }
+    Ok(())
+}
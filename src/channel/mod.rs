@@ -1,10 +1,13 @@
 use crate::channel::consumer::handler::TransactionConsumer;
 use crate::channel::consumer::ConsumerHandler;
+use crate::channel::lease::LeaseState;
 use crate::channel::producer::handler::TransactionProducer;
 use crate::channel::producer::ProducerHandler;
+use crate::configs::account::AccountConfig;
 use crate::context::Context;
 use crate::error::RelayerServerError;
-use anyhow::Result;
+use crate::signer::{AccountSigner, RemoteSignerTxManager};
+use anyhow::{bail, Result};
 use ethers_signers::{LocalWallet, Signer};
 use mystiko_ethers::{JsonRpcClientWrapper, ProviderWrapper, Providers};
 use mystiko_relayer_types::TransactRequestData;
@@ -13,20 +16,31 @@ use mystiko_server_utils::tx_manager::{TransactionMiddleware, TxManagerBuilder};
 use mystiko_types::TransactionType;
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use tokio::sync::mpsc::channel;
+use tokio::sync::Notify;
 
+pub mod cancel;
 pub mod consumer;
+pub mod lease;
 pub mod producer;
+pub mod webhook;
 
 pub const ARRAY_QUEUE_CAPACITY: usize = 50;
 
 #[derive(Debug)]
 pub struct SenderInfo<P: ProducerHandler = Box<dyn ProducerHandler<Error = RelayerServerError>>> {
     pub chain_id: u64,
-    pub private_key: String,
+    pub address: String,
+    // built once at startup, local keys are not kept anywhere else
+    pub signer: AccountSigner,
+    pub available: bool,
     pub supported_erc20_tokens: Vec<String>,
     pub producer: Arc<P>,
+    // notified by the consumer once its queue is closed and drained
+    pub drained: Arc<Notify>,
+    // only the instance holding the lease takes transactions for the account
+    pub lease: LeaseState,
 }
 
 impl<P> PartialEq<Self> for SenderInfo<P>
@@ -34,7 +48,7 @@ where
     P: ProducerHandler,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.chain_id == other.chain_id && self.private_key == other.private_key
+        self.chain_id == other.chain_id && self.address.eq_ignore_ascii_case(&other.address)
     }
 }
 
@@ -46,10 +60,57 @@ where
 {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.chain_id.hash(state);
-        self.private_key.hash(state);
+        self.address.to_lowercase().hash(state);
     }
 }
 
+// senders shared with the http handlers, mutable at runtime through the admin api
+pub type SenderSet = RwLock<HashSet<SenderInfo>>;
+
+pub fn read_senders(senders: &SenderSet) -> RwLockReadGuard<'_, HashSet<SenderInfo>> {
+    senders.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn write_senders(senders: &SenderSet) -> RwLockWriteGuard<'_, HashSet<SenderInfo>> {
+    senders.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn is_account_sender(sender: &SenderInfo, chain_id: u64, address: &str) -> bool {
+    sender.chain_id == chain_id && sender.address.eq_ignore_ascii_case(address)
+}
+
+pub fn contains_sender(senders: &SenderSet, chain_id: u64, address: &str) -> bool {
+    read_senders(senders)
+        .iter()
+        .any(|sender| is_account_sender(sender, chain_id, address))
+}
+
+pub fn take_sender(senders: &SenderSet, chain_id: u64, address: &str) -> Option<SenderInfo> {
+    let mut senders = write_senders(senders);
+    let (taken, kept): (HashSet<_>, HashSet<_>) = std::mem::take(&mut *senders)
+        .into_iter()
+        .partition(|sender| is_account_sender(sender, chain_id, address));
+    *senders = kept;
+    taken.into_iter().next()
+}
+
+pub fn update_sender<U>(senders: &SenderSet, chain_id: u64, address: &str, mut update: U) -> bool
+where
+    U: FnMut(&mut SenderInfo),
+{
+    let mut senders = write_senders(senders);
+    let (taken, mut kept): (HashSet<_>, HashSet<_>) = std::mem::take(&mut *senders)
+        .into_iter()
+        .partition(|sender| is_account_sender(sender, chain_id, address));
+    let found = !taken.is_empty();
+    for mut sender in taken {
+        update(&mut sender);
+        kept.insert(sender);
+    }
+    *senders = kept;
+    found
+}
+
 pub struct Channel<C: ConsumerHandler = Box<dyn ConsumerHandler>> {
     pub senders: HashSet<SenderInfo>,
     pub consumers: Vec<C>,
@@ -62,73 +123,110 @@ where
     pub async fn new(context: Arc<Context>) -> Result<Channel<Box<dyn ConsumerHandler>>> {
         let mut senders = HashSet::new();
         let mut consumers: Vec<Box<dyn ConsumerHandler>> = Vec::new();
-        for account in context.server_config.accounts.values() {
-            let chain_id = account.chain_id;
-            let private_key = &account.private_key;
-            let supported_erc20_tokens = account.supported_erc20_tokens.values().cloned().collect();
-            let chain_config = context
-                .mystiko_config
-                .find_chain(chain_id)
-                .unwrap_or_else(|| panic!("chain id {} config not found in mystiko config", chain_id));
-            let is_tx_eip1559 = chain_config.transaction_type() == &TransactionType::Eip1559;
-            let (sender, receiver) = channel::<(String, TransactRequestData)>(ARRAY_QUEUE_CAPACITY);
-            let producer = Arc::new(Box::new(TransactionProducer::new(
-                Arc::new(sender),
-                context.transaction_handler.clone(),
-            )) as Box<dyn ProducerHandler<Error = RelayerServerError>>);
-            senders.insert(SenderInfo {
-                chain_id,
-                supported_erc20_tokens,
-                producer,
-                private_key: private_key.to_string(),
-            });
-
-            let wallet: LocalWallet = private_key.parse::<LocalWallet>()?.with_chain_id(chain_id);
-
-            // create tx manager
-            let mut tx_manager_config = TxManagerConfig::new(None)?;
-            if let Some(safe_confirmations) = chain_config.safe_confirmations() {
-                let confirm_block: u32 = safe_confirmations.try_into()?;
-                // get chain config
-                let mut tm_chain_config = tx_manager_config.chain_config(&chain_id)?;
-                tm_chain_config.confirm_blocks = confirm_block;
-                let mut chains = HashMap::new();
-                chains.insert(chain_id, tm_chain_config);
-                tx_manager_config.chains = chains;
-            }
-            // create tx builder
-            let tx_builder = TxManagerBuilder::builder()
-                .config(tx_manager_config)
-                .chain_id(chain_id)
-                .wallet(wallet)
-                .build();
-            // get or create provider
-            let provider = context.providers.get_provider(chain_id).await?;
-            // build tx manager
-            let tx_manager = Box::new(tx_builder.build(Some(is_tx_eip1559), &provider).await?)
-                as Box<dyn TransactionMiddleware<ProviderWrapper<Box<dyn JsonRpcClientWrapper>>>>;
-
-            // found relayer chain config
-            let relayer_chain_config = context
-                .relayer_config
-                .find_chain_config(chain_id)
-                .unwrap_or_else(|| panic!("chain id {} config not found in relayer config", chain_id));
-
-            let consumer = TransactionConsumer {
-                chain_id,
-                is_tx_eip1559,
-                main_asset_symbol: relayer_chain_config.asset_symbol().to_string(),
-                main_asset_decimals: relayer_chain_config.asset_decimals(),
-                receiver,
-                providers: context.providers.clone(),
-                signer_providers: context.signer_providers.clone(),
-                handler: context.transaction_handler.clone(),
-                token_price: context.token_price.clone(),
-                tx_manager,
-            };
-            consumers.push(Box::new(consumer) as Box<dyn ConsumerHandler>);
+        for account in context.server_config.load().accounts.values() {
+            let (sender, consumer) = create_account_channel(&context, account).await?;
+            senders.insert(sender);
+            consumers.push(consumer);
         }
 
         Ok(Channel { senders, consumers })
     }
 }
+
+pub async fn create_account_channel(
+    context: &Context,
+    account: &AccountConfig,
+) -> Result<(SenderInfo, Box<dyn ConsumerHandler>)> {
+    let chain_id = account.chain_id;
+    let supported_erc20_tokens = account.supported_erc20_tokens.values().cloned().collect();
+    let mystiko_config = context.mystiko_config.load();
+    let Some(chain_config) = mystiko_config.find_chain(chain_id) else {
+        bail!("chain id {} config not found in mystiko config", chain_id);
+    };
+    let is_tx_eip1559 = chain_config.transaction_type() == &TransactionType::Eip1559;
+    let (sender, receiver) = channel::<(String, TransactRequestData)>(ARRAY_QUEUE_CAPACITY);
+    let producer = Arc::new(Box::new(TransactionProducer::new(
+        Arc::new(sender),
+        context.transaction_handler.clone(),
+    )) as Box<dyn ProducerHandler<Error = RelayerServerError>>);
+
+    let signer = account.signer()?.with_chain_id(chain_id);
+    if let AccountSigner::Remote(remote_signer) = &signer {
+        remote_signer.verify().await?;
+    }
+    // remote accounts hand the tx manager a placeholder, it never signs for them
+    let wallet = match &signer {
+        AccountSigner::Local(wallet) => wallet.clone(),
+        AccountSigner::Remote(_) => LocalWallet::new(&mut rand::thread_rng()).with_chain_id(chain_id),
+    };
+    let drained = Arc::new(Notify::new());
+    let lease = if context.server_config.load().lease.enabled {
+        LeaseState::not_held()
+    } else {
+        LeaseState::always_held()
+    };
+    let sender_info = SenderInfo {
+        chain_id,
+        address: format!("0x{}", hex::encode(signer.address())),
+        signer: signer.clone(),
+        available: account.available,
+        supported_erc20_tokens,
+        producer,
+        drained: drained.clone(),
+        lease: lease.clone(),
+    };
+
+    // create tx manager
+    let mut tx_manager_config = TxManagerConfig::new(None)?;
+    if let Some(safe_confirmations) = chain_config.safe_confirmations() {
+        let confirm_block: u32 = safe_confirmations.try_into()?;
+        // get chain config
+        let mut tm_chain_config = tx_manager_config.chain_config(&chain_id)?;
+        tm_chain_config.confirm_blocks = confirm_block;
+        let mut chains = HashMap::new();
+        chains.insert(chain_id, tm_chain_config);
+        tx_manager_config.chains = chains;
+    }
+    // create tx builder
+    let tx_builder = TxManagerBuilder::builder()
+        .config(tx_manager_config)
+        .chain_id(chain_id)
+        .wallet(wallet)
+        .build();
+    // get or create provider
+    let provider = context.providers.get_provider(chain_id).await?;
+    // build tx manager
+    let tx_manager = tx_builder.build(Some(is_tx_eip1559), &provider).await?;
+    let tx_manager = match signer {
+        AccountSigner::Local(_) => {
+            Box::new(tx_manager) as Box<dyn TransactionMiddleware<ProviderWrapper<Box<dyn JsonRpcClientWrapper>>>>
+        }
+        AccountSigner::Remote(remote_signer) => Box::new(RemoteSignerTxManager::new(tx_manager, remote_signer)),
+    };
+
+    // found relayer chain config
+    let relayer_config = context.relayer_config.load();
+    let Some(relayer_chain_config) = relayer_config.find_chain_config(chain_id) else {
+        bail!("chain id {} config not found in relayer config", chain_id);
+    };
+
+    let consumer = TransactionConsumer {
+        chain_id,
+        is_tx_eip1559,
+        main_asset_symbol: relayer_chain_config.asset_symbol().to_string(),
+        main_asset_decimals: relayer_chain_config.asset_decimals(),
+        receiver,
+        providers: context.providers.clone(),
+        signer_providers: context.signer_providers.clone(),
+        handler: context.transaction_handler.clone(),
+        token_price: context.token_price.clone(),
+        tx_manager,
+        drained,
+        lease,
+        quote_handler: context.quote_handler.clone(),
+        quote_risk_limit_percent: context.server_config.load().quote.risk_limit_percent,
+        gas_price_cache: context.gas_price_cache.clone(),
+    };
+
+    Ok((sender_info, Box::new(consumer) as Box<dyn ConsumerHandler>))
+}
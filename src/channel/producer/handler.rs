@@ -63,6 +63,15 @@ where
             }
         }
     }
+
+    async fn requeue(&self, id: &str, data: TransactRequestData) -> Result<()> {
+        self.sender
+            .send((id.to_string(), data))
+            .await
+            .map_err(|e| RelayerServerError::QueueSendError(e.to_string()))?;
+        info!("successfully requeued a transaction(id = {})", id);
+        Ok(())
+    }
 }
 
 impl<T> TransactionProducer<T>
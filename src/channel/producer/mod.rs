@@ -11,6 +11,8 @@ pub trait ProducerHandler: Send + Sync {
     type Error;
 
     async fn send(&self, data: TransactRequestData) -> Result<Document<Transaction>, Self::Error>;
+
+    async fn requeue(&self, id: &str, data: TransactRequestData) -> Result<(), Self::Error>;
 }
 
 #[async_trait]
@@ -20,4 +22,8 @@ impl ProducerHandler for Box<dyn ProducerHandler<Error = RelayerServerError>> {
     async fn send(&self, data: TransactRequestData) -> Result<Document<Transaction>, Self::Error> {
         self.as_ref().send(data).await
     }
+
+    async fn requeue(&self, id: &str, data: TransactRequestData) -> Result<(), Self::Error> {
+        self.as_ref().requeue(id, data).await
+    }
 }
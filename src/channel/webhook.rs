@@ -0,0 +1,459 @@
+use crate::configs::webhook::{validate_webhook_url, WebhookConfig};
+use crate::database::webhook::{
+    TransactionCallback as DocumentTransactionCallback, WebhookDelivery as DocumentWebhookDelivery,
+};
+use crate::error::RelayerServerError;
+use crate::handler::transaction::status::{StatusUpdate, TransactionStatusEvent, TransactionStatusSubscription};
+use crate::handler::transaction::{QueryTransactionOptions, SortOrder, TransactionSortBy};
+use crate::handler::types::current_timestamp;
+use crate::handler::webhook::{CreateWebhookDeliveryOptions, WebhookHandler};
+use crate::service::v2::stream::TransactionHandlerRef;
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use log::{debug, error, warn};
+use mystiko_relayer_types::{RelayTransactStatusResponse, TransactStatus};
+use mystiko_storage::Document;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Notify;
+
+pub const WEBHOOK_EVENT_TRANSACTION_STATUS: &str = "transaction.status";
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Relayer-Signature";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Relayer-Timestamp";
+
+// pending deliveries of restarts and other instances are looked up at least this often
+const DISPATCH_INTERVAL_MS: u64 = 1000;
+// a claimed delivery is due again this long after the request timeout
+const CLAIM_MARGIN_MS: u64 = 5000;
+// after a lag, transactions updated since this long before the last received change are enqueued again
+const LAGGED_LOOKBACK_MS: u64 = 60000;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub type WebhookHandlerRef = Arc<
+    Box<
+        dyn WebhookHandler<
+            Document<DocumentTransactionCallback>,
+            Document<DocumentWebhookDelivery>,
+            Error = RelayerServerError,
+        >,
+    >,
+>;
+
+// host of a callback url and the addresses it was checked against, empty for allowlisted hosts
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallbackTarget {
+    pub host: String,
+    pub addresses: Vec<SocketAddr>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookPayload {
+    pub event: String,
+    // seconds since the unix epoch, the same value as the timestamp header
+    pub timestamp: u64,
+    pub transaction: RelayTransactStatusResponse,
+}
+
+// posts status changes to the global webhook and to the callback url of the transaction
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    client: Client,
+    transaction_handler: TransactionHandlerRef,
+    webhook_handler: WebhookHandlerRef,
+    // wakes the dispatcher once a delivery was enqueued or attempted
+    wake: Notify,
+    // milliseconds, when the last status change was received
+    last_event_at: AtomicU64,
+}
+
+impl WebhookNotifier {
+    pub fn new(
+        config: WebhookConfig,
+        transaction_handler: TransactionHandlerRef,
+        webhook_handler: WebhookHandlerRef,
+    ) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+        Ok(WebhookNotifier {
+            config,
+            client,
+            transaction_handler,
+            webhook_handler,
+            wake: Notify::new(),
+            last_event_at: AtomicU64::new(current_timestamp()),
+        })
+    }
+
+    // changes are stored as deliveries first, the dispatcher posts and retries them from storage,
+    // which also picks up the deliveries left pending by a restart
+    pub async fn run(self: Arc<Self>, mut subscription: TransactionStatusSubscription) {
+        tokio::spawn(self.clone().dispatch_periodically());
+        loop {
+            match subscription.next().await {
+                StatusUpdate::Event(event) => {
+                    self.last_event_at.store(current_timestamp(), Ordering::Relaxed);
+                    self.enqueue(&event).await;
+                }
+                StatusUpdate::Lagged => {
+                    warn!("webhook notifier lagged behind, enqueue recently updated transactions again");
+                    self.enqueue_recent().await;
+                }
+                StatusUpdate::Closed => return,
+            }
+        }
+    }
+
+    // one delivery per target, a change already enqueued for a target is skipped
+    pub async fn enqueue(&self, event: &TransactionStatusEvent) {
+        let payload = match serde_json::to_string(&RelayTransactStatusResponse::from(event.clone())) {
+            Ok(payload) => payload,
+            Err(error) => {
+                error!("serialize status of transaction {} got error: {:?}", event.id, error);
+                return;
+            }
+        };
+        let enqueued = match self.webhook_handler.find_deliveries(&event.id).await {
+            Ok(deliveries) => deliveries,
+            Err(error) => {
+                error!(
+                    "find webhook deliveries of transaction {} got error: {:?}",
+                    event.id, error
+                );
+                vec![]
+            }
+        };
+        let status = status_name(&event.status);
+        for url in self.targets(event).await {
+            if enqueued
+                .iter()
+                .any(|delivery| delivery.data.url == url && delivery.data.payload == payload)
+            {
+                continue;
+            }
+            let options = CreateWebhookDeliveryOptions::builder()
+                .transaction_id(event.id.as_str())
+                .url(url.as_str())
+                .status(status.as_str())
+                .payload(payload.as_str())
+                .build();
+            if let Err(error) = self.webhook_handler.enqueue_delivery(&options).await {
+                error!(
+                    "enqueue webhook delivery of transaction {} got error: {:?}",
+                    event.id, error
+                );
+            }
+        }
+        self.wake.notify_one();
+    }
+
+    // the dropped changes are not known, the current status of every transaction updated around them is
+    // enqueued instead
+    async fn enqueue_recent(&self) {
+        let started_at = current_timestamp();
+        let updated_after = self
+            .last_event_at
+            .load(Ordering::Relaxed)
+            .saturating_sub(LAGGED_LOOKBACK_MS);
+        let options = QueryTransactionOptions::builder()
+            .updated_after(updated_after)
+            .sort_by(TransactionSortBy::UpdatedAt)
+            .order(SortOrder::Asc)
+            .build();
+        match self.transaction_handler.find(&options).await {
+            Ok(transactions) => {
+                for transaction in transactions.iter() {
+                    self.enqueue(&TransactionStatusEvent::from(transaction)).await;
+                }
+            }
+            Err(error) => error!("find recently updated transactions got error: {:?}", error),
+        }
+        self.last_event_at.store(started_at, Ordering::Relaxed);
+    }
+
+    async fn dispatch_periodically(self: Arc<Self>) {
+        loop {
+            let wait_ms = self.dispatch().await.map_or(DISPATCH_INTERVAL_MS, |next_attempt_at| {
+                next_attempt_at
+                    .saturating_sub(current_timestamp())
+                    .min(DISPATCH_INTERVAL_MS)
+            });
+            let _ = tokio::time::timeout(Duration::from_millis(wait_ms), self.wake.notified()).await;
+        }
+    }
+
+    // attempts the oldest pending delivery of every transaction and url, later changes wait until it was
+    // delivered or given up so receivers get them in order. returns when the next waiting one is due
+    pub async fn dispatch(self: &Arc<Self>) -> Option<u64> {
+        let mut pending = match self.webhook_handler.find_pending_deliveries().await {
+            Ok(pending) => pending,
+            Err(error) => {
+                error!("find pending webhook deliveries got error: {:?}", error);
+                return None;
+            }
+        };
+        // changes enqueued within the same millisecond
+        pending.sort_by_key(|delivery| (delivery.created_at, status_rank(&delivery.data.status)));
+        let now = current_timestamp();
+        let mut heads = HashSet::new();
+        let mut next_due_at: Option<u64> = None;
+        for delivery in pending {
+            if !heads.insert((delivery.data.transaction_id.clone(), delivery.data.url.clone())) {
+                continue;
+            }
+            let Some(next_attempt_at) = delivery.data.next_attempt_at else {
+                continue;
+            };
+            if next_attempt_at > now {
+                next_due_at = Some(next_due_at.map_or(next_attempt_at, |due_at| due_at.min(next_attempt_at)));
+                continue;
+            }
+            // claimed past the request timeout, a delivery of an instance that died mid-attempt is due again after
+            let claimed_until = now + self.config.timeout_ms + CLAIM_MARGIN_MS;
+            match self
+                .webhook_handler
+                .claim_delivery(&delivery.id, next_attempt_at, claimed_until)
+                .await
+            {
+                Ok(Some(delivery)) => {
+                    let notifier = self.clone();
+                    tokio::spawn(async move {
+                        notifier.attempt(delivery).await;
+                        notifier.wake.notify_one();
+                    });
+                }
+                Ok(None) => debug!("webhook delivery {} was claimed by another instance", delivery.id),
+                Err(error) => error!("claim webhook delivery {} got error: {:?}", delivery.id, error),
+            }
+        }
+        next_due_at
+    }
+
+    // posts a claimed delivery once, then schedules the next attempt with backoff or finishes it
+    pub async fn attempt(&self, mut delivery: Document<DocumentWebhookDelivery>) -> bool {
+        let attempt = delivery.data.attempt + 1;
+        let result = self.post(&delivery.data.url, &delivery.data.payload).await;
+        let data = &mut delivery.data;
+        data.attempt = attempt;
+        match result {
+            Ok(response_status) if (200..300).contains(&response_status) => {
+                data.delivered = true;
+                data.response_status = Some(response_status as u32);
+                data.error_message = None;
+            }
+            Ok(response_status) => {
+                data.response_status = Some(response_status as u32);
+                data.error_message = Some(format!("unexpected response status {}", response_status));
+            }
+            Err(error) => {
+                data.response_status = None;
+                data.error_message = Some(error.to_string());
+            }
+        }
+        data.next_attempt_at = if data.delivered {
+            debug!(
+                "delivered status {} of transaction {} to {}",
+                data.status, data.transaction_id, data.url
+            );
+            None
+        } else if attempt >= self.config.max_attempts {
+            warn!(
+                "give up delivering status {} of transaction {} to {} after {} attempts",
+                data.status, data.transaction_id, data.url, attempt
+            );
+            None
+        } else {
+            Some(current_timestamp() + self.config.backoff_ms(attempt))
+        };
+        let delivered = data.delivered;
+        if let Err(error) = self.webhook_handler.update_delivery(&delivery).await {
+            error!(
+                "update webhook delivery of transaction {} got error: {:?}",
+                delivery.data.transaction_id, error
+            );
+        }
+        delivered
+    }
+
+    async fn post(&self, url: &str, transaction: &str) -> Result<u16> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let payload = WebhookPayload {
+            event: WEBHOOK_EVENT_TRANSACTION_STATUS.to_string(),
+            timestamp,
+            transaction: serde_json::from_str(transaction)?,
+        };
+        let body = serde_json::to_vec(&payload)?;
+        let signature = sign_webhook(&self.config.secret, timestamp, &body);
+        let client = self.client_for(url).await?;
+        let response = client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={}", signature))
+            .body(body)
+            .send()
+            .await?;
+        Ok(response.status().as_u16())
+    }
+
+    // the configured url is trusted, a callback url is checked again on every attempt and pinned to the
+    // checked addresses without following redirects, so a changed dns answer can not reach internal hosts
+    async fn client_for(&self, url: &str) -> Result<Client> {
+        if self.config.url.as_deref() == Some(url) {
+            return Ok(self.client.clone());
+        }
+        let target = resolve_callback_url(&self.config, url)
+            .await
+            .map_err(|error| anyhow!("callback url {}", error))?;
+        let mut builder = Client::builder()
+            .timeout(Duration::from_millis(self.config.timeout_ms))
+            .redirect(Policy::none());
+        if !target.addresses.is_empty() {
+            builder = builder.resolve_to_addrs(&target.host, &target.addresses);
+        }
+        Ok(builder.build()?)
+    }
+
+    async fn targets(&self, event: &TransactionStatusEvent) -> Vec<String> {
+        let mut targets = vec![];
+        if let Some(url) = &self.config.url {
+            targets.push(url.clone());
+        }
+        if self.config.allow_callback_url {
+            if let Some(url) = self.callback_url(&event.id).await {
+                if !targets.contains(&url) {
+                    targets.push(url);
+                }
+            }
+        }
+        targets
+    }
+
+    async fn callback_url(&self, id: &str) -> Option<String> {
+        let transaction = match self.transaction_handler.find_by_id(id).await {
+            Ok(transaction) => transaction?,
+            Err(error) => {
+                error!("find transaction by id({}) got error: {:?}", id, error);
+                return None;
+            }
+        };
+        match self.webhook_handler.find_callback(&transaction.data.signature).await {
+            Ok(callback) => callback.map(|callback| callback.data.url),
+            Err(error) => {
+                error!("find callback of transaction {} got error: {:?}", id, error);
+                None
+            }
+        }
+    }
+}
+
+// hex hmac-sha256 of "{timestamp}.{body}"
+pub fn sign_webhook(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// a callback url must use an allowlisted host, or without an allowlist resolve to public addresses only
+pub async fn resolve_callback_url(config: &WebhookConfig, url: &str) -> Result<CallbackTarget, String> {
+    validate_webhook_url(url)?;
+    let parsed = Url::parse(url).map_err(|error| format!("invalid url: {}", error))?;
+    let host = parsed.host_str().unwrap_or_default().to_string();
+    if config.is_allowed_callback_host(&host) {
+        return Ok(CallbackTarget {
+            host,
+            addresses: vec![],
+        });
+    }
+    if !config.callback_allowed_hosts.is_empty() {
+        return Err(format!("host {} is not allowed", host));
+    }
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    // ipv6 hosts keep their brackets in the url
+    let addresses: Vec<SocketAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(|error| format!("host {} can not be resolved: {}", host, error))?
+            .collect(),
+    };
+    if addresses.is_empty() {
+        return Err(format!("host {} can not be resolved", host));
+    }
+    if let Some(address) = addresses.iter().find(|address| !is_public_address(&address.ip())) {
+        return Err(format!(
+            "host {} resolves to the non-public address {}",
+            host,
+            address.ip()
+        ));
+    }
+    Ok(CallbackTarget { host, addresses })
+}
+
+// loopback, private, link-local, shared, reserved and other special purpose ranges are not public
+pub fn is_public_address(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ipv4(&mapped),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: &Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: &Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        || (segments[0] == 0x64 && segments[1] == 0xff9b))
+}
+
+// later statuses of a transaction sort after earlier ones
+fn status_rank(status: &str) -> u8 {
+    if status == status_name(&TransactStatus::Queued) {
+        0
+    } else if status == status_name(&TransactStatus::Pending) {
+        1
+    } else {
+        2
+    }
+}
+
+fn status_name(status: &TransactStatus) -> String {
+    match serde_json::to_value(status) {
+        Ok(Value::String(name)) => name,
+        _ => format!("{:?}", status),
+    }
+}
@@ -0,0 +1,403 @@
+use crate::application::{run_application, ApplicationOptions};
+use crate::configs::admin::AdminConfig;
+use crate::configs::auth::{ApiKeyScope, AuthConfig};
+use crate::configs::load_server_config;
+use crate::configs::server::{ServerConfig, Settings};
+use crate::context::create_config;
+use crate::database::{init_sqlite_database, Database};
+use crate::handler::transaction::{QueryTransactionOptions, Transaction, TransactionHandler};
+use crate::provider::RelayerProviderOptions;
+use crate::service::admin::response::{TransactionListResponse, TransactionResponse};
+use crate::service::admin::DEFAULT_PAGE_LIMIT;
+use crate::service::auth::{
+    sign_request, ADMIN_PATH_PREFIX, API_KEY_ID_HEADER, API_NONCE_HEADER, API_SIGNATURE_HEADER, API_TIMESTAMP_HEADER,
+};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use ethers_core::types::Address;
+use ethers_core::utils::format_ether;
+use ethers_providers::Middleware;
+use mystiko_ethers::{ProviderPool, Providers};
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::SqlStatementFormatter;
+use mystiko_storage_sqlite::SqliteStorage;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Certificate, Identity, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_SERVER_CONFIG_PATH: &str = "./config.toml";
+
+#[derive(Parser, Debug)]
+#[command(name = "mystiko_relayer", version, about = "Mystiko relayer server")]
+pub struct Cli {
+    /// Server config file, MYSTIKO_RELAYER_* environment variables are read when it does not exist
+    #[arg(short, long, global = true, default_value = DEFAULT_SERVER_CONFIG_PATH)]
+    pub config: String,
+    /// Server config file as the only argument, the invocation before subcommands existed
+    #[arg(value_name = "CONFIG", hide = true, conflicts_with = "config")]
+    pub legacy_config: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    pub fn config_path(&self) -> &str {
+        self.legacy_config.as_deref().unwrap_or(&self.config)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Start the relayer server, the default without a subcommand
+    Serve,
+    /// Inspect the server config
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// List accounts with their addresses, chains and current balances
+    Accounts,
+    /// Inspect and requeue transactions
+    #[command(subcommand)]
+    Tx(TxCommand),
+    /// Manage the database
+    #[command(subcommand)]
+    Db(DbCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Validate the configs and print the effective server config with secrets redacted
+    Check,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TxCommand {
+    /// List transactions stored in the database
+    List(TxListArgs),
+    /// Show a transaction stored in the database
+    Show { id: String },
+    /// Requeue a failed or queued transaction through the admin api of the running server
+    Requeue(TxRequeueArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DbCommand {
+    /// Create the database if needed and run the migrations
+    Migrate,
+}
+
+#[derive(Args, Debug)]
+pub struct TxListArgs {
+    #[arg(long)]
+    pub chain_id: Option<u64>,
+    #[arg(long, value_parser = parse_status)]
+    pub status: Option<TransactStatus>,
+    #[arg(long, default_value_t = 0)]
+    pub offset: u64,
+    #[arg(long, default_value_t = DEFAULT_PAGE_LIMIT)]
+    pub limit: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct TxRequeueArgs {
+    pub id: String,
+    /// Recorded in the audit log
+    #[arg(long)]
+    pub reason: String,
+    /// Admin operator whose token is used, required when several are configured
+    #[arg(long)]
+    pub operator: Option<String>,
+    /// Server url, defaults to the configured port on localhost
+    #[arg(long)]
+    pub url: Option<String>,
+    /// Client certificate (pem) for an admin api that requires one
+    #[arg(long, requires = "client_key")]
+    pub client_cert: Option<String>,
+    /// Private key (pem) of the client certificate
+    #[arg(long, requires = "client_cert")]
+    pub client_key: Option<String>,
+    /// CA certificate (pem) trusted for the server certificate, e.g. a private CA
+    #[arg(long)]
+    pub ca_cert: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSummary {
+    pub account: u16,
+    pub chain_id: u64,
+    pub address: Option<String>,
+    pub available: bool,
+    pub supported_erc20_tokens: Vec<String>,
+    pub asset_symbol: Option<String>,
+    pub balance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub async fn run(cli: Cli) -> Result<()> {
+    let path = existing_config_path(cli.config_path())?;
+    let mut server_config = load_server_config(path.as_deref())?;
+    let command = cli.command.unwrap_or(Command::Serve);
+    if matches!(command, Command::Serve) {
+        // the server keeps the decrypted wallets, not the plaintext keys
+        server_config.load_signers()?;
+    }
+    let server_config = Arc::new(server_config);
+    let output = match command {
+        Command::Serve => return serve(server_config, path).await,
+        Command::Config(ConfigCommand::Check) => check_config(server_config).await?,
+        Command::Accounts => serde_json::to_value(list_accounts(server_config).await?)?,
+        Command::Tx(TxCommand::List(args)) => serde_json::to_value(list_transactions(&server_config, &args).await?)?,
+        Command::Tx(TxCommand::Show { id }) => serde_json::to_value(show_transaction(&server_config, &id).await?)?,
+        Command::Tx(TxCommand::Requeue(args)) => requeue_transaction(&server_config, &args).await?,
+        Command::Db(DbCommand::Migrate) => migrate_database(&server_config).await?,
+    };
+    // json on stdout so the output can be piped into other tools
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+pub async fn serve(server_config: Arc<ServerConfig>, path: Option<String>) -> Result<()> {
+    let mut options: ApplicationOptions<SqlStatementFormatter, SqliteStorage> =
+        ApplicationOptions::<SqlStatementFormatter, SqliteStorage>::from_server_config(server_config).await?;
+    options.server_config_path = path;
+    run_application(options).await
+}
+
+pub async fn check_config(server_config: Arc<ServerConfig>) -> Result<Value> {
+    let (relayer_config, mystiko_config) = create_config(server_config.clone()).await?;
+    server_config.validation(&relayer_config, &mystiko_config)?;
+    // stdout only carries the json output
+    for warning in server_config.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(serde_json::to_value(server_config.redacted())?)
+}
+
+// a failing account is reported in its summary, the others are still listed
+pub async fn list_accounts(server_config: Arc<ServerConfig>) -> Result<Vec<AccountSummary>> {
+    let (relayer_config, mystiko_config) = create_config(server_config.clone()).await?;
+    let provider_options = RelayerProviderOptions::builder()
+        .server_config(server_config.clone())
+        .mystiko_config(mystiko_config)
+        .build();
+    let providers: ProviderPool<RelayerProviderOptions> = ProviderPool::builder()
+        .chain_providers_options(provider_options)
+        .build();
+
+    let accounts = server_config.accounts.iter().collect::<BTreeMap<_, _>>();
+    let mut summaries = Vec::new();
+    for (key, account) in accounts {
+        let mut supported_erc20_tokens = account.supported_erc20_tokens.values().cloned().collect::<Vec<_>>();
+        supported_erc20_tokens.sort();
+        let mut summary = AccountSummary {
+            account: *key,
+            chain_id: account.chain_id,
+            address: None,
+            available: account.available,
+            supported_erc20_tokens,
+            asset_symbol: relayer_config
+                .find_chain_config(account.chain_id)
+                .map(|chain_config| chain_config.asset_symbol().to_string()),
+            balance: None,
+            error: None,
+        };
+        match account.address() {
+            Ok(address) => {
+                match account_balance(&providers, account.chain_id, &address).await {
+                    Ok(balance) => summary.balance = Some(balance),
+                    Err(error) => summary.error = Some(format!("get balance got error: {}", error)),
+                }
+                summary.address = Some(address);
+            }
+            Err(error) => summary.error = Some(format!("derive address got error: {}", error)),
+        }
+        summaries.push(summary);
+    }
+    Ok(summaries)
+}
+
+pub async fn list_transactions(server_config: &ServerConfig, args: &TxListArgs) -> Result<TransactionListResponse> {
+    let handler = Transaction::new(Arc::new(open_database(&server_config.settings).await?));
+    let options = QueryTransactionOptions {
+        chain_id: args.chain_id,
+        status: args.status.clone(),
+        spend_type: None,
+        asset_symbol: None,
+        created_after: None,
+        created_before: None,
+        updated_after: None,
+        error_message: None,
+        sort_by: Default::default(),
+        order: Default::default(),
+        offset: Some(args.offset),
+        limit: Some(args.limit),
+    };
+    let total = handler.count(&options).await?;
+    let transactions = handler.find(&options).await?;
+    Ok(TransactionListResponse {
+        total,
+        offset: args.offset,
+        limit: args.limit,
+        transactions: transactions.into_iter().map(TransactionResponse::from).collect(),
+    })
+}
+
+pub async fn show_transaction(server_config: &ServerConfig, id: &str) -> Result<TransactionResponse> {
+    let handler = Transaction::new(Arc::new(open_database(&server_config.settings).await?));
+    match handler.find_by_id(id).await? {
+        Some(transaction) => Ok(TransactionResponse::from(transaction)),
+        None => bail!("transaction {} not found", id),
+    }
+}
+
+// the queues live in the server process, so a requeue has to go through its admin api
+pub async fn requeue_transaction(server_config: &ServerConfig, args: &TxRequeueArgs) -> Result<Value> {
+    let token = admin_token(&server_config.admin, args.operator.as_deref())?;
+    let url = args
+        .url
+        .clone()
+        .unwrap_or_else(|| default_server_url(&server_config.settings));
+    let url = Url::parse(&format!(
+        "{}{}/transactions/{}/requeue",
+        url.trim_end_matches('/'),
+        ADMIN_PATH_PREFIX,
+        args.id
+    ))?;
+    let body = serde_json::to_vec(&json!({ "reason": args.reason }))?;
+    let mut request = admin_client(args)?
+        .post(url.clone())
+        .bearer_auth(token)
+        .header(CONTENT_TYPE, "application/json");
+    if let Some((name, key)) = admin_api_key(&server_config.auth) {
+        // signed like any other api client, a captured request can not be replayed
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let nonce = hex::encode(rand::random::<[u8; 16]>());
+        let path_and_query = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let signature = sign_request(key, timestamp, &nonce, "POST", &path_and_query, &body);
+        request = request
+            .header(API_KEY_ID_HEADER, name)
+            .header(API_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(API_NONCE_HEADER, nonce)
+            .header(API_SIGNATURE_HEADER, signature);
+    }
+    let response = request.body(body).send().await?;
+    let status = response.status();
+    let body = response.json::<Value>().await?;
+    if !status.is_success() {
+        bail!("requeue transaction {} got status {}: {}", args.id, status, body);
+    }
+    Ok(body)
+}
+
+pub async fn migrate_database(server_config: &ServerConfig) -> Result<Value> {
+    let Some(path) = &server_config.settings.sqlite_db_path else {
+        bail!("settings.sqlite_db_path is not set, the server uses an in-memory database");
+    };
+    init_sqlite_database(Some(path.clone())).await?;
+    Ok(json!({ "sqliteDbPath": path, "migrated": true }))
+}
+
+fn existing_config_path(path: &str) -> Result<Option<String>> {
+    if Path::new(path).try_exists()? {
+        Ok(Some(path.to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+// read commands open the file read-only, they must not create an empty database next to the real one
+// or migrate the one the server is using
+async fn open_database(settings: &Settings) -> Result<Database<SqlStatementFormatter, SqliteStorage>> {
+    let Some(path) = &settings.sqlite_db_path else {
+        bail!("settings.sqlite_db_path is not set, the server uses an in-memory database");
+    };
+    if !Path::new(path).is_file() {
+        bail!("sqlite db {} does not exist", path);
+    }
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await?;
+    Ok(Database::new(
+        SqlStatementFormatter::sqlite(),
+        SqliteStorage::from(pool),
+    ))
+}
+
+async fn account_balance(
+    providers: &ProviderPool<RelayerProviderOptions>,
+    chain_id: u64,
+    address: &str,
+) -> Result<String> {
+    let provider = providers.get_provider(chain_id).await?;
+    let balance = provider.get_balance(Address::from_str(address)?, None).await?;
+    Ok(format_ether(balance))
+}
+
+fn admin_token<'a>(admin: &'a AdminConfig, operator: Option<&str>) -> Result<&'a str> {
+    if !admin.enabled {
+        bail!("admin api is disabled in the server config");
+    }
+    if let Some(operator) = operator {
+        return match admin.tokens.get(operator) {
+            Some(token) => Ok(token.as_str()),
+            None => bail!("admin operator {} not found in the server config", operator),
+        };
+    }
+    match admin.tokens.values().collect::<Vec<_>>().as_slice() {
+        [token] => Ok(token.as_str()),
+        tokens => bail!(
+            "{} admin operators configured, choose one with --operator",
+            tokens.len()
+        ),
+    }
+}
+
+fn admin_api_key(auth: &AuthConfig) -> Option<(&str, &str)> {
+    if !auth.enabled {
+        return None;
+    }
+    let keys = auth.keys.iter().collect::<BTreeMap<_, _>>();
+    keys.into_iter()
+        .find(|(_, key)| key.scope == ApiKeyScope::Admin)
+        .map(|(name, key)| (name.as_str(), key.key.as_str()))
+}
+
+// the admin endpoints may require a client certificate, see settings.tls.admin_client_ca_path
+fn admin_client(args: &TxRequeueArgs) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(ca_cert) = &args.ca_cert {
+        let pem = std::fs::read(ca_cert).with_context(|| format!("read ca certificate {}", ca_cert))?;
+        builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+    }
+    if let (Some(client_cert), Some(client_key)) = (&args.client_cert, &args.client_key) {
+        let mut pem = std::fs::read(client_cert).with_context(|| format!("read client certificate {}", client_cert))?;
+        pem.push(b'\n');
+        pem.extend(std::fs::read(client_key).with_context(|| format!("read client key {}", client_key))?);
+        builder = builder.identity(Identity::from_pem(&pem)?);
+    }
+    Ok(builder.build()?)
+}
+
+fn default_server_url(settings: &Settings) -> String {
+    let scheme = if settings.tls.is_some() { "https" } else { "http" };
+    let host = if settings.host == "0.0.0.0" {
+        "127.0.0.1"
+    } else {
+        &settings.host
+    };
+    format!("{}://{}:{}", scheme, host, settings.port)
+}
+
+fn parse_status(status: &str) -> Result<TransactStatus, String> {
+    serde_json::from_value(Value::String(status.to_string())).map_err(|error| error.to_string())
+}
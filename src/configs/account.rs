@@ -1,16 +1,42 @@
+use crate::signer::{AccountSigner, RemoteSigner};
+use anyhow::{bail, Context, Result};
+use ethers_signers::{LocalWallet, Signer};
+use mystiko_validator::validate::is_ethereum_address;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 use typed_builder::TypedBuilder;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+use zeroize::{Zeroize, Zeroizing};
 
-#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[builder(field_defaults(setter(into)))]
+#[validate(schema(function = "is_valid_signer"))]
 pub struct AccountConfig {
     #[builder(default)]
     #[validate(range(min = 1))]
     pub chain_id: u64,
+    // plaintext hex key, prefer keystore_path
+    #[serde(default)]
     #[builder(default)]
     pub private_key: String,
+    // encrypted json keystore (web3 secret storage), used instead of private_key
+    #[serde(default)]
+    #[builder(default)]
+    pub keystore_path: Option<String>,
+    // file holding the keystore password, trailing newlines are ignored
+    #[serde(default)]
+    #[builder(default)]
+    pub keystore_password_file: Option<String>,
+    // name of the environment variable holding the keystore password
+    #[serde(default)]
+    #[builder(default)]
+    pub keystore_password_env: Option<String>,
+    // sign through a remote signing service, the key stays in that service
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub remote_signer: Option<RemoteSignerConfig>,
     #[serde(default = "default_available")]
     #[builder(default = default_available())]
     pub available: bool,
@@ -22,8 +48,164 @@ pub struct AccountConfig {
     #[serde(default)]
     #[builder(default)]
     pub balance_check_interval_ms: u64,
+    // set by load_signer, the key is decrypted once and private_key is cleared
+    #[serde(skip)]
+    #[builder(default, setter(skip))]
+    loaded: Option<LoadedSigner>,
+}
+
+#[derive(Clone)]
+struct LoadedSigner {
+    wallet: LocalWallet,
+    address: String,
+}
+
+impl PartialEq for LoadedSigner {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+pub struct RemoteSignerConfig {
+    // json-rpc endpoint serving eth_accounts, eth_sign and eth_signTransaction
+    #[validate(url)]
+    pub url: String,
+    #[validate(custom = "is_ethereum_address")]
+    pub address: String,
+    #[serde(default = "default_remote_signer_timeout_ms")]
+    #[builder(default = default_remote_signer_timeout_ms())]
+    #[validate(range(min = 100))]
+    pub timeout_ms: u64,
+    // extra http headers sent with every request, e.g. an authorization token
+    #[serde(default)]
+    #[builder(default)]
+    pub headers: HashMap<String, String>,
+}
+
+impl AccountConfig {
+    pub fn signer(&self) -> Result<AccountSigner> {
+        match &self.remote_signer {
+            Some(remote_signer) => Ok(AccountSigner::Remote(RemoteSigner::new(remote_signer)?)),
+            None => Ok(AccountSigner::Local(self.wallet()?)),
+        }
+    }
+
+    // decrypts the key once for the life of the config, the plaintext private_key is zeroized
+    pub fn load_signer(&mut self) -> Result<()> {
+        if self.remote_signer.is_some() || self.loaded.is_some() {
+            return Ok(());
+        }
+        let wallet = self.decrypt_wallet()?;
+        let address = format!("0x{}", hex::encode(wallet.address()));
+        self.loaded = Some(LoadedSigner { wallet, address });
+        self.private_key.zeroize();
+        Ok(())
+    }
+
+    pub fn wallet(&self) -> Result<LocalWallet> {
+        if self.remote_signer.is_some() {
+            bail!("account of chain id {} signs remotely, no local wallet", self.chain_id);
+        }
+        match &self.loaded {
+            Some(loaded) => Ok(loaded.wallet.clone()),
+            None => self.decrypt_wallet(),
+        }
+    }
+
+    pub fn address(&self) -> Result<String> {
+        if let Some(remote_signer) = &self.remote_signer {
+            return Ok(remote_signer.address.to_lowercase());
+        }
+        if let Some(loaded) = &self.loaded {
+            return Ok(loaded.address.clone());
+        }
+        Ok(format!("0x{}", hex::encode(self.decrypt_wallet()?.address())))
+    }
+
+    // a plaintext key, also when it was already loaded and cleared
+    pub fn has_private_key(&self) -> bool {
+        !self.private_key.is_empty() || (self.keystore_path.is_none() && self.loaded.is_some())
+    }
+
+    // the decrypted key bytes are zeroized once the wallet is built
+    fn decrypt_wallet(&self) -> Result<LocalWallet> {
+        let secret = match &self.keystore_path {
+            Some(keystore_path) => {
+                let password = self.keystore_password()?;
+                Zeroizing::new(
+                    eth_keystore::decrypt_key(keystore_path, password.as_bytes())
+                        .with_context(|| format!("decrypt keystore {}", keystore_path))?,
+                )
+            }
+            None => Zeroizing::new(hex::decode(self.private_key.trim().trim_start_matches("0x"))?),
+        };
+        Ok(LocalWallet::from_bytes(&secret)?)
+    }
+
+    fn keystore_password(&self) -> Result<Zeroizing<String>> {
+        if let Some(path) = &self.keystore_password_file {
+            let password = Zeroizing::new(
+                std::fs::read_to_string(path).with_context(|| format!("read keystore password file {}", path))?,
+            );
+            return Ok(Zeroizing::new(password.trim_end_matches(['\r', '\n']).to_string()));
+        }
+        if let Some(name) = &self.keystore_password_env {
+            return Ok(Zeroizing::new(
+                std::env::var(name).with_context(|| format!("read keystore password env {}", name))?,
+            ));
+        }
+        bail!("keystore password file or env is required")
+    }
+}
+
+// keeps keys and password sources out of logs
+impl Debug for AccountConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountConfig")
+            .field("chain_id", &self.chain_id)
+            .field(
+                "private_key",
+                &if self.private_key.is_empty() { "" } else { "<redacted>" },
+            )
+            .field("keystore_path", &self.keystore_path)
+            .field(
+                "remote_signer",
+                &self.remote_signer.as_ref().map(|signer| &signer.address),
+            )
+            .field("available", &self.available)
+            .field("supported_erc20_tokens", &self.supported_erc20_tokens)
+            .field("balance_alarm_threshold", &self.balance_alarm_threshold)
+            .field("balance_check_interval_ms", &self.balance_check_interval_ms)
+            .finish()
+    }
+}
+
+fn is_valid_signer(account: &AccountConfig) -> Result<(), ValidationError> {
+    if account.remote_signer.is_some() {
+        return if account.keystore_path.is_none() && !account.has_private_key() {
+            Ok(())
+        } else {
+            Err(ValidationError::new(
+                "remote_signer excludes private_key and keystore_path",
+            ))
+        };
+    }
+    match (&account.keystore_path, !account.has_private_key()) {
+        (None, true) => Err(ValidationError::new("private_key or keystore_path is required")),
+        (Some(_), false) => Err(ValidationError::new("private_key and keystore_path are exclusive")),
+        (Some(_), true) if account.keystore_password_file.is_none() && account.keystore_password_env.is_none() => Err(
+            ValidationError::new("keystore_password_file or keystore_password_env is required"),
+        ),
+        _ => Ok(()),
+    }
 }
 
 fn default_available() -> bool {
     true
 }
+
+fn default_remote_signer_timeout_ms() -> u64 {
+    10000
+}
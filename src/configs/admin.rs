@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use typed_builder::TypedBuilder;
+use validator::Validate;
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+pub struct AdminConfig {
+    #[serde(default)]
+    #[builder(default)]
+    pub enabled: bool,
+    // operator name -> bearer token
+    #[serde(default)]
+    #[builder(default)]
+    pub tokens: HashMap<String, String>,
+}
+
+impl AdminConfig {
+    pub fn find_operator(&self, token: &str) -> Option<&str> {
+        if !self.enabled {
+            return None;
+        }
+        self.tokens
+            .iter()
+            .find(|(_, expected)| !expected.is_empty() && constant_time_eq(expected.as_bytes(), token.as_bytes()))
+            .map(|(operator, _)| operator.as_str())
+    }
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,83 @@
+use crate::configs::admin::constant_time_eq;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use typed_builder::TypedBuilder;
+use validator::Validate;
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+pub struct AuthConfig {
+    #[serde(default)]
+    #[builder(default)]
+    pub enabled: bool,
+    // accepted distance between a signed request timestamp and the server clock
+    #[serde(default = "default_max_clock_skew_secs")]
+    #[builder(default = default_max_clock_skew_secs())]
+    pub max_clock_skew_secs: u64,
+    // usage counters are kept in memory and written to the database this often
+    #[validate(range(min = 1000))]
+    #[serde(default = "default_usage_flush_interval_ms")]
+    #[builder(default = default_usage_flush_interval_ms())]
+    pub usage_flush_interval_ms: u64,
+    // key name -> key
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub keys: HashMap<String, ApiKeyConfig>,
+}
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+pub struct ApiKeyConfig {
+    // sent as is in the X-Api-Key header, or used as the HMAC secret of signed requests
+    #[validate(length(min = 16))]
+    pub key: String,
+    #[serde(default)]
+    #[builder(default)]
+    pub scope: ApiKeyScope,
+}
+
+#[derive(Serialize, Deserialize, Debug, Display, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyScope {
+    #[default]
+    #[display(fmt = "public")]
+    Public,
+    #[display(fmt = "admin")]
+    Admin,
+}
+
+impl ApiKeyScope {
+    pub fn allows(&self, required: ApiKeyScope) -> bool {
+        *self == ApiKeyScope::Admin || required == ApiKeyScope::Public
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig::builder().build()
+    }
+}
+
+impl AuthConfig {
+    pub fn find_by_key(&self, key: &str) -> Option<(&str, &ApiKeyConfig)> {
+        self.keys
+            .iter()
+            .find(|(_, config)| !config.key.is_empty() && constant_time_eq(config.key.as_bytes(), key.as_bytes()))
+            .map(|(name, config)| (name.as_str(), config))
+    }
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn default_max_clock_skew_secs() -> u64 {
+    300
+}
+
+fn default_usage_flush_interval_ms() -> u64 {
+    10_000
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use log::{info, warn};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use typed_builder::TypedBuilder;
+
+pub const DEFAULT_RELAYER_REMOTE_CONFIG_BASE_URL: &str = "https://static.mystiko.network/relayer_config";
+pub const DEFAULT_MYSTIKO_REMOTE_CONFIG_BASE_URL: &str = "https://static.mystiko.network/config";
+// a hanging config host falls back to the cached copy after this long
+pub const DEFAULT_REMOTE_CONFIG_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(TypedBuilder, Debug, Clone)]
+#[builder(field_defaults(setter(into)))]
+pub struct RemoteConfigSource {
+    // file name prefix in the cache directory, e.g. relayer_config
+    pub name: String,
+    pub base_url: String,
+    pub cache_dir: PathBuf,
+    #[builder(default)]
+    pub is_staging: bool,
+    #[builder(default)]
+    pub is_testnet: bool,
+    #[builder(default = DEFAULT_REMOTE_CONFIG_FETCH_TIMEOUT)]
+    pub fetch_timeout: Duration,
+}
+
+impl RemoteConfigSource {
+    // the layout the config libraries fetch from without a cache, see test_create_config_from_remote
+    pub fn url(&self) -> String {
+        format!(
+            "{}/{}/{}/latest.json",
+            self.base_url.trim_end_matches('/'),
+            self.environment(),
+            self.network()
+        )
+    }
+
+    pub fn cache_path(&self) -> PathBuf {
+        self.cache_dir
+            .join(format!("{}_{}_{}.json", self.name, self.environment(), self.network()))
+    }
+
+    fn environment(&self) -> &str {
+        if self.is_staging {
+            "staging"
+        } else {
+            "production"
+        }
+    }
+
+    fn network(&self) -> &str {
+        if self.is_testnet {
+            "testnet"
+        } else {
+            "mainnet"
+        }
+    }
+}
+
+// fetch the remote config and keep a copy of it, falls back to the copy when the fetch fails
+pub async fn load_remote_config<T, F, Fut>(source: &RemoteConfigSource, load: F) -> Result<T>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let cache_path = source.cache_path();
+    let error = match fetch_remote_config(source, &cache_path, &load).await {
+        Ok(config) => return Ok(config),
+        Err(error) => error,
+    };
+    if !cache_path.exists() {
+        return Err(error.context(format!("no cached {} at {}", source.name, cache_path.display())));
+    }
+    warn!(
+        "fetch {} from {} got error: {:?}, falling back to cached copy {}",
+        source.name,
+        source.url(),
+        error,
+        cache_path.display()
+    );
+    load(cache_path.to_string_lossy().to_string()).await
+}
+
+async fn fetch_remote_config<T, F, Fut>(source: &RemoteConfigSource, cache_path: &Path, load: &F) -> Result<T>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let url = source.url();
+    let client = reqwest::Client::builder().timeout(source.fetch_timeout).build()?;
+    let content = client.get(&url).send().await?.error_for_status()?.bytes().await?;
+
+    // only a config that loads replaces the cached copy
+    tokio::fs::create_dir_all(&source.cache_dir).await?;
+    let temp_path = cache_path.with_extension("json.tmp");
+    tokio::fs::write(&temp_path, &content).await?;
+    match load(temp_path.to_string_lossy().to_string()).await {
+        Ok(config) => {
+            tokio::fs::rename(&temp_path, cache_path).await?;
+            info!(
+                "fetched {} from {} and cached at {}",
+                source.name,
+                url,
+                cache_path.display()
+            );
+            Ok(config)
+        }
+        Err(error) => {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            Err(error)
+        }
+    }
+}
@@ -4,13 +4,13 @@ use std::collections::HashMap;
 use typed_builder::TypedBuilder;
 use validator::Validate;
 
-#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ChainConfig {
     pub provider_config: Option<ProviderConfig>,
     pub signer_endpoint: Option<String>,
 }
 
-#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ProviderConfig {
     pub urls: HashMap<u16, String>,
     #[serde(default = "default_provider_type")]
@@ -0,0 +1,130 @@
+use actix_web::http::header::HeaderName;
+use actix_web::http::Method;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use validator::{Validate, ValidationError};
+
+pub const ANY: &str = "*";
+
+// the defaults keep the policy the server always had: any origin, GET/POST and Content-Type
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+#[validate(schema(function = "is_valid_credentials"))]
+pub struct CorsConfig {
+    // exact origins like https://app.mystiko.network, patterns like https://*.mystiko.network, or * for any origin
+    #[serde(default = "default_allowed_origins")]
+    #[builder(default = default_allowed_origins())]
+    #[validate(custom(function = "is_valid_origins"))]
+    pub allowed_origins: Vec<String>,
+    #[serde(default = "default_allowed_methods")]
+    #[builder(default = default_allowed_methods())]
+    #[validate(custom(function = "is_valid_methods"))]
+    pub allowed_methods: Vec<String>,
+    #[serde(default = "default_allowed_headers")]
+    #[builder(default = default_allowed_headers())]
+    #[validate(custom(function = "is_valid_headers"))]
+    pub allowed_headers: Vec<String>,
+    #[serde(default)]
+    #[builder(default)]
+    pub allow_credentials: bool,
+    // how long browsers may cache a preflight response
+    #[serde(default)]
+    #[builder(default)]
+    pub max_age_secs: Option<usize>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig::builder().build()
+    }
+}
+
+impl CorsConfig {
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == ANY)
+    }
+
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|pattern| origin_matches(pattern, origin))
+    }
+}
+
+// case-insensitive match where each * stands for any run of characters
+pub fn origin_matches(pattern: &str, origin: &str) -> bool {
+    let pattern = pattern.trim_end_matches('/').to_lowercase();
+    let origin = origin.to_lowercase();
+    let parts = pattern.split(ANY).collect::<Vec<_>>();
+    if parts.len() == 1 {
+        return pattern == origin;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if origin.len() < first.len() + last.len() || !origin.starts_with(first) || !origin.ends_with(last) {
+        return false;
+    }
+    let mut rest = &origin[first.len()..origin.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn is_valid_origins(origins: &Vec<String>) -> Result<(), ValidationError> {
+    let valid = origins
+        .iter()
+        .all(|origin| origin == ANY || origin.starts_with("http://") || origin.starts_with("https://"));
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new(
+            "origins must be * or start with http:// or https://",
+        ))
+    }
+}
+
+fn is_valid_methods(methods: &Vec<String>) -> Result<(), ValidationError> {
+    if methods
+        .iter()
+        .all(|method| method == ANY || Method::from_bytes(method.to_uppercase().as_bytes()).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid http method"))
+    }
+}
+
+fn is_valid_headers(headers: &Vec<String>) -> Result<(), ValidationError> {
+    if headers
+        .iter()
+        .all(|header| header == ANY || HeaderName::from_bytes(header.as_bytes()).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid http header name"))
+    }
+}
+
+fn is_valid_credentials(config: &CorsConfig) -> Result<(), ValidationError> {
+    // browsers refuse credentials for a wildcard origin, list the origins explicitly instead
+    if config.allow_credentials && config.allows_any_origin() {
+        Err(ValidationError::new("allow_credentials can not be used with origin *"))
+    } else {
+        Ok(())
+    }
+}
+
+fn default_allowed_origins() -> Vec<String> {
+    vec![ANY.to_string()]
+}
+
+fn default_allowed_methods() -> Vec<String> {
+    vec!["GET".to_string(), "POST".to_string()]
+}
+
+fn default_allowed_headers() -> Vec<String> {
+    vec!["content-type".to_string()]
+}
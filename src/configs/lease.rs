@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use validator::{Validate, ValidationError};
+
+// per account leases in the shared database, only the holder drives the signer
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+#[validate(schema(function = "is_valid_heartbeat"))]
+pub struct LeaseConfig {
+    #[serde(default)]
+    #[builder(default)]
+    pub enabled: bool,
+    // owner name stored with the leases, a random one is picked at startup when not set
+    #[serde(default)]
+    #[builder(default)]
+    #[validate(length(min = 1, max = 128))]
+    pub instance_id: Option<String>,
+    #[serde(default = "default_ttl_ms")]
+    #[builder(default = default_ttl_ms())]
+    #[validate(range(min = 1000))]
+    pub ttl_ms: u64,
+    #[serde(default = "default_heartbeat_interval_ms")]
+    #[builder(default = default_heartbeat_interval_ms())]
+    #[validate(range(min = 100))]
+    pub heartbeat_interval_ms: u64,
+    // an expired lease is taken over only this long after its expiry, the largest clock skew between instances
+    #[serde(default = "default_max_clock_skew_ms")]
+    #[builder(default = default_max_clock_skew_ms())]
+    pub max_clock_skew_ms: u64,
+}
+
+impl Default for LeaseConfig {
+    fn default() -> Self {
+        LeaseConfig::builder().build()
+    }
+}
+
+// a lease is only trusted until one heartbeat before it expires, so renewals need some slack
+fn is_valid_heartbeat(config: &LeaseConfig) -> Result<(), ValidationError> {
+    if config.heartbeat_interval_ms.saturating_mul(2) > config.ttl_ms {
+        return Err(ValidationError::new(
+            "heartbeat_interval_ms must be at most half of ttl_ms",
+        ));
+    }
+    Ok(())
+}
+
+fn default_ttl_ms() -> u64 {
+    30000
+}
+
+fn default_heartbeat_interval_ms() -> u64 {
+    10000
+}
+
+fn default_max_clock_skew_ms() -> u64 {
+    5000
+}
@@ -1,6 +1,19 @@
 pub mod account;
+pub mod admin;
+pub mod auth;
+pub mod cache;
 pub mod chain;
+pub mod cors;
+pub mod lease;
+pub mod price_cache;
+pub mod quote;
+pub mod rate_limit;
+pub mod reload;
+pub mod remote;
 pub mod server;
+pub mod tls;
+pub mod validation;
+pub mod webhook;
 
 use crate::configs::server::ServerConfig;
 use anyhow::Result;
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use validator::{Validate, ValidationError};
+
+// gas prices per chain and token prices per symbol, shared by the api and the consumers
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+#[validate(schema(function = "is_valid_refresh_interval"))]
+pub struct PriceCacheConfig {
+    // a zero ttl disables the cache, every call queries the provider
+    #[serde(default = "default_gas_price_ttl_ms")]
+    #[builder(default = default_gas_price_ttl_ms())]
+    #[validate(range(max = 600000))]
+    pub gas_price_ttl_ms: u64,
+    // a zero ttl disables the cache, every swap queries the price service
+    #[serde(default = "default_token_price_ttl_ms")]
+    #[builder(default = default_token_price_ttl_ms())]
+    #[validate(range(max = 3600000))]
+    pub token_price_ttl_ms: u64,
+    // cached entries are refreshed in the background at this interval, before they expire
+    #[serde(default = "default_refresh_interval_ms")]
+    #[builder(default = default_refresh_interval_ms())]
+    #[validate(range(min = 1000))]
+    pub refresh_interval_ms: u64,
+}
+
+impl Default for PriceCacheConfig {
+    fn default() -> Self {
+        PriceCacheConfig::builder().build()
+    }
+}
+
+// the background refresh has to come before an enabled cache expires, otherwise requests refresh it themselves
+fn is_valid_refresh_interval(config: &PriceCacheConfig) -> Result<(), ValidationError> {
+    let ttls = [config.gas_price_ttl_ms, config.token_price_ttl_ms];
+    if ttls
+        .iter()
+        .any(|ttl_ms| *ttl_ms > 0 && config.refresh_interval_ms >= *ttl_ms)
+    {
+        return Err(ValidationError::new(
+            "refresh_interval_ms must be less than the enabled gas_price_ttl_ms and token_price_ttl_ms",
+        ));
+    }
+    Ok(())
+}
+
+fn default_gas_price_ttl_ms() -> u64 {
+    15000
+}
+
+fn default_token_price_ttl_ms() -> u64 {
+    60000
+}
+
+fn default_refresh_interval_ms() -> u64 {
+    10000
+}
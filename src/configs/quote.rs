@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use validator::{Validate, ValidationError};
+
+// signed minimum fee quotes of /info, honoured by /transact until they expire
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+#[validate(schema(function = "is_valid_quote"))]
+pub struct QuoteConfig {
+    #[serde(default)]
+    #[builder(default)]
+    pub enabled: bool,
+    // hmac key of the quote signatures, shared by every instance
+    #[serde(default)]
+    #[builder(default)]
+    pub secret: String,
+    #[serde(default = "default_ttl_ms")]
+    #[builder(default = default_ttl_ms())]
+    #[validate(range(min = 1000, max = 3600000))]
+    pub ttl_ms: u64,
+    // how much more than the quoted relayer fee the transaction may cost when it is sent
+    #[serde(default = "default_risk_limit_percent")]
+    #[builder(default = default_risk_limit_percent())]
+    #[validate(range(max = 100))]
+    pub risk_limit_percent: u32,
+}
+
+impl Default for QuoteConfig {
+    fn default() -> Self {
+        QuoteConfig::builder().build()
+    }
+}
+
+fn is_valid_quote(config: &QuoteConfig) -> Result<(), ValidationError> {
+    if config.enabled && config.secret.len() < 16 {
+        return Err(ValidationError::new(
+            "secret of at least 16 characters is required when quotes are enabled",
+        ));
+    }
+    Ok(())
+}
+
+fn default_ttl_ms() -> u64 {
+    120000
+}
+
+fn default_risk_limit_percent() -> u32 {
+    20
+}
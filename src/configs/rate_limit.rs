@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use validator::Validate;
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    #[builder(default)]
+    pub enabled: bool,
+    // identify clients without an api key by the X-Forwarded-For/Forwarded headers instead of the peer address
+    #[serde(default)]
+    #[builder(default)]
+    pub trust_forwarded_for: bool,
+    // bucket of every api key, or ip address for unauthenticated clients
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub client: Option<TokenBucketConfig>,
+    // bucket of every chain id per client
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub chain: Option<TokenBucketConfig>,
+}
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+pub struct TokenBucketConfig {
+    #[validate(range(min = 1))]
+    pub capacity: u32,
+    #[validate(range(min = 0.001))]
+    pub refill_per_second: f64,
+}
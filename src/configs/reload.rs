@@ -0,0 +1,281 @@
+use crate::channel::{update_sender, SenderSet};
+use crate::configs::account::AccountConfig;
+use crate::configs::chain::ChainConfig;
+use crate::configs::load_server_config;
+use crate::configs::server::ServerConfig;
+use crate::configs::validation::log_server_config_warnings;
+use crate::context::Context;
+use crate::handler::account::UpdateAccountOptions;
+use crate::logger::set_log_level;
+use crate::provider::{RelayerProviderOptions, RelayerSignerOptions};
+use anyhow::{bail, Result};
+use log::{error, info, warn, LevelFilter};
+use mystiko_ethers::{Provider, ProviderPool, Providers};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Debug)]
+pub struct ConfigReloader {
+    path: Option<String>,
+    context: Arc<Context>,
+    senders: Arc<SenderSet>,
+    // one reload at a time, the applied config is swapped into the context
+    reloading: Mutex<()>,
+}
+
+impl ConfigReloader {
+    pub fn new(path: Option<String>, context: Arc<Context>, senders: Arc<SenderSet>) -> Self {
+        ConfigReloader {
+            path,
+            context,
+            senders,
+            reloading: Mutex::new(()),
+        }
+    }
+
+    pub fn current(&self) -> Arc<ServerConfig> {
+        self.context.server_config.load()
+    }
+
+    pub async fn reload(&self) -> Result<()> {
+        let _reloading = self.reloading.lock().await;
+        let current = self.context.server_config.load();
+        let mut server_config = load_server_config(self.path.as_deref())?;
+        server_config.validation(&self.context.relayer_config.load(), &self.context.mystiko_config.load())?;
+        log_server_config_warnings(&server_config);
+        server_config.load_signers()?;
+        let server_config = Arc::new(server_config);
+
+        let changes = restart_required_changes(&current, &server_config);
+        if !changes.is_empty() {
+            bail!("changes of {} require a restart", changes.join(", "));
+        }
+        let log_level = LevelFilter::from_str(&server_config.settings.log_level)?;
+
+        // build everything that can fail before applying anything
+        let providers = self.create_providers(&current, &server_config).await?;
+        let signer_providers = self.create_signer_providers(&current, &server_config).await?;
+        let accounts = account_changes(&current, &server_config)?;
+
+        for (chain_id, provider) in providers {
+            info!("reload providers of chain id {}", chain_id);
+            self.context.providers.set_provider(chain_id, provider).await;
+        }
+        for (chain_id, provider) in signer_providers {
+            info!("reload signer provider of chain id {}", chain_id);
+            self.context.signer_providers.set_provider(chain_id, provider).await;
+        }
+        for (address, account) in accounts {
+            self.apply_account(&address, account).await;
+        }
+        if current.settings.log_level != server_config.settings.log_level {
+            info!("change log level to {}", log_level);
+            set_log_level(log_level);
+        }
+
+        self.context.server_config.store(server_config);
+        info!("reload server config successful");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    pub async fn watch(self) {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(error) => {
+                error!("listen to SIGHUP got error: {:?}", error);
+                return;
+            }
+        };
+        info!("send SIGHUP to reload server config");
+        while hangup.recv().await.is_some() {
+            info!("SIGHUP received, reloading server config");
+            if let Err(error) = self.reload().await {
+                error!("reload server config refused: {}", error);
+            }
+        }
+    }
+
+    async fn create_providers(
+        &self,
+        current: &ServerConfig,
+        server_config: &Arc<ServerConfig>,
+    ) -> Result<HashMap<u64, Arc<Provider>>> {
+        let provider_options = RelayerProviderOptions::builder()
+            .mystiko_config(self.context.mystiko_config.load())
+            .server_config(server_config.clone())
+            .build();
+        let pool: ProviderPool<RelayerProviderOptions> = ProviderPool::builder()
+            .chain_providers_options(provider_options)
+            .build();
+        let mut providers = HashMap::new();
+        for chain_id in changed_chains(current, server_config, |chain| chain.provider_config.clone()) {
+            providers.insert(chain_id, pool.get_provider(chain_id).await?);
+        }
+        Ok(providers)
+    }
+
+    async fn create_signer_providers(
+        &self,
+        current: &ServerConfig,
+        server_config: &Arc<ServerConfig>,
+    ) -> Result<HashMap<u64, Arc<Provider>>> {
+        let signer_options = RelayerSignerOptions::builder()
+            .mystiko_config(self.context.mystiko_config.load())
+            .server_config(server_config.clone())
+            .build();
+        let pool: ProviderPool<RelayerSignerOptions> =
+            ProviderPool::builder().chain_providers_options(signer_options).build();
+        let mut providers = HashMap::new();
+        for chain_id in changed_chains(current, server_config, |chain| chain.signer_endpoint.clone()) {
+            providers.insert(chain_id, pool.get_provider(chain_id).await?);
+        }
+        Ok(providers)
+    }
+
+    async fn apply_account(&self, address: &str, account: &AccountConfig) {
+        let chain_id = account.chain_id;
+        let tokens = account.supported_erc20_tokens.values().cloned().collect::<Vec<_>>();
+        let updated = update_sender(&self.senders, chain_id, address, |sender| {
+            sender.available = account.available;
+            sender.supported_erc20_tokens = tokens.clone();
+        });
+        if !updated {
+            warn!(
+                "account {} on chain id {} is not active, only its database record is reloaded",
+                address, chain_id
+            );
+        }
+        let options = UpdateAccountOptions::builder()
+            .available(account.available)
+            .supported_erc20_tokens(tokens)
+            .build();
+        match self
+            .context
+            .account_handler
+            .update_by_address(chain_id, address, &options)
+            .await
+        {
+            Ok(_) => info!("reload account {} on chain id {}", address, chain_id),
+            Err(error) => error!("reload account {} got error: {:?}", address, error),
+        }
+    }
+}
+
+pub fn restart_required_changes(current: &ServerConfig, server_config: &ServerConfig) -> Vec<String> {
+    let mut changes = Vec::new();
+    let (settings, new_settings) = (&current.settings, &server_config.settings);
+    if settings.api_version != new_settings.api_version {
+        changes.push("settings.api_version".to_string());
+    }
+    if settings.network_type != new_settings.network_type {
+        changes.push("settings.network_type".to_string());
+    }
+    if settings.sqlite_db_path != new_settings.sqlite_db_path {
+        changes.push("settings.sqlite_db_path".to_string());
+    }
+    if settings.host != new_settings.host || settings.port != new_settings.port {
+        changes.push("settings.host/port".to_string());
+    }
+    if settings.coin_market_cap_api_key != new_settings.coin_market_cap_api_key {
+        changes.push("settings.coin_market_cap_api_key".to_string());
+    }
+    // certificate file contents are reloaded on their own, only the settings need a restart
+    if settings.tls != new_settings.tls {
+        changes.push("settings.tls".to_string());
+    }
+    if settings.cors != new_settings.cors {
+        changes.push("settings.cors".to_string());
+    }
+    if current.options != server_config.options {
+        changes.push("options".to_string());
+    }
+    if current.admin != server_config.admin {
+        changes.push("admin".to_string());
+    }
+    if current.auth != server_config.auth {
+        changes.push("auth".to_string());
+    }
+    if current.rate_limit != server_config.rate_limit {
+        changes.push("rate_limit".to_string());
+    }
+    if current.lease != server_config.lease {
+        changes.push("lease".to_string());
+    }
+    if current.webhook != server_config.webhook {
+        changes.push("webhook".to_string());
+    }
+    if current.quote != server_config.quote {
+        changes.push("quote".to_string());
+    }
+    if current.price_cache != server_config.price_cache {
+        changes.push("price_cache".to_string());
+    }
+
+    let accounts = account_keys(current);
+    let new_accounts = account_keys(server_config);
+    if accounts.keys().collect::<HashSet<_>>() != new_accounts.keys().collect::<HashSet<_>>() {
+        changes.push("accounts (added, removed or signer changed)".to_string());
+    } else if accounts.iter().any(|(key, account)| {
+        let new_account = new_accounts[key];
+        account.balance_alarm_threshold != new_account.balance_alarm_threshold
+            || account.balance_check_interval_ms != new_account.balance_check_interval_ms
+    }) {
+        changes.push("accounts balance check".to_string());
+    }
+    changes
+}
+
+type AccountKey = (u64, Option<String>, Option<String>);
+
+// accounts keyed by chain id, signer address and keystore, loaded signers answer from their cache
+fn account_keys(server_config: &ServerConfig) -> HashMap<AccountKey, &AccountConfig> {
+    server_config
+        .accounts
+        .values()
+        .map(|account| {
+            let key = (account.chain_id, account.address().ok(), account.keystore_path.clone());
+            (key, account)
+        })
+        .collect()
+}
+
+// accounts whose availability or token list changed, keyed by address
+fn account_changes<'a>(
+    current: &ServerConfig,
+    server_config: &'a ServerConfig,
+) -> Result<Vec<(String, &'a AccountConfig)>> {
+    let accounts = account_keys(current);
+    let mut changes = Vec::new();
+    for (key, account) in account_keys(server_config) {
+        let unchanged = accounts.get(&key).map_or(false, |current| {
+            current.available == account.available && current.supported_erc20_tokens == account.supported_erc20_tokens
+        });
+        if !unchanged {
+            changes.push((account.address()?, account));
+        }
+    }
+    Ok(changes)
+}
+
+fn changed_chains<T, F>(current: &ServerConfig, server_config: &ServerConfig, field: F) -> Vec<u64>
+where
+    T: PartialEq,
+    F: Fn(&ChainConfig) -> Option<T>,
+{
+    current
+        .chains
+        .keys()
+        .chain(server_config.chains.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter(|chain_id| {
+            current.chains.get(chain_id).and_then(&field) != server_config.chains.get(chain_id).and_then(&field)
+        })
+        .copied()
+        .collect()
+}
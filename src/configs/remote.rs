@@ -0,0 +1,198 @@
+use crate::configs::server::ServerConfig;
+use crate::context::{create_config, Context};
+use anyhow::Result;
+use log::{info, warn};
+use mystiko_config::MystikoConfig;
+use mystiko_relayer_config::wrapper::relayer::RelayerConfig;
+use mystiko_types::{AssetType, CircuitType};
+use std::collections::BTreeMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+// every circuit type a gas cost may be configured for
+pub const CIRCUIT_TYPES: [CircuitType; 11] = [
+    CircuitType::Rollup1,
+    CircuitType::Rollup2,
+    CircuitType::Rollup4,
+    CircuitType::Rollup8,
+    CircuitType::Rollup16,
+    CircuitType::Transaction1x0,
+    CircuitType::Transaction1x1,
+    CircuitType::Transaction1x2,
+    CircuitType::Transaction2x0,
+    CircuitType::Transaction2x1,
+    CircuitType::Transaction2x2,
+];
+
+// a config that can be swapped at runtime, readers keep the snapshot they loaded
+#[derive(Debug)]
+pub struct SharedConfig<T> {
+    inner: RwLock<Arc<T>>,
+}
+
+impl<T> SharedConfig<T> {
+    pub fn new(config: Arc<T>) -> Self {
+        SharedConfig {
+            inner: RwLock::new(config),
+        }
+    }
+
+    pub fn load(&self) -> Arc<T> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    pub fn store(&self, config: Arc<T>) -> Arc<T> {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *inner, config)
+    }
+}
+
+#[derive(Debug)]
+pub struct RemoteConfigRefresher {
+    context: Arc<Context>,
+    interval: Duration,
+}
+
+impl RemoteConfigRefresher {
+    pub fn new(context: Arc<Context>, interval: Duration) -> Self {
+        RemoteConfigRefresher { context, interval }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        // the first tick completes immediately, the configs were just loaded at startup
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.refresh().await {
+                warn!("refresh remote configs got error, keep the current ones: {:?}", error);
+            }
+        }
+    }
+
+    pub async fn refresh(&self) -> Result<()> {
+        let server_config = self.context.server_config.load();
+        let (relayer_config, mystiko_config) = create_config(server_config.clone()).await?;
+        validate_remote_configs(&server_config, &relayer_config, &mystiko_config)?;
+
+        let previous = self.context.relayer_config.store(relayer_config.clone());
+        let changes = relayer_config_diff(&previous, &relayer_config);
+        if !changes.is_empty() {
+            info!("relayer config refreshed:\n  {}", changes.join("\n  "));
+        }
+        let previous = self.context.mystiko_config.store(mystiko_config.clone());
+        let changes = mystiko_config_diff(&previous, &mystiko_config);
+        if !changes.is_empty() {
+            info!("mystiko config refreshed:\n  {}", changes.join("\n  "));
+        }
+        Ok(())
+    }
+}
+
+pub fn validate_remote_configs(
+    server_config: &ServerConfig,
+    relayer_config: &RelayerConfig,
+    mystiko_config: &MystikoConfig,
+) -> Result<()> {
+    Ok(server_config.validation(relayer_config, mystiko_config)?)
+}
+
+pub fn relayer_config_diff(previous: &RelayerConfig, current: &RelayerConfig) -> Vec<String> {
+    let mut changes = Vec::new();
+    if previous.version() != current.version() {
+        changes.push(format!("version {} -> {}", previous.version(), current.version()));
+    }
+    let previous_values = relayer_config_values(previous);
+    let current_values = relayer_config_values(current);
+    for (key, value) in &current_values {
+        match previous_values.get(key) {
+            None => changes.push(format!("{} added: {}", key, value)),
+            Some(previous_value) if previous_value != value => {
+                changes.push(format!("{}: {} -> {}", key, previous_value, value))
+            }
+            _ => {}
+        }
+    }
+    for key in previous_values.keys() {
+        if !current_values.contains_key(key) {
+            changes.push(format!("{} removed", key));
+        }
+    }
+    changes
+}
+
+pub fn mystiko_config_diff(previous: &MystikoConfig, current: &MystikoConfig) -> Vec<String> {
+    let mut changes = Vec::new();
+    if previous.version() != current.version() {
+        changes.push(format!("version {} -> {}", previous.version(), current.version()));
+    }
+    let previous_pools = mystiko_pool_addresses(previous);
+    let current_pools = mystiko_pool_addresses(current);
+    for (chain_id, pools) in &current_pools {
+        match previous_pools.get(chain_id) {
+            None => changes.push(format!("chain {} added", chain_id)),
+            Some(previous) => {
+                for pool in pools.iter().filter(|pool| !previous.contains(pool)) {
+                    changes.push(format!("chain {} pool {} added", chain_id, pool));
+                }
+                for pool in previous.iter().filter(|pool| !pools.contains(pool)) {
+                    changes.push(format!("chain {} pool {} removed", chain_id, pool));
+                }
+            }
+        }
+    }
+    for chain_id in previous_pools.keys() {
+        if !current_pools.contains_key(chain_id) {
+            changes.push(format!("chain {} removed", chain_id));
+        }
+    }
+    changes
+}
+
+// flattened fee and gas cost values of every chain, keyed by a readable path
+fn relayer_config_values(config: &RelayerConfig) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::new();
+    for chain in config.chains() {
+        let chain_id = chain.chain_id();
+        values.insert(
+            format!("chain {} relayer contract", chain_id),
+            chain.relayer_contract_address().to_string(),
+        );
+        for contract in chain.contracts() {
+            values.insert(
+                format!(
+                    "chain {} {} relayer_fee_of_ten_thousandth",
+                    chain_id,
+                    contract.asset_symbol()
+                ),
+                contract.relayer_fee_of_ten_thousandth().to_string(),
+            );
+        }
+        for asset_type in [AssetType::Main, AssetType::Erc20] {
+            for circuit_type in CIRCUIT_TYPES {
+                if let Ok(gas_cost) = chain.find_gas_cost(&asset_type, &circuit_type) {
+                    values.insert(
+                        format!("chain {} {:?} {:?} gas cost", chain_id, asset_type, circuit_type),
+                        gas_cost.to_string(),
+                    );
+                }
+            }
+        }
+    }
+    values
+}
+
+fn mystiko_pool_addresses(config: &MystikoConfig) -> BTreeMap<u64, Vec<String>> {
+    config
+        .chains()
+        .into_iter()
+        .map(|chain| {
+            let pools = chain
+                .pool_contracts()
+                .into_iter()
+                .map(|pool| pool.address().to_lowercase())
+                .collect();
+            (chain.chain_id(), pools)
+        })
+        .collect()
+}
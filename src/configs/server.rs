@@ -1,7 +1,18 @@
 use crate::configs::account::AccountConfig;
+use crate::configs::admin::AdminConfig;
+use crate::configs::auth::AuthConfig;
 use crate::configs::chain::ChainConfig;
-use anyhow::{bail, Result};
+use crate::configs::cors::CorsConfig;
+use crate::configs::lease::LeaseConfig;
+use crate::configs::price_cache::PriceCacheConfig;
+use crate::configs::quote::QuoteConfig;
+use crate::configs::rate_limit::RateLimitConfig;
+use crate::configs::tls::TlsConfig;
+use crate::configs::validation::{validate_server_config, validate_server_config_warnings, ConfigError, ConfigErrors};
+use crate::configs::webhook::WebhookConfig;
+use anyhow::{bail, Context, Result};
 use log::debug;
+use mystiko_config::MystikoConfig;
 use mystiko_relayer_config::wrapper::relayer::RelayerConfig;
 use mystiko_types::NetworkType;
 use mystiko_validator::validate::is_api_version;
@@ -10,7 +21,9 @@ use std::collections::HashMap;
 use typed_builder::TypedBuilder;
 use validator::Validate;
 
-#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default)]
+pub const REDACTED: &str = "<redacted>";
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[builder(field_defaults(setter(into)))]
 pub struct ServerConfig {
     #[validate]
@@ -26,9 +39,37 @@ pub struct ServerConfig {
     #[serde(default)]
     #[builder(default)]
     pub options: Options,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub admin: AdminConfig,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub auth: AuthConfig,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub rate_limit: RateLimitConfig,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub lease: LeaseConfig,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub webhook: WebhookConfig,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub quote: QuoteConfig,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub price_cache: PriceCacheConfig,
 }
 
-#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[builder(field_defaults(setter(into)))]
 pub struct Settings {
     #[builder(default)]
@@ -52,9 +93,18 @@ pub struct Settings {
     #[builder(default)]
     #[validate(length(min = 1))]
     pub coin_market_cap_api_key: String,
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub cors: CorsConfig,
+    // serve https instead of http when set
+    #[validate]
+    #[serde(default)]
+    #[builder(default)]
+    pub tls: Option<TlsConfig>,
 }
 
-#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[builder(field_defaults(setter(into)))]
 pub struct Options {
     #[serde(default)]
@@ -75,6 +125,15 @@ pub struct Options {
     #[serde(default)]
     #[builder(default)]
     pub relayer_remote_config_base_url: Option<String>,
+    // reload the relayer and mystiko configs periodically, disabled when not set
+    #[serde(default)]
+    #[builder(default)]
+    #[validate(range(min = 1000))]
+    pub config_refresh_interval_ms: Option<u64>,
+    // keep the last fetched remote configs here and use them when the remote is unreachable
+    #[serde(default)]
+    #[builder(default)]
+    pub remote_config_cache_dir: Option<String>,
 }
 
 impl ServerConfig {
@@ -97,35 +156,82 @@ impl ServerConfig {
             .map(|accounts| accounts.into_iter().filter(|account| account.available).collect())
     }
 
-    pub fn validation(&self, relayer_config: &RelayerConfig) -> Result<()> {
-        for account in self.accounts.values() {
-            // validate account supported erc20 tokens
-            let chain_config_opt = relayer_config.find_chain_config(account.chain_id);
-            if chain_config_opt.is_none() {
-                bail!("chain id {} not found in relayer config", account.chain_id);
+    // decrypts every local key once, the plaintext keys are cleared from the config
+    pub fn load_signers(&mut self) -> Result<()> {
+        for (key, account) in self.accounts.iter_mut() {
+            account
+                .load_signer()
+                .with_context(|| format!("load signer of account {}", key))?;
+        }
+        Ok(())
+    }
+
+    // a copy safe to print, keys, tokens and signer headers are masked
+    pub fn redacted(&self) -> ServerConfig {
+        let mut config = self.clone();
+        redact(&mut config.settings.coin_market_cap_api_key);
+        for account in config.accounts.values_mut() {
+            redact(&mut account.private_key);
+            if let Some(remote_signer) = account.remote_signer.as_mut() {
+                remote_signer.headers.values_mut().for_each(redact);
             }
-            let chain_config = chain_config_opt.unwrap();
-            let symbols = chain_config
-                .contracts()
-                .iter()
-                .map(|contract| contract.asset_symbol().to_lowercase())
-                .collect::<Vec<String>>();
-            debug!("chain id {} symbols {:?}", account.chain_id, symbols);
-            debug!(
-                "server config supported erc20 tokens {:?}",
-                &account.supported_erc20_tokens
+        }
+        config.admin.tokens.values_mut().for_each(redact);
+        for key in config.auth.keys.values_mut() {
+            redact(&mut key.key);
+        }
+        redact(&mut config.webhook.secret);
+        redact(&mut config.quote.secret);
+        config
+    }
+
+    // checks everything at once, the error lists every problem found
+    pub fn validation(
+        &self,
+        relayer_config: &RelayerConfig,
+        mystiko_config: &MystikoConfig,
+    ) -> std::result::Result<(), ConfigErrors> {
+        validate_server_config(self, relayer_config, mystiko_config)
+    }
+
+    // problems that do not stop the relayer from starting, e.g. chains no account uses
+    pub fn warnings(&self) -> Vec<ConfigError> {
+        validate_server_config_warnings(self)
+    }
+}
+
+pub fn validate_account(account: &AccountConfig, relayer_config: &RelayerConfig) -> Result<()> {
+    // validate account supported erc20 tokens
+    let chain_config_opt = relayer_config.find_chain_config(account.chain_id);
+    if chain_config_opt.is_none() {
+        bail!("chain id {} not found in relayer config", account.chain_id);
+    }
+    let chain_config = chain_config_opt.unwrap();
+    let symbols = chain_config
+        .contracts()
+        .iter()
+        .map(|contract| contract.asset_symbol().to_lowercase())
+        .collect::<Vec<String>>();
+    debug!("chain id {} symbols {:?}", account.chain_id, symbols);
+    debug!(
+        "server config supported erc20 tokens {:?}",
+        &account.supported_erc20_tokens
+    );
+    for tokens in account.supported_erc20_tokens.values() {
+        if !symbols.contains(&tokens.to_lowercase()) {
+            bail!(
+                "chain_id {} token {} not found in relayer chain config",
+                account.chain_id,
+                tokens
             );
-            for tokens in account.supported_erc20_tokens.values() {
-                if !symbols.contains(&tokens.to_lowercase()) {
-                    bail!(
-                        "chain_id {} token {} not found in relayer chain config",
-                        account.chain_id,
-                        tokens
-                    );
-                }
-            }
         }
-        Ok(self.validate()?)
+    }
+    Ok(())
+}
+
+fn redact(secret: &mut String) {
+    if !secret.is_empty() {
+        *secret = REDACTED.to_string();
     }
 }
 
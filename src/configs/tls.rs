@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use validator::Validate;
+
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+pub struct TlsConfig {
+    // pem encoded certificate chain, leaf first
+    #[validate(length(min = 1))]
+    pub cert_path: String,
+    // pem encoded pkcs8, rsa or ec private key
+    #[validate(length(min = 1))]
+    pub key_path: String,
+    // when set, the admin endpoints require a client certificate issued by this ca
+    #[serde(default)]
+    #[builder(default)]
+    pub admin_client_ca_path: Option<String>,
+    // how often the certificate files are checked for changes
+    #[serde(default = "default_reload_interval_secs")]
+    #[builder(default = default_reload_interval_secs())]
+    #[validate(range(min = 1))]
+    pub reload_interval_secs: u64,
+}
+
+fn default_reload_interval_secs() -> u64 {
+    60
+}
@@ -0,0 +1,216 @@
+use crate::configs::account::AccountConfig;
+use crate::configs::server::ServerConfig;
+use log::warn;
+use mystiko_config::MystikoConfig;
+use mystiko_relayer_config::wrapper::relayer::RelayerConfig;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use thiserror::Error;
+use validator::Validate;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("accounts.{account} has an invalid signer: {reason}")]
+    InvalidSigner { account: u16, reason: String },
+    #[error("accounts.{account} duplicates the signer of accounts.{duplicate_of} on chain id {chain_id}")]
+    DuplicateAccount {
+        account: u16,
+        duplicate_of: u16,
+        chain_id: u64,
+    },
+    #[error("accounts.{account}: chain id {chain_id} not found in relayer config")]
+    ChainNotInRelayerConfig { account: u16, chain_id: u64 },
+    #[error("accounts.{account}: chain id {chain_id} not found in mystiko config")]
+    ChainNotInMystikoConfig { account: u16, chain_id: u64 },
+    #[error("accounts.{account}: chain_id {chain_id} token {token} not found in relayer chain config")]
+    UnsupportedToken { account: u16, chain_id: u64, token: String },
+    #[error("chains.{chain_id} is not used by any account")]
+    UnusedChain { chain_id: u64 },
+    #[error("settings.sqlite_db_path {path}: {reason}")]
+    InvalidSqliteDbPath { path: String, reason: String },
+}
+
+// every problem found in a config, reported together instead of one per restart
+#[derive(Error, Debug, Clone, PartialEq)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+impl ConfigErrors {
+    pub fn errors(&self) -> &[ConfigError] {
+        &self.0
+    }
+}
+
+impl Display for ConfigErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid server config, {} problem(s):", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn validate_server_config(
+    server_config: &ServerConfig,
+    relayer_config: &RelayerConfig,
+    mystiko_config: &MystikoConfig,
+) -> Result<(), ConfigErrors> {
+    let mut errors = Vec::new();
+    if let Err(validation_errors) = server_config.validate() {
+        errors.push(ConfigError::Invalid(validation_errors.to_string()));
+    }
+
+    // sorted so the report is stable between runs
+    let accounts = server_config.accounts.iter().collect::<BTreeMap<_, _>>();
+    let mut signers = HashMap::new();
+    for (key, account) in &accounts {
+        match signer_identity(account) {
+            Ok(identity) => {
+                if let Some(duplicate_of) = signers.insert((account.chain_id, identity), **key) {
+                    errors.push(ConfigError::DuplicateAccount {
+                        account: **key,
+                        duplicate_of,
+                        chain_id: account.chain_id,
+                    });
+                }
+            }
+            Err(reason) => errors.push(ConfigError::InvalidSigner { account: **key, reason }),
+        }
+        errors.extend(account_chain_errors(**key, account, relayer_config));
+        if mystiko_config.find_chain(account.chain_id).is_none() {
+            errors.push(ConfigError::ChainNotInMystikoConfig {
+                account: **key,
+                chain_id: account.chain_id,
+            });
+        }
+    }
+
+    if let Some(path) = &server_config.settings.sqlite_db_path {
+        if let Err(error) = validate_sqlite_db_path(path) {
+            errors.push(error);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigErrors(errors))
+    }
+}
+
+// logged once when a server config is loaded, the remote config refresh does not change them
+pub fn log_server_config_warnings(server_config: &ServerConfig) {
+    for warning in validate_server_config_warnings(server_config) {
+        warn!("server config: {}", warning);
+    }
+}
+
+// problems that do not stop the relayer from starting
+pub fn validate_server_config_warnings(server_config: &ServerConfig) -> Vec<ConfigError> {
+    let used_chains = server_config
+        .accounts
+        .values()
+        .map(|account| account.chain_id)
+        .collect::<HashSet<_>>();
+    let mut unused_chains = server_config
+        .chains
+        .keys()
+        .filter(|chain_id| !used_chains.contains(chain_id))
+        .collect::<Vec<_>>();
+    unused_chains.sort();
+    unused_chains
+        .into_iter()
+        .map(|chain_id| ConfigError::UnusedChain { chain_id: *chain_id })
+        .collect()
+}
+
+// the chain must exist in the relayer config and serve every configured token
+pub fn account_chain_errors(key: u16, account: &AccountConfig, relayer_config: &RelayerConfig) -> Vec<ConfigError> {
+    let chain_id = account.chain_id;
+    let Some(chain_config) = relayer_config.find_chain_config(chain_id) else {
+        return vec![ConfigError::ChainNotInRelayerConfig { account: key, chain_id }];
+    };
+    let symbols = chain_config
+        .contracts()
+        .iter()
+        .map(|contract| contract.asset_symbol().to_lowercase())
+        .collect::<HashSet<String>>();
+    let mut tokens = account.supported_erc20_tokens.iter().collect::<Vec<_>>();
+    tokens.sort();
+    tokens
+        .into_iter()
+        .filter(|(_, token)| !symbols.contains(&token.to_lowercase()))
+        .map(|(_, token)| ConfigError::UnsupportedToken {
+            account: key,
+            chain_id,
+            token: token.clone(),
+        })
+        .collect()
+}
+
+pub fn validate_sqlite_db_path(path: &str) -> Result<(), ConfigError> {
+    let invalid = |reason: &str| ConfigError::InvalidSqliteDbPath {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    };
+    let db_path = Path::new(path);
+    if path.trim().is_empty() {
+        return Err(invalid("path is empty"));
+    }
+    if db_path.is_dir() {
+        return Err(invalid("path is a directory"));
+    }
+    match db_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            Err(invalid("parent directory does not exist"))
+        }
+        _ => Ok(()),
+    }
+}
+
+// signers are compared by address, a keystore and a private key of the same wallet are duplicates
+fn signer_identity(account: &AccountConfig) -> Result<String, String> {
+    if let Some(remote_signer) = &account.remote_signer {
+        return Ok(normalize_address(&remote_signer.address));
+    }
+    if let Some(keystore_path) = &account.keystore_path {
+        if !Path::new(keystore_path).is_file() {
+            return Err(format!("keystore {} not found", keystore_path));
+        }
+        if let Some(address) = keystore_address(keystore_path)? {
+            return Ok(address);
+        }
+        // keystores without the plain address field are decrypted, as load_signers does later
+        return account
+            .address()
+            .map(|address| normalize_address(&address))
+            .map_err(|error| format!("invalid keystore: {}", error));
+    }
+    if !account.has_private_key() {
+        return Err("private_key, keystore_path or remote_signer is required".to_string());
+    }
+    account
+        .address()
+        .map(|address| normalize_address(&address))
+        .map_err(|error| format!("invalid private key: {}", error))
+}
+
+// the unencrypted address most keystore writers store next to the crypto section
+fn keystore_address(keystore_path: &str) -> Result<Option<String>, String> {
+    let keystore = std::fs::read_to_string(keystore_path)
+        .map_err(|error| format!("read keystore {}: {}", keystore_path, error))?;
+    let keystore = serde_json::from_str::<serde_json::Value>(&keystore)
+        .map_err(|error| format!("parse keystore {}: {}", keystore_path, error))?;
+    Ok(keystore
+        .get("address")
+        .and_then(serde_json::Value::as_str)
+        .filter(|address| !address.is_empty())
+        .map(normalize_address))
+}
+
+fn normalize_address(address: &str) -> String {
+    format!("0x{}", address.trim_start_matches("0x").to_lowercase())
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use validator::{Validate, ValidationError};
+
+// status changes of transactions are posted to the global url and to the callback url of the request
+#[derive(TypedBuilder, Validate, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[builder(field_defaults(setter(into)))]
+#[validate(schema(function = "is_valid_webhook"))]
+pub struct WebhookConfig {
+    #[serde(default)]
+    #[builder(default)]
+    pub enabled: bool,
+    // receives the status changes of every transaction
+    #[serde(default)]
+    #[builder(default)]
+    #[validate(custom = "is_webhook_url")]
+    pub url: Option<String>,
+    // hmac key of the X-Relayer-Signature header
+    #[serde(default)]
+    #[builder(default)]
+    pub secret: String,
+    // accept a callback_url on transact requests
+    #[serde(default)]
+    #[builder(default)]
+    pub allow_callback_url: bool,
+    // when set, callback urls must use one of these hosts, which may also resolve to private addresses.
+    // otherwise any host is accepted as long as it only resolves to public addresses
+    #[serde(default)]
+    #[builder(default)]
+    pub callback_allowed_hosts: Vec<String>,
+    #[serde(default = "default_max_attempts")]
+    #[builder(default = default_max_attempts())]
+    #[validate(range(min = 1, max = 20))]
+    pub max_attempts: u32,
+    // doubled after every failed attempt up to max_backoff_ms
+    #[serde(default = "default_initial_backoff_ms")]
+    #[builder(default = default_initial_backoff_ms())]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_ms")]
+    #[builder(default = default_max_backoff_ms())]
+    pub max_backoff_ms: u64,
+    #[serde(default = "default_timeout_ms")]
+    #[builder(default = default_timeout_ms())]
+    #[validate(range(min = 100))]
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig::builder().build()
+    }
+}
+
+impl WebhookConfig {
+    pub fn backoff_ms(&self, attempt: u32) -> u64 {
+        self.initial_backoff_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(32))
+            .min(self.max_backoff_ms)
+    }
+
+    pub fn is_allowed_callback_host(&self, host: &str) -> bool {
+        self.callback_allowed_hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    }
+}
+
+// http or https with a host, shared with the callback urls of requests
+pub fn validate_webhook_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|error| format!("invalid url: {}", error))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err("url must be http or https with a host".to_string());
+    }
+    if url.len() > 255 {
+        return Err("url must be at most 255 characters".to_string());
+    }
+    Ok(())
+}
+
+fn is_webhook_url(url: &str) -> Result<(), ValidationError> {
+    validate_webhook_url(url).map_err(|_| ValidationError::new("invalid webhook url"))
+}
+
+fn is_valid_webhook(config: &WebhookConfig) -> Result<(), ValidationError> {
+    if config.enabled && config.secret.is_empty() {
+        return Err(ValidationError::new("secret is required when webhooks are enabled"));
+    }
+    Ok(())
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    60000
+}
+
+fn default_timeout_ms() -> u64 {
+    10000
+}
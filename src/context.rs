@@ -1,12 +1,30 @@
+use crate::channel::lease::LeaseHandlerRef;
+use crate::channel::webhook::WebhookHandlerRef;
 use crate::configs::account::AccountConfig;
+use crate::configs::cache::{
+    load_remote_config, RemoteConfigSource, DEFAULT_MYSTIKO_REMOTE_CONFIG_BASE_URL,
+    DEFAULT_RELAYER_REMOTE_CONFIG_BASE_URL,
+};
+use crate::configs::remote::SharedConfig;
 use crate::configs::server::ServerConfig;
+use crate::configs::validation::log_server_config_warnings;
 use crate::database::account::Account as DocumentAccount;
+use crate::database::api_key::ApiKey as DocumentApiKey;
+use crate::database::audit::AuditLog as DocumentAuditLog;
+use crate::database::lease::AccountLease as DocumentAccountLease;
 use crate::database::transaction::Transaction as DocumentTransaction;
 use crate::database::Database;
 use crate::error::RelayerServerError;
 use crate::handler::account::handler::Account;
 use crate::handler::account::AccountHandler;
+use crate::handler::api_key::{ApiKey, ApiKeyHandler};
+use crate::handler::audit::{AuditHandler, AuditLog};
+use crate::handler::lease::{Lease, LeaseHandler};
+use crate::handler::quote::{Quote, QuoteHandlerRef};
+use crate::handler::transaction::status::TransactionStatusBus;
 use crate::handler::transaction::{Transaction, TransactionHandler};
+use crate::handler::webhook::Webhook;
+use crate::price_cache::{GasPriceCache, TokenPriceCache};
 use crate::provider::{RelayerProviderOptions, RelayerSignerOptions};
 use anyhow::Result;
 use mystiko_config::{MystikoConfig, MystikoConfigOptions};
@@ -14,35 +32,47 @@ use mystiko_ethers::{ProviderPool, Providers};
 use mystiko_relayer_config::wrapper::relayer::RelayerConfig;
 use mystiko_server_utils::token_price::config::TokenPriceConfig;
 use mystiko_server_utils::token_price::{PriceMiddleware, TokenPrice};
-use mystiko_storage::{Document, SqlStatementFormatter};
-use mystiko_storage_sqlite::SqliteStorage;
+use mystiko_storage::{Document, StatementFormatter, Storage};
 use mystiko_types::NetworkType;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 #[derive(Clone, Debug)]
 pub struct Context {
-    pub server_config: Arc<ServerConfig>,
-    pub relayer_config: Arc<RelayerConfig>,
-    pub mystiko_config: Arc<MystikoConfig>,
+    // swapped by a config reload, readers keep the snapshot they loaded
+    pub server_config: Arc<SharedConfig<ServerConfig>>,
+    pub relayer_config: Arc<SharedConfig<RelayerConfig>>,
+    pub mystiko_config: Arc<SharedConfig<MystikoConfig>>,
     pub providers: Arc<Box<dyn Providers>>,
     pub signer_providers: Arc<Box<dyn Providers>>,
     pub transaction_handler:
         Arc<Box<dyn TransactionHandler<Document<DocumentTransaction>, Error = RelayerServerError>>>,
+    pub status_bus: Arc<TransactionStatusBus>,
     pub account_handler: Arc<Box<dyn AccountHandler<Document<DocumentAccount>, Error = RelayerServerError>>>,
+    pub audit_handler: Arc<Box<dyn AuditHandler<Document<DocumentAuditLog>, Error = RelayerServerError>>>,
+    pub api_key_handler: Arc<Box<dyn ApiKeyHandler<Document<DocumentApiKey>, Error = RelayerServerError>>>,
+    pub lease_handler: LeaseHandlerRef,
+    pub webhook_handler: WebhookHandlerRef,
+    pub quote_handler: QuoteHandlerRef,
     pub token_price: Arc<RwLock<Box<dyn PriceMiddleware>>>,
+    pub gas_price_cache: Arc<GasPriceCache>,
+    // the cache behind token_price, none when token prices are not cached
+    pub token_price_cache: Option<TokenPriceCache>,
 }
 
 impl Context {
-    pub async fn new(
-        server_config: Arc<ServerConfig>,
-        database: Arc<Database<SqlStatementFormatter, SqliteStorage>>,
-    ) -> Result<Self> {
+    pub async fn new<F, S>(server_config: Arc<ServerConfig>, database: Arc<Database<F, S>>) -> Result<Self>
+    where
+        F: StatementFormatter + 'static,
+        S: Storage + 'static,
+    {
         // create relayer and mystiko config
         let (relayer_config, mystiko_config) = create_config(server_config.clone()).await?;
 
         // validation server config
-        server_config.validation(&relayer_config)?;
+        server_config.validation(&relayer_config, &mystiko_config)?;
+        log_server_config_warnings(&server_config);
 
         // create signer provider
         let relayer_signer_options = RelayerSignerOptions::builder()
@@ -64,8 +94,9 @@ impl Context {
             .build();
         let providers = Arc::new(Box::new(providers) as Box<dyn Providers>);
 
-        // create transaction handler
-        let transaction_handler = Transaction::new(database.clone());
+        // create transaction handler, publishing status changes on the bus
+        let status_bus = Arc::new(TransactionStatusBus::default());
+        let transaction_handler = Transaction::with_status_bus(database.clone(), status_bus.clone());
         let transaction_handler = Arc::new(Box::new(transaction_handler)
             as Box<dyn TransactionHandler<Document<DocumentTransaction>, Error = RelayerServerError>>);
 
@@ -86,21 +117,76 @@ impl Context {
                     dyn AccountHandler<Document<DocumentAccount>, Error = RelayerServerError>,
                 >);
 
-        // init token price
-        let token_price = Arc::new(RwLock::new(Box::new(TokenPrice::new(
+        // create audit handler
+        let audit_handler = AuditLog::new(database.clone());
+        let audit_handler =
+            Arc::new(Box::new(audit_handler)
+                as Box<
+                    dyn AuditHandler<Document<DocumentAuditLog>, Error = RelayerServerError>,
+                >);
+
+        // create api key handler
+        let api_key_handler = ApiKey::new(database.clone(), &server_config.auth).await?;
+        let api_key_handler =
+            Arc::new(Box::new(api_key_handler)
+                as Box<
+                    dyn ApiKeyHandler<Document<DocumentApiKey>, Error = RelayerServerError>,
+                >);
+
+        // create lease handler
+        let lease_handler = Lease::new(
+            database.clone(),
+            Duration::from_millis(server_config.lease.max_clock_skew_ms),
+        );
+        let lease_handler = Arc::new(Box::new(lease_handler)
+            as Box<dyn LeaseHandler<Document<DocumentAccountLease>, Error = RelayerServerError>>);
+
+        // create webhook handler
+        let webhook_handler: WebhookHandlerRef = Arc::new(Box::new(Webhook::new(database.clone())));
+
+        // create quote handler
+        let quote_handler: QuoteHandlerRef = Arc::new(Box::new(Quote::new(database.clone())));
+
+        // init token price, cached in front of the price service unless disabled
+        let token_price = Box::new(TokenPrice::new(
             &TokenPriceConfig::new(server_config.settings.network_type == NetworkType::Testnet, None)?,
             &server_config.settings.coin_market_cap_api_key,
-        )?) as Box<dyn PriceMiddleware>));
+        )?) as Box<dyn PriceMiddleware>;
+        let price_cache_config = &server_config.price_cache;
+        let (token_price, token_price_cache) = if price_cache_config.token_price_ttl_ms > 0 {
+            let cache = TokenPriceCache::new(
+                Duration::from_millis(price_cache_config.token_price_ttl_ms),
+                token_price,
+            );
+            (Box::new(cache.clone()) as Box<dyn PriceMiddleware>, Some(cache))
+        } else {
+            (token_price, None)
+        };
+        let token_price = Arc::new(RwLock::new(token_price));
+
+        // init gas price cache
+        let gas_price_cache = Arc::new(GasPriceCache::new(
+            Duration::from_millis(price_cache_config.gas_price_ttl_ms),
+            providers.clone(),
+        ));
 
         Ok(Self {
-            server_config,
-            relayer_config,
-            mystiko_config,
+            server_config: Arc::new(SharedConfig::new(server_config)),
+            relayer_config: Arc::new(SharedConfig::new(relayer_config)),
+            mystiko_config: Arc::new(SharedConfig::new(mystiko_config)),
             providers,
             signer_providers,
             transaction_handler,
+            status_bus,
             account_handler,
+            audit_handler,
+            api_key_handler,
+            lease_handler,
+            webhook_handler,
+            quote_handler,
             token_price,
+            gas_price_cache,
+            token_price_cache,
         })
     }
 }
@@ -109,9 +195,31 @@ pub async fn create_config(server_config: Arc<ServerConfig>) -> Result<(Arc<Rela
     let relayer_config_path = &server_config.options.relayer_config_path;
     let mystiko_config_path = &server_config.options.mystiko_config_path;
 
+    let is_testnet = server_config.settings.network_type == NetworkType::Testnet;
+    let cache_dir = &server_config.options.remote_config_cache_dir;
+
     // load default relayer config
-    let relayer_config = match relayer_config_path {
-        None => {
+    let relayer_config = match (relayer_config_path, cache_dir) {
+        (None, Some(cache_dir)) => {
+            let source = RemoteConfigSource::builder()
+                .name("relayer_config")
+                .base_url(
+                    server_config
+                        .options
+                        .relayer_remote_config_base_url
+                        .as_deref()
+                        .unwrap_or(DEFAULT_RELAYER_REMOTE_CONFIG_BASE_URL),
+                )
+                .cache_dir(cache_dir)
+                .is_staging(server_config.options.relayer_config_is_staging)
+                .is_testnet(is_testnet)
+                .build();
+            load_remote_config(&source, |path| async move {
+                Ok(RelayerConfig::from_json_file(&path).await?)
+            })
+            .await?
+        }
+        (None, None) => {
             let mut options = if let Some(base_url) = &server_config.options.relayer_remote_config_base_url {
                 mystiko_relayer_config::wrapper::relayer::RemoteOptions::builder()
                     .base_url(base_url.to_string())
@@ -125,12 +233,31 @@ pub async fn create_config(server_config: Arc<ServerConfig>) -> Result<(Arc<Rela
             options.is_staging = server_config.options.relayer_config_is_staging;
             RelayerConfig::from_options(options).await?
         }
-        Some(path) => RelayerConfig::from_json_file(path).await?,
+        (Some(path), _) => RelayerConfig::from_json_file(path).await?,
     };
 
     // load default mystiko config
-    let mystiko_config = match mystiko_config_path {
-        None => {
+    let mystiko_config = match (mystiko_config_path, cache_dir) {
+        (None, Some(cache_dir)) => {
+            let source = RemoteConfigSource::builder()
+                .name("mystiko_config")
+                .base_url(
+                    server_config
+                        .options
+                        .mystiko_remote_config_base_url
+                        .as_deref()
+                        .unwrap_or(DEFAULT_MYSTIKO_REMOTE_CONFIG_BASE_URL),
+                )
+                .cache_dir(cache_dir)
+                .is_staging(server_config.options.mystiko_config_is_staging)
+                .is_testnet(is_testnet)
+                .build();
+            load_remote_config(&source, |path| async move {
+                Ok(MystikoConfig::from_json_file(&path).await?)
+            })
+            .await?
+        }
+        (None, None) => {
             let mut options = if let Some(base_url) = &server_config.options.mystiko_remote_config_base_url {
                 MystikoConfigOptions::builder()
                     .remote_base_url(base_url.to_string())
@@ -144,7 +271,7 @@ pub async fn create_config(server_config: Arc<ServerConfig>) -> Result<(Arc<Rela
             options.is_staging = server_config.options.mystiko_config_is_staging;
             MystikoConfig::from_options(options).await?
         }
-        Some(path) => MystikoConfig::from_json_file(path).await?,
+        (Some(path), _) => MystikoConfig::from_json_file(path).await?,
     };
 
     Ok((Arc::new(relayer_config), Arc::new(mystiko_config)))
@@ -0,0 +1,19 @@
+use mystiko_storage::{DocumentData, UniqueColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(uniques = uniques())]
+pub struct ApiKey {
+    #[column(length_limit = 128)]
+    pub name: String,
+    #[column(length_limit = 64)]
+    pub key_hash: String,
+    #[column(length_limit = 16)]
+    pub scope: String,
+    pub usage_count: u64,
+    pub last_used_at: Option<u64>,
+}
+
+fn uniques() -> Vec<UniqueColumns> {
+    vec![vec![ApiKeyColumn::Name].into()]
+}
@@ -0,0 +1,21 @@
+use mystiko_storage::{DocumentData, IndexColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(indexes = indexes())]
+pub struct AuditLog {
+    #[column(length_limit = 255)]
+    pub target: String,
+    #[column(length_limit = 32)]
+    pub action: String,
+    #[column(length_limit = 128)]
+    pub operator: String,
+    pub reason: String,
+    pub detail: Option<String>,
+}
+
+fn indexes() -> Vec<IndexColumns> {
+    vec![IndexColumns::builder()
+        .column_names(vec![AuditLogColumn::Target.to_string()])
+        .build()]
+}
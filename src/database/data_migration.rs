@@ -0,0 +1,14 @@
+use mystiko_storage::{DocumentData, UniqueColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+// one document per data migration that completed, so it is skipped on the next start
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(uniques = uniques())]
+pub struct DataMigration {
+    #[column(length_limit = 64)]
+    pub name: String,
+}
+
+fn uniques() -> Vec<UniqueColumns> {
+    vec![vec![DataMigrationColumn::Name].into()]
+}
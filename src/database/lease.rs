@@ -0,0 +1,17 @@
+use mystiko_storage::{DocumentData, UniqueColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(uniques = uniques())]
+pub struct AccountLease {
+    // chain id and lowercase address of the account
+    #[column(length_limit = 128)]
+    pub lease_key: String,
+    #[column(length_limit = 128)]
+    pub owner: String,
+    pub expires_at: u64,
+}
+
+fn uniques() -> Vec<UniqueColumns> {
+    vec![vec![AccountLeaseColumn::LeaseKey].into()]
+}
@@ -1,21 +1,62 @@
 pub mod account;
+pub mod api_key;
+pub mod audit;
+pub mod data_migration;
+pub mod lease;
+pub mod nonce;
+pub mod quote;
+pub mod request;
+pub mod serial_number;
 pub mod transaction;
+pub mod webhook;
 
 use crate::database::account::AccountCollection;
+use crate::database::api_key::ApiKeyCollection;
+use crate::database::audit::AuditLogCollection;
+use crate::database::data_migration::{DataMigration, DataMigrationCollection, DataMigrationColumn};
+use crate::database::lease::AccountLeaseCollection;
+use crate::database::nonce::ApiKeyNonceCollection;
+use crate::database::quote::AcceptedQuoteCollection;
+use crate::database::request::TransactionRequestCollection;
+use crate::database::serial_number::{
+    serial_number_key, TransactionSerialNumber, TransactionSerialNumberCollection, TransactionSerialNumberColumn,
+};
 use crate::database::transaction::TransactionCollection;
+use crate::database::webhook::{TransactionCallbackCollection, WebhookDeliveryCollection};
 use anyhow::Result;
 use log::info;
-use mystiko_storage::{Collection, Document, MigrationHistory, SqlStatementFormatter, StatementFormatter, Storage};
+use mystiko_protos::storage::v1::{Condition, Order, OrderBy, QueryFilter, SubFilter};
+use mystiko_storage::{
+    Collection, Document, MigrationHistory, SqlStatementFormatter, StatementFormatter, Storage, DOCUMENT_ID_FIELD,
+};
 use mystiko_storage_sqlite::SqliteStorage;
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
 
+// data migrations that run once per database
+pub const UPPERCASE_ASSET_SYMBOLS: &str = "uppercase_asset_symbols";
+pub const BACKFILL_SERIAL_NUMBERS: &str = "backfill_serial_numbers";
+
+// documents read at a time by a data migration
+const DATA_MIGRATION_BATCH: u64 = 500;
+
 #[derive(Debug)]
 pub struct Database<F: StatementFormatter, S: Storage> {
     pub accounts: AccountCollection<F, S>,
     pub transactions: TransactionCollection<F, S>,
+    pub requests: TransactionRequestCollection<F, S>,
+    pub serial_numbers: TransactionSerialNumberCollection<F, S>,
+    pub audit_logs: AuditLogCollection<F, S>,
+    pub api_keys: ApiKeyCollection<F, S>,
+    pub api_key_nonces: ApiKeyNonceCollection<F, S>,
+    pub leases: AccountLeaseCollection<F, S>,
+    pub callbacks: TransactionCallbackCollection<F, S>,
+    pub webhook_deliveries: WebhookDeliveryCollection<F, S>,
+    pub quotes: AcceptedQuoteCollection<F, S>,
+    pub data_migrations: DataMigrationCollection<F, S>,
 }
 
 impl<F: StatementFormatter, S: Storage> Database<F, S> {
@@ -23,15 +64,154 @@ impl<F: StatementFormatter, S: Storage> Database<F, S> {
         let collection = Arc::new(Collection::new(formatter, storage));
         Database {
             accounts: AccountCollection::new(collection.clone()),
-            transactions: TransactionCollection::new(collection),
+            transactions: TransactionCollection::new(collection.clone()),
+            requests: TransactionRequestCollection::new(collection.clone()),
+            serial_numbers: TransactionSerialNumberCollection::new(collection.clone()),
+            audit_logs: AuditLogCollection::new(collection.clone()),
+            api_keys: ApiKeyCollection::new(collection.clone()),
+            api_key_nonces: ApiKeyNonceCollection::new(collection.clone()),
+            leases: AccountLeaseCollection::new(collection.clone()),
+            callbacks: TransactionCallbackCollection::new(collection.clone()),
+            webhook_deliveries: WebhookDeliveryCollection::new(collection.clone()),
+            quotes: AcceptedQuoteCollection::new(collection.clone()),
+            data_migrations: DataMigrationCollection::new(collection),
         }
     }
 
     pub async fn migrate(&self) -> Result<Vec<Document<MigrationHistory>>> {
-        let migrations: Vec<Document<MigrationHistory>> =
-            vec![self.accounts.migrate().await?, self.transactions.migrate().await?];
+        let migrations: Vec<Document<MigrationHistory>> = vec![
+            self.accounts.migrate().await?,
+            self.transactions.migrate().await?,
+            self.requests.migrate().await?,
+            self.serial_numbers.migrate().await?,
+            self.audit_logs.migrate().await?,
+            self.api_keys.migrate().await?,
+            self.api_key_nonces.migrate().await?,
+            self.leases.migrate().await?,
+            self.callbacks.migrate().await?,
+            self.webhook_deliveries.migrate().await?,
+            self.quotes.migrate().await?,
+            self.data_migrations.migrate().await?,
+        ];
+        if !self.is_data_migrated(UPPERCASE_ASSET_SYMBOLS).await? {
+            self.uppercase_asset_symbols().await?;
+            self.mark_data_migrated(UPPERCASE_ASSET_SYMBOLS).await?;
+        }
+        if !self.is_data_migrated(BACKFILL_SERIAL_NUMBERS).await? {
+            self.backfill_serial_numbers().await?;
+            self.mark_data_migrated(BACKFILL_SERIAL_NUMBERS).await?;
+        }
         Ok(migrations)
     }
+
+    pub async fn is_data_migrated(&self, name: &str) -> Result<bool> {
+        let query_filter = SubFilter::equal(DataMigrationColumn::Name, name);
+        Ok(self.data_migrations.find_one(query_filter).await?.is_some())
+    }
+
+    // another replica starting at the same time may have marked it first
+    pub async fn mark_data_migrated(&self, name: &str) -> Result<()> {
+        let migration = DataMigration { name: name.to_string() };
+        if let Err(error) = self.data_migrations.insert(&migration).await {
+            if !self.is_data_migrated(name).await? {
+                return Err(error.into());
+            }
+        }
+        Ok(())
+    }
+
+    // transactions stored before asset symbols were kept uppercase
+    async fn uppercase_asset_symbols(&self) -> Result<()> {
+        let mut offset = 0;
+        let mut updated = 0;
+        loop {
+            let mut query_filter = QueryFilter::from(Condition::and(vec![]));
+            query_filter.order_by = Some(
+                OrderBy::builder()
+                    .columns(vec![DOCUMENT_ID_FIELD.to_string()])
+                    .order(Order::Asc)
+                    .build(),
+            );
+            query_filter.offset = Some(offset);
+            query_filter.limit = Some(DATA_MIGRATION_BATCH);
+            let transactions = self.transactions.find(query_filter).await?;
+            let batch_size = transactions.len() as u64;
+            for mut transaction in transactions {
+                let asset_symbol = transaction.data.asset_symbol.to_uppercase();
+                if transaction.data.asset_symbol != asset_symbol {
+                    transaction.data.asset_symbol = asset_symbol;
+                    self.transactions.update(&transaction).await?;
+                    updated += 1;
+                }
+            }
+            if batch_size < DATA_MIGRATION_BATCH {
+                break;
+            }
+            offset += DATA_MIGRATION_BATCH;
+        }
+        if updated > 0 {
+            info!("uppercased asset symbols of {} existing transactions", updated);
+        }
+        Ok(())
+    }
+
+    // transactions stored before the serial number collection existed get their rows,
+    // only the missing ones are written in case a replica starting at the same time wrote some
+    async fn backfill_serial_numbers(&self) -> Result<()> {
+        let mut offset = 0;
+        let mut backfilled = 0;
+        loop {
+            let mut query_filter = QueryFilter::from(Condition::and(vec![]));
+            query_filter.order_by = Some(
+                OrderBy::builder()
+                    .columns(vec![DOCUMENT_ID_FIELD.to_string()])
+                    .order(Order::Asc)
+                    .build(),
+            );
+            query_filter.offset = Some(offset);
+            query_filter.limit = Some(DATA_MIGRATION_BATCH);
+            let transactions = self.transactions.find(query_filter).await?;
+            let batch_size = transactions.len() as u64;
+            let ids = transactions
+                .iter()
+                .map(|transaction| transaction.id.clone())
+                .collect::<Vec<_>>();
+            let indexed = self
+                .serial_numbers
+                .find(SubFilter::in_list(TransactionSerialNumberColumn::TransactionId, ids))
+                .await?
+                .into_iter()
+                .map(|serial_number| serial_number.data.transaction_id)
+                .collect::<HashSet<_>>();
+            let missing = transactions
+                .iter()
+                .filter(|transaction| !indexed.contains(&transaction.id))
+                .flat_map(|transaction| {
+                    transaction
+                        .data
+                        .serial_numbers
+                        .iter()
+                        .flatten()
+                        .map(|serial_number| TransactionSerialNumber {
+                            transaction_id: transaction.id.clone(),
+                            serial_number: serial_number_key(serial_number),
+                        })
+                })
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                self.serial_numbers.insert_batch(&missing).await?;
+                backfilled += missing.len();
+            }
+            if batch_size < DATA_MIGRATION_BATCH {
+                break;
+            }
+            offset += DATA_MIGRATION_BATCH;
+        }
+        if backfilled > 0 {
+            info!("backfill {} serial numbers of existing transactions", backfilled);
+        }
+        Ok(())
+    }
 }
 
 pub async fn init_sqlite_database(path: Option<String>) -> Result<Database<SqlStatementFormatter, SqliteStorage>> {
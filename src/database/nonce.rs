@@ -0,0 +1,17 @@
+use mystiko_storage::{DocumentData, UniqueColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+// nonce of a signed request, shared by all instances so a request is accepted once
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(uniques = uniques())]
+pub struct ApiKeyNonce {
+    #[column(length_limit = 128)]
+    pub name: String,
+    #[column(length_limit = 64)]
+    pub nonce: String,
+    pub expires_at: u64,
+}
+
+fn uniques() -> Vec<UniqueColumns> {
+    vec![vec![ApiKeyNonceColumn::Name, ApiKeyNonceColumn::Nonce].into()]
+}
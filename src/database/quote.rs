@@ -0,0 +1,18 @@
+use mystiko_storage::{DocumentData, UniqueColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+// fee quote a transact request was accepted with, saved by signature for the consumer
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(uniques = uniques())]
+pub struct AcceptedQuote {
+    #[column(length_limit = 255)]
+    pub signature: String,
+    #[column(length_limit = 64)]
+    pub quote_id: String,
+    #[column(length_limit = 128)]
+    pub minimum_gas_fee: String,
+}
+
+fn uniques() -> Vec<UniqueColumns> {
+    vec![vec![AcceptedQuoteColumn::Signature].into()]
+}
@@ -0,0 +1,15 @@
+use mystiko_storage::{DocumentData, UniqueColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+// keeps the original relay request so that a job can be re-enqueued later
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(uniques = uniques())]
+pub struct TransactionRequest {
+    #[column(length_limit = 64)]
+    pub transaction_id: String,
+    pub data: String,
+}
+
+fn uniques() -> Vec<UniqueColumns> {
+    vec![vec![TransactionRequestColumn::TransactionId].into()]
+}
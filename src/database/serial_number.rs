@@ -0,0 +1,29 @@
+use mystiko_storage::{DocumentData, IndexColumns};
+use mystiko_storage_macros::CollectionBuilder;
+use num_bigint::BigUint;
+
+// one document per serial number of a transaction, the transaction keeps them in a single column
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(indexes = indexes())]
+pub struct TransactionSerialNumber {
+    #[column(length_limit = 64)]
+    pub transaction_id: String,
+    #[column(length_limit = 128)]
+    pub serial_number: String,
+}
+
+// lowercase 0x prefixed hex, the same for every way a serial number was given
+pub fn serial_number_key(serial_number: &BigUint) -> String {
+    format!("{:#x}", serial_number)
+}
+
+fn indexes() -> Vec<IndexColumns> {
+    vec![
+        IndexColumns::builder()
+            .column_names(vec![TransactionSerialNumberColumn::SerialNumber.to_string()])
+            .build(),
+        IndexColumns::builder()
+            .column_names(vec![TransactionSerialNumberColumn::TransactionId.to_string()])
+            .build(),
+    ]
+}
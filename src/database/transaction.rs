@@ -57,5 +57,8 @@ fn indexes() -> Vec<IndexColumns> {
         IndexColumns::builder()
             .column_names(vec![TransactionColumn::Signature.to_string()])
             .build(),
+        IndexColumns::builder()
+            .column_names(vec![TransactionColumn::TransactionHash.to_string()])
+            .build(),
     ]
 }
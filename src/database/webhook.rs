@@ -0,0 +1,48 @@
+use mystiko_storage::{DocumentData, IndexColumns, UniqueColumns};
+use mystiko_storage_macros::CollectionBuilder;
+
+// callback url of a transact request, saved by signature before the transaction is queued
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(uniques = callback_uniques())]
+pub struct TransactionCallback {
+    #[column(length_limit = 255)]
+    pub signature: String,
+    #[column(length_limit = 255)]
+    pub url: String,
+}
+
+// one document per status change and url, it carries the outcome of the latest attempt
+#[derive(CollectionBuilder, Clone, Debug, PartialEq)]
+#[collection(indexes = delivery_indexes())]
+pub struct WebhookDelivery {
+    #[column(length_limit = 64)]
+    pub transaction_id: String,
+    #[column(length_limit = 255)]
+    pub url: String,
+    #[column(length_limit = 64)]
+    pub status: String,
+    // json of the transaction status at the change, posted on every attempt
+    pub payload: String,
+    // attempts made so far
+    pub attempt: u32,
+    pub delivered: bool,
+    pub response_status: Option<u32>,
+    pub error_message: Option<String>,
+    // milliseconds, none once delivered or given up
+    pub next_attempt_at: Option<u64>,
+}
+
+fn callback_uniques() -> Vec<UniqueColumns> {
+    vec![vec![TransactionCallbackColumn::Signature].into()]
+}
+
+fn delivery_indexes() -> Vec<IndexColumns> {
+    vec![
+        IndexColumns::builder()
+            .column_names(vec![WebhookDeliveryColumn::TransactionId.to_string()])
+            .build(),
+        IndexColumns::builder()
+            .column_names(vec![WebhookDeliveryColumn::NextAttemptAt.to_string()])
+            .build(),
+    ]
+}
@@ -1,5 +1,5 @@
 use actix_web::body::BoxBody;
-use actix_web::http::header::ContentType;
+use actix_web::http::header::{ContentType, RETRY_AFTER};
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
 use derive_more::Display;
@@ -49,11 +49,31 @@ pub enum ResponseError {
     ChainIdNotFoundInRelayerConfig { chain_id: u64 },
     #[display(fmt = "execute transaction failed: {}", error)]
     TransactionFailed { error: String },
+    #[display(fmt = "unauthorized")]
+    Unauthorized,
+    #[display(fmt = "transaction id {} is {}, action not allowed", id, status)]
+    InvalidTransactionStatus { id: String, status: String },
+    #[display(fmt = "original request of transaction id {} not found", id)]
+    TransactionRequestNotFound { id: String },
+    #[display(fmt = "cancel transaction failed: {}", error)]
+    CancelTransactionFailed { error: String },
+    #[display(fmt = "account {} not found on chain id {}", address, chain_id)]
+    AccountNotFound { chain_id: u64, address: String },
+    #[display(fmt = "account {} already exists on chain id {}", address, chain_id)]
+    AccountAlreadyExists { chain_id: u64, address: String },
+    #[display(fmt = "invalid account: {}", error)]
+    InvalidAccount { error: String },
+    #[display(fmt = "too many requests, retry after {} seconds", retry_after_secs)]
+    RateLimited { retry_after_secs: u64 },
 }
 
 impl actix_web::error::ResponseError for ResponseError {
     fn status_code(&self) -> StatusCode {
-        StatusCode::OK
+        match self {
+            ResponseError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ResponseError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::OK,
+        }
     }
 
     fn error_response(&self) -> HttpResponse<BoxBody> {
@@ -62,9 +82,12 @@ impl actix_web::error::ResponseError for ResponseError {
             data: None,
             message: Some(self.to_string()),
         };
-        HttpResponse::build(self.status_code())
-            .insert_header(ContentType::json())
-            .json(response_json)
+        let mut response = HttpResponse::build(self.status_code());
+        response.insert_header(ContentType::json());
+        if let ResponseError::RateLimited { retry_after_secs } = self {
+            response.insert_header((RETRY_AFTER, retry_after_secs.to_string()));
+        }
+        response.json(response_json)
     }
 }
 
@@ -82,5 +105,13 @@ pub fn get_error_code(error: &ResponseError) -> ResponseCode {
         ResponseError::ChainIdNotFoundInRelayerConfig { .. } => ResponseCode::ChainIdNotFound,
         ResponseError::AccountNotFoundInDatabase => ResponseCode::AccountNotFoundInDatabase,
         ResponseError::TransactionFailed { .. } => ResponseCode::Failed,
+        ResponseError::Unauthorized => ResponseCode::Failed,
+        ResponseError::InvalidTransactionStatus { .. } => ResponseCode::Failed,
+        ResponseError::TransactionRequestNotFound { .. } => ResponseCode::TransactionNotFound,
+        ResponseError::CancelTransactionFailed { .. } => ResponseCode::Failed,
+        ResponseError::AccountNotFound { .. } => ResponseCode::AccountNotFoundInDatabase,
+        ResponseError::AccountAlreadyExists { .. } => ResponseCode::Failed,
+        ResponseError::InvalidAccount { .. } => ResponseCode::ValidateError,
+        ResponseError::RateLimited { .. } => ResponseCode::Failed,
     }
 }
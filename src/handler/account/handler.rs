@@ -8,10 +8,16 @@ use async_trait::async_trait;
 use log::debug;
 use mystiko_protos::storage::v1::SubFilter;
 use mystiko_storage::{Document, StatementFormatter, Storage};
-use secp256k1::{PublicKey, Secp256k1, SecretKey};
-use sha3::{Digest, Keccak256};
-use std::str::FromStr;
 use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+#[derive(TypedBuilder, Debug, Clone)]
+pub struct UpdateAccountOptions {
+    #[builder(default, setter(strip_option))]
+    pub available: Option<bool>,
+    #[builder(default, setter(strip_option))]
+    pub supported_erc20_tokens: Option<Vec<String>>,
+}
 
 #[derive(Debug)]
 pub struct Account<F: StatementFormatter, S: Storage> {
@@ -26,6 +32,14 @@ where
 {
     type Error = RelayerServerError;
 
+    async fn find_all(&self) -> Result<Vec<Document<DocumentAccount>>> {
+        self.db
+            .accounts
+            .find_all()
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
     async fn find_by_chain_id(&self, chain_id: u64) -> Result<Vec<Document<DocumentAccount>>> {
         let query_filter = SubFilter::equal(AccountColumn::ChainId, chain_id);
         self.db
@@ -34,6 +48,61 @@ where
             .await
             .map_err(RelayerServerError::StorageError)
     }
+
+    async fn find_by_address(&self, chain_id: u64, address: &str) -> Result<Option<Document<DocumentAccount>>> {
+        Ok(self
+            .find_by_chain_id(chain_id)
+            .await?
+            .into_iter()
+            .find(|account| account.data.chain_address.eq_ignore_ascii_case(address)))
+    }
+
+    async fn create(&self, account: &AccountConfig) -> Result<Document<DocumentAccount>> {
+        let doc = self.account_config_to_document(account)?;
+        self.db
+            .accounts
+            .insert(&doc)
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn update_by_address(
+        &self,
+        chain_id: u64,
+        address: &str,
+        options: &UpdateAccountOptions,
+    ) -> Result<Option<Document<DocumentAccount>>> {
+        if let Some(mut account) = self.find_by_address(chain_id, address).await? {
+            if let Some(available) = options.available {
+                account.data.available = available;
+            }
+            if let Some(supported_erc20_tokens) = &options.supported_erc20_tokens {
+                account.data.supported_erc20_tokens = supported_erc20_tokens
+                    .iter()
+                    .map(|token| token.to_lowercase())
+                    .collect();
+            }
+            let account = self
+                .db
+                .accounts
+                .update(&account)
+                .await
+                .map_err(RelayerServerError::StorageError)?;
+            return Ok(Some(account));
+        }
+        Ok(None)
+    }
+
+    async fn delete_by_address(&self, chain_id: u64, address: &str) -> Result<()> {
+        if let Some(account) = self.find_by_address(chain_id, address).await? {
+            self.db
+                .accounts
+                .delete(&account)
+                .await
+                .map_err(RelayerServerError::StorageError)?;
+        }
+        Ok(())
+    }
 }
 
 impl<F, S> Account<F, S>
@@ -49,47 +118,56 @@ where
 
     async fn init_data(&self, accounts: &[AccountConfig]) -> Result<()> {
         debug!("init accounts database");
-        // clear data
-        self.db
-            .accounts
-            .delete_all()
-            .await
-            .map_err(RelayerServerError::StorageError)?;
-        // batch insert accounts data
-        let mut docs = Vec::new();
+        // upsert the configured accounts, rows of other instances sharing the database are kept
         for account in accounts.iter() {
-            // private key to public key
-            let address = self.get_address(&account.private_key)?;
-            let supported_erc20_tokens: Vec<String> = account.supported_erc20_tokens.values().cloned().collect();
-            let doc = DocumentAccount {
-                chain_address: address,
-                chain_id: account.chain_id,
-                available: account.available,
-                supported_erc20_tokens: supported_erc20_tokens
-                    .iter()
-                    .map(|token| token.to_lowercase())
-                    .collect(),
-                balance_alarm_threshold: account.balance_alarm_threshold,
-                balance_check_interval_ms: account.balance_check_interval_ms,
-                insufficient_balances: false,
-            };
-            docs.push(doc);
+            let doc = self.account_config_to_document(account)?;
+            let query_filter = SubFilter::equal(AccountColumn::ChainId, doc.chain_id);
+            let existing = self
+                .db
+                .accounts
+                .find(query_filter)
+                .await
+                .map_err(RelayerServerError::StorageError)?
+                .into_iter()
+                .find(|existing| existing.data.chain_address.eq_ignore_ascii_case(&doc.chain_address));
+            match existing {
+                Some(mut existing) => {
+                    existing.data = DocumentAccount {
+                        insufficient_balances: existing.data.insufficient_balances,
+                        ..doc
+                    };
+                    self.db
+                        .accounts
+                        .update(&existing)
+                        .await
+                        .map_err(RelayerServerError::StorageError)?;
+                }
+                None => {
+                    self.db
+                        .accounts
+                        .insert(&doc)
+                        .await
+                        .map_err(RelayerServerError::StorageError)?;
+                }
+            }
         }
-        self.db
-            .accounts
-            .insert_batch(&docs)
-            .await
-            .map_err(RelayerServerError::StorageError)?;
         Ok(())
     }
 
-    fn get_address(&self, secret_key: &str) -> Result<String> {
-        let secp256k1 = Secp256k1::new();
-        let sk = SecretKey::from_str(secret_key).map_err(RelayerServerError::Secp256k1Error)?;
-        let pk = PublicKey::from_secret_key(&secp256k1, &sk);
-        let bytes = pk.serialize_uncompressed();
-        let hash = Keccak256::digest(&bytes[1..]);
-        let address = format!("0x{}", hex::encode(&hash[12..]));
-        Ok(address)
+    fn account_config_to_document(&self, account: &AccountConfig) -> Result<DocumentAccount> {
+        let address = account.address()?;
+        let supported_erc20_tokens: Vec<String> = account.supported_erc20_tokens.values().cloned().collect();
+        Ok(DocumentAccount {
+            chain_address: address,
+            chain_id: account.chain_id,
+            available: account.available,
+            supported_erc20_tokens: supported_erc20_tokens
+                .iter()
+                .map(|token| token.to_lowercase())
+                .collect(),
+            balance_alarm_threshold: account.balance_alarm_threshold,
+            balance_check_interval_ms: account.balance_check_interval_ms,
+            insufficient_balances: false,
+        })
     }
 }
@@ -1,11 +1,28 @@
 pub mod handler;
 
+use crate::configs::account::AccountConfig;
 use async_trait::async_trait;
+pub use handler::UpdateAccountOptions;
 use std::fmt::Debug;
 
 #[async_trait]
 pub trait AccountHandler<A>: Debug + Send + Sync {
     type Error;
 
+    async fn find_all(&self) -> Result<Vec<A>, Self::Error>;
+
     async fn find_by_chain_id(&self, chain_id: u64) -> Result<Vec<A>, Self::Error>;
+
+    async fn find_by_address(&self, chain_id: u64, address: &str) -> Result<Option<A>, Self::Error>;
+
+    async fn create(&self, account: &AccountConfig) -> Result<A, Self::Error>;
+
+    async fn update_by_address(
+        &self,
+        chain_id: u64,
+        address: &str,
+        options: &UpdateAccountOptions,
+    ) -> Result<Option<A>, Self::Error>;
+
+    async fn delete_by_address(&self, chain_id: u64, address: &str) -> Result<(), Self::Error>;
 }
@@ -0,0 +1,211 @@
+use crate::configs::auth::{hash_api_key, AuthConfig};
+use crate::database::api_key::{ApiKey as DocumentApiKey, ApiKeyColumn};
+use crate::database::nonce::{ApiKeyNonce as DocumentApiKeyNonce, ApiKeyNonceColumn};
+use crate::database::Database;
+use crate::error::RelayerServerError;
+use crate::handler::api_key::ApiKeyHandler;
+use crate::handler::types::{current_timestamp, Result};
+use async_trait::async_trait;
+use log::debug;
+use mystiko_protos::storage::v1::{Condition, SubFilter};
+use mystiko_storage::{Document, StatementFormatter, Storage};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+// expired nonces are deleted at most this often
+const NONCE_PURGE_INTERVAL_MS: u64 = 60_000;
+
+#[derive(Debug)]
+pub struct ApiKey<F: StatementFormatter, S: Storage> {
+    db: Arc<Database<F, S>>,
+    // usage since the last flush, key name -> (count, last used at)
+    pending_usage: Mutex<HashMap<String, (u64, u64)>>,
+    nonces_purged_at: AtomicU64,
+}
+
+#[async_trait]
+impl<F, S> ApiKeyHandler<Document<DocumentApiKey>> for ApiKey<F, S>
+where
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
+{
+    type Error = RelayerServerError;
+
+    async fn find_all(&self) -> Result<Vec<Document<DocumentApiKey>>> {
+        self.db
+            .api_keys
+            .find_all()
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Document<DocumentApiKey>>> {
+        let query_filter = SubFilter::equal(ApiKeyColumn::Name, name);
+        self.db
+            .api_keys
+            .find_one(query_filter)
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn record_usage(&self, name: &str) -> Result<()> {
+        let now = current_timestamp();
+        let mut pending_usage = self.pending_usage.lock().unwrap_or_else(PoisonError::into_inner);
+        let usage = pending_usage.entry(name.to_string()).or_insert((0, now));
+        usage.0 += 1;
+        usage.1 = now;
+        Ok(())
+    }
+
+    async fn flush_usage(&self) -> Result<()> {
+        let pending_usage = std::mem::take(&mut *self.pending_usage.lock().unwrap_or_else(PoisonError::into_inner));
+        let mut failed = Vec::new();
+        let mut result = Ok(());
+        for (name, (count, last_used_at)) in pending_usage {
+            if let Err(error) = self.add_usage(&name, count, last_used_at).await {
+                failed.push((name, (count, last_used_at)));
+                result = Err(error);
+            }
+        }
+        // counts that could not be written are retried with the next flush
+        let mut pending_usage = self.pending_usage.lock().unwrap_or_else(PoisonError::into_inner);
+        for (name, (count, last_used_at)) in failed {
+            let usage = pending_usage.entry(name).or_insert((0, last_used_at));
+            usage.0 += count;
+            usage.1 = usage.1.max(last_used_at);
+        }
+        result
+    }
+
+    async fn use_nonce(&self, name: &str, nonce: &str, expires_at: u64) -> Result<bool> {
+        let now = current_timestamp();
+        self.purge_nonces(now).await?;
+        let used_nonce = DocumentApiKeyNonce {
+            name: name.to_string(),
+            nonce: nonce.to_string(),
+            expires_at,
+        };
+        if let Err(error) = self.db.api_key_nonces.insert(&used_nonce).await {
+            // the nonce is taken, possibly by a request to another instance
+            match self.find_nonce(name, nonce).await? {
+                Some(used_nonce) if used_nonce.data.expires_at > now => return Ok(false),
+                Some(mut used_nonce) => {
+                    used_nonce.data.expires_at = expires_at;
+                    self.db
+                        .api_key_nonces
+                        .update(&used_nonce)
+                        .await
+                        .map_err(RelayerServerError::StorageError)?;
+                }
+                None => return Err(RelayerServerError::StorageError(error)),
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<F, S> ApiKey<F, S>
+where
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
+{
+    pub async fn new(db: Arc<Database<F, S>>, auth: &AuthConfig) -> Result<Self> {
+        let api_key = ApiKey {
+            db,
+            pending_usage: Mutex::new(HashMap::new()),
+            nonces_purged_at: AtomicU64::new(0),
+        };
+        api_key.init_data(auth).await?;
+        Ok(api_key)
+    }
+
+    async fn add_usage(&self, name: &str, count: u64, last_used_at: u64) -> Result<()> {
+        if let Some(mut api_key) = self.find_by_name(name).await? {
+            api_key.data.usage_count += count;
+            api_key.data.last_used_at = Some(api_key.data.last_used_at.unwrap_or_default().max(last_used_at));
+            self.db
+                .api_keys
+                .update(&api_key)
+                .await
+                .map_err(RelayerServerError::StorageError)?;
+        }
+        Ok(())
+    }
+
+    async fn find_nonce(&self, name: &str, nonce: &str) -> Result<Option<Document<DocumentApiKeyNonce>>> {
+        let query_filter = Condition::and(vec![
+            SubFilter::equal(ApiKeyNonceColumn::Name, name),
+            SubFilter::equal(ApiKeyNonceColumn::Nonce, nonce),
+        ]);
+        self.db
+            .api_key_nonces
+            .find_one(query_filter)
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn purge_nonces(&self, now: u64) -> Result<()> {
+        let purged_at = self.nonces_purged_at.load(Ordering::Relaxed);
+        if now.saturating_sub(purged_at) < NONCE_PURGE_INTERVAL_MS
+            || self
+                .nonces_purged_at
+                .compare_exchange(purged_at, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return Ok(());
+        }
+        self.db
+            .api_key_nonces
+            .delete_by_filter(SubFilter::less_equal(ApiKeyNonceColumn::ExpiresAt, now))
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        Ok(())
+    }
+
+    async fn init_data(&self, auth: &AuthConfig) -> Result<()> {
+        debug!("init api keys database");
+        // keys removed from the config are dropped, the counters of the others are kept
+        for api_key in self.find_all().await? {
+            if !auth.keys.contains_key(&api_key.data.name) {
+                self.db
+                    .api_keys
+                    .delete(&api_key)
+                    .await
+                    .map_err(RelayerServerError::StorageError)?;
+            }
+        }
+        for (name, config) in auth.keys.iter() {
+            let key_hash = hash_api_key(&config.key);
+            let scope = config.scope.to_string();
+            match self.find_by_name(name).await? {
+                Some(mut api_key) => {
+                    if api_key.data.key_hash != key_hash || api_key.data.scope != scope {
+                        api_key.data.key_hash = key_hash;
+                        api_key.data.scope = scope;
+                        self.db
+                            .api_keys
+                            .update(&api_key)
+                            .await
+                            .map_err(RelayerServerError::StorageError)?;
+                    }
+                }
+                None => {
+                    let api_key = DocumentApiKey {
+                        name: name.clone(),
+                        key_hash,
+                        scope,
+                        usage_count: 0,
+                        last_used_at: None,
+                    };
+                    self.db
+                        .api_keys
+                        .insert(&api_key)
+                        .await
+                        .map_err(RelayerServerError::StorageError)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+pub mod handler;
+
+use crate::database::api_key::ApiKey as DocumentApiKey;
+use crate::error::RelayerServerError;
+use async_trait::async_trait;
+pub use handler::*;
+use log::error;
+use mystiko_storage::Document;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub type ApiKeyHandlerRef = Arc<Box<dyn ApiKeyHandler<Document<DocumentApiKey>, Error = RelayerServerError>>>;
+
+#[async_trait]
+pub trait ApiKeyHandler<A>: Debug + Send + Sync {
+    type Error;
+
+    async fn find_all(&self) -> Result<Vec<A>, Self::Error>;
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<A>, Self::Error>;
+
+    // counted in memory, written to the database by flush_usage
+    async fn record_usage(&self, name: &str) -> Result<(), Self::Error>;
+
+    async fn flush_usage(&self) -> Result<(), Self::Error>;
+
+    // returns false when the nonce of a signed request was seen before by any instance, it is kept until expires_at
+    async fn use_nonce(&self, name: &str, nonce: &str, expires_at: u64) -> Result<bool, Self::Error>;
+}
+
+pub async fn flush_usage_periodically(handler: ApiKeyHandlerRef, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if let Err(error) = handler.flush_usage().await {
+            error!("flush api key usage got error: {:?}", error);
+        }
+    }
+}
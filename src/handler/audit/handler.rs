@@ -0,0 +1,95 @@
+use crate::database::audit::{AuditLog as DocumentAuditLog, AuditLogColumn};
+use crate::database::Database;
+use crate::error::RelayerServerError;
+use crate::handler::audit::AuditHandler;
+use crate::handler::types::Result;
+use async_trait::async_trait;
+use derive_more::Display;
+use log::info;
+use mystiko_protos::storage::v1::SubFilter;
+use mystiko_storage::{Document, StatementFormatter, Storage};
+use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    #[display(fmt = "requeue")]
+    Requeue,
+    #[display(fmt = "fail")]
+    Fail,
+    #[display(fmt = "cancel")]
+    Cancel,
+    #[display(fmt = "add_account")]
+    AddAccount,
+    #[display(fmt = "enable_account")]
+    EnableAccount,
+    #[display(fmt = "disable_account")]
+    DisableAccount,
+    #[display(fmt = "update_account_tokens")]
+    UpdateAccountTokens,
+    #[display(fmt = "retire_account")]
+    RetireAccount,
+}
+
+#[derive(TypedBuilder, Debug, Clone)]
+#[builder(field_defaults(setter(into)))]
+pub struct CreateAuditLogOptions {
+    pub target: String,
+    pub action: AuditAction,
+    pub operator: String,
+    pub reason: String,
+    #[builder(default, setter(strip_option))]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AuditLog<F: StatementFormatter, S: Storage> {
+    db: Arc<Database<F, S>>,
+}
+
+#[async_trait]
+impl<F, S> AuditHandler<Document<DocumentAuditLog>> for AuditLog<F, S>
+where
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
+{
+    type Error = RelayerServerError;
+
+    async fn create(&self, options: &CreateAuditLogOptions) -> Result<Document<DocumentAuditLog>> {
+        info!(
+            "audit: operator {} performed {} on {} (reason = {}, detail = {:?})",
+            options.operator, options.action, options.target, options.reason, options.detail
+        );
+        let log = DocumentAuditLog {
+            target: options.target.clone(),
+            action: options.action.to_string(),
+            operator: options.operator.clone(),
+            reason: options.reason.clone(),
+            detail: options.detail.clone(),
+        };
+        self.db
+            .audit_logs
+            .insert(&log)
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn find_by_target(&self, target: &str) -> Result<Vec<Document<DocumentAuditLog>>> {
+        let query_filter = SubFilter::equal(AuditLogColumn::Target, target);
+        self.db
+            .audit_logs
+            .find(query_filter)
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+}
+
+impl<F, S> AuditLog<F, S>
+where
+    F: StatementFormatter,
+    S: Storage,
+{
+    pub fn new(db: Arc<Database<F, S>>) -> Self {
+        Self { db }
+    }
+}
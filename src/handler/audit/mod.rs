@@ -0,0 +1,14 @@
+pub mod handler;
+
+use async_trait::async_trait;
+pub use handler::*;
+use std::fmt::Debug;
+
+#[async_trait]
+pub trait AuditHandler<A>: Debug + Send + Sync {
+    type Error;
+
+    async fn create(&self, options: &CreateAuditLogOptions) -> Result<A, Self::Error>;
+
+    async fn find_by_target(&self, target: &str) -> Result<Vec<A>, Self::Error>;
+}
@@ -0,0 +1,136 @@
+use crate::database::lease::{AccountLease as DocumentAccountLease, AccountLeaseColumn};
+use crate::database::Database;
+use crate::error::RelayerServerError;
+use crate::handler::lease::LeaseHandler;
+use crate::handler::types::{current_timestamp, Result};
+use async_trait::async_trait;
+use log::debug;
+use mystiko_protos::storage::v1::{ColumnValue, Condition, SubFilter};
+use mystiko_storage::{Document, StatementFormatter, Storage, DOCUMENT_UPDATED_AT_FIELD};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct Lease<F: StatementFormatter, S: Storage> {
+    db: Arc<Database<F, S>>,
+    // an expired lease is only taken over this long after its expiry, covers clock skew between instances
+    max_clock_skew: Duration,
+}
+
+#[async_trait]
+impl<F, S> LeaseHandler<Document<DocumentAccountLease>> for Lease<F, S>
+where
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
+{
+    type Error = RelayerServerError;
+
+    async fn try_acquire(&self, chain_id: u64, address: &str, owner: &str, ttl_ms: u64) -> Result<bool> {
+        let lease_key = lease_key(chain_id, address);
+        let now = current_timestamp();
+        match self.find_by_key(&lease_key).await? {
+            None => {
+                let lease = DocumentAccountLease {
+                    lease_key,
+                    owner: owner.to_string(),
+                    expires_at: now + ttl_ms,
+                };
+                // the unique key rejects the insert when another instance was faster
+                match self.db.leases.insert(&lease).await {
+                    Ok(_) => Ok(true),
+                    Err(error) => {
+                        debug!("insert lease {} got error: {:?}", lease.lease_key, error);
+                        Ok(false)
+                    }
+                }
+            }
+            Some(lease) => {
+                // one conditional write each, a concurrent taker's write no longer matches once this one landed
+                let expires_at = now + ttl_ms;
+                let renewed = lease.data.owner == owner
+                    && self
+                        .update_if(
+                            &lease_key,
+                            owner,
+                            expires_at,
+                            SubFilter::equal(AccountLeaseColumn::Owner, owner),
+                        )
+                        .await?;
+                let expired_before = now.saturating_sub(self.max_clock_skew.as_millis() as u64);
+                Ok(renewed
+                    || (lease.data.expires_at < expired_before
+                        && self
+                            .update_if(
+                                &lease_key,
+                                owner,
+                                expires_at,
+                                SubFilter::less(AccountLeaseColumn::ExpiresAt, expired_before),
+                            )
+                            .await?))
+            }
+        }
+    }
+
+    async fn release(&self, chain_id: u64, address: &str, owner: &str) -> Result<()> {
+        if let Some(lease) = self.find(chain_id, address).await? {
+            if lease.data.owner == owner {
+                self.db
+                    .leases
+                    .delete(&lease)
+                    .await
+                    .map_err(RelayerServerError::StorageError)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn find(&self, chain_id: u64, address: &str) -> Result<Option<Document<DocumentAccountLease>>> {
+        self.find_by_key(&lease_key(chain_id, address)).await
+    }
+}
+
+impl<F, S> Lease<F, S>
+where
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
+{
+    pub fn new(db: Arc<Database<F, S>>, max_clock_skew: Duration) -> Self {
+        Lease { db, max_clock_skew }
+    }
+
+    async fn find_by_key(&self, lease_key: &str) -> Result<Option<Document<DocumentAccountLease>>> {
+        self.db
+            .leases
+            .find_one(SubFilter::equal(AccountLeaseColumn::LeaseKey, lease_key))
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    // the storage reports no affected rows, the row is read back to see whether this write landed
+    async fn update_if(&self, lease_key: &str, owner: &str, expires_at: u64, condition: SubFilter) -> Result<bool> {
+        let column_values: Vec<(String, ColumnValue)> = vec![
+            (AccountLeaseColumn::Owner.to_string(), owner.to_string().into()),
+            (AccountLeaseColumn::ExpiresAt.to_string(), expires_at.into()),
+            (DOCUMENT_UPDATED_AT_FIELD.to_string(), current_timestamp().into()),
+        ];
+        self.db
+            .leases
+            .update_by_filter(
+                column_values,
+                Condition::and(vec![
+                    SubFilter::equal(AccountLeaseColumn::LeaseKey, lease_key),
+                    condition,
+                ]),
+            )
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        Ok(self
+            .find_by_key(lease_key)
+            .await?
+            .is_some_and(|lease| lease.data.owner == owner && lease.data.expires_at == expires_at))
+    }
+}
+
+fn lease_key(chain_id: u64, address: &str) -> String {
+    format!("{}:{}", chain_id, address.to_lowercase())
+}
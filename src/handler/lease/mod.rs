@@ -0,0 +1,17 @@
+pub mod handler;
+
+use async_trait::async_trait;
+pub use handler::*;
+use std::fmt::Debug;
+
+#[async_trait]
+pub trait LeaseHandler<L>: Debug + Send + Sync {
+    type Error;
+
+    // takes a free or expired lease or renews one already held, returns whether the owner holds it now
+    async fn try_acquire(&self, chain_id: u64, address: &str, owner: &str, ttl_ms: u64) -> Result<bool, Self::Error>;
+
+    async fn release(&self, chain_id: u64, address: &str, owner: &str) -> Result<(), Self::Error>;
+
+    async fn find(&self, chain_id: u64, address: &str) -> Result<Option<L>, Self::Error>;
+}
@@ -1,3 +1,8 @@
 pub mod account;
+pub mod api_key;
+pub mod audit;
+pub mod lease;
+pub mod quote;
 pub mod transaction;
 pub mod types;
+pub mod webhook;
@@ -0,0 +1,83 @@
+use crate::database::quote::{AcceptedQuote as DocumentAcceptedQuote, AcceptedQuoteColumn};
+use crate::database::Database;
+use crate::error::RelayerServerError;
+use crate::handler::quote::QuoteHandler;
+use crate::handler::types::Result;
+use async_trait::async_trait;
+use mystiko_protos::storage::v1::SubFilter;
+use mystiko_storage::{Document, StatementFormatter, Storage};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Quote<F: StatementFormatter, S: Storage> {
+    db: Arc<Database<F, S>>,
+}
+
+#[async_trait]
+impl<F, S> QuoteHandler<Document<DocumentAcceptedQuote>> for Quote<F, S>
+where
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
+{
+    type Error = RelayerServerError;
+
+    async fn save(
+        &self,
+        signature: &str,
+        quote_id: &str,
+        minimum_gas_fee: &str,
+    ) -> Result<Document<DocumentAcceptedQuote>> {
+        match self.find(signature).await? {
+            Some(mut quote) => {
+                quote.data.quote_id = quote_id.to_string();
+                quote.data.minimum_gas_fee = minimum_gas_fee.to_string();
+                self.db
+                    .quotes
+                    .update(&quote)
+                    .await
+                    .map_err(RelayerServerError::StorageError)
+            }
+            None => {
+                let quote = DocumentAcceptedQuote {
+                    signature: signature.to_string(),
+                    quote_id: quote_id.to_string(),
+                    minimum_gas_fee: minimum_gas_fee.to_string(),
+                };
+                self.db
+                    .quotes
+                    .insert(&quote)
+                    .await
+                    .map_err(RelayerServerError::StorageError)
+            }
+        }
+    }
+
+    async fn find(&self, signature: &str) -> Result<Option<Document<DocumentAcceptedQuote>>> {
+        self.db
+            .quotes
+            .find_one(SubFilter::equal(AcceptedQuoteColumn::Signature, signature))
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn delete(&self, signature: &str) -> Result<()> {
+        if let Some(quote) = self.find(signature).await? {
+            self.db
+                .quotes
+                .delete(&quote)
+                .await
+                .map_err(RelayerServerError::StorageError)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F, S> Quote<F, S>
+where
+    F: StatementFormatter,
+    S: Storage,
+{
+    pub fn new(db: Arc<Database<F, S>>) -> Self {
+        Self { db }
+    }
+}
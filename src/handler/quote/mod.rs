@@ -0,0 +1,23 @@
+pub mod handler;
+
+use crate::database::quote::AcceptedQuote as DocumentAcceptedQuote;
+use crate::error::RelayerServerError;
+use async_trait::async_trait;
+pub use handler::*;
+use mystiko_storage::Document;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+pub type QuoteHandlerRef = Arc<Box<dyn QuoteHandler<Document<DocumentAcceptedQuote>, Error = RelayerServerError>>>;
+
+#[async_trait]
+pub trait QuoteHandler<Q>: Debug + Send + Sync {
+    type Error;
+
+    // replaces the quote of a signature, a failed job may be submitted again
+    async fn save(&self, signature: &str, quote_id: &str, minimum_gas_fee: &str) -> Result<Q, Self::Error>;
+
+    async fn find(&self, signature: &str) -> Result<Option<Q>, Self::Error>;
+
+    async fn delete(&self, signature: &str) -> Result<(), Self::Error>;
+}
@@ -1,16 +1,31 @@
+use crate::database::request::{TransactionRequest as DocumentTransactionRequest, TransactionRequestColumn};
+use crate::database::serial_number::{
+    serial_number_key, TransactionSerialNumber as DocumentTransactionSerialNumber, TransactionSerialNumberColumn,
+};
 use crate::database::transaction::{Transaction as DocumentTransaction, TransactionColumn};
 use crate::database::Database;
 use crate::error::RelayerServerError;
+use crate::handler::transaction::status::{TransactionStatusBus, TransactionStatusEvent};
 use crate::handler::transaction::TransactionHandler;
-use crate::handler::types::Result;
+use crate::handler::types::{current_timestamp, Result};
 use async_trait::async_trait;
-use mystiko_protos::storage::v1::SubFilter;
+use log::error;
+use mystiko_protos::core::v1::SpendType;
+use mystiko_protos::storage::v1::{ColumnValue, Condition, Order, OrderBy, QueryFilter, SubFilter};
 use mystiko_relayer_types::{TransactRequestData, TransactStatus};
-use mystiko_storage::{Document, StatementFormatter, Storage};
+use mystiko_storage::{
+    Document, StatementFormatter, Storage, DOCUMENT_CREATED_AT_FIELD, DOCUMENT_ID_FIELD, DOCUMENT_UPDATED_AT_FIELD,
+};
 use mystiko_utils::convert::u256_to_biguint;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tokio::sync::Mutex;
 use typed_builder::TypedBuilder;
 
+// transactions read from storage at a time when matching an error message substring
+const ERROR_MESSAGE_SCAN_BATCH: u64 = 500;
+
 #[derive(TypedBuilder, Debug, Clone)]
 pub struct UpdateTransactionOptions {
     #[builder(default, setter(strip_option))]
@@ -21,9 +36,60 @@ pub struct UpdateTransactionOptions {
     pub transaction_hash: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionSortBy {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    ChainId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(TypedBuilder, Debug, Clone, Default)]
+pub struct QueryTransactionOptions {
+    #[builder(default, setter(strip_option))]
+    pub chain_id: Option<u64>,
+    #[builder(default, setter(strip_option))]
+    pub status: Option<TransactStatus>,
+    #[builder(default, setter(strip_option))]
+    pub spend_type: Option<SpendType>,
+    #[builder(default, setter(strip_option, into))]
+    pub asset_symbol: Option<String>,
+    // created_at lower bound in milliseconds, inclusive
+    #[builder(default, setter(strip_option))]
+    pub created_after: Option<u64>,
+    // created_at upper bound in milliseconds, inclusive
+    #[builder(default, setter(strip_option))]
+    pub created_before: Option<u64>,
+    // updated_at lower bound in milliseconds, inclusive
+    #[builder(default, setter(strip_option))]
+    pub updated_after: Option<u64>,
+    // case-insensitive substring of the error message
+    #[builder(default, setter(strip_option, into))]
+    pub error_message: Option<String>,
+    #[builder(default)]
+    pub sort_by: TransactionSortBy,
+    #[builder(default)]
+    pub order: SortOrder,
+    #[builder(default, setter(strip_option))]
+    pub offset: Option<u64>,
+    #[builder(default, setter(strip_option))]
+    pub limit: Option<u64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Transaction<F: StatementFormatter, S: Storage> {
     db: Arc<Database<F, S>>,
+    status_bus: Arc<TransactionStatusBus>,
+    status_lock: Arc<Mutex<()>>,
 }
 
 #[async_trait]
@@ -35,13 +101,27 @@ where
     type Error = RelayerServerError;
 
     async fn create_by_request(&self, data: TransactRequestData) -> Result<Document<DocumentTransaction>> {
+        let raw_request = serde_json::to_string(&data)?;
         // convert request data to document transaction
         let transaction = self.request_data_to_document_transaction(data)?;
-        self.db
+        let transaction = self
+            .db
             .transactions
             .insert(&transaction)
             .await
-            .map_err(RelayerServerError::StorageError)
+            .map_err(RelayerServerError::StorageError)?;
+        // the storage has no transactions, a queued row without its request would never be sent but still
+        // block resubmissions of the signature, so the rows written so far are removed again
+        if let Err(error) = self.insert_request_and_serial_numbers(&transaction, raw_request).await {
+            if let Err(rollback_error) = self.delete_created(&transaction).await {
+                error!(
+                    "roll back transaction {} got error: {:?}",
+                    transaction.id, rollback_error
+                );
+            }
+            return Err(error);
+        }
+        Ok(transaction)
     }
 
     async fn find_by_id(&self, id: &str) -> Result<Option<Document<DocumentTransaction>>> {
@@ -52,6 +132,67 @@ where
             .map_err(RelayerServerError::StorageError)
     }
 
+    async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Document<DocumentTransaction>>> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+        self.db
+            .transactions
+            .find(SubFilter::in_list(DOCUMENT_ID_FIELD, ids.to_vec()))
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn find_request_by_id(&self, id: &str) -> Result<Option<TransactRequestData>> {
+        let query_filter = SubFilter::equal(TransactionRequestColumn::TransactionId, id);
+        let request = self
+            .db
+            .requests
+            .find_one(query_filter)
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        match request {
+            Some(request) => Ok(Some(serde_json::from_str(&request.data.data)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_transaction_hash(&self, transaction_hash: &str) -> Result<Vec<Document<DocumentTransaction>>> {
+        // stored hashes may come with or without the 0x prefix
+        let transaction_hash = transaction_hash.to_lowercase();
+        let unprefixed = transaction_hash.trim_start_matches("0x");
+        let hashes = vec![unprefixed.to_string(), format!("0x{}", unprefixed)];
+        self.db
+            .transactions
+            .find(SubFilter::in_list(TransactionColumn::TransactionHash, hashes))
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn find_by_signature(&self, signature: &str) -> Result<Vec<Document<DocumentTransaction>>> {
+        self.db
+            .transactions
+            .find(SubFilter::equal(TransactionColumn::Signature, signature))
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn find_by_serial_number(&self, serial_number: &BigUint) -> Result<Vec<Document<DocumentTransaction>>> {
+        let ids = self
+            .db
+            .serial_numbers
+            .find(SubFilter::equal(
+                TransactionSerialNumberColumn::SerialNumber,
+                serial_number_key(serial_number),
+            ))
+            .await
+            .map_err(RelayerServerError::StorageError)?
+            .into_iter()
+            .map(|serial_number| serial_number.data.transaction_id)
+            .collect::<Vec<_>>();
+        self.find_by_ids(&ids).await
+    }
+
     async fn update_by_id(
         &self,
         id: &str,
@@ -60,6 +201,71 @@ where
         self.update(self.find_by_id(id).await?, options).await
     }
 
+    async fn update_by_id_if_status(
+        &self,
+        id: &str,
+        expected: &[TransactStatus],
+        options: &UpdateTransactionOptions,
+    ) -> Result<Option<Document<DocumentTransaction>>> {
+        let expected = expected.iter().map(|status| status.to_string()).collect::<Vec<_>>();
+        self.update_by_id_if(
+            id,
+            vec![SubFilter::in_list(TransactionColumn::Status, expected)],
+            options,
+        )
+        .await
+    }
+
+    async fn update_by_id_if_claimed(
+        &self,
+        id: &str,
+        claimed_before: u64,
+        options: &UpdateTransactionOptions,
+    ) -> Result<Option<Document<DocumentTransaction>>> {
+        // a claimed job is pending without a hash, see the consumer
+        self.update_by_id_if(
+            id,
+            vec![
+                SubFilter::equal(TransactionColumn::Status, TransactStatus::Pending.to_string()),
+                SubFilter::is_null(TransactionColumn::TransactionHash),
+                SubFilter::less_equal(DOCUMENT_UPDATED_AT_FIELD, claimed_before),
+            ],
+            options,
+        )
+        .await
+    }
+
+    async fn find_unsent(
+        &self,
+        chain_id: u64,
+        queued_before: u64,
+        claimed_before: u64,
+    ) -> Result<Vec<Document<DocumentTransaction>>> {
+        let queued = Condition::and(vec![
+            SubFilter::equal(TransactionColumn::ChainId, chain_id),
+            SubFilter::equal(TransactionColumn::Status, TransactStatus::Queued.to_string()),
+            SubFilter::less_equal(DOCUMENT_UPDATED_AT_FIELD, queued_before),
+        ]);
+        let claimed = Condition::and(vec![
+            SubFilter::equal(TransactionColumn::ChainId, chain_id),
+            SubFilter::equal(TransactionColumn::Status, TransactStatus::Pending.to_string()),
+            SubFilter::is_null(TransactionColumn::TransactionHash),
+            SubFilter::less_equal(DOCUMENT_UPDATED_AT_FIELD, claimed_before),
+        ]);
+        let mut transactions = vec![];
+        for condition in [queued, claimed] {
+            transactions.extend(
+                self.db
+                    .transactions
+                    .find(condition)
+                    .await
+                    .map_err(RelayerServerError::StorageError)?,
+            );
+        }
+        transactions.sort_by_key(|transaction| transaction.created_at);
+        Ok(transactions)
+    }
+
     async fn is_repeated_transaction(&self, signature: &str) -> Result<bool> {
         let query_filter = SubFilter::equal(TransactionColumn::Signature, signature);
         let transactions = self.db.transactions.find(query_filter).await?;
@@ -70,6 +276,36 @@ where
         }
         Ok(false)
     }
+
+    async fn find(&self, options: &QueryTransactionOptions) -> Result<Vec<Document<DocumentTransaction>>> {
+        if options.error_message.is_none() {
+            return self
+                .db
+                .transactions
+                .find(self.query_filter(options, true))
+                .await
+                .map_err(RelayerServerError::StorageError);
+        }
+        let offset = options.offset.unwrap_or(0) as usize;
+        let keep = options
+            .limit
+            .map_or(usize::MAX, |limit| offset.saturating_add(limit as usize));
+        let (transactions, _) = self.scan_error_message(options, keep).await?;
+        Ok(transactions.into_iter().skip(offset).collect())
+    }
+
+    async fn count(&self, options: &QueryTransactionOptions) -> Result<u64> {
+        if options.error_message.is_none() {
+            return self
+                .db
+                .transactions
+                .count(self.query_filter(options, false))
+                .await
+                .map_err(RelayerServerError::StorageError);
+        }
+        let (_, count) = self.scan_error_message(options, 0).await?;
+        Ok(count)
+    }
 }
 
 impl<F, S> Transaction<F, S>
@@ -78,7 +314,125 @@ where
     S: Storage,
 {
     pub fn new(db: Arc<Database<F, S>>) -> Self {
-        Self { db }
+        Self::with_status_bus(db, Arc::new(TransactionStatusBus::default()))
+    }
+
+    pub fn with_status_bus(db: Arc<Database<F, S>>, status_bus: Arc<TransactionStatusBus>) -> Self {
+        Self {
+            db,
+            status_bus,
+            status_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // the conditional write of update_by_id_if_status and update_by_id_if_claimed
+    async fn update_by_id_if(
+        &self,
+        id: &str,
+        conditions: Vec<SubFilter>,
+        options: &UpdateTransactionOptions,
+    ) -> Result<Option<Document<DocumentTransaction>>> {
+        // the lock orders claims of this instance, the conditional write guards against other writers
+        let _guard = self.status_lock.lock().await;
+        let updated_at = current_timestamp();
+        let mut column_values: Vec<(String, ColumnValue)> =
+            vec![(DOCUMENT_UPDATED_AT_FIELD.to_string(), updated_at.into())];
+        if let Some(status) = &options.status {
+            column_values.push((TransactionColumn::Status.to_string(), status.to_string().into()));
+        }
+        if let Some(error_message) = &options.error_message {
+            column_values.push((
+                TransactionColumn::ErrorMessage.to_string(),
+                error_message.clone().into(),
+            ));
+        }
+        if let Some(transaction_hash) = &options.transaction_hash {
+            column_values.push((
+                TransactionColumn::TransactionHash.to_string(),
+                transaction_hash.clone().into(),
+            ));
+        }
+        let mut sub_filters = vec![SubFilter::equal(DOCUMENT_ID_FIELD, id)];
+        sub_filters.extend(conditions);
+        self.db
+            .transactions
+            .update_by_filter(column_values, Condition::and(sub_filters))
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        // the write only landed if the row now carries this update
+        match self.find_by_id(id).await? {
+            Some(transaction)
+                if transaction.updated_at == updated_at
+                    && options
+                        .status
+                        .as_ref()
+                        .map_or(true, |status| transaction.data.status.eq(status)) =>
+            {
+                self.status_bus.publish(TransactionStatusEvent::from(&transaction));
+                Ok(Some(transaction))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn insert_request_and_serial_numbers(
+        &self,
+        transaction: &Document<DocumentTransaction>,
+        raw_request: String,
+    ) -> Result<()> {
+        // keep original request for requeue
+        self.db
+            .requests
+            .insert(&DocumentTransactionRequest {
+                transaction_id: transaction.id.clone(),
+                data: raw_request,
+            })
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        // serial numbers are looked up one by one
+        let serial_numbers = transaction
+            .data
+            .serial_numbers
+            .iter()
+            .flatten()
+            .map(|serial_number| DocumentTransactionSerialNumber {
+                transaction_id: transaction.id.clone(),
+                serial_number: serial_number_key(serial_number),
+            })
+            .collect::<Vec<_>>();
+        if !serial_numbers.is_empty() {
+            self.db
+                .serial_numbers
+                .insert_batch(&serial_numbers)
+                .await
+                .map_err(RelayerServerError::StorageError)?;
+        }
+        Ok(())
+    }
+
+    async fn delete_created(&self, transaction: &Document<DocumentTransaction>) -> Result<()> {
+        self.db
+            .serial_numbers
+            .delete_by_filter(SubFilter::equal(
+                TransactionSerialNumberColumn::TransactionId,
+                transaction.id.as_str(),
+            ))
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        self.db
+            .requests
+            .delete_by_filter(SubFilter::equal(
+                TransactionRequestColumn::TransactionId,
+                transaction.id.as_str(),
+            ))
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        self.db
+            .transactions
+            .delete(transaction)
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        Ok(())
     }
 
     fn request_data_to_document_transaction(&self, data: TransactRequestData) -> Result<DocumentTransaction> {
@@ -88,7 +442,8 @@ where
             bridge_type: data.bridge_type,
             status: TransactStatus::Queued,
             pool_address: data.pool_address,
-            asset_symbol: data.asset_symbol,
+            // symbols are matched case-insensitively everywhere, one casing lets storage filter them
+            asset_symbol: data.asset_symbol.to_uppercase(),
             asset_decimals: data.asset_decimals,
             circuit_type: data.circuit_type,
             proof: serde_json::to_string(&data.contract_param.proof)?,
@@ -148,6 +503,113 @@ where
         })
     }
 
+    // everything but the error message substring is filtered, sorted and paged by storage
+    fn query_filter(&self, options: &QueryTransactionOptions, paged: bool) -> QueryFilter {
+        let mut sub_filters = vec![];
+        if let Some(chain_id) = options.chain_id {
+            sub_filters.push(SubFilter::equal(TransactionColumn::ChainId, chain_id));
+        }
+        if let Some(status) = &options.status {
+            sub_filters.push(SubFilter::equal(TransactionColumn::Status, status.to_string()));
+        }
+        if let Some(spend_type) = &options.spend_type {
+            sub_filters.push(SubFilter::equal(TransactionColumn::SpendType, *spend_type as i32));
+        }
+        if let Some(asset_symbol) = &options.asset_symbol {
+            // stored uppercase, see request_data_to_document_transaction
+            sub_filters.push(SubFilter::equal(
+                TransactionColumn::AssetSymbol,
+                asset_symbol.to_uppercase(),
+            ));
+        }
+        if let Some(created_after) = options.created_after {
+            sub_filters.push(SubFilter::greater_equal(DOCUMENT_CREATED_AT_FIELD, created_after));
+        }
+        if let Some(created_before) = options.created_before {
+            sub_filters.push(SubFilter::less_equal(DOCUMENT_CREATED_AT_FIELD, created_before));
+        }
+        if let Some(updated_after) = options.updated_after {
+            sub_filters.push(SubFilter::greater_equal(DOCUMENT_UPDATED_AT_FIELD, updated_after));
+        }
+        if options.error_message.is_some() {
+            sub_filters.push(SubFilter::is_not_null(TransactionColumn::ErrorMessage));
+        }
+
+        let sort_column = match options.sort_by {
+            TransactionSortBy::CreatedAt => DOCUMENT_CREATED_AT_FIELD.to_string(),
+            TransactionSortBy::UpdatedAt => DOCUMENT_UPDATED_AT_FIELD.to_string(),
+            TransactionSortBy::ChainId => TransactionColumn::ChainId.to_string(),
+        };
+        let order = match options.order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        };
+        let mut query_filter = QueryFilter::from(Condition::and(sub_filters));
+        query_filter.order_by = Some(
+            OrderBy::builder()
+                .columns(vec![sort_column, DOCUMENT_ID_FIELD.to_string()])
+                .order(order)
+                .build(),
+        );
+        if paged {
+            query_filter.offset = options.offset;
+            query_filter.limit = options.limit;
+        }
+        query_filter
+    }
+
+    // storage has no substring match, so the error message is matched on batches read from storage in query
+    // order. the scan stops once `keep` matches are held, with nothing to keep every match is counted
+    async fn scan_error_message(
+        &self,
+        options: &QueryTransactionOptions,
+        keep: usize,
+    ) -> Result<(Vec<Document<DocumentTransaction>>, u64)> {
+        let mut kept = vec![];
+        let mut count = 0;
+        let mut offset = 0;
+        loop {
+            let mut query_filter = self.query_filter(options, false);
+            query_filter.offset = Some(offset);
+            query_filter.limit = Some(ERROR_MESSAGE_SCAN_BATCH);
+            let batch = self
+                .db
+                .transactions
+                .find(query_filter)
+                .await
+                .map_err(RelayerServerError::StorageError)?;
+            let batch_size = batch.len() as u64;
+            for transaction in batch
+                .into_iter()
+                .filter(|transaction| Self::matches_error_message(options, transaction))
+            {
+                count += 1;
+                if kept.len() < keep {
+                    kept.push(transaction);
+                    if kept.len() == keep {
+                        return Ok((kept, count));
+                    }
+                }
+            }
+            if batch_size < ERROR_MESSAGE_SCAN_BATCH {
+                return Ok((kept, count));
+            }
+            offset += ERROR_MESSAGE_SCAN_BATCH;
+        }
+    }
+
+    fn matches_error_message(options: &QueryTransactionOptions, transaction: &Document<DocumentTransaction>) -> bool {
+        match &options.error_message {
+            Some(error_message) => transaction
+                .data
+                .error_message
+                .as_ref()
+                .map(|existing_error| existing_error.to_lowercase().contains(&error_message.to_lowercase()))
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
     async fn update(
         &self,
         existing_transaction: Option<Document<DocumentTransaction>>,
@@ -186,7 +648,9 @@ where
                 }
             }
             return if has_update {
-                Ok(Some(self.db.transactions.update(&existing_transaction).await?))
+                let transaction = self.db.transactions.update(&existing_transaction).await?;
+                self.status_bus.publish(TransactionStatusEvent::from(&transaction));
+                Ok(Some(transaction))
             } else {
                 Ok(Some(existing_transaction))
             };
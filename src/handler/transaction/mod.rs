@@ -1,11 +1,13 @@
 pub mod handler;
+pub mod status;
 
 use crate::database::transaction::Transaction as DocumentTransaction;
 use crate::error::RelayerServerError;
 use async_trait::async_trait;
 pub use handler::*;
-use mystiko_relayer_types::TransactRequestData;
+use mystiko_relayer_types::{TransactRequestData, TransactStatus};
 use mystiko_storage::Document;
+use num_bigint::BigUint;
 use std::fmt::Debug;
 
 #[async_trait]
@@ -16,9 +18,46 @@ pub trait TransactionHandler<T>: Debug + Send + Sync {
 
     async fn find_by_id(&self, id: &str) -> Result<Option<T>, Self::Error>;
 
+    // unknown ids are left out
+    async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<T>, Self::Error>;
+
+    async fn find_request_by_id(&self, id: &str) -> Result<Option<TransactRequestData>, Self::Error>;
+
+    async fn find_by_transaction_hash(&self, transaction_hash: &str) -> Result<Vec<T>, Self::Error>;
+
+    // a failed job can be submitted again, the signature and serial numbers may match several jobs
+    async fn find_by_signature(&self, signature: &str) -> Result<Vec<T>, Self::Error>;
+
+    async fn find_by_serial_number(&self, serial_number: &BigUint) -> Result<Vec<T>, Self::Error>;
+
     async fn update_by_id(&self, id: &str, options: &UpdateTransactionOptions) -> Result<Option<T>, Self::Error>;
 
+    // only updates a job whose status is still one of the expected ones, returns none when it was not
+    async fn update_by_id_if_status(
+        &self,
+        id: &str,
+        expected: &[TransactStatus],
+        options: &UpdateTransactionOptions,
+    ) -> Result<Option<T>, Self::Error>;
+
+    // only updates a job a consumer claimed without sending it and that was left alone since claimed_before,
+    // e.g. after the consumer crashed, returns none otherwise
+    async fn update_by_id_if_claimed(
+        &self,
+        id: &str,
+        claimed_before: u64,
+        options: &UpdateTransactionOptions,
+    ) -> Result<Option<T>, Self::Error>;
+
+    // jobs of a chain nothing sent yet: queued ones left alone since queued_before and claimed ones left alone
+    // since claimed_before, oldest first
+    async fn find_unsent(&self, chain_id: u64, queued_before: u64, claimed_before: u64) -> Result<Vec<T>, Self::Error>;
+
     async fn is_repeated_transaction(&self, signature: &str) -> Result<bool, Self::Error>;
+
+    async fn find(&self, options: &QueryTransactionOptions) -> Result<Vec<T>, Self::Error>;
+
+    async fn count(&self, options: &QueryTransactionOptions) -> Result<u64, Self::Error>;
 }
 
 #[async_trait]
@@ -35,6 +74,32 @@ impl TransactionHandler<Document<DocumentTransaction>>
         self.as_ref().find_by_id(id).await
     }
 
+    async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().find_by_ids(ids).await
+    }
+
+    async fn find_request_by_id(&self, id: &str) -> Result<Option<TransactRequestData>, Self::Error> {
+        self.as_ref().find_request_by_id(id).await
+    }
+
+    async fn find_by_transaction_hash(
+        &self,
+        transaction_hash: &str,
+    ) -> Result<Vec<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().find_by_transaction_hash(transaction_hash).await
+    }
+
+    async fn find_by_signature(&self, signature: &str) -> Result<Vec<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().find_by_signature(signature).await
+    }
+
+    async fn find_by_serial_number(
+        &self,
+        serial_number: &BigUint,
+    ) -> Result<Vec<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().find_by_serial_number(serial_number).await
+    }
+
     async fn update_by_id(
         &self,
         id: &str,
@@ -43,7 +108,42 @@ impl TransactionHandler<Document<DocumentTransaction>>
         self.as_ref().update_by_id(id, options).await
     }
 
+    async fn update_by_id_if_status(
+        &self,
+        id: &str,
+        expected: &[TransactStatus],
+        options: &UpdateTransactionOptions,
+    ) -> Result<Option<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().update_by_id_if_status(id, expected, options).await
+    }
+
+    async fn update_by_id_if_claimed(
+        &self,
+        id: &str,
+        claimed_before: u64,
+        options: &UpdateTransactionOptions,
+    ) -> Result<Option<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().update_by_id_if_claimed(id, claimed_before, options).await
+    }
+
+    async fn find_unsent(
+        &self,
+        chain_id: u64,
+        queued_before: u64,
+        claimed_before: u64,
+    ) -> Result<Vec<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().find_unsent(chain_id, queued_before, claimed_before).await
+    }
+
     async fn is_repeated_transaction(&self, signature: &str) -> Result<bool, Self::Error> {
         self.as_ref().is_repeated_transaction(signature).await
     }
+
+    async fn find(&self, options: &QueryTransactionOptions) -> Result<Vec<Document<DocumentTransaction>>, Self::Error> {
+        self.as_ref().find(options).await
+    }
+
+    async fn count(&self, options: &QueryTransactionOptions) -> Result<u64, Self::Error> {
+        self.as_ref().count(options).await
+    }
 }
@@ -0,0 +1,144 @@
+use crate::database::transaction::Transaction as DocumentTransaction;
+use mystiko_protos::core::v1::SpendType;
+use mystiko_relayer_types::{RelayTransactStatusResponse, TransactStatus};
+use mystiko_storage::Document;
+use std::collections::HashSet;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+
+pub const DEFAULT_STATUS_BUS_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionStatusEvent {
+    pub id: String,
+    pub chain_id: u64,
+    pub spend_type: SpendType,
+    pub status: TransactStatus,
+    pub transaction_hash: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl TransactionStatusEvent {
+    // succeeded and failed transactions do not change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(self.status, TransactStatus::Succeeded | TransactStatus::Failed)
+    }
+}
+
+impl From<&Document<DocumentTransaction>> for TransactionStatusEvent {
+    fn from(transaction: &Document<DocumentTransaction>) -> Self {
+        TransactionStatusEvent {
+            id: transaction.id.clone(),
+            chain_id: transaction.data.chain_id,
+            spend_type: transaction.data.spend_type,
+            status: transaction.data.status.clone(),
+            transaction_hash: transaction.data.transaction_hash.clone(),
+            error_message: transaction.data.error_message.clone(),
+        }
+    }
+}
+
+impl From<TransactionStatusEvent> for RelayTransactStatusResponse {
+    fn from(event: TransactionStatusEvent) -> Self {
+        RelayTransactStatusResponse::builder()
+            .uuid(event.id)
+            .chain_id(event.chain_id)
+            .spend_type(event.spend_type)
+            .status(event.status)
+            .transaction_hash(event.transaction_hash)
+            .error_msg(event.error_message)
+            .build()
+    }
+}
+
+// in process broadcast of transaction status changes, published by the transaction handler
+#[derive(Debug, Clone)]
+pub struct TransactionStatusBus {
+    sender: Sender<TransactionStatusEvent>,
+}
+
+impl Default for TransactionStatusBus {
+    fn default() -> Self {
+        TransactionStatusBus::new(DEFAULT_STATUS_BUS_CAPACITY)
+    }
+}
+
+impl TransactionStatusBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = channel(capacity);
+        TransactionStatusBus { sender }
+    }
+
+    // nobody listening is not an error
+    pub fn publish(&self, event: TransactionStatusEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self, id: &str) -> TransactionStatusSubscription {
+        let mut subscription = self.subscribe_none();
+        subscription.watch(id);
+        subscription
+    }
+
+    // ids are added later with `watch`
+    pub fn subscribe_none(&self) -> TransactionStatusSubscription {
+        TransactionStatusSubscription {
+            ids: HashSet::new(),
+            all: false,
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    // every transaction, e.g. for the webhook notifier
+    pub fn subscribe_all(&self) -> TransactionStatusSubscription {
+        TransactionStatusSubscription {
+            ids: HashSet::new(),
+            all: true,
+            receiver: self.sender.subscribe(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusUpdate {
+    Event(TransactionStatusEvent),
+    // events were dropped for a slow subscriber, the transaction has to be read again
+    Lagged,
+    Closed,
+}
+
+#[derive(Debug)]
+pub struct TransactionStatusSubscription {
+    ids: HashSet<String>,
+    all: bool,
+    receiver: Receiver<TransactionStatusEvent>,
+}
+
+impl TransactionStatusSubscription {
+    pub fn ids(&self) -> &HashSet<String> {
+        &self.ids
+    }
+
+    pub fn is_watching(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn watch(&mut self, id: &str) -> bool {
+        self.ids.insert(id.to_string())
+    }
+
+    pub fn unwatch(&mut self, id: &str) -> bool {
+        self.ids.remove(id)
+    }
+
+    pub async fn next(&mut self) -> StatusUpdate {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.all || self.ids.contains(&event.id) => return StatusUpdate::Event(event),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => return StatusUpdate::Lagged,
+                Err(RecvError::Closed) => return StatusUpdate::Closed,
+            }
+        }
+    }
+}
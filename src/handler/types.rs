@@ -1,3 +1,12 @@
 use crate::error::RelayerServerError;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub type Result<T> = anyhow::Result<T, RelayerServerError>;
+
+// milliseconds since the unix epoch
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,175 @@
+use crate::database::webhook::{
+    TransactionCallback as DocumentTransactionCallback, TransactionCallbackColumn,
+    WebhookDelivery as DocumentWebhookDelivery, WebhookDeliveryColumn,
+};
+use crate::database::Database;
+use crate::error::RelayerServerError;
+use crate::handler::types::{current_timestamp, Result};
+use crate::handler::webhook::WebhookHandler;
+use async_trait::async_trait;
+use mystiko_protos::storage::v1::{ColumnValue, Condition, SubFilter};
+use mystiko_storage::{Document, StatementFormatter, Storage, DOCUMENT_ID_FIELD, DOCUMENT_UPDATED_AT_FIELD};
+use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+#[derive(TypedBuilder, Debug, Clone)]
+#[builder(field_defaults(setter(into)))]
+pub struct CreateWebhookDeliveryOptions {
+    pub transaction_id: String,
+    pub url: String,
+    pub status: String,
+    pub payload: String,
+}
+
+#[derive(Debug)]
+pub struct Webhook<F: StatementFormatter, S: Storage> {
+    db: Arc<Database<F, S>>,
+}
+
+#[async_trait]
+impl<F, S> WebhookHandler<Document<DocumentTransactionCallback>, Document<DocumentWebhookDelivery>> for Webhook<F, S>
+where
+    F: StatementFormatter + 'static,
+    S: Storage + 'static,
+{
+    type Error = RelayerServerError;
+
+    async fn save_callback(&self, signature: &str, url: &str) -> Result<Document<DocumentTransactionCallback>> {
+        match self.find_callback(signature).await? {
+            Some(mut callback) => {
+                callback.data.url = url.to_string();
+                self.db
+                    .callbacks
+                    .update(&callback)
+                    .await
+                    .map_err(RelayerServerError::StorageError)
+            }
+            None => {
+                let callback = DocumentTransactionCallback {
+                    signature: signature.to_string(),
+                    url: url.to_string(),
+                };
+                self.db
+                    .callbacks
+                    .insert(&callback)
+                    .await
+                    .map_err(RelayerServerError::StorageError)
+            }
+        }
+    }
+
+    async fn delete_callback(&self, signature: &str) -> Result<()> {
+        if let Some(callback) = self.find_callback(signature).await? {
+            self.db
+                .callbacks
+                .delete(&callback)
+                .await
+                .map_err(RelayerServerError::StorageError)?;
+        }
+        Ok(())
+    }
+
+    async fn find_callback(&self, signature: &str) -> Result<Option<Document<DocumentTransactionCallback>>> {
+        self.db
+            .callbacks
+            .find_one(SubFilter::equal(TransactionCallbackColumn::Signature, signature))
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn enqueue_delivery(
+        &self,
+        options: &CreateWebhookDeliveryOptions,
+    ) -> Result<Document<DocumentWebhookDelivery>> {
+        let delivery = DocumentWebhookDelivery {
+            transaction_id: options.transaction_id.clone(),
+            url: options.url.clone(),
+            status: options.status.clone(),
+            payload: options.payload.clone(),
+            attempt: 0,
+            delivered: false,
+            response_status: None,
+            error_message: None,
+            next_attempt_at: Some(current_timestamp()),
+        };
+        self.db
+            .webhook_deliveries
+            .insert(&delivery)
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn find_pending_deliveries(&self) -> Result<Vec<Document<DocumentWebhookDelivery>>> {
+        let mut deliveries = self
+            .db
+            .webhook_deliveries
+            .find(SubFilter::is_not_null(WebhookDeliveryColumn::NextAttemptAt))
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        deliveries.sort_by_key(|delivery| delivery.created_at);
+        Ok(deliveries)
+    }
+
+    async fn claim_delivery(
+        &self,
+        id: &str,
+        next_attempt_at: u64,
+        claimed_until: u64,
+    ) -> Result<Option<Document<DocumentWebhookDelivery>>> {
+        let column_values: Vec<(String, ColumnValue)> = vec![
+            (WebhookDeliveryColumn::NextAttemptAt.to_string(), claimed_until.into()),
+            (DOCUMENT_UPDATED_AT_FIELD.to_string(), current_timestamp().into()),
+        ];
+        self.db
+            .webhook_deliveries
+            .update_by_filter(
+                column_values,
+                Condition::and(vec![
+                    SubFilter::equal(DOCUMENT_ID_FIELD, id),
+                    SubFilter::equal(WebhookDeliveryColumn::NextAttemptAt, next_attempt_at),
+                ]),
+            )
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        // the storage reports no affected rows, the row is read back to see whether this claim landed
+        Ok(self
+            .db
+            .webhook_deliveries
+            .find_by_id(id)
+            .await
+            .map_err(RelayerServerError::StorageError)?
+            .filter(|delivery| delivery.data.next_attempt_at == Some(claimed_until)))
+    }
+
+    async fn update_delivery(
+        &self,
+        delivery: &Document<DocumentWebhookDelivery>,
+    ) -> Result<Document<DocumentWebhookDelivery>> {
+        self.db
+            .webhook_deliveries
+            .update(delivery)
+            .await
+            .map_err(RelayerServerError::StorageError)
+    }
+
+    async fn find_deliveries(&self, transaction_id: &str) -> Result<Vec<Document<DocumentWebhookDelivery>>> {
+        let mut deliveries = self
+            .db
+            .webhook_deliveries
+            .find(SubFilter::equal(WebhookDeliveryColumn::TransactionId, transaction_id))
+            .await
+            .map_err(RelayerServerError::StorageError)?;
+        deliveries.sort_by_key(|delivery| delivery.created_at);
+        Ok(deliveries)
+    }
+}
+
+impl<F, S> Webhook<F, S>
+where
+    F: StatementFormatter,
+    S: Storage,
+{
+    pub fn new(db: Arc<Database<F, S>>) -> Self {
+        Self { db }
+    }
+}
@@ -0,0 +1,36 @@
+pub mod handler;
+
+use async_trait::async_trait;
+pub use handler::*;
+use std::fmt::Debug;
+
+#[async_trait]
+pub trait WebhookHandler<C, D>: Debug + Send + Sync {
+    type Error;
+
+    // replaces the callback of a signature
+    async fn save_callback(&self, signature: &str, url: &str) -> Result<C, Self::Error>;
+
+    async fn delete_callback(&self, signature: &str) -> Result<(), Self::Error>;
+
+    async fn find_callback(&self, signature: &str) -> Result<Option<C>, Self::Error>;
+
+    // due right away
+    async fn enqueue_delivery(&self, options: &CreateWebhookDeliveryOptions) -> Result<D, Self::Error>;
+
+    // deliveries with a next attempt, oldest first
+    async fn find_pending_deliveries(&self) -> Result<Vec<D>, Self::Error>;
+
+    // moves the next attempt of a delivery still due at next_attempt_at to claimed_until,
+    // returns none when another instance claimed it first
+    async fn claim_delivery(
+        &self,
+        id: &str,
+        next_attempt_at: u64,
+        claimed_until: u64,
+    ) -> Result<Option<D>, Self::Error>;
+
+    async fn update_delivery(&self, delivery: &D) -> Result<D, Self::Error>;
+
+    async fn find_deliveries(&self, transaction_id: &str) -> Result<Vec<D>, Self::Error>;
+}
@@ -1,14 +1,18 @@
+extern crate actix_tls;
 extern crate actix_web;
 extern crate anyhow;
 extern crate async_trait;
+extern crate clap;
 extern crate config;
 extern crate derive_more;
 extern crate env_logger;
+extern crate eth_keystore;
 extern crate ethereum_types;
 extern crate ethers_core;
 extern crate ethers_middleware;
 extern crate ethers_signers;
 extern crate hex;
+extern crate hmac;
 extern crate log;
 extern crate mystiko_abi;
 extern crate mystiko_config;
@@ -24,23 +28,32 @@ extern crate mystiko_utils;
 extern crate num_bigint;
 extern crate num_traits;
 extern crate rand;
+extern crate reqwest;
 extern crate rust_decimal;
+extern crate rustls;
+extern crate rustls_pemfile;
 extern crate secp256k1;
 extern crate serde;
 extern crate serde_json;
+extern crate sha2;
 extern crate sha3;
 extern crate sqlx;
 extern crate thiserror;
 extern crate tokio;
 extern crate typed_builder;
 extern crate validator;
+extern crate zeroize;
 
 pub mod application;
 pub mod channel;
+pub mod cli;
 pub mod configs;
 pub mod context;
 pub mod database;
 pub mod error;
 pub mod handler;
+pub mod logger;
+pub mod price_cache;
 pub mod provider;
 pub mod service;
+pub mod signer;
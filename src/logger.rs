@@ -0,0 +1,57 @@
+use env_logger::Logger;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+static LOGGER: OnceLock<&'static RelayerLogger> = OnceLock::new();
+
+// env_logger keeps its filter for the life of the process, so a reload swaps in a newly built logger
+#[derive(Debug)]
+struct RelayerLogger {
+    inner: RwLock<Logger>,
+}
+
+impl Log for RelayerLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).flush()
+    }
+}
+
+// RUST_LOG is applied first, the configured level then overrides it for the relayer modules
+fn build_logger(level: LevelFilter) -> Logger {
+    env_logger::builder()
+        .filter_module("mystiko_relayer", level)
+        .filter_module("mystiko_server_utils", level)
+        .build()
+}
+
+// returns false when another logger was installed before
+pub fn init_logger(level: LevelFilter) -> bool {
+    let logger: &'static RelayerLogger = Box::leak(Box::new(RelayerLogger {
+        inner: RwLock::new(build_logger(level)),
+    }));
+    if log::set_logger(logger).is_err() {
+        return false;
+    }
+    let _ = LOGGER.set(logger);
+    set_log_level(level);
+    true
+}
+
+pub fn set_log_level(level: LevelFilter) {
+    if let Some(logger) = LOGGER.get() {
+        let new_logger = build_logger(level);
+        log::set_max_level(new_logger.filter());
+        *logger.inner.write().unwrap_or_else(PoisonError::into_inner) = new_logger;
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use ethers_core::types::U256;
+use ethers_signers::LocalWallet;
+use log::{debug, warn};
+use mystiko_ethers::Providers;
+use mystiko_server_utils::tx_manager::config::TxManagerConfig;
+use mystiko_server_utils::tx_manager::{TransactionMiddleware, TxManagerBuilder};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+#[derive(Debug, Clone, Copy)]
+struct GasPriceEntry {
+    gas_price: U256,
+    is_tx_eip1559: bool,
+    updated_at: Instant,
+}
+
+// gas prices per chain, also fed by the consumers with the prices they send with
+#[derive(Debug)]
+pub struct GasPriceCache {
+    ttl: Duration,
+    providers: Arc<Box<dyn Providers>>,
+    entries: RwLock<HashMap<u64, GasPriceEntry>>,
+    // one refresh per chain at a time, callers arriving meanwhile take its price
+    refreshing: Mutex<HashMap<u64, Arc<Mutex<()>>>>,
+}
+
+impl GasPriceCache {
+    pub fn new(ttl: Duration, providers: Arc<Box<dyn Providers>>) -> Self {
+        GasPriceCache {
+            ttl,
+            providers,
+            entries: RwLock::new(HashMap::new()),
+            refreshing: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // the cached price while it is younger than the ttl, otherwise a fresh one
+    pub async fn gas_price(&self, chain_id: u64, is_tx_eip1559: bool) -> Result<U256> {
+        if self.ttl.is_zero() {
+            return self.refresh(chain_id, is_tx_eip1559).await;
+        }
+        if let Some(gas_price) = self.cached(chain_id).await {
+            return Ok(gas_price);
+        }
+        let lock = self.refreshing.lock().await.entry(chain_id).or_default().clone();
+        let _refreshing = lock.lock().await;
+        // refreshed by the caller this one waited for
+        if let Some(gas_price) = self.cached(chain_id).await {
+            return Ok(gas_price);
+        }
+        self.refresh(chain_id, is_tx_eip1559).await
+    }
+
+    async fn cached(&self, chain_id: u64) -> Option<U256> {
+        self.entries
+            .read()
+            .await
+            .get(&chain_id)
+            .filter(|entry| entry.updated_at.elapsed() < self.ttl)
+            .map(|entry| entry.gas_price)
+    }
+
+    pub async fn refresh(&self, chain_id: u64, is_tx_eip1559: bool) -> Result<U256> {
+        let gas_price = gas_price_by_chain_id(chain_id, self.providers.clone(), is_tx_eip1559).await?;
+        debug!("chain id {} gas price {} refreshed", chain_id, gas_price);
+        self.insert(chain_id, is_tx_eip1559, gas_price).await;
+        Ok(gas_price)
+    }
+
+    pub async fn insert(&self, chain_id: u64, is_tx_eip1559: bool, gas_price: U256) {
+        self.entries.write().await.insert(
+            chain_id,
+            GasPriceEntry {
+                gas_price,
+                is_tx_eip1559,
+                updated_at: Instant::now(),
+            },
+        );
+    }
+
+    // a chain that fails keeps its price until it expires
+    pub async fn refresh_all(&self) {
+        let chains = self
+            .entries
+            .read()
+            .await
+            .iter()
+            .map(|(chain_id, entry)| (*chain_id, entry.is_tx_eip1559))
+            .collect::<Vec<_>>();
+        for (chain_id, is_tx_eip1559) in chains {
+            if let Err(error) = self.refresh(chain_id, is_tx_eip1559).await {
+                warn!("refresh gas price of chain id {} got error: {:?}", chain_id, error);
+            }
+        }
+    }
+
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        // the first tick completes immediately, nothing is cached yet
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            self.refresh_all().await;
+        }
+    }
+}
+
+pub async fn gas_price_by_chain_id<P: Providers>(
+    chain_id: u64,
+    providers: Arc<P>,
+    is_tx_eip1559: bool,
+) -> Result<U256> {
+    let provider = providers.get_provider(chain_id).await?;
+    let tx_builder = TxManagerBuilder::builder()
+        .config(TxManagerConfig::new(None)?)
+        .chain_id(chain_id)
+        .wallet(LocalWallet::new(&mut rand::thread_rng()))
+        .build();
+    let tx_manager = tx_builder.build(Some(is_tx_eip1559), &provider).await?;
+    Ok(tx_manager.gas_price(&provider).await?)
+}
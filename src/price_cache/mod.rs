@@ -0,0 +1,5 @@
+pub mod gas_price;
+pub mod token_price;
+
+pub use gas_price::*;
+pub use token_price::*;
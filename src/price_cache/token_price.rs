@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use ethers_core::types::U256;
+use log::{debug, warn};
+use mystiko_server_utils::token_price::{PriceMiddleware, PriceMiddlewareError};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+// usd prices per symbol in front of the price service, swaps of any amount are derived from the cached prices
+#[derive(Debug, Clone)]
+pub struct TokenPriceCache {
+    ttl: Duration,
+    source: Arc<Box<dyn PriceMiddleware>>,
+    prices: Arc<RwLock<HashMap<String, (f64, Instant)>>>,
+}
+
+impl TokenPriceCache {
+    pub fn new(ttl: Duration, source: Box<dyn PriceMiddleware>) -> Self {
+        TokenPriceCache {
+            ttl,
+            source: Arc::new(source),
+            prices: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn refresh(&self, symbol: &str) -> Result<f64, PriceMiddlewareError> {
+        let price = self.source.price(symbol).await?;
+        debug!("token price of {} refreshed: {}", symbol, price);
+        self.prices
+            .write()
+            .await
+            .insert(symbol.to_uppercase(), (price, Instant::now()));
+        Ok(price)
+    }
+
+    // a symbol that fails keeps its price until it expires
+    pub async fn refresh_all(&self) {
+        let symbols = self.prices.read().await.keys().cloned().collect::<Vec<_>>();
+        for symbol in symbols {
+            if let Err(error) = self.refresh(&symbol).await {
+                warn!("refresh token price of {} got error: {:?}", symbol, error);
+            }
+        }
+    }
+
+    pub async fn run(self, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        // the first tick completes immediately, nothing is cached yet
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            self.refresh_all().await;
+        }
+    }
+}
+
+#[async_trait]
+impl PriceMiddleware for TokenPriceCache {
+    async fn price(&self, symbol: &str) -> Result<f64, PriceMiddlewareError> {
+        if let Some((price, updated_at)) = self.prices.read().await.get(&symbol.to_uppercase()) {
+            if updated_at.elapsed() < self.ttl {
+                return Ok(*price);
+            }
+        }
+        self.refresh(symbol).await
+    }
+
+    async fn swap(
+        &self,
+        asset_a: &str,
+        decimal_a: u32,
+        amount_a: U256,
+        asset_b: &str,
+        decimal_b: u32,
+    ) -> Result<U256, PriceMiddlewareError> {
+        let price_a = self.price(asset_a).await?;
+        let price_b = self.price(asset_b).await?;
+        swap_amount(price_a, decimal_a, amount_a, price_b, decimal_b)
+    }
+}
+
+// the conversion of the price service: the amount is valued in whole tokens as a float and converted back to
+// the units of asset_b, truncated
+pub fn swap_amount(
+    price_a: f64,
+    decimal_a: u32,
+    amount_a: U256,
+    price_b: f64,
+    decimal_b: u32,
+) -> Result<U256, PriceMiddlewareError> {
+    if !price_a.is_finite() || !price_b.is_finite() || price_a <= 0.0 || price_b <= 0.0 {
+        return Err(PriceMiddlewareError::InternalError);
+    }
+    let amount_a = amount_a
+        .to_string()
+        .parse::<f64>()
+        .map_err(|_| PriceMiddlewareError::InternalError)?
+        / 10f64.powi(decimal_a as i32);
+    let amount_b = (amount_a * price_a / price_b * 10f64.powi(decimal_b as i32)).trunc();
+    if !amount_b.is_finite() {
+        return Err(PriceMiddlewareError::InternalError);
+    }
+    U256::from_dec_str(&format!("{:.0}", amount_b)).map_err(|_| PriceMiddlewareError::InternalError)
+}
@@ -0,0 +1,734 @@
+use crate::channel::cancel::send_cancel_transaction;
+use crate::channel::producer::ProducerHandler;
+use crate::channel::{
+    contains_sender, create_account_channel, read_senders, take_sender, update_sender, write_senders, SenderSet,
+};
+use crate::configs::account::AccountConfig;
+use crate::configs::server::validate_account;
+use crate::context::Context;
+use crate::database::account::Account as DocumentAccount;
+use crate::database::transaction::Transaction as DocumentTransaction;
+use crate::error::ResponseError;
+use crate::handler::account::UpdateAccountOptions;
+use crate::handler::audit::{AuditAction, CreateAuditLogOptions};
+use crate::handler::transaction::{QueryTransactionOptions, UpdateTransactionOptions};
+use crate::handler::types::current_timestamp;
+use crate::service::admin::request::{
+    AccountActionRequest, AddAccountRequest, ListTransactionRequest, TransactionActionRequest,
+    UpdateAccountTokensRequest,
+};
+use crate::service::admin::response::{
+    AccountListResponse, AccountResponse, TransactionActionResponse, TransactionListResponse, TransactionResponse,
+};
+use crate::service::admin::{AdminOperator, DEFAULT_PAGE_LIMIT, REQUEUE_STUCK_AFTER_MS};
+use crate::service::find_sender;
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{get, post, put, Responder};
+use ethers_core::abi::AbiEncode;
+use ethers_core::types::TxHash;
+use ethers_signers::Signer;
+use log::{error, info, warn};
+use mystiko_relayer_types::response::success;
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::Document;
+use mystiko_types::AssetType;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use validator::Validate;
+
+#[get("/transactions")]
+pub async fn list_transactions(
+    operator: AdminOperator,
+    request: Query<ListTransactionRequest>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    info!("admin {} list transactions with {:?}", operator.name, request);
+
+    if let Err(err) = request.validate() {
+        error!("list transactions request validate error {:?}", err);
+        return Err(ResponseError::ValidateError { error: err.to_string() });
+    }
+
+    let request = request.into_inner();
+    let offset = request.offset.unwrap_or(0);
+    let limit = request.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    let options = QueryTransactionOptions {
+        chain_id: request.chain_id,
+        status: request.status,
+        spend_type: request.spend_type,
+        asset_symbol: request.asset_symbol,
+        created_after: request.created_after,
+        created_before: request.created_before,
+        updated_after: None,
+        error_message: request.error,
+        sort_by: request.sort_by.unwrap_or_default(),
+        order: request.order.unwrap_or_default(),
+        offset: Some(offset),
+        limit: Some(limit),
+    };
+
+    let handler = &context.transaction_handler;
+    let total = handler.count(&options).await.map_err(|error| {
+        error!("count transactions got error: {:?}", error);
+        ResponseError::DatabaseError
+    })?;
+    let transactions = handler.find(&options).await.map_err(|error| {
+        error!("find transactions got error: {:?}", error);
+        ResponseError::DatabaseError
+    })?;
+
+    Ok(success(TransactionListResponse {
+        total,
+        offset,
+        limit,
+        transactions: transactions.into_iter().map(TransactionResponse::from).collect(),
+    }))
+}
+
+#[post("/transactions/{id}/requeue")]
+pub async fn requeue_transaction(
+    operator: AdminOperator,
+    id: Path<String>,
+    request: Json<TransactionActionRequest>,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    validate_action_request(&request)?;
+    let id = id.into_inner();
+    let handler = &context.transaction_handler;
+    let transaction = find_transaction(&context, &id).await?;
+    // a queued job is only requeued once no consumer picked it up for a while, the claim drops the extra copy
+    let is_stuck = transaction.data.status == TransactStatus::Queued
+        && current_timestamp().saturating_sub(transaction.updated_at) >= REQUEUE_STUCK_AFTER_MS;
+    let is_stale_claim = is_stale_claim(&transaction);
+    if transaction.data.status != TransactStatus::Failed && !is_stuck && !is_stale_claim {
+        return Err(invalid_status(&id, &transaction.data.status));
+    }
+
+    let data = handler
+        .find_request_by_id(&id)
+        .await
+        .map_err(|error| {
+            error!("find request of transaction id({}) got error: {:?}", id, error);
+            ResponseError::DatabaseError
+        })?
+        .ok_or(ResponseError::TransactionRequestNotFound { id: id.clone() })?;
+
+    let relayer_config = context.relayer_config.load();
+    let chain_config =
+        relayer_config
+            .find_chain_config(data.chain_id)
+            .ok_or(ResponseError::ChainIdNotFoundInRelayerConfig {
+                chain_id: data.chain_id,
+            })?;
+    let asset_type = if chain_config.asset_symbol().eq_ignore_ascii_case(&data.asset_symbol) {
+        AssetType::Main
+    } else {
+        AssetType::Erc20
+    };
+    let producer = find_sender(senders, data.chain_id, &data.asset_symbol, asset_type)
+        .ok_or(ResponseError::UnsupportedTransaction)?;
+
+    let options = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Queued)
+        .build();
+    if is_stale_claim {
+        update_transaction_if_stale_claim(&context, &id, options).await?;
+    } else {
+        update_transaction_if_status(&context, &id, &[transaction.data.status.clone()], options).await?;
+    }
+    if let Err(error) = producer.requeue(&id, data).await {
+        error!("requeue transaction id({}) got error: {:?}", id, error);
+        update_transaction(
+            &context,
+            &id,
+            UpdateTransactionOptions::builder()
+                .status(TransactStatus::Failed)
+                .error_message(error.to_string())
+                .build(),
+        )
+        .await?;
+        return Err(ResponseError::TransactionChannelError {
+            error: error.to_string(),
+        });
+    }
+
+    record_action(&context, &id, AuditAction::Requeue, &operator, &request.reason, None).await;
+    let transaction = find_transaction(&context, &id).await?;
+    Ok(success(TransactionActionResponse {
+        transaction: transaction.into(),
+        replacement_transaction_hash: None,
+    }))
+}
+
+#[post("/transactions/{id}/fail")]
+pub async fn fail_transaction(
+    operator: AdminOperator,
+    id: Path<String>,
+    request: Json<TransactionActionRequest>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    validate_action_request(&request)?;
+    let id = id.into_inner();
+    let transaction = find_transaction(&context, &id).await?;
+    if transaction.data.status == TransactStatus::Succeeded {
+        return Err(invalid_status(&id, &transaction.data.status));
+    }
+
+    let transaction = update_transaction(
+        &context,
+        &id,
+        UpdateTransactionOptions::builder()
+            .status(TransactStatus::Failed)
+            .error_message(format!("{} (marked failed by {})", request.reason, operator.name))
+            .build(),
+    )
+    .await?;
+
+    record_action(&context, &id, AuditAction::Fail, &operator, &request.reason, None).await;
+    Ok(success(TransactionActionResponse {
+        transaction: transaction.into(),
+        replacement_transaction_hash: None,
+    }))
+}
+
+#[post("/transactions/{id}/cancel")]
+pub async fn cancel_transaction(
+    operator: AdminOperator,
+    id: Path<String>,
+    request: Json<TransactionActionRequest>,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    validate_action_request(&request)?;
+    let id = id.into_inner();
+    let transaction = find_transaction(&context, &id).await?;
+
+    let replacement_transaction_hash = match (&transaction.data.status, &transaction.data.transaction_hash) {
+        // not submitted yet, failing it wins over the consumer claim or loses to it atomically
+        (TransactStatus::Queued, _) => None,
+        // claimed by a consumer that never sent it
+        (TransactStatus::Pending, None) if is_stale_claim(&transaction) => None,
+        // submitted, replace it with a same-nonce zero-value transaction
+        (TransactStatus::Pending, Some(tx_hash)) => {
+            let chain_id = transaction.data.chain_id;
+            let tx_hash = TxHash::from_str(tx_hash).map_err(|error| ResponseError::CancelTransactionFailed {
+                error: error.to_string(),
+            })?;
+            let wallets = read_senders(&senders)
+                .iter()
+                .filter(|sender| sender.chain_id == chain_id)
+                .map(|sender| sender.signer.clone().with_chain_id(chain_id))
+                .collect::<Vec<_>>();
+            let provider = context.providers.get_provider(chain_id).await.map_err(|error| {
+                error!("get provider of chain id {} got error: {:?}", chain_id, error);
+                ResponseError::CancelTransactionFailed {
+                    error: error.to_string(),
+                }
+            })?;
+            let replacement_hash = send_cancel_transaction(&provider, &wallets, tx_hash)
+                .await
+                .map_err(|error| {
+                    error!("cancel transaction id({}) got error: {:?}", id, error);
+                    ResponseError::CancelTransactionFailed {
+                        error: error.to_string(),
+                    }
+                })?;
+            Some(replacement_hash.encode_hex())
+        }
+        _ => return Err(invalid_status(&id, &transaction.data.status)),
+    };
+
+    let error_message = match &replacement_transaction_hash {
+        None => format!("cancelled by {}: {}", operator.name, request.reason),
+        Some(hash) => format!(
+            "cancelled by {}: {}, replacement transaction {}",
+            operator.name, request.reason, hash
+        ),
+    };
+    let options = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Failed)
+        .error_message(error_message)
+        .build();
+    let transaction = if replacement_transaction_hash.is_some() {
+        update_transaction(&context, &id, options).await?
+    } else if transaction.data.status == TransactStatus::Queued {
+        update_transaction_if_status(&context, &id, &[TransactStatus::Queued], options).await?
+    } else {
+        update_transaction_if_stale_claim(&context, &id, options).await?
+    };
+
+    record_action(
+        &context,
+        &id,
+        AuditAction::Cancel,
+        &operator,
+        &request.reason,
+        replacement_transaction_hash
+            .as_ref()
+            .map(|hash| format!("replacement transaction {}", hash)),
+    )
+    .await;
+    Ok(success(TransactionActionResponse {
+        transaction: transaction.into(),
+        replacement_transaction_hash,
+    }))
+}
+
+#[get("/accounts")]
+pub async fn list_accounts(
+    operator: AdminOperator,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    info!("admin {} list accounts", operator.name);
+    let accounts = context.account_handler.find_all().await.map_err(|error| {
+        error!("find accounts got error: {:?}", error);
+        ResponseError::DatabaseError
+    })?;
+    let accounts = accounts
+        .into_iter()
+        .map(|account| {
+            let active = contains_sender(&senders, account.data.chain_id, &account.data.chain_address);
+            AccountResponse::new(account, active)
+        })
+        .collect();
+    Ok(success(AccountListResponse { accounts }))
+}
+
+#[post("/accounts")]
+pub async fn add_account(
+    operator: AdminOperator,
+    request: Json<AddAccountRequest>,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    request.validate().map_err(|err| {
+        error!("add account request validate error {:?}", err);
+        ResponseError::ValidateError { error: err.to_string() }
+    })?;
+    let request = request.into_inner();
+    if request.keystore_path.is_none() && request.remote_signer.is_none() {
+        return Err(ResponseError::ValidateError {
+            error: "keystorePath or remoteSigner is required".to_string(),
+        });
+    }
+    let chain_id = request.chain_id;
+    let mut account = AccountConfig::builder()
+        .chain_id(chain_id)
+        .keystore_path(request.keystore_path)
+        .keystore_password_file(request.keystore_password_file)
+        .keystore_password_env(request.keystore_password_env)
+        .remote_signer(request.remote_signer)
+        .available(request.available.unwrap_or(true))
+        .supported_erc20_tokens(to_token_map(request.supported_erc20_tokens))
+        .balance_alarm_threshold(request.balance_alarm_threshold.unwrap_or_default())
+        .balance_check_interval_ms(request.balance_check_interval_ms.unwrap_or_default())
+        .build();
+    let address = validate_new_account(&context, &mut account)?;
+    match find_account(&context, chain_id, &address).await {
+        Ok(_) => return Err(ResponseError::AccountAlreadyExists { chain_id, address }),
+        Err(ResponseError::AccountNotFound { .. }) => {}
+        Err(error) => return Err(error),
+    }
+
+    let document = context.account_handler.create(&account).await.map_err(|error| {
+        error!(
+            "create account {} on chain id {} got error: {:?}",
+            address, chain_id, error
+        );
+        ResponseError::DatabaseError
+    })?;
+    let (sender, mut consumer) = match create_account_channel(&context, &account).await {
+        Ok(channel) => channel,
+        Err(error) => {
+            error!("create channel of account {} got error: {:?}", address, error);
+            delete_account(&context, chain_id, &address).await;
+            return Err(ResponseError::TransactionChannelError {
+                error: error.to_string(),
+            });
+        }
+    };
+    tokio::spawn(async move {
+        consumer.consume().await;
+    });
+    write_senders(&senders).insert(sender);
+    warn!(
+        "account {} on chain id {} added at runtime, add it to the config file to keep it after restart",
+        address, chain_id
+    );
+
+    let target = account_target(chain_id, &address);
+    record_action(
+        &context,
+        &target,
+        AuditAction::AddAccount,
+        &operator,
+        &request.reason,
+        None,
+    )
+    .await;
+    Ok(success(AccountResponse::new(document, true)))
+}
+
+#[post("/accounts/{chain_id}/{address}/enable")]
+pub async fn enable_account(
+    operator: AdminOperator,
+    path: Path<(u64, String)>,
+    request: Json<AccountActionRequest>,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    let (chain_id, address) = path.into_inner();
+    let account = set_account_available(&context, &senders, &operator, chain_id, &address, &request, true).await?;
+    Ok(success(account))
+}
+
+#[post("/accounts/{chain_id}/{address}/disable")]
+pub async fn disable_account(
+    operator: AdminOperator,
+    path: Path<(u64, String)>,
+    request: Json<AccountActionRequest>,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    let (chain_id, address) = path.into_inner();
+    let account = set_account_available(&context, &senders, &operator, chain_id, &address, &request, false).await?;
+    Ok(success(account))
+}
+
+#[put("/accounts/{chain_id}/{address}/tokens")]
+pub async fn update_account_tokens(
+    operator: AdminOperator,
+    path: Path<(u64, String)>,
+    request: Json<UpdateAccountTokensRequest>,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    request.validate().map_err(|err| {
+        error!("update account tokens request validate error {:?}", err);
+        ResponseError::ValidateError { error: err.to_string() }
+    })?;
+    let (chain_id, address) = path.into_inner();
+    let tokens = request.supported_erc20_tokens.clone();
+    let account = AccountConfig::builder()
+        .chain_id(chain_id)
+        .supported_erc20_tokens(to_token_map(tokens.clone()))
+        .build();
+    validate_account(&account, &context.relayer_config.load()).map_err(|error| ResponseError::InvalidAccount {
+        error: error.to_string(),
+    })?;
+
+    // stored first, a failed write leaves the running sender as it was
+    let options = UpdateAccountOptions::builder()
+        .supported_erc20_tokens(tokens.clone())
+        .build();
+    let document = update_account(&context, chain_id, &address, &options).await?;
+    update_sender(&senders, chain_id, &address, |sender| {
+        sender.supported_erc20_tokens = tokens.clone()
+    });
+    let active = contains_sender(&senders, chain_id, &address);
+
+    let target = account_target(chain_id, &address);
+    let detail = format!("supported erc20 tokens {:?}", tokens);
+    record_action(
+        &context,
+        &target,
+        AuditAction::UpdateAccountTokens,
+        &operator,
+        &request.reason,
+        Some(detail),
+    )
+    .await;
+    Ok(success(AccountResponse::new(document, active)))
+}
+
+#[post("/accounts/{chain_id}/{address}/retire")]
+pub async fn retire_account(
+    operator: AdminOperator,
+    path: Path<(u64, String)>,
+    request: Json<AccountActionRequest>,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    validate_account_action_request(&request)?;
+    let (chain_id, address) = path.into_inner();
+    let sender = take_sender(&senders, chain_id, &address).ok_or(ResponseError::AccountNotFound {
+        chain_id,
+        address: address.clone(),
+    })?;
+    // dropping the last producer closes the queue, the consumer drains what is left and stops
+    let drained = sender.drained.clone();
+    drop(sender);
+
+    let options = UpdateAccountOptions::builder().available(false).build();
+    let document = update_account(&context, chain_id, &address, &options).await?;
+    let account_handler = context.account_handler.clone();
+    let retired_address = address.clone();
+    tokio::spawn(async move {
+        drained.notified().await;
+        match account_handler.delete_by_address(chain_id, &retired_address).await {
+            Ok(_) => info!("account {} on chain id {} retired", retired_address, chain_id),
+            Err(error) => error!("delete retired account {} got error: {:?}", retired_address, error),
+        }
+    });
+
+    let target = account_target(chain_id, &address);
+    record_action(
+        &context,
+        &target,
+        AuditAction::RetireAccount,
+        &operator,
+        &request.reason,
+        None,
+    )
+    .await;
+    Ok(success(AccountResponse::new(document, false)))
+}
+
+async fn set_account_available(
+    context: &Context,
+    senders: &SenderSet,
+    operator: &AdminOperator,
+    chain_id: u64,
+    address: &str,
+    request: &AccountActionRequest,
+    available: bool,
+) -> Result<AccountResponse, ResponseError> {
+    validate_account_action_request(request)?;
+    // stored first, a failed write leaves the running sender as it was
+    let options = UpdateAccountOptions::builder().available(available).build();
+    let document = update_account(context, chain_id, address, &options).await?;
+    update_sender(senders, chain_id, address, |sender| sender.available = available);
+
+    let action = if available {
+        AuditAction::EnableAccount
+    } else {
+        AuditAction::DisableAccount
+    };
+    record_action(
+        context,
+        &account_target(chain_id, address),
+        action,
+        operator,
+        &request.reason,
+        None,
+    )
+    .await;
+    Ok(AccountResponse::new(
+        document,
+        contains_sender(senders, chain_id, address),
+    ))
+}
+
+fn validate_new_account(context: &Context, account: &mut AccountConfig) -> Result<String, ResponseError> {
+    account.validate().map_err(|error| ResponseError::InvalidAccount {
+        error: error.to_string(),
+    })?;
+    account.load_signer().map_err(|error| ResponseError::InvalidAccount {
+        error: error.to_string(),
+    })?;
+    let address = account.address().map_err(|error| ResponseError::InvalidAccount {
+        error: error.to_string(),
+    })?;
+    if context.mystiko_config.load().find_chain(account.chain_id).is_none() {
+        return Err(ResponseError::InvalidAccount {
+            error: format!("chain id {} not found in mystiko config", account.chain_id),
+        });
+    }
+    validate_account(account, &context.relayer_config.load()).map_err(|error| ResponseError::InvalidAccount {
+        error: error.to_string(),
+    })?;
+    Ok(address)
+}
+
+fn validate_account_action_request(request: &AccountActionRequest) -> Result<(), ResponseError> {
+    request.validate().map_err(|err| {
+        error!("admin account action request validate error {:?}", err);
+        ResponseError::ValidateError { error: err.to_string() }
+    })
+}
+
+fn to_token_map(tokens: Vec<String>) -> HashMap<u16, String> {
+    tokens
+        .into_iter()
+        .enumerate()
+        .map(|(index, token)| (index as u16, token))
+        .collect()
+}
+
+fn account_target(chain_id: u64, address: &str) -> String {
+    format!("{}:{}", chain_id, address.to_lowercase())
+}
+
+async fn find_account(
+    context: &Context,
+    chain_id: u64,
+    address: &str,
+) -> Result<Document<DocumentAccount>, ResponseError> {
+    match context.account_handler.find_by_address(chain_id, address).await {
+        Ok(Some(account)) => Ok(account),
+        Ok(None) => Err(ResponseError::AccountNotFound {
+            chain_id,
+            address: address.to_string(),
+        }),
+        Err(error) => {
+            error!(
+                "find account {} on chain id {} got error: {:?}",
+                address, chain_id, error
+            );
+            Err(ResponseError::DatabaseError)
+        }
+    }
+}
+
+async fn update_account(
+    context: &Context,
+    chain_id: u64,
+    address: &str,
+    options: &UpdateAccountOptions,
+) -> Result<Document<DocumentAccount>, ResponseError> {
+    match context
+        .account_handler
+        .update_by_address(chain_id, address, options)
+        .await
+    {
+        Ok(Some(account)) => Ok(account),
+        Ok(None) => Err(ResponseError::AccountNotFound {
+            chain_id,
+            address: address.to_string(),
+        }),
+        Err(error) => {
+            error!(
+                "update account {} on chain id {} got error: {:?}",
+                address, chain_id, error
+            );
+            Err(ResponseError::DatabaseError)
+        }
+    }
+}
+
+async fn delete_account(context: &Context, chain_id: u64, address: &str) {
+    if let Err(error) = context.account_handler.delete_by_address(chain_id, address).await {
+        error!(
+            "delete account {} on chain id {} got error: {:?}",
+            address, chain_id, error
+        );
+    }
+}
+
+fn validate_action_request(request: &TransactionActionRequest) -> Result<(), ResponseError> {
+    request.validate().map_err(|err| {
+        error!("admin action request validate error {:?}", err);
+        ResponseError::ValidateError { error: err.to_string() }
+    })
+}
+
+fn invalid_status(id: &str, status: &TransactStatus) -> ResponseError {
+    ResponseError::InvalidTransactionStatus {
+        id: id.to_string(),
+        status: format!("{:?}", status),
+    }
+}
+
+async fn find_transaction(context: &Context, id: &str) -> Result<Document<DocumentTransaction>, ResponseError> {
+    match context.transaction_handler.find_by_id(id).await {
+        Ok(Some(transaction)) => Ok(transaction),
+        Ok(None) => Err(ResponseError::TransactionNotFound { id: id.to_string() }),
+        Err(error) => {
+            error!("find transaction by id({}) got error: {:?}", id, error);
+            Err(ResponseError::DatabaseError)
+        }
+    }
+}
+
+async fn update_transaction(
+    context: &Context,
+    id: &str,
+    options: UpdateTransactionOptions,
+) -> Result<Document<DocumentTransaction>, ResponseError> {
+    match context.transaction_handler.update_by_id(id, &options).await {
+        Ok(Some(transaction)) => Ok(transaction),
+        Ok(None) => Err(ResponseError::TransactionNotFound { id: id.to_string() }),
+        Err(error) => {
+            error!("update transaction by id({}) got error: {:?}", id, error);
+            Err(ResponseError::DatabaseError)
+        }
+    }
+}
+
+// fails with the current status when the job moved on since it was read
+async fn update_transaction_if_status(
+    context: &Context,
+    id: &str,
+    expected: &[TransactStatus],
+    options: UpdateTransactionOptions,
+) -> Result<Document<DocumentTransaction>, ResponseError> {
+    match context
+        .transaction_handler
+        .update_by_id_if_status(id, expected, &options)
+        .await
+    {
+        Ok(Some(transaction)) => Ok(transaction),
+        Ok(None) => {
+            let transaction = find_transaction(context, id).await?;
+            Err(invalid_status(id, &transaction.data.status))
+        }
+        Err(error) => {
+            error!("update transaction by id({}) got error: {:?}", id, error);
+            Err(ResponseError::DatabaseError)
+        }
+    }
+}
+
+// claimed by a consumer but not sent and left alone for a while, the consumer crashed or lost the job
+fn is_stale_claim(transaction: &Document<DocumentTransaction>) -> bool {
+    transaction.data.status == TransactStatus::Pending
+        && transaction.data.transaction_hash.is_none()
+        && current_timestamp().saturating_sub(transaction.updated_at) >= REQUEUE_STUCK_AFTER_MS
+}
+
+// fails with the current status when the job was sent or touched again since it was read
+async fn update_transaction_if_stale_claim(
+    context: &Context,
+    id: &str,
+    options: UpdateTransactionOptions,
+) -> Result<Document<DocumentTransaction>, ResponseError> {
+    let claimed_before = current_timestamp().saturating_sub(REQUEUE_STUCK_AFTER_MS);
+    match context
+        .transaction_handler
+        .update_by_id_if_claimed(id, claimed_before, &options)
+        .await
+    {
+        Ok(Some(transaction)) => Ok(transaction),
+        Ok(None) => {
+            let transaction = find_transaction(context, id).await?;
+            Err(invalid_status(id, &transaction.data.status))
+        }
+        Err(error) => {
+            error!("update transaction by id({}) got error: {:?}", id, error);
+            Err(ResponseError::DatabaseError)
+        }
+    }
+}
+
+async fn record_action(
+    context: &Context,
+    target: &str,
+    action: AuditAction,
+    operator: &AdminOperator,
+    reason: &str,
+    detail: Option<String>,
+) {
+    let options = CreateAuditLogOptions {
+        target: target.to_string(),
+        action,
+        operator: operator.name.clone(),
+        reason: reason.to_string(),
+        detail,
+    };
+    if let Err(error) = context.audit_handler.create(&options).await {
+        error!("record admin action {:?} got error: {:?}", options, error);
+    }
+}
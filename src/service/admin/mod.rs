@@ -0,0 +1,51 @@
+pub mod handler;
+pub mod request;
+pub mod response;
+
+use crate::context::Context;
+use crate::error::ResponseError;
+use actix_web::dev::Payload;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::web::Data;
+use actix_web::{FromRequest, HttpRequest};
+use log::warn;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+pub const DEFAULT_PAGE_LIMIT: u64 = 20;
+// a queued job nobody claimed, or a claimed one nobody sent, for this long can be requeued or cancelled
+pub const REQUEUE_STUCK_AFTER_MS: u64 = 10 * 60 * 1000;
+
+#[derive(Debug, Clone)]
+pub struct AdminOperator {
+    pub name: String,
+}
+
+impl FromRequest for AdminOperator {
+    type Error = ResponseError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(authenticate(req))
+    }
+}
+
+fn authenticate(req: &HttpRequest) -> Result<AdminOperator, ResponseError> {
+    let context = req.app_data::<Data<Arc<Context>>>().ok_or(ResponseError::Unknown)?;
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim())
+        .ok_or(ResponseError::Unauthorized)?;
+    match context.server_config.load().admin.find_operator(token) {
+        Some(operator) => Ok(AdminOperator {
+            name: operator.to_string(),
+        }),
+        None => {
+            warn!("rejected admin request to {} from {:?}", req.path(), req.peer_addr());
+            Err(ResponseError::Unauthorized)
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::configs::account::RemoteSignerConfig;
+use crate::handler::transaction::{SortOrder, TransactionSortBy};
+use mystiko_protos::core::v1::SpendType;
+use mystiko_relayer_types::TransactStatus;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Validate, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTransactionRequest {
+    pub chain_id: Option<u64>,
+    pub status: Option<TransactStatus>,
+    pub spend_type: Option<SpendType>,
+    pub asset_symbol: Option<String>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    #[validate(length(min = 1))]
+    pub error: Option<String>,
+    pub offset: Option<u64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<u64>,
+    pub sort_by: Option<TransactionSortBy>,
+    pub order: Option<SortOrder>,
+}
+
+#[derive(Validate, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionActionRequest {
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
+
+#[derive(Validate, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AddAccountRequest {
+    #[validate(range(min = 1))]
+    pub chain_id: u64,
+    // a path on the relayer host, plaintext keys are never accepted over the api
+    #[validate(length(min = 1))]
+    pub keystore_path: Option<String>,
+    pub keystore_password_file: Option<String>,
+    pub keystore_password_env: Option<String>,
+    // used instead of a keystore, the key stays in the signing service
+    #[validate]
+    pub remote_signer: Option<RemoteSignerConfig>,
+    pub available: Option<bool>,
+    #[serde(default)]
+    pub supported_erc20_tokens: Vec<String>,
+    pub balance_alarm_threshold: Option<f64>,
+    pub balance_check_interval_ms: Option<u64>,
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
+
+#[derive(Validate, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAccountTokensRequest {
+    pub supported_erc20_tokens: Vec<String>,
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
+
+#[derive(Validate, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountActionRequest {
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
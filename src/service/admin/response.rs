@@ -0,0 +1,98 @@
+use crate::database::account::Account as DocumentAccount;
+use crate::database::transaction::Transaction as DocumentTransaction;
+use mystiko_protos::core::v1::SpendType;
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::Document;
+use mystiko_types::{BridgeType, CircuitType};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionListResponse {
+    pub total: u64,
+    pub offset: u64,
+    pub limit: u64,
+    pub transactions: Vec<TransactionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionResponse {
+    pub id: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub chain_id: u64,
+    pub spend_type: SpendType,
+    pub bridge_type: BridgeType,
+    pub status: TransactStatus,
+    pub pool_address: String,
+    pub asset_symbol: String,
+    pub asset_decimals: u32,
+    pub circuit_type: CircuitType,
+    pub signature: String,
+    pub transaction_hash: Option<String>,
+    pub error_message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionActionResponse {
+    pub transaction: TransactionResponse,
+    pub replacement_transaction_hash: Option<String>,
+}
+
+impl From<Document<DocumentTransaction>> for TransactionResponse {
+    fn from(transaction: Document<DocumentTransaction>) -> Self {
+        TransactionResponse {
+            id: transaction.id,
+            created_at: transaction.created_at,
+            updated_at: transaction.updated_at,
+            chain_id: transaction.data.chain_id,
+            spend_type: transaction.data.spend_type,
+            bridge_type: transaction.data.bridge_type,
+            status: transaction.data.status,
+            pool_address: transaction.data.pool_address,
+            asset_symbol: transaction.data.asset_symbol,
+            asset_decimals: transaction.data.asset_decimals,
+            circuit_type: transaction.data.circuit_type,
+            signature: transaction.data.signature,
+            transaction_hash: transaction.data.transaction_hash,
+            error_message: transaction.data.error_message,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountListResponse {
+    pub accounts: Vec<AccountResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountResponse {
+    pub chain_id: u64,
+    pub address: String,
+    pub available: bool,
+    pub supported_erc20_tokens: Vec<String>,
+    pub balance_alarm_threshold: f64,
+    pub balance_check_interval_ms: u64,
+    pub insufficient_balances: bool,
+    // whether the account is currently accepting transactions through its channel
+    pub active: bool,
+}
+
+impl AccountResponse {
+    pub fn new(account: Document<DocumentAccount>, active: bool) -> Self {
+        AccountResponse {
+            chain_id: account.data.chain_id,
+            address: account.data.chain_address,
+            available: account.data.available,
+            supported_erc20_tokens: account.data.supported_erc20_tokens,
+            balance_alarm_threshold: account.data.balance_alarm_threshold,
+            balance_check_interval_ms: account.data.balance_check_interval_ms,
+            insufficient_balances: account.data.insufficient_balances,
+            active,
+        }
+    }
+}
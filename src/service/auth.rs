@@ -0,0 +1,148 @@
+use crate::configs::auth::{ApiKeyScope, AuthConfig};
+use crate::context::Context;
+use crate::error::ResponseError;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web::{Bytes, Data};
+use actix_web::HttpMessage;
+use hmac::{Hmac, Mac};
+use log::{error, warn};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+pub const API_KEY_ID_HEADER: &str = "x-api-key-id";
+pub const API_TIMESTAMP_HEADER: &str = "x-api-timestamp";
+pub const API_NONCE_HEADER: &str = "x-api-nonce";
+pub const API_SIGNATURE_HEADER: &str = "x-api-signature";
+
+pub(crate) const ADMIN_PATH_PREFIX: &str = "/api/admin";
+
+type HmacSha256 = Hmac<Sha256>;
+
+// the authenticated caller, available to handlers through the request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiClient {
+    pub name: String,
+    pub scope: ApiKeyScope,
+}
+
+pub async fn authenticate(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let context = match req.app_data::<Data<Arc<Context>>>() {
+        Some(context) if context.server_config.load().auth.enabled => context.get_ref().clone(),
+        _ => return next.call(req).await,
+    };
+    let server_config = context.server_config.load();
+    let auth = &server_config.auth;
+    let required_scope = if req.path().starts_with(ADMIN_PATH_PREFIX) {
+        ApiKeyScope::Admin
+    } else {
+        ApiKeyScope::Public
+    };
+
+    let client = if req.headers().contains_key(API_SIGNATURE_HEADER) {
+        verify_signature(&mut req, auth, &context).await
+    } else {
+        verify_api_key(&req, auth)
+    };
+    let client = match client {
+        Some(client) if client.scope.allows(required_scope) => client,
+        _ => {
+            warn!(
+                "rejected unauthenticated request to {} from {:?}",
+                req.path(),
+                req.peer_addr()
+            );
+            return Err(ResponseError::Unauthorized.into());
+        }
+    };
+
+    if let Err(error) = context.api_key_handler.record_usage(&client.name).await {
+        error!("record usage of api key {} got error: {:?}", client.name, error);
+    }
+    req.extensions_mut().insert(client);
+    next.call(req).await
+}
+
+pub fn sign_request(key: &str, timestamp: u64, nonce: &str, method: &str, path_and_query: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).expect("hmac accepts keys of any size");
+    mac.update(signing_prefix(timestamp, nonce, method, path_and_query).as_bytes());
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn verify_api_key(req: &ServiceRequest, auth: &AuthConfig) -> Option<ApiClient> {
+    let key = header_value(req, API_KEY_HEADER)?;
+    auth.find_by_key(&key).map(|(name, config)| ApiClient {
+        name: name.to_string(),
+        scope: config.scope,
+    })
+}
+
+async fn verify_signature(req: &mut ServiceRequest, auth: &AuthConfig, context: &Context) -> Option<ApiClient> {
+    let name = header_value(req, API_KEY_ID_HEADER)?;
+    let timestamp = header_value(req, API_TIMESTAMP_HEADER)?.parse::<u64>().ok()?;
+    let nonce = header_value(req, API_NONCE_HEADER).filter(|nonce| (8..=64).contains(&nonce.len()))?;
+    let signature = hex::decode(header_value(req, API_SIGNATURE_HEADER)?.trim_start_matches("0x")).ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    if now.abs_diff(timestamp) > auth.max_clock_skew_secs {
+        return None;
+    }
+    let config = auth.keys.get(&name)?;
+
+    // the body is part of the signature, put it back for the handler once read
+    let body = req.extract::<Bytes>().await.ok()?;
+    req.set_payload(bytes_to_payload(body.clone()));
+
+    let path_and_query = req.uri().path_and_query().map(|path| path.as_str()).unwrap_or("/");
+    let mut mac = HmacSha256::new_from_slice(config.key.as_bytes()).ok()?;
+    mac.update(signing_prefix(timestamp, &nonce, req.method().as_str(), path_and_query).as_bytes());
+    mac.update(&body);
+    mac.verify_slice(&signature).ok()?;
+
+    // a signed request is accepted once, the nonce is remembered while its timestamp is accepted
+    let expires_at = (timestamp + auth.max_clock_skew_secs + 1) * 1000;
+    match context.api_key_handler.use_nonce(&name, &nonce, expires_at).await {
+        Ok(true) => {}
+        Ok(false) => {
+            warn!("rejected replayed request of api key {} with nonce {}", name, nonce);
+            return None;
+        }
+        Err(error) => {
+            error!("check nonce of api key {} got error: {:?}", name, error);
+            return None;
+        }
+    }
+    Some(ApiClient {
+        name,
+        scope: config.scope,
+    })
+}
+
+fn signing_prefix(timestamp: u64, nonce: &str, method: &str, path_and_query: &str) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n",
+        timestamp,
+        nonce,
+        method.to_uppercase(),
+        path_and_query
+    )
+}
+
+fn header_value(req: &ServiceRequest, name: &str) -> Option<String> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+}
+
+pub(crate) fn bytes_to_payload(bytes: Bytes) -> Payload {
+    let (_, mut payload) = actix_http::h1::Payload::create(true);
+    payload.unread_data(bytes);
+    Payload::from(payload)
+}
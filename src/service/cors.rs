@@ -0,0 +1,36 @@
+use crate::configs::cors::{CorsConfig, ANY};
+use actix_cors::Cors;
+use actix_web::http::Method;
+
+pub fn create_cors(config: &CorsConfig) -> Cors {
+    let mut cors = Cors::default();
+    if config.allows_any_origin() {
+        cors = cors.allow_any_origin();
+    } else {
+        let config = config.clone();
+        cors = cors.allowed_origin_fn(move |origin, _| {
+            origin
+                .to_str()
+                .map(|origin| config.is_origin_allowed(origin))
+                .unwrap_or(false)
+        });
+    }
+    if config.allowed_methods.iter().any(|method| method == ANY) {
+        cors = cors.allow_any_method();
+    } else {
+        let methods = config
+            .allowed_methods
+            .iter()
+            .filter_map(|method| Method::from_bytes(method.to_uppercase().as_bytes()).ok());
+        cors = cors.allowed_methods(methods);
+    }
+    if config.allowed_headers.iter().any(|header| header == ANY) {
+        cors = cors.allow_any_header();
+    } else {
+        cors = cors.allowed_headers(config.allowed_headers.iter().map(String::as_str));
+    }
+    if config.allow_credentials {
+        cors = cors.supports_credentials();
+    }
+    cors.max_age(config.max_age_secs)
+}
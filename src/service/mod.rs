@@ -1,8 +1,15 @@
+pub mod admin;
+pub mod auth;
+pub mod cors;
+pub mod quote;
+pub mod rate_limit;
+pub mod tls;
 pub mod v1;
 pub mod v2;
 
 use crate::channel::producer::ProducerHandler;
-use crate::channel::SenderInfo;
+use crate::channel::{read_senders, SenderSet};
+use crate::configs::remote::CIRCUIT_TYPES;
 use crate::context::Context;
 use crate::error::{RelayerServerError, ResponseError};
 use actix_web::web::Data;
@@ -10,27 +17,29 @@ use actix_web::{get, Responder};
 use anyhow::bail;
 use anyhow::Result;
 use ethereum_types::U256;
-use ethers_signers::LocalWallet;
 use log::debug;
-use mystiko_ethers::Providers;
 use mystiko_relayer_config::wrapper::relayer::RelayerConfig;
 use mystiko_relayer_types::response::success;
 use mystiko_relayer_types::HandshakeResponse;
 use mystiko_relayer_types::RegisterOptions;
 use mystiko_server_utils::token_price::PriceMiddleware;
-use mystiko_server_utils::tx_manager::config::TxManagerConfig;
-use mystiko_server_utils::tx_manager::{TransactionMiddleware, TxManagerBuilder};
-use mystiko_types::AssetType;
+use mystiko_types::{AssetType, CircuitType};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use std::collections::HashSet;
 use std::ops::Mul;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 #[get("/handshake")]
 pub async fn handshake(context: Data<Arc<Context>>) -> actix_web::Result<impl Responder, ResponseError> {
-    let api_version: Vec<String> = context.server_config.settings.api_version.values().cloned().collect();
+    let api_version: Vec<String> = context
+        .server_config
+        .load()
+        .settings
+        .api_version
+        .values()
+        .cloned()
+        .collect();
     let package_version = env!("CARGO_PKG_VERSION");
     Ok(success(
         HandshakeResponse::builder()
@@ -41,15 +50,16 @@ pub async fn handshake(context: Data<Arc<Context>>) -> actix_web::Result<impl Re
 }
 
 pub fn find_sender(
-    senders: Data<Arc<HashSet<SenderInfo>>>,
+    senders: Data<Arc<SenderSet>>,
     chain_id: u64,
     asset_symbol: &str,
     asset_type: AssetType,
 ) -> Option<Arc<Box<dyn ProducerHandler<Error = RelayerServerError>>>> {
+    let senders = read_senders(&senders);
     let matches = senders
         .iter()
         .filter(|sender| {
-            if chain_id != sender.chain_id {
+            if chain_id != sender.chain_id || !sender.available || !sender.lease.is_held() {
                 return false;
             }
             if asset_type == AssetType::Main {
@@ -73,17 +83,7 @@ pub fn find_sender(
     None
 }
 
-async fn gas_price_by_chain_id<P: Providers>(chain_id: u64, providers: Arc<P>, is_tx_eip1559: bool) -> Result<U256> {
-    let provider = providers.get_provider(chain_id).await?;
-    let tx_builder = TxManagerBuilder::builder()
-        .config(TxManagerConfig::new(None)?)
-        .chain_id(chain_id)
-        .wallet(LocalWallet::new(&mut rand::thread_rng()))
-        .build();
-    let tx_manager = tx_builder.build(Some(is_tx_eip1559), &provider).await?;
-    Ok(tx_manager.gas_price(&provider).await?)
-}
-
+// scaled like the fees of every circuit type, so a single fee agrees with the listed ones
 async fn minimum_gas_fee(
     relayer_config: &RelayerConfig,
     chain_id: u64,
@@ -91,55 +91,83 @@ async fn minimum_gas_fee(
     token: Arc<RwLock<Box<dyn PriceMiddleware>>>,
     options: &RegisterOptions,
 ) -> Result<U256> {
-    let asset_symbol = &options.asset_symbol;
     let circuit_type = &options.circuit_type;
-
-    let relayer_chain_config = relayer_config.find_chain_config(chain_id);
-    if relayer_chain_config.is_none() {
-        bail!("chain id {} config not found in relayer config", chain_id)
+    let fees = minimum_gas_fees(relayer_config, chain_id, gas_price, token, &options.asset_symbol).await?;
+    match fees
+        .into_iter()
+        .find(|(fee_circuit_type, _)| fee_circuit_type == circuit_type)
+    {
+        Some((_, fee)) => Ok(fee),
+        None => bail!(
+            "circuit type {:?} gas cost not found in chain id {} config",
+            circuit_type,
+            chain_id
+        ),
     }
-    let relayer_chain_config = relayer_chain_config.unwrap();
+}
 
-    let contract_config = relayer_chain_config.find_contract(asset_symbol);
-    if contract_config.is_none() {
+// minimum gas fees of every circuit type with a configured gas cost. only the largest fee is swapped, so the
+// prices of both symbols are looked up once, the others are scaled from it and rounded up
+async fn minimum_gas_fees(
+    relayer_config: &RelayerConfig,
+    chain_id: u64,
+    gas_price: U256,
+    token: Arc<RwLock<Box<dyn PriceMiddleware>>>,
+    asset_symbol: &str,
+) -> Result<Vec<(CircuitType, U256)>> {
+    let Some(relayer_chain_config) = relayer_config.find_chain_config(chain_id) else {
+        bail!("chain id {} config not found in relayer config", chain_id)
+    };
+    let Some(contract_config) = relayer_chain_config.find_contract(asset_symbol) else {
         bail!(
             "asset symbol {} contract config not found in chain id {} config",
             asset_symbol,
             chain_id
         )
-    }
-    let contract_config = contract_config.unwrap();
-
+    };
     let main_asset_symbol = relayer_chain_config.asset_symbol();
     let main_asset_decimals = relayer_chain_config.asset_decimals();
-    debug!(
-        "chain id {}, main asset symbol {}, main asset decimals {}",
-        chain_id, main_asset_symbol, main_asset_decimals
-    );
-
     let asset_type: &AssetType = contract_config.asset_type();
     let asset_decimals = contract_config.asset_decimals();
-    debug!(
-        "asset symbol {} asset type {:?}, asset decimals {}",
-        asset_symbol, asset_type, asset_decimals
-    );
 
-    let gas_cost = relayer_chain_config.find_gas_cost(asset_type, circuit_type)?;
-    debug!("circuit type {:?} gas cost {}", circuit_type, gas_cost);
-
-    let minimum_gas_fee = gas_price.mul(gas_cost);
-
-    let token_price = token.write().await;
-    let result = token_price
+    let gas_costs = CIRCUIT_TYPES
+        .into_iter()
+        .filter_map(|circuit_type| {
+            relayer_chain_config
+                .find_gas_cost(asset_type, &circuit_type)
+                .ok()
+                .map(|gas_cost| (circuit_type, U256::from(gas_cost)))
+        })
+        .collect::<Vec<_>>();
+    let Some(max_gas_cost) = gas_costs.iter().map(|(_, gas_cost)| *gas_cost).max() else {
+        return Ok(vec![]);
+    };
+    let max_fee = token
+        .read()
+        .await
         .swap(
             main_asset_symbol,
             main_asset_decimals,
-            minimum_gas_fee,
+            gas_price.mul(max_gas_cost),
             asset_symbol,
             asset_decimals,
         )
         .await?;
-    drop(token_price);
 
-    Ok(result)
+    let fees = gas_costs
+        .into_iter()
+        .map(|(circuit_type, gas_cost)| {
+            let fee = if max_gas_cost.is_zero() {
+                U256::zero()
+            } else {
+                (max_fee.mul(gas_cost) + max_gas_cost - 1) / max_gas_cost
+            };
+            debug!(
+                "chain id {} asset symbol {} circuit type {:?} minimum gas fee {}",
+                chain_id, asset_symbol, circuit_type, fee
+            );
+            (circuit_type, fee)
+        })
+        .collect();
+    Ok(fees)
 }
@@ -0,0 +1,78 @@
+use crate::configs::quote::QuoteConfig;
+use crate::handler::types::current_timestamp;
+use hmac::{Hmac, Mac};
+use mystiko_types::CircuitType;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// minimum fee of one asset and circuit type, signed by the relayer and valid until expires_at
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeQuote {
+    pub quote_id: String,
+    pub chain_id: u64,
+    pub asset_symbol: String,
+    pub circuit_type: CircuitType,
+    // decimal amount in the asset, like minimum_gas_fee of /info
+    pub minimum_gas_fee: String,
+    // milliseconds since the unix epoch
+    pub expires_at: u64,
+    pub signature: String,
+}
+
+impl FeeQuote {
+    pub fn new(
+        config: &QuoteConfig,
+        chain_id: u64,
+        asset_symbol: &str,
+        circuit_type: CircuitType,
+        minimum_gas_fee: &str,
+    ) -> Self {
+        let mut quote = FeeQuote {
+            quote_id: hex::encode(rand::random::<[u8; 16]>()),
+            chain_id,
+            asset_symbol: asset_symbol.to_string(),
+            circuit_type,
+            minimum_gas_fee: minimum_gas_fee.to_string(),
+            expires_at: current_timestamp() + config.ttl_ms,
+            signature: String::new(),
+        };
+        quote.signature = quote.sign(&config.secret);
+        quote
+    }
+
+    pub fn sign(&self, secret: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+        mac.update(self.signing_message().as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    // the signature was made with this secret and the quote has not expired
+    pub fn verify(&self, secret: &str) -> Result<(), String> {
+        let signature = hex::decode(self.signature.trim_start_matches("0x"))
+            .map_err(|_| format!("fee quote {} has an invalid signature", self.quote_id))?;
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+        mac.update(self.signing_message().as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| format!("fee quote {} has an invalid signature", self.quote_id))?;
+        if current_timestamp() > self.expires_at {
+            return Err(format!("fee quote {} has expired", self.quote_id));
+        }
+        Ok(())
+    }
+
+    fn signing_message(&self) -> String {
+        let circuit_type = serde_json::to_string(&self.circuit_type).unwrap_or_default();
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            self.quote_id,
+            self.chain_id,
+            self.asset_symbol.to_lowercase(),
+            circuit_type,
+            self.minimum_gas_fee,
+            self.expires_at
+        )
+    }
+}
@@ -0,0 +1,184 @@
+use crate::configs::rate_limit::{RateLimitConfig, TokenBucketConfig};
+use crate::error::ResponseError;
+use crate::service::auth::{bytes_to_payload, ApiClient};
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::web::{Bytes, Data};
+use actix_web::{HttpMessage, HttpRequest};
+use log::warn;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+// buckets are pruned once a map grows past this size, full buckets carry no state worth keeping
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+const CHAIN_ID_FIELDS: [&str; 2] = ["chainId", "chain_id"];
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(config: &TokenBucketConfig, now: Instant) -> Self {
+        TokenBucket {
+            capacity: config.capacity as f64,
+            refill_per_second: config.refill_per_second,
+            tokens: config.capacity as f64,
+            updated_at: now,
+        }
+    }
+
+    // takes one token, or returns how long until one is available
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_second))
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.updated_at = now;
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.capacity
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clients: Mutex<HashMap<String, TokenBucket>>,
+    // keyed by client and chain id, one busy client does not use up the chain for the others
+    chains: Mutex<HashMap<(String, u64), TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            clients: Mutex::new(HashMap::new()),
+            chains: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check_client(&self, client: &str) -> Result<(), Duration> {
+        match &self.config.client {
+            Some(config) => acquire(&self.clients, client.to_string(), config),
+            None => Ok(()),
+        }
+    }
+
+    pub fn check_chain(&self, client: &str, chain_id: u64) -> Result<(), Duration> {
+        match &self.config.chain {
+            Some(config) => acquire(&self.chains, (client.to_string(), chain_id), config),
+            None => Ok(()),
+        }
+    }
+
+    // the same limits for requests arriving as websocket messages, the middleware only sees the handshake
+    pub fn check_message(&self, client: &str, chain_id: Option<u64>) -> Result<(), ResponseError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        self.check_client(client).map_err(rate_limited)?;
+        match chain_id {
+            Some(chain_id) => self.check_chain(client, chain_id).map_err(rate_limited),
+            None => Ok(()),
+        }
+    }
+
+    pub fn trust_forwarded_for(&self) -> bool {
+        self.config.trust_forwarded_for
+    }
+}
+
+pub async fn rate_limit(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let limiter = match req.app_data::<Data<Arc<RateLimiter>>>() {
+        Some(limiter) if limiter.config.enabled => limiter.get_ref().clone(),
+        _ => return next.call(req).await,
+    };
+
+    let client = client_identity(req.request(), limiter.config.trust_forwarded_for);
+    if let Err(retry_after) = limiter.check_client(&client) {
+        warn!("rate limited client {} on {}", client, req.path());
+        return Err(rate_limited(retry_after).into());
+    }
+
+    if limiter.config.chain.is_some() {
+        if let Some(chain_id) = request_chain_id(&mut req).await {
+            if let Err(retry_after) = limiter.check_chain(&client, chain_id) {
+                warn!("rate limited chain id {} request from {}", chain_id, client);
+                return Err(rate_limited(retry_after).into());
+            }
+        }
+    }
+    next.call(req).await
+}
+
+fn acquire<K: Eq + Hash>(
+    buckets: &Mutex<HashMap<K, TokenBucket>>,
+    key: K,
+    config: &TokenBucketConfig,
+) -> Result<(), Duration> {
+    let now = Instant::now();
+    let mut buckets = buckets.lock().unwrap_or_else(PoisonError::into_inner);
+    if buckets.len() >= MAX_TRACKED_BUCKETS {
+        buckets.retain(|_, bucket| !bucket.is_full(now));
+    }
+    buckets
+        .entry(key)
+        .or_insert_with(|| TokenBucket::new(config, now))
+        .try_acquire(now)
+}
+
+// api key name when authenticated, otherwise the client ip address
+pub(crate) fn client_identity(req: &HttpRequest, trust_forwarded_for: bool) -> String {
+    if let Some(client) = req.extensions().get::<ApiClient>() {
+        return format!("key:{}", client.name);
+    }
+    let connection = req.connection_info();
+    let address = if trust_forwarded_for {
+        connection.realip_remote_addr().map(|address| address.to_string())
+    } else {
+        req.peer_addr().map(|address| address.ip().to_string())
+    };
+    format!("ip:{}", address.unwrap_or_default())
+}
+
+async fn request_chain_id(req: &mut ServiceRequest) -> Option<u64> {
+    if req.method() != Method::POST {
+        return None;
+    }
+    let body = req.extract::<Bytes>().await.ok()?;
+    req.set_payload(bytes_to_payload(body.clone()));
+    let json = serde_json::from_slice::<Value>(&body).ok()?;
+    CHAIN_ID_FIELDS
+        .iter()
+        .find_map(|field| json.get(field).and_then(Value::as_u64))
+}
+
+fn rate_limited(retry_after: Duration) -> ResponseError {
+    // round up, a zero hint would invite an immediate retry that is rejected again
+    let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    ResponseError::RateLimited {
+        retry_after_secs: retry_after_secs.max(1),
+    }
+}
@@ -0,0 +1,177 @@
+use crate::configs::tls::TlsConfig;
+use crate::context::Context;
+use crate::error::ResponseError;
+use crate::service::auth::ADMIN_PATH_PREFIX;
+use actix_tls::accept::rustls_0_21::TlsStream;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Extensions, ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web::Data;
+use anyhow::{anyhow, bail, Context as AnyhowContext, Result};
+use log::{info, warn};
+use rustls::server::{AllowAnyAnonymousOrAuthenticatedClient, ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+use rustls_pemfile::Item;
+use std::any::Any;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+// marks a connection that presented a client certificate verified against the admin client ca
+#[derive(Debug, Clone)]
+pub struct ClientCertificate {
+    pub der: Vec<u8>,
+}
+
+// serves the current certificate, swapped in place when the files change
+pub struct TlsCertResolver {
+    config: TlsConfig,
+    current: RwLock<LoadedCert>,
+}
+
+struct LoadedCert {
+    cert_pem: Vec<u8>,
+    key_pem: Vec<u8>,
+    key: Arc<CertifiedKey>,
+}
+
+impl TlsCertResolver {
+    pub fn new(config: TlsConfig) -> Result<Self> {
+        let current = load_cert(&config)?;
+        Ok(TlsCertResolver {
+            config,
+            current: RwLock::new(current),
+        })
+    }
+
+    pub fn certificate(&self) -> Arc<CertifiedKey> {
+        self.current.read().unwrap_or_else(PoisonError::into_inner).key.clone()
+    }
+
+    // returns whether a new certificate was loaded, a broken one keeps the current certificate
+    pub fn reload_if_changed(&self) -> Result<bool> {
+        let cert_pem = std::fs::read(&self.config.cert_path)?;
+        let key_pem = std::fs::read(&self.config.key_path)?;
+        {
+            let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+            if current.cert_pem == cert_pem && current.key_pem == key_pem {
+                return Ok(false);
+            }
+        }
+        let loaded = parse_cert(cert_pem, key_pem)?;
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = loaded;
+        Ok(true)
+    }
+
+    pub async fn watch(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.reload_interval_secs));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match self.reload_if_changed() {
+                Ok(true) => info!("reloaded tls certificate from {}", self.config.cert_path),
+                Ok(false) => {}
+                Err(error) => warn!("reload tls certificate got error, keep the current one: {:?}", error),
+            }
+        }
+    }
+}
+
+impl ResolvesServerCert for TlsCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.certificate())
+    }
+}
+
+pub fn create_tls_config(config: &TlsConfig) -> Result<(ServerConfig, Arc<TlsCertResolver>)> {
+    let resolver = Arc::new(TlsCertResolver::new(config.clone())?);
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let mut server_config = match &config.admin_client_ca_path {
+        Some(ca_path) => {
+            // client certificates are optional on the handshake, the admin endpoints check for one
+            let mut roots = RootCertStore::empty();
+            for cert in read_certs(&std::fs::read(ca_path)?)? {
+                roots.add(&cert)?;
+            }
+            builder
+                .with_client_cert_verifier(AllowAnyAnonymousOrAuthenticatedClient::new(roots).boxed())
+                .with_cert_resolver(resolver.clone())
+        }
+        None => builder.with_no_client_auth().with_cert_resolver(resolver.clone()),
+    };
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok((server_config, resolver))
+}
+
+// passed to HttpServer::on_connect, records the verified client certificate of tls connections
+pub fn tls_on_connect(connection: &dyn Any, extensions: &mut Extensions) {
+    if let Some(stream) = connection.downcast_ref::<TlsStream<TcpStream>>() {
+        let (_, session) = stream.get_ref();
+        if let Some(cert) = session.peer_certificates().and_then(|certs| certs.first()) {
+            extensions.insert(ClientCertificate { der: cert.0.clone() });
+        }
+    }
+}
+
+pub async fn require_admin_client_certificate(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let required = req.app_data::<Data<Arc<Context>>>().map_or(false, |context| {
+        context
+            .server_config
+            .load()
+            .settings
+            .tls
+            .as_ref()
+            .map_or(false, |tls| tls.admin_client_ca_path.is_some())
+    });
+    if required && req.path().starts_with(ADMIN_PATH_PREFIX) && req.request().conn_data::<ClientCertificate>().is_none()
+    {
+        warn!(
+            "rejected admin request to {} without a client certificate from {:?}",
+            req.path(),
+            req.peer_addr()
+        );
+        return Err(ResponseError::Unauthorized.into());
+    }
+    next.call(req).await
+}
+
+fn load_cert(config: &TlsConfig) -> Result<LoadedCert> {
+    let cert_pem = std::fs::read(&config.cert_path).with_context(|| format!("read {}", config.cert_path))?;
+    let key_pem = std::fs::read(&config.key_path).with_context(|| format!("read {}", config.key_path))?;
+    parse_cert(cert_pem, key_pem)
+}
+
+fn parse_cert(cert_pem: Vec<u8>, key_pem: Vec<u8>) -> Result<LoadedCert> {
+    let certs = read_certs(&cert_pem)?;
+    if certs.is_empty() {
+        bail!("no certificate found in pem");
+    }
+    let key = read_private_key(&key_pem)?;
+    let signing_key = rustls::sign::any_supported_type(&key).map_err(|error| anyhow!("{}", error))?;
+    Ok(LoadedCert {
+        cert_pem,
+        key_pem,
+        key: Arc::new(CertifiedKey::new(certs, signing_key)),
+    })
+}
+
+fn read_certs(pem: &[u8]) -> Result<Vec<Certificate>> {
+    Ok(rustls_pemfile::certs(&mut &pem[..])?
+        .into_iter()
+        .map(Certificate)
+        .collect())
+}
+
+fn read_private_key(pem: &[u8]) -> Result<PrivateKey> {
+    for item in rustls_pemfile::read_all(&mut &pem[..])? {
+        match item {
+            Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => {}
+        }
+    }
+    bail!("no private key found in pem")
+}
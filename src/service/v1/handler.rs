@@ -1,20 +1,21 @@
 use crate::channel::producer::ProducerHandler;
-use crate::channel::SenderInfo;
+use crate::channel::SenderSet;
 use crate::context::Context;
 use crate::error::ResponseError;
+use crate::handler::transaction::status::{StatusUpdate, TransactionStatusEvent};
 use crate::service::v1::parse_transact_request;
 use crate::service::v1::request::{ChainStatusRequest, TransactRequestV1};
 use crate::service::v1::response::{
     ChainStatusResponse, ContractResponse, JobStatusResponse, ResponseQueueData, TransactResponse,
 };
-use crate::service::{find_sender, gas_price_by_chain_id, minimum_gas_fee};
+use crate::service::v2::stream::{TransactionHandlerRef, STATUS_RESYNC_INTERVAL};
+use crate::service::{find_sender, minimum_gas_fee};
 use actix_web::web::{Data, Json, Path};
 use actix_web::{get, post, Responder};
 use log::{debug, error, info};
 use mystiko_relayer_types::response::success;
 use mystiko_relayer_types::{RegisterOptions, TransactStatus};
 use mystiko_types::{AssetType, TransactionType};
-use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
@@ -28,11 +29,11 @@ pub async fn chain_status(
     info!("api v1 version chain status");
 
     let chain_id = request.chain_id;
-    let relayer_config = &context.relayer_config;
-    let mystiko_config = &context.mystiko_config;
+    let relayer_config = &context.relayer_config.load();
+    let mystiko_config = &context.mystiko_config.load();
     let handler = &context.account_handler;
     let token_price = &context.token_price;
-    let providers = &context.providers;
+    let gas_price_cache = &context.gas_price_cache;
 
     let is_tx_eip1559 = match mystiko_config.find_chain(chain_id) {
         None => {
@@ -128,7 +129,7 @@ pub async fn chain_status(
                 continue;
             }
             let minimum_gas_fee = if let Some(options) = &request.options {
-                let gas_price = gas_price_by_chain_id(chain_id, providers.clone(), is_tx_eip1559).await;
+                let gas_price = gas_price_cache.gas_price(chain_id, is_tx_eip1559).await;
                 if gas_price.is_err() {
                     return Err(ResponseError::GetGasPriceError { chain_id });
                 }
@@ -211,12 +212,12 @@ pub async fn job_status(
 pub async fn transact_v1(
     request: Json<TransactRequestV1>,
     context: Data<Arc<Context>>,
-    senders: Data<Arc<HashSet<SenderInfo>>>,
+    senders: Data<Arc<SenderSet>>,
 ) -> actix_web::Result<impl Responder, ResponseError> {
     info!("api v1 version transact");
 
-    let relayer_config = &context.relayer_config;
-    let mystiko_config = &context.mystiko_config;
+    let relayer_config = &context.relayer_config.load();
+    let mystiko_config = &context.mystiko_config.load();
     let handler = &context.transaction_handler;
 
     // validate
@@ -269,38 +270,29 @@ pub async fn transact_v1(
                     hash: "".to_string(),
                     chain_id: transaction.data.chain_id,
                 };
-                let timeout_duration = Duration::from_secs(120);
-                let result = timeout(timeout_duration, async {
+                // read once after subscribing, the consumer may have been faster
+                let mut subscription = context.status_bus.subscribe(&transaction.id);
+                let mut current = find_status(handler, &transaction.id).await?;
+                let result = timeout(Duration::from_secs(120), async {
                     loop {
-                        // wait transaction hash
-                        let transaction = handler.find_by_id(transaction.id.as_str()).await.map_err(|error| {
-                            error!("find transaction by id({}) got error: {:?}", transaction.id, error);
-                            ResponseError::TransactionNotFound {
-                                id: transaction.id.to_string(),
+                        if let Some(event) = current.take() {
+                            if event.status == TransactStatus::Failed {
+                                return Err(ResponseError::TransactionFailed {
+                                    error: event.error_message.unwrap_or("unknown".to_string()),
+                                });
                             }
-                        })?;
-                        match transaction {
-                            None => {
-                                info!("transaction not found, continue wait");
-                            }
-                            Some(doc) => {
-                                if doc.data.status == TransactStatus::Failed {
-                                    return Err(ResponseError::TransactionFailed {
-                                        error: doc.data.error_message.unwrap_or("unknown".to_string()),
-                                    });
-                                }
-                                match doc.data.transaction_hash {
-                                    None => {
-                                        info!("transaction hash not found, continue wait");
-                                    }
-                                    Some(hash) => {
-                                        response.hash = hash;
-                                        return Ok(());
-                                    }
-                                }
+                            if let Some(hash) = event.transaction_hash {
+                                response.hash = hash;
+                                return Ok(());
                             }
+                            info!("transaction hash not found, continue wait");
                         }
-                        tokio::time::sleep(Duration::from_secs(2)).await;
+                        current = match timeout(STATUS_RESYNC_INTERVAL, subscription.next()).await {
+                            Ok(StatusUpdate::Event(event)) => Some(event),
+                            Ok(StatusUpdate::Closed) => return Err(ResponseError::Unknown),
+                            // lagged behind or no event for a while, read the transaction again
+                            _ => find_status(handler, &transaction.id).await?,
+                        };
                     }
                 })
                 .await;
@@ -324,3 +316,16 @@ pub async fn transact_v1(
         },
     }
 }
+
+async fn find_status(
+    handler: &TransactionHandlerRef,
+    id: &str,
+) -> Result<Option<TransactionStatusEvent>, ResponseError> {
+    match handler.find_by_id(id).await {
+        Ok(transaction) => Ok(transaction.as_ref().map(TransactionStatusEvent::from)),
+        Err(error) => {
+            error!("find transaction by id({}) got error: {:?}", id, error);
+            Err(ResponseError::TransactionNotFound { id: id.to_string() })
+        }
+    }
+}
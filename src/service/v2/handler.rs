@@ -1,43 +1,303 @@
 use crate::channel::producer::ProducerHandler;
-use crate::channel::SenderInfo;
+use crate::channel::webhook::resolve_callback_url;
+use crate::channel::SenderSet;
+use crate::configs::quote::QuoteConfig;
+use crate::configs::webhook::WebhookConfig;
 use crate::context::Context;
-use crate::error::ResponseError;
-use crate::service::{find_sender, gas_price_by_chain_id, minimum_gas_fee};
-use actix_web::web::{Data, Json, Path};
-use actix_web::{get, post, Responder};
+use crate::database::account::Account as DocumentAccount;
+use crate::database::transaction::Transaction as DocumentTransaction;
+use crate::error::{RelayerServerError, ResponseError};
+use crate::handler::transaction::status::TransactionStatusEvent;
+use crate::service::quote::FeeQuote;
+use crate::service::v2::request::{
+    ChainsQuery, RegisterInfoRequestV2, TransactRequestV2, TransactionStatusBatchRequest,
+};
+use crate::service::v2::response::{
+    AssetGasFees, ChainContractInfo, ChainInfo, CircuitGasFee, RegisterInfoResponseV2, TransactionStatusBatchResponse,
+};
+use crate::service::v2::stream::{
+    transaction_status_stream as status_stream, STATUS_RESYNC_INTERVAL, TEXT_EVENT_STREAM,
+};
+use crate::service::{find_sender, minimum_gas_fee, minimum_gas_fees};
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{get, post, HttpResponse, Responder};
+use ethers_core::types::U256;
 use log::{debug, error};
+use mystiko_relayer_config::wrapper::relayer::RelayerConfig;
 use mystiko_relayer_types::response::success;
 use mystiko_relayer_types::{
     ContractInfo, RegisterInfoRequest, RegisterInfoResponse, RelayTransactResponse, RelayTransactStatusResponse,
     TransactRequestData,
 };
+use mystiko_storage::Document;
 use mystiko_types::{AssetType, TransactionType};
-use std::collections::HashSet;
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use validator::Validate;
 
 #[post("/info")]
 pub async fn info(
-    request: Json<RegisterInfoRequest>,
+    request: Json<RegisterInfoRequestV2>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    let request = request.into_inner();
+    let info = register_info(&context, &request.data).await?;
+    let quote = if request.quote {
+        quote_fee(&context.server_config.load().quote, &request.data, &info)?
+    } else {
+        None
+    };
+    let minimum_gas_fees = if request.all_fees {
+        all_minimum_gas_fees(&context, &info).await?
+    } else {
+        None
+    };
+    Ok(success(RegisterInfoResponseV2 {
+        info,
+        quote,
+        minimum_gas_fees,
+    }))
+}
+
+// unsupported or unavailable assets are not quoted
+fn quote_fee(
+    config: &QuoteConfig,
+    request: &RegisterInfoRequest,
+    info: &RegisterInfoResponse,
+) -> Result<Option<FeeQuote>, ResponseError> {
+    if !config.enabled {
+        return Err(ResponseError::ValidateError {
+            error: "fee quotes are not supported by this relayer".to_string(),
+        });
+    }
+    let Some(options) = &request.options else {
+        return Err(ResponseError::ValidateError {
+            error: "a fee quote needs the asset symbol and circuit type options".to_string(),
+        });
+    };
+    let minimum_gas_fee = info
+        .contracts
+        .iter()
+        .flatten()
+        .find(|contract| contract.asset_symbol.eq_ignore_ascii_case(&options.asset_symbol))
+        .and_then(|contract| contract.minimum_gas_fee.clone());
+    Ok(minimum_gas_fee.map(|minimum_gas_fee| {
+        FeeQuote::new(
+            config,
+            request.chain_id,
+            &options.asset_symbol,
+            options.circuit_type,
+            &minimum_gas_fee,
+        )
+    }))
+}
+
+#[get("/chains")]
+pub async fn chains(
+    query: Query<ChainsQuery>,
     context: Data<Arc<Context>>,
 ) -> actix_web::Result<impl Responder, ResponseError> {
+    Ok(success(list_chains(&context, query.fees).await?))
+}
+
+// every chain with configured accounts, sorted by chain id
+async fn list_chains(context: &Context, fees: bool) -> Result<Vec<ChainInfo>, ResponseError> {
+    let relayer_config = &context.relayer_config.load();
+    let mystiko_config = &context.mystiko_config.load();
+    let accounts = context.account_handler.find_all().await.map_err(|e| {
+        error!("Failed to query accounts: {:?}", e);
+        ResponseError::DatabaseError
+    })?;
+    let mut chain_accounts: BTreeMap<u64, Vec<Document<DocumentAccount>>> = BTreeMap::new();
+    for account in accounts {
+        chain_accounts.entry(account.data.chain_id).or_default().push(account);
+    }
+
+    let mut chains = Vec::new();
+    for (chain_id, accounts) in chain_accounts {
+        let (Some(chain_config), Some(relayer_chain_config)) = (
+            mystiko_config.find_chain(chain_id),
+            relayer_config.find_chain_config(chain_id),
+        ) else {
+            chains.push(ChainInfo {
+                chain_id,
+                support: false,
+                available: false,
+                relayer_contract_address: None,
+                contracts: vec![],
+            });
+            continue;
+        };
+        let available = accounts.iter().any(|account| account.data.available);
+        let mut contracts = Vec::new();
+        if available {
+            // one gas price per chain, a failing provider only drops the fees of its chain
+            let gas_price = if fees {
+                let is_tx_eip1559 = chain_config.transaction_type() == &TransactionType::Eip1559;
+                match context.gas_price_cache.gas_price(chain_id, is_tx_eip1559).await {
+                    Ok(gas_price) => Some(gas_price),
+                    Err(error) => {
+                        error!("get chain id {} gas price error {}", chain_id, error);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let supported_symbols = supported_erc20_symbols(&accounts);
+            for contract in relayer_chain_config.contracts() {
+                let asset_symbol = contract.asset_symbol();
+                if !supported_symbols.contains(&asset_symbol.to_lowercase())
+                    && !relayer_chain_config.asset_symbol().eq_ignore_ascii_case(asset_symbol)
+                {
+                    continue;
+                }
+                let minimum_gas_fees = match gas_price {
+                    Some(gas_price) => {
+                        match circuit_gas_fees(relayer_config, chain_id, gas_price, context, asset_symbol).await {
+                            Ok(fees) => Some(fees),
+                            Err(error) => {
+                                error!(
+                                    "Failed to get minimum gas fees of chain id {} asset symbol {}: {:?}",
+                                    chain_id, asset_symbol, error
+                                );
+                                None
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                contracts.push(ChainContractInfo {
+                    asset_symbol: asset_symbol.to_string(),
+                    relayer_fee_of_ten_thousandth: contract.relayer_fee_of_ten_thousandth(),
+                    minimum_gas_fees,
+                });
+            }
+        }
+        chains.push(ChainInfo {
+            chain_id,
+            support: true,
+            available,
+            relayer_contract_address: Some(relayer_chain_config.relayer_contract_address().to_string()),
+            contracts,
+        });
+    }
+    Ok(chains)
+}
+
+async fn circuit_gas_fees(
+    relayer_config: &RelayerConfig,
+    chain_id: u64,
+    gas_price: U256,
+    context: &Context,
+    asset_symbol: &str,
+) -> anyhow::Result<Vec<CircuitGasFee>> {
+    let fees = minimum_gas_fees(
+        relayer_config,
+        chain_id,
+        gas_price,
+        context.token_price.clone(),
+        asset_symbol,
+    )
+    .await?;
+    Ok(fees
+        .into_iter()
+        .map(|(circuit_type, minimum_gas_fee)| CircuitGasFee {
+            circuit_type,
+            minimum_gas_fee: minimum_gas_fee.to_string(),
+        })
+        .collect())
+}
+
+// minimum gas fees of every listed asset and circuit type, computed with one gas price
+async fn all_minimum_gas_fees(
+    context: &Context,
+    info: &RegisterInfoResponse,
+) -> Result<Option<Vec<AssetGasFees>>, ResponseError> {
+    let Some(contracts) = &info.contracts else {
+        return Ok(None);
+    };
+    let chain_id = info.chain_id;
+    let relayer_config = &context.relayer_config.load();
+    let is_tx_eip1559 = context
+        .mystiko_config
+        .load()
+        .find_chain(chain_id)
+        .map(|chain_config| chain_config.transaction_type() == &TransactionType::Eip1559)
+        .unwrap_or_default();
+    let gas_price = context
+        .gas_price_cache
+        .gas_price(chain_id, is_tx_eip1559)
+        .await
+        .map_err(|error| {
+            error!("get chain id {} gas price error {}", chain_id, error);
+            ResponseError::GetGasPriceError { chain_id }
+        })?;
+    debug!("chain id {} gas prices {:?}", chain_id, gas_price);
+
+    // like /chains, an asset whose fees fail only drops its own fees
+    let mut fees = Vec::new();
+    for contract in contracts {
+        let minimum_gas_fees =
+            match circuit_gas_fees(relayer_config, chain_id, gas_price, context, &contract.asset_symbol).await {
+                Ok(fees) => Some(fees),
+                Err(error) => {
+                    error!(
+                        "Failed to get minimum gas fees of chain id {} asset symbol {}: {:?}",
+                        chain_id, contract.asset_symbol, error
+                    );
+                    None
+                }
+            };
+        fees.push(AssetGasFees {
+            asset_symbol: contract.asset_symbol.clone(),
+            minimum_gas_fees,
+        });
+    }
+    Ok(Some(fees))
+}
+
+// lowercase erc20 symbols supported by any of the accounts
+fn supported_erc20_symbols(accounts: &[Document<DocumentAccount>]) -> Vec<String> {
+    accounts
+        .iter()
+        .flat_map(|account| {
+            account
+                .data
+                .supported_erc20_tokens
+                .iter()
+                .map(|symbol| symbol.to_lowercase())
+        })
+        .fold(Vec::new(), |mut acc, symbol| {
+            if !acc.contains(&symbol) {
+                acc.push(symbol);
+            }
+            acc
+        })
+}
+
+// shared by the http and websocket api
+pub async fn register_info(
+    context: &Context,
+    request: &RegisterInfoRequest,
+) -> Result<RegisterInfoResponse, ResponseError> {
     let chain_id = request.chain_id;
 
-    let relayer_config = &context.relayer_config;
-    let mystiko_config = &context.mystiko_config;
+    let relayer_config = &context.relayer_config.load();
+    let mystiko_config = &context.mystiko_config.load();
     let handler = &context.account_handler;
     let token_price = &context.token_price;
-    let providers = &context.providers;
+    let gas_price_cache = &context.gas_price_cache;
 
     let is_tx_eip1559 = match mystiko_config.find_chain(chain_id) {
         None => {
-            return Ok(success(
-                RegisterInfoResponse::builder()
-                    .chain_id(chain_id)
-                    .support(false)
-                    .available(false)
-                    .build(),
-            ));
+            return Ok(RegisterInfoResponse::builder()
+                .chain_id(chain_id)
+                .support(false)
+                .available(false)
+                .build());
         }
         Some(chain_config) => chain_config.transaction_type() == &TransactionType::Eip1559,
     };
@@ -54,22 +314,7 @@ pub async fn info(
             return Err(ResponseError::AccountNotFoundInDatabase);
         }
 
-        let account_supported_erc20_symbol = accounts
-            .iter()
-            .flat_map(|account| {
-                account
-                    .data
-                    .supported_erc20_tokens
-                    .iter()
-                    .map(|symbol| symbol.to_lowercase())
-            })
-            .fold(Vec::new(), |mut acc, symbol| {
-                let lowercase_symbol = symbol.to_lowercase();
-                if !acc.contains(&lowercase_symbol) {
-                    acc.push(lowercase_symbol);
-                }
-                acc
-            });
+        let account_supported_erc20_symbol = supported_erc20_symbols(&accounts);
         debug!(
             "chain_id: {}, account_supported_symbol: {:?}",
             chain_id, account_supported_erc20_symbol
@@ -85,25 +330,21 @@ pub async fn info(
                     .iter()
                     .any(|account| account.data.supported_erc20_tokens.contains(asset_symbol_lowercase))
             {
-                return Ok(success(
-                    RegisterInfoResponse::builder()
-                        .chain_id(chain_id)
-                        .support(false)
-                        .available(false)
-                        .build(),
-                ));
+                return Ok(RegisterInfoResponse::builder()
+                    .chain_id(chain_id)
+                    .support(false)
+                    .available(false)
+                    .build());
             }
         }
 
         // Check available
         if accounts.iter().all(|account| !account.data.available) {
-            return Ok(success(
-                RegisterInfoResponse::builder()
-                    .chain_id(chain_id)
-                    .support(true)
-                    .available(false)
-                    .build(),
-            ));
+            return Ok(RegisterInfoResponse::builder()
+                .chain_id(chain_id)
+                .support(true)
+                .available(false)
+                .build());
         }
 
         let contracts_config = match &request.options {
@@ -124,7 +365,7 @@ pub async fn info(
                 continue;
             }
             let minimum_gas_fee = if let Some(options) = &request.options {
-                let gas_price = gas_price_by_chain_id(chain_id, providers.clone(), is_tx_eip1559).await;
+                let gas_price = gas_price_cache.gas_price(chain_id, is_tx_eip1559).await;
                 if gas_price.is_err() {
                     error!("get chain id {} gas price error {}", chain_id, gas_price.unwrap_err());
                     return Err(ResponseError::GetGasPriceError { chain_id });
@@ -150,40 +391,56 @@ pub async fn info(
                     .build(),
             );
         }
-        Ok(success(
-            RegisterInfoResponse::builder()
-                .chain_id(chain_id)
-                .support(true)
-                .available(true)
-                .relayer_contract_address(String::from(relayer_chain_config.relayer_contract_address()))
-                .contracts(contracts)
-                .build(),
-        ))
+        Ok(RegisterInfoResponse::builder()
+            .chain_id(chain_id)
+            .support(true)
+            .available(true)
+            .relayer_contract_address(String::from(relayer_chain_config.relayer_contract_address()))
+            .contracts(contracts)
+            .build())
     } else {
-        Ok(success(
-            RegisterInfoResponse::builder()
-                .chain_id(chain_id)
-                .support(false)
-                .available(false)
-                .build(),
-        ))
+        Ok(RegisterInfoResponse::builder()
+            .chain_id(chain_id)
+            .support(false)
+            .available(false)
+            .build())
     }
 }
 
 #[post("/transact")]
 pub async fn transact(
-    request: Json<TransactRequestData>,
+    request: Json<TransactRequestV2>,
     context: Data<Arc<Context>>,
-    senders: Data<Arc<HashSet<SenderInfo>>>,
+    senders: Data<Arc<SenderSet>>,
 ) -> actix_web::Result<impl Responder, ResponseError> {
+    Ok(success(submit_transact(&context, senders, request.into_inner()).await?))
+}
+
+// shared by the http and websocket api
+pub async fn submit_transact(
+    context: &Context,
+    senders: Data<Arc<SenderSet>>,
+    request: TransactRequestV2,
+) -> Result<RelayTransactResponse, ResponseError> {
+    let TransactRequestV2 {
+        data: request,
+        callback_url,
+        quote,
+    } = request;
     let handler = &context.transaction_handler;
-    let relayer_config = &context.relayer_config;
+    let relayer_config = &context.relayer_config.load();
 
     // validate
     if let Err(err) = request.validate() {
         error!("transact request body validate error {:?}", err);
         return Err(ResponseError::ValidateError { error: err.to_string() });
     }
+    if let Some(url) = &callback_url {
+        validate_callback_url(&context.server_config.load().webhook, url).await?;
+    }
+    if let Some(quote) = &quote {
+        validate_quote(&context.server_config.load().quote, quote, &request)?;
+    }
 
     // check repeated transaction
     if let Ok(repeat) = handler.is_repeated_transaction(&request.signature).await {
@@ -210,19 +467,100 @@ pub async fn transact(
 
     // save data and sent
     match find_sender(senders, request.chain_id, &request.asset_symbol, asset_type) {
-        Some(producer) => match producer.send(request.into_inner()).await {
-            Ok(transaction) => Ok(success(RelayTransactResponse { uuid: transaction.id })),
-            Err(error) => {
-                error!("send transact request to queue got error: {:?}", error);
-                Err(ResponseError::TransactionChannelError {
-                    error: error.to_string(),
-                })
+        Some(producer) => {
+            // saved before queueing, the first status change may come right after
+            // a resubmitted signature must not reuse an older callback
+            if context.server_config.load().webhook.enabled {
+                let saved = match &callback_url {
+                    Some(url) => context
+                        .webhook_handler
+                        .save_callback(&request.signature, url)
+                        .await
+                        .map(|_| ()),
+                    None => context.webhook_handler.delete_callback(&request.signature).await,
+                };
+                if let Err(error) = saved {
+                    error!("save callback of transact request got error: {:?}", error);
+                    return Err(ResponseError::DatabaseError);
+                }
             }
-        },
+            // the consumer honours the quote instead of rejecting a fee that was quoted correctly
+            if context.server_config.load().quote.enabled {
+                let saved = match &quote {
+                    Some(quote) => context
+                        .quote_handler
+                        .save(&request.signature, &quote.quote_id, &quote.minimum_gas_fee)
+                        .await
+                        .map(|_| ()),
+                    None => context.quote_handler.delete(&request.signature).await,
+                };
+                if let Err(error) = saved {
+                    error!("save fee quote of transact request got error: {:?}", error);
+                    return Err(ResponseError::DatabaseError);
+                }
+            }
+            match producer.send(request).await {
+                Ok(transaction) => Ok(RelayTransactResponse { uuid: transaction.id }),
+                Err(error) => {
+                    error!("send transact request to queue got error: {:?}", error);
+                    Err(ResponseError::TransactionChannelError {
+                        error: error.to_string(),
+                    })
+                }
+            }
+        }
         None => Err(ResponseError::UnsupportedTransaction),
     }
 }
 
+fn validate_quote(config: &QuoteConfig, quote: &FeeQuote, request: &TransactRequestData) -> Result<(), ResponseError> {
+    if !config.enabled {
+        return Err(ResponseError::ValidateError {
+            error: "fee quotes are not supported by this relayer".to_string(),
+        });
+    }
+    quote
+        .verify(&config.secret)
+        .map_err(|error| ResponseError::ValidateError { error })?;
+    if quote.chain_id != request.chain_id
+        || !quote.asset_symbol.eq_ignore_ascii_case(&request.asset_symbol)
+        || quote.circuit_type != request.circuit_type
+    {
+        return Err(ResponseError::ValidateError {
+            error: format!(
+                "fee quote {} was made for another chain, asset or circuit type",
+                quote.quote_id
+            ),
+        });
+    }
+    let minimum_gas_fee = U256::from_dec_str(&quote.minimum_gas_fee).map_err(|_| ResponseError::ValidateError {
+        error: format!("fee quote {} has an invalid minimum gas fee", quote.quote_id),
+    })?;
+    if request.contract_param.relayer_fee_amount < minimum_gas_fee {
+        return Err(ResponseError::ValidateError {
+            error: format!(
+                "relayer fee amount {} is less than the quoted minimum gas fee {}",
+                request.contract_param.relayer_fee_amount, minimum_gas_fee
+            ),
+        });
+    }
+    Ok(())
+}
+
+async fn validate_callback_url(config: &WebhookConfig, url: &str) -> Result<(), ResponseError> {
+    if !config.enabled || !config.allow_callback_url {
+        return Err(ResponseError::ValidateError {
+            error: "callback url is not supported by this relayer".to_string(),
+        });
+    }
+    resolve_callback_url(config, url)
+        .await
+        .map(|_| ())
+        .map_err(|error| ResponseError::ValidateError {
+            error: format!("callback url {}", error),
+        })
+}
+
 #[get("/transaction/status/{id}")]
 pub async fn transaction_status(
     id: Path<String>,
@@ -248,3 +586,130 @@ pub async fn transaction_status(
         }
     }
 }
+
+#[post("/transaction/status")]
+pub async fn transaction_status_batch(
+    request: Json<TransactionStatusBatchRequest>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    if let Err(err) = request.validate() {
+        error!("transaction status batch request validate error {:?}", err);
+        return Err(ResponseError::ValidateError { error: err.to_string() });
+    }
+
+    let mut ids = request.into_inner().ids;
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+
+    let handler = &context.transaction_handler;
+    let mut transactions = handler
+        .find_by_ids(&ids)
+        .await
+        .map_err(|error| {
+            error!("find transactions by ids got error: {:?}", error);
+            ResponseError::DatabaseError
+        })?
+        .into_iter()
+        .map(|transaction| (transaction.id.clone(), transaction))
+        .collect::<HashMap<_, _>>();
+
+    let mut response = TransactionStatusBatchResponse {
+        transactions: vec![],
+        not_found: vec![],
+    };
+    for id in ids {
+        match transactions.remove(&id) {
+            Some(transaction) => response
+                .transactions
+                .push(TransactionStatusEvent::from(&transaction).into()),
+            None => response.not_found.push(id),
+        }
+    }
+    Ok(success(response))
+}
+
+#[get("/transaction/hash/{transaction_hash}")]
+pub async fn transactions_by_hash(
+    transaction_hash: Path<String>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    let transactions = context
+        .transaction_handler
+        .find_by_transaction_hash(transaction_hash.as_str())
+        .await;
+    Ok(success(status_list(transactions, transaction_hash.as_str())?))
+}
+
+#[get("/transaction/signature/{signature}")]
+pub async fn transactions_by_signature(
+    signature: Path<String>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    let transactions = context.transaction_handler.find_by_signature(signature.as_str()).await;
+    Ok(success(status_list(transactions, signature.as_str())?))
+}
+
+#[get("/transaction/serial_number/{serial_number}")]
+pub async fn transactions_by_serial_number(
+    serial_number: Path<String>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<impl Responder, ResponseError> {
+    let parsed = parse_serial_number(serial_number.as_str())?;
+    let transactions = context.transaction_handler.find_by_serial_number(&parsed).await;
+    Ok(success(status_list(transactions, serial_number.as_str())?))
+}
+
+// newest job first, an empty result is reported like an unknown id
+fn status_list(
+    transactions: Result<Vec<Document<DocumentTransaction>>, RelayerServerError>,
+    key: &str,
+) -> Result<Vec<RelayTransactStatusResponse>, ResponseError> {
+    let mut transactions = transactions.map_err(|error| {
+        error!("find transactions by {} got error: {:?}", key, error);
+        ResponseError::DatabaseError
+    })?;
+    if transactions.is_empty() {
+        return Err(ResponseError::TransactionNotFound { id: key.to_string() });
+    }
+    transactions.sort_by_key(|transaction| std::cmp::Reverse(transaction.created_at));
+    Ok(transactions
+        .iter()
+        .map(|transaction| TransactionStatusEvent::from(transaction).into())
+        .collect())
+}
+
+// 0x prefixed hex or decimal
+fn parse_serial_number(serial_number: &str) -> Result<BigUint, ResponseError> {
+    let parsed = match serial_number.strip_prefix("0x") {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(serial_number.as_bytes(), 10),
+    };
+    parsed.ok_or_else(|| ResponseError::ValidateError {
+        error: format!("invalid serial number {}", serial_number),
+    })
+}
+
+#[get("/transaction/status/{id}/stream")]
+pub async fn transaction_status_stream(
+    id: Path<String>,
+    context: Data<Arc<Context>>,
+) -> actix_web::Result<HttpResponse, ResponseError> {
+    // subscribe before reading, a change in between is not lost
+    let subscription = context.status_bus.subscribe(id.as_str());
+    match context.transaction_handler.find_by_id(id.as_str()).await {
+        Ok(Some(transaction)) => Ok(HttpResponse::Ok()
+            .content_type(TEXT_EVENT_STREAM)
+            .insert_header((CACHE_CONTROL, "no-cache"))
+            .streaming(status_stream(
+                subscription,
+                context.transaction_handler.clone(),
+                TransactionStatusEvent::from(&transaction),
+                STATUS_RESYNC_INTERVAL,
+            ))),
+        Ok(None) => Err(ResponseError::TransactionNotFound { id: id.into_inner() }),
+        Err(error) => {
+            error!("find transaction by id({}) got error: {:?}", id, error);
+            Err(ResponseError::DatabaseError)
+        }
+    }
+}
@@ -1 +1,5 @@
 pub mod handler;
+pub mod request;
+pub mod response;
+pub mod stream;
+pub mod ws;
@@ -0,0 +1,43 @@
+use crate::service::quote::FeeQuote;
+use mystiko_relayer_types::{RegisterInfoRequest, TransactRequestData};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// the info request, asking for a signed fee quote when quote is set
+// and for the minimum gas fees of every asset and circuit type when all_fees is set
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterInfoRequestV2 {
+    #[serde(flatten)]
+    pub data: RegisterInfoRequest,
+    #[serde(default)]
+    pub quote: bool,
+    #[serde(default)]
+    pub all_fees: bool,
+}
+
+// the transact request data with the optional callback url of the status webhook and fee quote
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactRequestV2 {
+    #[serde(flatten)]
+    pub data: TransactRequestData,
+    #[serde(default, alias = "callback_url", skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<FeeQuote>,
+}
+
+#[derive(Validate, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionStatusBatchRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub ids: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ChainsQuery {
+    // also compute the minimum gas fees of every contract and circuit type
+    #[serde(default)]
+    pub fees: bool,
+}
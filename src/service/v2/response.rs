@@ -0,0 +1,62 @@
+use crate::service::quote::FeeQuote;
+use mystiko_relayer_types::{RegisterInfoResponse, RelayTransactStatusResponse};
+use mystiko_types::CircuitType;
+use serde::{Deserialize, Serialize};
+
+// statuses in the order of the requested ids, unknown ids are listed on their own
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionStatusBatchResponse {
+    pub transactions: Vec<RelayTransactStatusResponse>,
+    pub not_found: Vec<String>,
+}
+
+// the info response with the fee quote and the minimum gas fees, when they were asked for
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterInfoResponseV2 {
+    #[serde(flatten)]
+    pub info: RegisterInfoResponse,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<FeeQuote>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_gas_fees: Option<Vec<AssetGasFees>>,
+}
+
+// one chain with configured accounts, contracts are only listed while it is supported and available
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfo {
+    pub chain_id: u64,
+    pub support: bool,
+    pub available: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relayer_contract_address: Option<String>,
+    #[serde(default)]
+    pub contracts: Vec<ChainContractInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainContractInfo {
+    pub asset_symbol: String,
+    pub relayer_fee_of_ten_thousandth: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_gas_fees: Option<Vec<CircuitGasFee>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitGasFee {
+    pub circuit_type: CircuitType,
+    pub minimum_gas_fee: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetGasFees {
+    pub asset_symbol: String,
+    // none when the fees of this asset can not be computed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_gas_fees: Option<Vec<CircuitGasFee>>,
+}
@@ -0,0 +1,102 @@
+use crate::database::transaction::Transaction as DocumentTransaction;
+use crate::error::RelayerServerError;
+use crate::handler::transaction::status::{StatusUpdate, TransactionStatusEvent, TransactionStatusSubscription};
+use crate::handler::transaction::TransactionHandler;
+use actix_web::web::Bytes;
+use futures::stream::{unfold, Stream};
+use log::{error, warn};
+use mystiko_relayer_types::RelayTransactStatusResponse;
+use mystiko_storage::Document;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+pub const TEXT_EVENT_STREAM: &str = "text/event-stream";
+
+// also re-reads the transaction, updates made by another instance are not published on this bus
+pub const STATUS_RESYNC_INTERVAL: Duration = Duration::from_secs(15);
+
+pub type TransactionHandlerRef =
+    Arc<Box<dyn TransactionHandler<Document<DocumentTransaction>, Error = RelayerServerError>>>;
+
+struct StatusStream {
+    id: String,
+    subscription: TransactionStatusSubscription,
+    handler: TransactionHandlerRef,
+    pending: Option<TransactionStatusEvent>,
+    last: Option<TransactionStatusEvent>,
+    resync_interval: Duration,
+    finished: bool,
+}
+
+// one `status` event per change, starting with the current status and ending after a final one
+pub fn transaction_status_stream(
+    subscription: TransactionStatusSubscription,
+    handler: TransactionHandlerRef,
+    current: TransactionStatusEvent,
+    resync_interval: Duration,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let state = StatusStream {
+        id: current.id.clone(),
+        subscription,
+        handler,
+        pending: Some(current),
+        last: None,
+        resync_interval,
+        finished: false,
+    };
+    unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+        state.next_frame().await.map(|frame| (Ok(frame), state))
+    })
+}
+
+impl StatusStream {
+    async fn next_frame(&mut self) -> Option<Bytes> {
+        loop {
+            if let Some(event) = self.pending.take() {
+                if self.last.as_ref() != Some(&event) {
+                    return Some(self.emit(event));
+                }
+            }
+            match timeout(self.resync_interval, self.subscription.next()).await {
+                Ok(StatusUpdate::Event(event)) => self.pending = Some(event),
+                Ok(StatusUpdate::Lagged) => {
+                    warn!("status stream of transaction {} lagged behind", self.id);
+                    self.pending = self.reload().await;
+                }
+                Ok(StatusUpdate::Closed) => return None,
+                Err(_) => {
+                    self.pending = self.reload().await;
+                    if self.pending.is_none() || self.pending == self.last {
+                        // comment line, keeps proxies from closing an idle connection
+                        return Some(Bytes::from_static(b": keep-alive\n\n"));
+                    }
+                }
+            }
+        }
+    }
+
+    fn emit(&mut self, event: TransactionStatusEvent) -> Bytes {
+        self.finished = event.is_final();
+        self.last = Some(event.clone());
+        status_event_frame(event)
+    }
+
+    async fn reload(&self) -> Option<TransactionStatusEvent> {
+        match self.handler.find_by_id(&self.id).await {
+            Ok(transaction) => transaction.as_ref().map(TransactionStatusEvent::from),
+            Err(error) => {
+                error!("find transaction by id({}) got error: {:?}", self.id, error);
+                None
+            }
+        }
+    }
+}
+
+pub fn status_event_frame(event: TransactionStatusEvent) -> Bytes {
+    let data = serde_json::to_string(&RelayTransactStatusResponse::from(event)).unwrap_or_default();
+    Bytes::from(format!("event: status\ndata: {}\n\n", data))
+}
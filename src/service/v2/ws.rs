@@ -0,0 +1,445 @@
+use crate::channel::SenderSet;
+use crate::context::Context;
+use crate::error::{get_error_code, ResponseError};
+use crate::handler::transaction::status::{StatusUpdate, TransactionStatusEvent, TransactionStatusSubscription};
+use crate::service::auth::ApiClient;
+use crate::service::quote::FeeQuote;
+use crate::service::rate_limit::{client_identity, RateLimiter};
+use crate::service::v2::handler::{register_info, submit_transact};
+use crate::service::v2::request::TransactRequestV2;
+use actix_web::web::{Data, Payload};
+use actix_web::{get, HttpRequest, HttpResponse};
+use actix_ws::{Message, MessageStream, Session};
+use log::{debug, error, warn};
+use mystiko_relayer_types::response::ResponseCode;
+use mystiko_relayer_types::{RegisterInfoRequest, RelayTransactStatusResponse, TransactRequestData};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::{interval, MissedTickBehavior};
+
+pub const MAX_SUBSCRIBED_TRANSACTIONS: usize = 100;
+pub const MAX_SUBSCRIBED_INFOS: usize = 16;
+pub const INFO_PUSH_INTERVAL: Duration = Duration::from_secs(30);
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsRequest {
+    #[serde(rename_all = "camelCase")]
+    Transact {
+        #[serde(default)]
+        request_id: Option<String>,
+        data: TransactRequestData,
+        #[serde(default)]
+        callback_url: Option<String>,
+        #[serde(default)]
+        quote: Option<FeeQuote>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Subscribe {
+        #[serde(default)]
+        request_id: Option<String>,
+        ids: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Unsubscribe {
+        #[serde(default)]
+        request_id: Option<String>,
+        ids: Vec<String>,
+    },
+    // pushes the /info response now and again whenever it changes, e.g. with the gas price
+    #[serde(rename_all = "camelCase")]
+    SubscribeInfo {
+        #[serde(default)]
+        request_id: Option<String>,
+        data: RegisterInfoRequest,
+    },
+    #[serde(rename_all = "camelCase")]
+    UnsubscribeInfo {
+        #[serde(default)]
+        request_id: Option<String>,
+        data: RegisterInfoRequest,
+    },
+}
+
+impl WsRequest {
+    pub fn request_id(&self) -> Option<String> {
+        match self {
+            WsRequest::Transact { request_id, .. }
+            | WsRequest::Subscribe { request_id, .. }
+            | WsRequest::Unsubscribe { request_id, .. }
+            | WsRequest::SubscribeInfo { request_id, .. }
+            | WsRequest::UnsubscribeInfo { request_id, .. } => request_id.clone(),
+        }
+    }
+
+    // the chain a message is limited on, like the chainId in an http request body
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            WsRequest::Transact { data, .. } => Some(data.chain_id),
+            WsRequest::SubscribeInfo { data, .. } => Some(data.chain_id),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WsMessageType {
+    Transact,
+    Subscribe,
+    Unsubscribe,
+    SubscribeInfo,
+    UnsubscribeInfo,
+    Status,
+    Info,
+    Error,
+}
+
+// same code, data and message as the http api responses
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WsMessage {
+    #[serde(rename = "type")]
+    pub message_type: WsMessageType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub code: i32,
+    #[serde(default)]
+    pub data: Option<Value>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl WsMessage {
+    pub fn success<T: Serialize>(message_type: WsMessageType, request_id: Option<String>, data: T) -> Self {
+        WsMessage {
+            message_type,
+            request_id,
+            code: ResponseCode::Successful as i32,
+            data: serde_json::to_value(data).ok(),
+            message: None,
+        }
+    }
+
+    pub fn error(request_id: Option<String>, error: &ResponseError) -> Self {
+        WsMessage {
+            message_type: WsMessageType::Error,
+            request_id,
+            code: get_error_code(error) as i32,
+            data: None,
+            message: Some(error.to_string()),
+        }
+    }
+
+    pub fn status(event: TransactionStatusEvent) -> Self {
+        WsMessage::success(WsMessageType::Status, None, RelayTransactStatusResponse::from(event))
+    }
+}
+
+#[get("/ws")]
+pub async fn ws(
+    request: HttpRequest,
+    body: Payload,
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+) -> actix_web::Result<HttpResponse> {
+    let (response, session, messages) = actix_ws::handle(&request, body)?;
+    let limiter = request
+        .app_data::<Data<Arc<RateLimiter>>>()
+        .map(|limiter| limiter.get_ref().clone());
+    let client = limiter
+        .as_ref()
+        .map(|limiter| client_identity(&request, limiter.trust_forwarded_for()))
+        .unwrap_or_default();
+    let connection = WsConnection {
+        subscription: context.status_bus.subscribe_none(),
+        context,
+        senders,
+        infos: HashMap::new(),
+        limiter,
+        client,
+        api_client: request.extensions().get::<ApiClient>().cloned(),
+    };
+    actix_web::rt::spawn(connection.run(session, messages));
+    Ok(response)
+}
+
+struct WsConnection {
+    context: Data<Arc<Context>>,
+    senders: Data<Arc<SenderSet>>,
+    subscription: TransactionStatusSubscription,
+    // info subscriptions by their request json, with the last pushed response
+    infos: HashMap<String, (RegisterInfoRequest, Option<Value>)>,
+    limiter: Option<Arc<RateLimiter>>,
+    // rate limit key of the connection, the api key name or the client ip
+    client: String,
+    // the api key that authenticated the handshake, none when auth is disabled
+    api_client: Option<ApiClient>,
+}
+
+impl WsConnection {
+    async fn run(mut self, mut session: Session, mut messages: MessageStream) {
+        let mut heartbeat = interval(HEARTBEAT_INTERVAL);
+        let mut info_push = interval(INFO_PUSH_INTERVAL);
+        info_push.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_seen = Instant::now();
+        let reason = loop {
+            let replies = tokio::select! {
+                message = messages.recv() => {
+                    last_seen = Instant::now();
+                    match message {
+                        Some(Ok(Message::Text(text))) => self.handle_text(&text).await,
+                        Some(Ok(Message::Ping(bytes))) => {
+                            if session.pong(&bytes).await.is_err() {
+                                break None;
+                            }
+                            vec![]
+                        }
+                        Some(Ok(Message::Binary(_))) => vec![WsMessage::error(
+                            None,
+                            &ResponseError::ValidateError {
+                                error: "binary messages are not supported".to_string(),
+                            },
+                        )],
+                        Some(Ok(Message::Close(reason))) => break reason,
+                        Some(Ok(_)) => vec![],
+                        Some(Err(error)) => {
+                            debug!("websocket protocol error: {:?}", error);
+                            break None;
+                        }
+                        None => break None,
+                    }
+                }
+                update = self.subscription.next() => match update {
+                    StatusUpdate::Event(event) => vec![self.status_message(event)],
+                    StatusUpdate::Lagged => {
+                        warn!("websocket status subscription lagged behind");
+                        self.reload_statuses().await
+                    }
+                    StatusUpdate::Closed => break None,
+                },
+                _ = info_push.tick() => self.push_infos().await,
+                _ = heartbeat.tick() => {
+                    if last_seen.elapsed() > CLIENT_TIMEOUT {
+                        debug!("websocket client timed out");
+                        break None;
+                    }
+                    if session.ping(b"").await.is_err() {
+                        break None;
+                    }
+                    vec![]
+                }
+            };
+            if !send(&mut session, replies).await {
+                return;
+            }
+        };
+        let _ = session.close(reason).await;
+    }
+
+    async fn handle_text(&mut self, text: &str) -> Vec<WsMessage> {
+        let request = match serde_json::from_str::<WsRequest>(text) {
+            Ok(request) => request,
+            Err(error) => {
+                let error = ResponseError::ValidateError {
+                    error: error.to_string(),
+                };
+                return vec![WsMessage::error(None, &error)];
+            }
+        };
+        if let Err(error) = self.admit(&request).await {
+            return vec![WsMessage::error(request.request_id(), &error)];
+        }
+        match request {
+            WsRequest::Transact {
+                request_id,
+                data,
+                callback_url,
+                quote,
+            } => {
+                // the new job is watched, so it counts against the subscription limit
+                if self.subscription.ids().len() >= MAX_SUBSCRIBED_TRANSACTIONS {
+                    return vec![WsMessage::error(request_id, &too_many_transactions())];
+                }
+                let request = TransactRequestV2 {
+                    data,
+                    callback_url,
+                    quote,
+                };
+                match submit_transact(&self.context, self.senders.clone(), request).await {
+                    Ok(response) => {
+                        // the new job is followed right away
+                        self.subscription.watch(&response.uuid);
+                        vec![WsMessage::success(WsMessageType::Transact, request_id, response)]
+                    }
+                    Err(error) => vec![WsMessage::error(request_id, &error)],
+                }
+            }
+            WsRequest::Subscribe { request_id, ids } => self.subscribe(request_id, ids).await,
+            WsRequest::Unsubscribe { request_id, ids } => {
+                for id in &ids {
+                    self.subscription.unwatch(id);
+                }
+                vec![WsMessage::success(WsMessageType::Unsubscribe, request_id, ids)]
+            }
+            WsRequest::SubscribeInfo { request_id, data } => self.subscribe_info(request_id, data).await,
+            WsRequest::UnsubscribeInfo { request_id, data } => {
+                self.infos.remove(&info_key(&data));
+                vec![WsMessage::success(WsMessageType::UnsubscribeInfo, request_id, data)]
+            }
+        }
+    }
+
+    // every message counts like an http request against the rate limits and the api key usage
+    async fn admit(&self, request: &WsRequest) -> Result<(), ResponseError> {
+        if let Some(limiter) = &self.limiter {
+            limiter
+                .check_message(&self.client, request.chain_id())
+                .map_err(|error| {
+                    warn!("rate limited websocket message from {}", self.client);
+                    error
+                })?;
+        }
+        if let Some(api_client) = &self.api_client {
+            if let Err(error) = self.context.api_key_handler.record_usage(&api_client.name).await {
+                error!("record usage of api key {} got error: {:?}", api_client.name, error);
+            }
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&mut self, request_id: Option<String>, ids: Vec<String>) -> Vec<WsMessage> {
+        let new_ids = ids.iter().filter(|id| !self.subscription.is_watching(id)).count();
+        if self.subscription.ids().len() + new_ids > MAX_SUBSCRIBED_TRANSACTIONS {
+            return vec![WsMessage::error(request_id, &too_many_transactions())];
+        }
+        let mut replies = vec![WsMessage::success(
+            WsMessageType::Subscribe,
+            request_id.clone(),
+            ids.clone(),
+        )];
+        for id in ids {
+            // watch before reading, a change in between is not lost
+            self.subscription.watch(&id);
+            match self.context.transaction_handler.find_by_id(&id).await {
+                Ok(Some(transaction)) => {
+                    replies.push(self.status_message(TransactionStatusEvent::from(&transaction)));
+                }
+                Ok(None) => {
+                    self.subscription.unwatch(&id);
+                    replies.push(WsMessage::error(
+                        request_id.clone(),
+                        &ResponseError::TransactionNotFound { id },
+                    ));
+                }
+                Err(err) => {
+                    error!("find transaction by id({}) got error: {:?}", id, err);
+                    self.subscription.unwatch(&id);
+                    replies.push(WsMessage::error(request_id.clone(), &ResponseError::DatabaseError));
+                }
+            }
+        }
+        replies
+    }
+
+    async fn subscribe_info(&mut self, request_id: Option<String>, data: RegisterInfoRequest) -> Vec<WsMessage> {
+        let key = info_key(&data);
+        if !self.infos.contains_key(&key) && self.infos.len() >= MAX_SUBSCRIBED_INFOS {
+            let error = ResponseError::ValidateError {
+                error: format!("at most {} info requests can be subscribed", MAX_SUBSCRIBED_INFOS),
+            };
+            return vec![WsMessage::error(request_id, &error)];
+        }
+        match register_info(&self.context, &data).await {
+            Ok(response) => {
+                let response = serde_json::to_value(response).ok();
+                self.infos.insert(key, (data, response.clone()));
+                vec![WsMessage {
+                    message_type: WsMessageType::Info,
+                    request_id,
+                    code: ResponseCode::Successful as i32,
+                    data: response,
+                    message: None,
+                }]
+            }
+            Err(error) => vec![WsMessage::error(request_id, &error)],
+        }
+    }
+
+    // only changed responses are pushed, a failed refresh keeps the last one
+    async fn push_infos(&mut self) -> Vec<WsMessage> {
+        let mut replies = vec![];
+        for (request, last) in self.infos.values_mut() {
+            match register_info(&self.context, request).await {
+                Ok(response) => {
+                    let response = serde_json::to_value(response).ok();
+                    if *last != response {
+                        *last = response.clone();
+                        replies.push(WsMessage {
+                            message_type: WsMessageType::Info,
+                            request_id: None,
+                            code: ResponseCode::Successful as i32,
+                            data: response,
+                            message: None,
+                        });
+                    }
+                }
+                Err(error) => debug!("refresh info of chain id {} got error: {}", request.chain_id, error),
+            }
+        }
+        replies
+    }
+
+    async fn reload_statuses(&mut self) -> Vec<WsMessage> {
+        let mut replies = vec![];
+        for id in self.subscription.ids().clone() {
+            match self.context.transaction_handler.find_by_id(&id).await {
+                Ok(Some(transaction)) => {
+                    replies.push(self.status_message(TransactionStatusEvent::from(&transaction)));
+                }
+                Ok(None) => {
+                    self.subscription.unwatch(&id);
+                }
+                Err(error) => error!("find transaction by id({}) got error: {:?}", id, error),
+            }
+        }
+        replies
+    }
+
+    // final transactions do not change anymore and are dropped from the subscription
+    fn status_message(&mut self, event: TransactionStatusEvent) -> WsMessage {
+        if event.is_final() {
+            self.subscription.unwatch(&event.id);
+        }
+        WsMessage::status(event)
+    }
+}
+
+async fn send(session: &mut Session, messages: Vec<WsMessage>) -> bool {
+    for message in messages {
+        match serde_json::to_string(&message) {
+            Ok(text) => {
+                if session.text(text).await.is_err() {
+                    return false;
+                }
+            }
+            Err(error) => error!("serialize websocket message got error: {:?}", error),
+        }
+    }
+    true
+}
+
+fn too_many_transactions() -> ResponseError {
+    ResponseError::ValidateError {
+        error: format!("at most {} transactions can be subscribed", MAX_SUBSCRIBED_TRANSACTIONS),
+    }
+}
+
+fn info_key(request: &RegisterInfoRequest) -> String {
+    serde_json::to_string(request).unwrap_or_else(|_| request.chain_id.to_string())
+}
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::transaction::eip712::Eip712;
+use ethers_core::types::{Address, Signature};
+use ethers_signers::{LocalWallet, Signer, WalletError};
+use thiserror::Error;
+
+pub mod remote;
+pub mod tx_manager;
+
+pub use remote::{RemoteSigner, RemoteSignerError};
+pub use tx_manager::RemoteSignerTxManager;
+
+// signs for an account, either in process or through a remote signing service
+#[derive(Debug, Clone)]
+pub enum AccountSigner {
+    Local(LocalWallet),
+    Remote(RemoteSigner),
+}
+
+#[derive(Error, Debug)]
+pub enum AccountSignerError {
+    #[error(transparent)]
+    Local(#[from] WalletError),
+    #[error(transparent)]
+    Remote(#[from] RemoteSignerError),
+}
+
+#[async_trait]
+impl Signer for AccountSigner {
+    type Error = AccountSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        match self {
+            AccountSigner::Local(wallet) => Ok(wallet.sign_message(message).await?),
+            AccountSigner::Remote(signer) => Ok(signer.sign_message(message).await?),
+        }
+    }
+
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            AccountSigner::Local(wallet) => Ok(wallet.sign_transaction(message).await?),
+            AccountSigner::Remote(signer) => Ok(signer.sign_transaction(message).await?),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        match self {
+            AccountSigner::Local(wallet) => Ok(wallet.sign_typed_data(payload).await?),
+            AccountSigner::Remote(signer) => Ok(signer.sign_typed_data(payload).await?),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            AccountSigner::Local(wallet) => wallet.address(),
+            AccountSigner::Remote(signer) => signer.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            AccountSigner::Local(wallet) => wallet.chain_id(),
+            AccountSigner::Remote(signer) => signer.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            AccountSigner::Local(wallet) => AccountSigner::Local(wallet.with_chain_id(chain_id)),
+            AccountSigner::Remote(signer) => AccountSigner::Remote(signer.with_chain_id(chain_id)),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::configs::account::RemoteSignerConfig;
+use async_trait::async_trait;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::transaction::eip712::Eip712;
+use ethers_core::types::{Address, Bytes, Signature};
+use ethers_core::utils::rlp::Rlp;
+use ethers_signers::Signer;
+use log::debug;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RemoteSignerError {
+    #[error("remote signer request got error: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("remote signer returned error {code}: {message}")]
+    RpcError { code: i64, message: String },
+    #[error("remote signer returned invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("remote signer does not hold account {0:?}")]
+    AccountNotFound(Address),
+    #[error("remote signer does not support {0}")]
+    Unsupported(String),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("remote signer got invalid config: {0}")]
+    InvalidConfig(String),
+}
+
+// signs through a json-rpc signing service, e.g. web3signer, the key never enters the relayer
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: String,
+    address: Address,
+    chain_id: u64,
+    client: reqwest::Client,
+    request_id: Arc<AtomicU64>,
+}
+
+impl RemoteSigner {
+    pub fn new(config: &RemoteSignerConfig) -> Result<Self, RemoteSignerError> {
+        let address = Address::from_str(&config.address)
+            .map_err(|error| RemoteSignerError::InvalidConfig(format!("invalid address: {}", error)))?;
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &config.headers {
+            let name = reqwest::header::HeaderName::from_str(name)
+                .map_err(|error| RemoteSignerError::InvalidConfig(error.to_string()))?;
+            let value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|error| RemoteSignerError::InvalidConfig(error.to_string()))?;
+            headers.insert(name, value);
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .default_headers(headers)
+            .build()?;
+        Ok(RemoteSigner {
+            url: config.url.clone(),
+            address,
+            chain_id: 1,
+            client,
+            request_id: Arc::new(AtomicU64::new(1)),
+        })
+    }
+
+    // fails when the service is unreachable or does not hold the configured account
+    pub async fn verify(&self) -> Result<(), RemoteSignerError> {
+        let accounts: Vec<Address> = serde_json::from_value(self.request("eth_accounts", json!([])).await?)
+            .map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))?;
+        if accounts.contains(&self.address) {
+            Ok(())
+        } else {
+            Err(RemoteSignerError::AccountNotFound(self.address))
+        }
+    }
+
+    // the signed rlp encoded transaction, ready for eth_sendRawTransaction
+    pub async fn sign_transaction_raw(&self, tx: &TypedTransaction) -> Result<Bytes, RemoteSignerError> {
+        let mut tx = tx.clone();
+        tx.set_from(self.address);
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        // ethers skips chainId when serializing, the service has to sign for this chain
+        let mut params = serde_json::to_value(&tx)?;
+        if let Some(chain_id) = tx.chain_id() {
+            params["chainId"] = json!(chain_id);
+        }
+        let result = self.request("eth_signTransaction", json!([params])).await?;
+        let raw = parse_bytes(&result)?;
+
+        // never broadcast a transaction the service signed with another key or for other content
+        let (signed_tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw))
+            .map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))?;
+        let signer = signature
+            .recover(signed_tx.sighash())
+            .map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))?;
+        if signer != self.address || signed_tx.sighash() != tx.sighash() {
+            return Err(RemoteSignerError::InvalidResponse(
+                "signed transaction does not match the request".to_string(),
+            ));
+        }
+        Ok(raw)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, RemoteSignerError> {
+        let id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        debug!("remote signer request {} id {} to {}", method, id, self.url);
+        let body = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(RemoteSignerError::RpcError {
+                code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+                message: error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| RemoteSignerError::InvalidResponse("missing result".to_string()))
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    type Error = RemoteSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        let message = Bytes::from(message.as_ref().to_vec());
+        let result = self.request("eth_sign", json!([self.address, message])).await?;
+        let signature = parse_bytes(&result)?;
+        Signature::try_from(signature.as_ref()).map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))
+    }
+
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let raw = self.sign_transaction_raw(message).await?;
+        let (_, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw))
+            .map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))?;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, _payload: &T) -> Result<Signature, Self::Error> {
+        Err(RemoteSignerError::Unsupported("typed data".to_string()))
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+fn parse_bytes(value: &Value) -> Result<Bytes, RemoteSignerError> {
+    value
+        .as_str()
+        .and_then(|value| Bytes::from_str(value).ok())
+        .ok_or_else(|| RemoteSignerError::InvalidResponse(format!("expected hex bytes, got {}", value)))
+}
@@ -0,0 +1,119 @@
+use crate::signer::RemoteSigner;
+use async_trait::async_trait;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{
+    BlockNumber, Eip1559TransactionRequest, TransactionReceipt, TransactionRequest, TxHash, U256,
+};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use log::info;
+use mystiko_ethers::{JsonRpcClientWrapper, Provider, ProviderWrapper};
+use mystiko_server_utils::tx_manager::{TransactionData, TransactionMiddleware, TransactionMiddlewareError};
+use std::cmp::min;
+use std::fmt::Debug;
+
+// the tx manager built by TxManagerBuilder prices and confirms transactions,
+// everything that needs the account key goes through the remote signer
+#[derive(Debug)]
+pub struct RemoteSignerTxManager<TX> {
+    inner: TX,
+    signer: RemoteSigner,
+}
+
+impl<TX> RemoteSignerTxManager<TX> {
+    pub fn new(inner: TX, signer: RemoteSigner) -> Self {
+        RemoteSignerTxManager { inner, signer }
+    }
+
+    fn request(&self, data: &TransactionData) -> TransactionRequest {
+        TransactionRequest::new()
+            .from(self.signer.address())
+            .to(data.to)
+            .data(data.data.clone())
+            .value(data.value)
+    }
+}
+
+#[async_trait]
+impl<TX> TransactionMiddleware<ProviderWrapper<Box<dyn JsonRpcClientWrapper>>> for RemoteSignerTxManager<TX>
+where
+    TX: TransactionMiddleware<ProviderWrapper<Box<dyn JsonRpcClientWrapper>>> + Debug,
+{
+    fn tx_eip1559(&self) -> bool {
+        self.inner.tx_eip1559()
+    }
+
+    async fn gas_price(&self, provider: &Provider) -> Result<U256, TransactionMiddlewareError> {
+        self.inner.gas_price(provider).await
+    }
+
+    // estimated from the remote account, the inner manager only knows a placeholder wallet
+    async fn estimate_gas(
+        &self,
+        data: &TransactionData,
+        provider: &Provider,
+    ) -> Result<U256, TransactionMiddlewareError> {
+        let tx: TypedTransaction = self.request(data).into();
+        provider
+            .estimate_gas(&tx, None)
+            .await
+            .map_err(|error| TransactionMiddlewareError::SendTxError(format!("estimate gas: {}", error)))
+    }
+
+    async fn send(&self, data: &TransactionData, provider: &Provider) -> Result<TxHash, TransactionMiddlewareError> {
+        let nonce = provider
+            .get_transaction_count(self.signer.address(), Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|error| TransactionMiddlewareError::SendTxError(format!("get nonce: {}", error)))?;
+        let tx: TypedTransaction = if self.inner.tx_eip1559() {
+            let (_, priority_fee) = provider
+                .estimate_eip1559_fees(None)
+                .await
+                .map_err(|error| TransactionMiddlewareError::SendTxError(format!("estimate fees: {}", error)))?;
+            Eip1559TransactionRequest::new()
+                .from(self.signer.address())
+                .to(data.to)
+                .data(data.data.clone())
+                .value(data.value)
+                .gas(data.gas)
+                .nonce(nonce)
+                .max_fee_per_gas(data.max_price)
+                .max_priority_fee_per_gas(min(priority_fee, data.max_price))
+                .chain_id(self.signer.chain_id())
+                .into()
+        } else {
+            let gas_price = min(self.inner.gas_price(provider).await?, data.max_price);
+            self.request(data)
+                .gas(data.gas)
+                .gas_price(gas_price)
+                .nonce(nonce)
+                .chain_id(self.signer.chain_id())
+                .into()
+        };
+
+        let raw = self
+            .signer
+            .sign_transaction_raw(&tx)
+            .await
+            .map_err(|error| TransactionMiddlewareError::SendTxError(error.to_string()))?;
+        let pending = provider
+            .send_raw_transaction(raw)
+            .await
+            .map_err(|error| TransactionMiddlewareError::SendTxError(error.to_string()))?;
+        info!(
+            "sent remotely signed transaction(hash = {:?}, nonce = {}) from {:?}",
+            pending.tx_hash(),
+            nonce,
+            self.signer.address()
+        );
+        Ok(pending.tx_hash())
+    }
+
+    async fn confirm(
+        &self,
+        tx_hash: &TxHash,
+        provider: &Provider,
+    ) -> Result<TransactionReceipt, TransactionMiddlewareError> {
+        self.inner.confirm(tx_hash, provider).await
+    }
+}
@@ -3,21 +3,26 @@ use crate::common::{
     default_transact_request_data, default_transaction, default_transaction_receipt, MockProvider, MockProviders,
     MockTokenPrice, MockTxManager,
 };
-use crate::handler::MockTransactions;
+use crate::handler::{MockQuotes, MockTransactions};
 use ethers_core::types::{TxHash, U256};
 use log::LevelFilter;
 use mystiko_ethers::{Provider, ProviderWrapper};
 use mystiko_relayer::channel::consumer::handler::TransactionConsumer;
 use mystiko_relayer::channel::consumer::ConsumerHandler;
+use mystiko_relayer::channel::lease::LeaseState;
+use mystiko_relayer::database::quote::AcceptedQuote;
+use mystiko_relayer::database::transaction::Transaction;
 use mystiko_relayer::error::RelayerServerError;
-use mystiko_relayer_types::TransactRequestData;
+use mystiko_relayer::price_cache::GasPriceCache;
+use mystiko_relayer_types::{TransactRequestData, TransactStatus};
 use mystiko_server_utils::tx_manager::TransactionMiddlewareError;
 use mystiko_storage::{Document, StorageError};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc::Receiver;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use typed_builder::TypedBuilder;
 
 #[test]
@@ -64,6 +69,7 @@ fn test_consumer_execution_success() {
         // send
         tx_manager.expect_send().returning(move |_, _| Ok(tx_hash));
         // transaction handler update
+        expect_claim(&mut transaction_handler);
         transaction_handler.expect_update_by_id().returning(|_, _| {
             Ok(Some(Document::new(
                 String::from("123456"),
@@ -90,6 +96,7 @@ fn test_consumer_execution_success() {
             transaction_handler,
             token_price,
             tx_manager,
+            quote_handler: no_quotes(),
         });
 
         tokio::spawn(async move {
@@ -148,6 +155,8 @@ fn test_consumer_send_tx_error() {
             .expect_send()
             .returning(move |_, _| Err(TransactionMiddlewareError::SendTxError("mock error".to_string())));
         // transaction handler update
+        expect_claim(&mut transaction_handler);
+        expect_fail(&mut transaction_handler);
         transaction_handler.expect_update_by_id().returning(|_, _| {
             Ok(Some(Document::new(
                 String::from("123456"),
@@ -169,6 +178,7 @@ fn test_consumer_send_tx_error() {
             transaction_handler,
             token_price,
             tx_manager,
+            quote_handler: no_quotes(),
         });
 
         tokio::spawn(async move {
@@ -227,6 +237,8 @@ fn test_validate_relayer_fee_error() {
         // send
         tx_manager.expect_send().returning(move |_, _| Ok(tx_hash));
         // transaction handler update
+        expect_claim(&mut transaction_handler);
+        expect_fail(&mut transaction_handler);
         transaction_handler.expect_update_by_id().returning(|_, _| {
             Ok(Some(Document::new(
                 String::from("123456"),
@@ -253,6 +265,7 @@ fn test_validate_relayer_fee_error() {
             transaction_handler,
             token_price,
             tx_manager,
+            quote_handler: no_quotes(),
         });
 
         tokio::spawn(async move {
@@ -311,6 +324,7 @@ fn test_update_transaction_status_failed() {
         // send
         tx_manager.expect_send().returning(move |_, _| Ok(tx_hash));
         // transaction handler update
+        expect_claim(&mut transaction_handler);
         transaction_handler.expect_update_by_id().returning(|_, _| {
             Err(RelayerServerError::StorageError(StorageError::NoSuchColumnError(
                 "mock error".to_string(),
@@ -334,6 +348,7 @@ fn test_update_transaction_status_failed() {
             transaction_handler,
             token_price,
             tx_manager,
+            quote_handler: no_quotes(),
         });
 
         tokio::spawn(async move {
@@ -348,6 +363,354 @@ fn test_update_transaction_status_failed() {
     });
 }
 
+#[test]
+fn test_consumer_skip_cancelled_transaction() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let chain_id = 99;
+        let mut transaction_handler = MockTransactions::new();
+        // the job was cancelled while it waited in the queue
+        transaction_handler
+            .expect_update_by_id_if_status()
+            .withf(|_, expected, _| expected == [TransactStatus::Queued])
+            .times(1)
+            .returning(|_, _, _| Ok(None));
+        // no tx manager or update expectations: a skipped job must not be sent
+        let mock = create_default_sender_and_receiver();
+        let sender = mock.sender;
+        let mut consumer = setup(MockOptions {
+            chain_id,
+            is_tx_eip1559: false,
+            main_asset_symbol: "MTT".to_string(),
+            main_asset_decimals: 16,
+            receiver: mock.receiver,
+            providers: HashMap::new(),
+            signer_providers: HashMap::new(),
+            transaction_handler,
+            token_price: MockTokenPrice::new(),
+            tx_manager: MockTxManager::new(),
+            quote_handler: no_quotes(),
+        });
+
+        let result = sender
+            .send(("123456".to_string(), default_transact_request_data(chain_id)))
+            .await;
+        assert!(result.is_ok());
+        drop(sender);
+        // returns once the queue is drained and closed
+        consumer.consume().await;
+        consumer.drained.notified().await;
+    });
+}
+
+#[test]
+fn test_consumer_not_send_transaction_failed_after_claim() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let chain_id = 99;
+        let mut signer_providers = HashMap::new();
+        signer_providers.insert(chain_id, MockProvider::new());
+        let mut transaction_handler = MockTransactions::new();
+        let mut token_price = MockTokenPrice::new();
+        let mut tx_manager = MockTxManager::new();
+        tx_manager.expect_gas_price().returning(|_| Ok(U256::from(1000000)));
+        tx_manager
+            .expect_estimate_gas()
+            .returning(|_, _| Ok(U256::from(1000000)));
+        token_price
+            .expect_swap()
+            .returning(|_, _, _, _, _| Ok(U256::from(1100000000000u64)));
+        tx_manager.expect_send().never();
+        transaction_handler
+            .expect_update_by_id_if_status()
+            .withf(|_, expected, _| expected == [TransactStatus::Queued])
+            .returning(|id, _, _| Ok(Some(claimed_transaction(id))));
+        // an admin failed the job while the consumer prepared it
+        transaction_handler.expect_find_by_id().returning(|id| {
+            let mut transaction = claimed_transaction(id);
+            transaction.data.status = TransactStatus::Failed;
+            Ok(Some(transaction))
+        });
+        transaction_handler
+            .expect_update_by_id_if_status()
+            .withf(|_, expected, _| expected == [TransactStatus::Pending])
+            .times(1)
+            .returning(|_, _, _| Ok(None));
+        let mock = create_default_sender_and_receiver();
+        let sender = mock.sender;
+        let mut consumer = setup(MockOptions {
+            chain_id,
+            is_tx_eip1559: false,
+            main_asset_symbol: "MTT".to_string(),
+            main_asset_decimals: 16,
+            receiver: mock.receiver,
+            providers: HashMap::new(),
+            signer_providers,
+            transaction_handler,
+            token_price,
+            tx_manager,
+            quote_handler: no_quotes(),
+        });
+
+        let result = sender
+            .send(("123456".to_string(), default_transact_request_data(chain_id)))
+            .await;
+        assert!(result.is_ok());
+        drop(sender);
+        consumer.consume().await;
+        consumer.drained.notified().await;
+    });
+}
+
+#[test]
+fn test_consumer_skip_transaction_without_lease() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let chain_id = 99;
+        let mut transaction_handler = MockTransactions::new();
+        // the job is left queued for the instance holding the lease
+        transaction_handler.expect_update_by_id_if_status().never();
+        transaction_handler.expect_update_by_id_if_claimed().never();
+        // no tx manager expectations: another instance drives the account
+        let mock = create_default_sender_and_receiver();
+        let sender = mock.sender;
+        let mut consumer = setup(MockOptions {
+            chain_id,
+            is_tx_eip1559: false,
+            main_asset_symbol: "MTT".to_string(),
+            main_asset_decimals: 16,
+            receiver: mock.receiver,
+            providers: HashMap::new(),
+            signer_providers: HashMap::new(),
+            transaction_handler,
+            token_price: MockTokenPrice::new(),
+            tx_manager: MockTxManager::new(),
+            quote_handler: no_quotes(),
+        });
+        consumer.lease = LeaseState::not_held();
+
+        let result = sender
+            .send(("123456".to_string(), default_transact_request_data(chain_id)))
+            .await;
+        assert!(result.is_ok());
+        drop(sender);
+        consumer.consume().await;
+        consumer.drained.notified().await;
+    });
+}
+
+#[test]
+fn test_consumer_release_transaction_lease_lost_before_send() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let chain_id = 99;
+        let mut signer_providers = HashMap::new();
+        signer_providers.insert(chain_id, MockProvider::new());
+        let mut transaction_handler = MockTransactions::new();
+        let mut token_price = MockTokenPrice::new();
+        let mut tx_manager = MockTxManager::new();
+        let lease = LeaseState::not_held();
+        lease.hold_until(u64::MAX);
+        let lost = lease.clone();
+        tx_manager.expect_gas_price().returning(|_| Ok(U256::from(1000000)));
+        // the heartbeat loses the lease while the job is prepared
+        tx_manager.expect_estimate_gas().returning(move |_, _| {
+            lost.lose();
+            Ok(U256::from(1000000))
+        });
+        token_price
+            .expect_swap()
+            .returning(|_, _, _, _, _| Ok(U256::from(1100000000000u64)));
+        tx_manager.expect_send().never();
+        expect_claim(&mut transaction_handler);
+        // released, not failed: the new holder of the lease sends it
+        transaction_handler
+            .expect_update_by_id_if_claimed()
+            .withf(|id, claimed_before, options| {
+                id == "123456" && *claimed_before == u64::MAX && options.status == Some(TransactStatus::Queued)
+            })
+            .times(1)
+            .returning(|id, _, _| Ok(Some(claimed_transaction(id))));
+        let mock = create_default_sender_and_receiver();
+        let sender = mock.sender;
+        let mut consumer = setup(MockOptions {
+            chain_id,
+            is_tx_eip1559: false,
+            main_asset_symbol: "MTT".to_string(),
+            main_asset_decimals: 16,
+            receiver: mock.receiver,
+            providers: HashMap::new(),
+            signer_providers,
+            transaction_handler,
+            token_price,
+            tx_manager,
+            quote_handler: no_quotes(),
+        });
+        consumer.lease = lease;
+
+        let result = sender
+            .send(("123456".to_string(), default_transact_request_data(chain_id)))
+            .await;
+        assert!(result.is_ok());
+        drop(sender);
+        consumer.consume().await;
+        consumer.drained.notified().await;
+    });
+}
+
+#[test]
+fn test_consumer_honour_fee_quote() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let chain_id = 99;
+        let mut providers = HashMap::new();
+        providers.insert(chain_id, MockProvider::new());
+        let mut signer_providers = HashMap::new();
+        signer_providers.insert(chain_id, MockProvider::new());
+        let mut transaction_handler = MockTransactions::new();
+        let mut token_price = MockTokenPrice::new();
+        let mut tx_manager = MockTxManager::new();
+        let mock = create_default_sender_and_receiver();
+        let sender = mock.sender;
+
+        let tx_hash = TxHash::random();
+        tx_manager.expect_gas_price().returning(|_| Ok(U256::from(1000000)));
+        // the estimate is 1.2e12, above the swapped relayer fee but within the 20% risk limit
+        tx_manager
+            .expect_estimate_gas()
+            .returning(|_, _| Ok(U256::from(1200000)));
+        token_price
+            .expect_swap()
+            .returning(|_, _, _, _, _| Ok(U256::from(1100000000000u64)));
+        tx_manager.expect_send().times(1).returning(move |_, _| Ok(tx_hash));
+        tx_manager
+            .expect_confirm()
+            .withf(move |hash, _| hash == &tx_hash)
+            .returning(move |_, _| Ok(default_transaction_receipt(tx_hash)));
+        expect_claim(&mut transaction_handler);
+        transaction_handler
+            .expect_update_by_id()
+            .withf(|_, options| options.status != Some(TransactStatus::Failed))
+            .returning(|id, _| Ok(Some(queued_transaction(id))));
+
+        let mut consumer = setup(MockOptions {
+            chain_id,
+            is_tx_eip1559: false,
+            main_asset_symbol: "MTT".to_string(),
+            main_asset_decimals: 16,
+            receiver: mock.receiver,
+            providers,
+            signer_providers,
+            transaction_handler,
+            token_price,
+            tx_manager,
+            quote_handler: accepted_quotes(),
+        });
+
+        let result = sender
+            .send(("123456".to_string(), default_transact_request_data(chain_id)))
+            .await;
+        assert!(result.is_ok());
+        drop(sender);
+        consumer.consume().await;
+        consumer.drained.notified().await;
+    });
+}
+
+#[test]
+fn test_consumer_fee_quote_over_risk_limit() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let chain_id = 99;
+        let mut providers = HashMap::new();
+        providers.insert(chain_id, MockProvider::new());
+        let mut signer_providers = HashMap::new();
+        signer_providers.insert(chain_id, MockProvider::new());
+        let mut transaction_handler = MockTransactions::new();
+        let mut token_price = MockTokenPrice::new();
+        let mut tx_manager = MockTxManager::new();
+        let mock = create_default_sender_and_receiver();
+        let sender = mock.sender;
+
+        tx_manager.expect_gas_price().returning(|_| Ok(U256::from(1000000)));
+        // the estimate is 2e12, more than 20% above the swapped relayer fee
+        tx_manager
+            .expect_estimate_gas()
+            .returning(|_, _| Ok(U256::from(2000000)));
+        token_price
+            .expect_swap()
+            .returning(|_, _, _, _, _| Ok(U256::from(1100000000000u64)));
+        tx_manager.expect_send().never();
+        expect_claim(&mut transaction_handler);
+        transaction_handler
+            .expect_update_by_id_if_status()
+            .withf(|id, expected, options| {
+                id == "123456"
+                    && expected == [TransactStatus::Pending]
+                    && options.status == Some(TransactStatus::Failed)
+            })
+            .times(1)
+            .returning(|id, _, _| Ok(Some(claimed_transaction(id))));
+
+        let mut consumer = setup(MockOptions {
+            chain_id,
+            is_tx_eip1559: false,
+            main_asset_symbol: "MTT".to_string(),
+            main_asset_decimals: 16,
+            receiver: mock.receiver,
+            providers,
+            signer_providers,
+            transaction_handler,
+            token_price,
+            tx_manager,
+            quote_handler: accepted_quotes(),
+        });
+
+        let result = sender
+            .send(("123456".to_string(), default_transact_request_data(chain_id)))
+            .await;
+        assert!(result.is_ok());
+        drop(sender);
+        consumer.consume().await;
+        consumer.drained.notified().await;
+    });
+}
+
+fn queued_transaction(id: &str) -> Document<Transaction> {
+    let mut transaction = default_transaction();
+    transaction.status = TransactStatus::Queued;
+    Document::new(id.to_string(), 1234567890u64, 1234567891u64, transaction)
+}
+
+fn claimed_transaction(id: &str) -> Document<Transaction> {
+    let mut transaction = default_transaction();
+    transaction.status = TransactStatus::Pending;
+    transaction.transaction_hash = None;
+    Document::new(id.to_string(), 1234567890u64, 1234567891u64, transaction)
+}
+
+// the consumer claims the queued job and reads it back right before sending
+fn expect_claim(transaction_handler: &mut MockTransactions) {
+    transaction_handler
+        .expect_update_by_id_if_status()
+        .withf(|_, expected, options| {
+            expected == [TransactStatus::Queued] && options.status == Some(TransactStatus::Pending)
+        })
+        .returning(|id, _, _| Ok(Some(claimed_transaction(id))));
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(claimed_transaction(id))));
+}
+
+fn expect_fail(transaction_handler: &mut MockTransactions) {
+    transaction_handler
+        .expect_update_by_id_if_status()
+        .withf(|_, expected, options| {
+            expected == [TransactStatus::Pending] && options.status == Some(TransactStatus::Failed)
+        })
+        .returning(|id, _, _| Ok(Some(claimed_transaction(id))));
+}
+
 #[derive(Debug, TypedBuilder)]
 struct MockOptions {
     chain_id: u64,
@@ -360,6 +723,7 @@ struct MockOptions {
     transaction_handler: MockTransactions,
     token_price: MockTokenPrice,
     tx_manager: MockTxManager,
+    quote_handler: MockQuotes,
 }
 
 fn setup(options: MockOptions) -> TransactionConsumer {
@@ -402,5 +766,36 @@ fn setup(options: MockOptions) -> TransactionConsumer {
         handler: Arc::new(Box::new(options.transaction_handler)),
         token_price: Arc::new(RwLock::new(Box::new(options.token_price))),
         tx_manager: Box::new(options.tx_manager),
+        drained: Arc::new(Notify::new()),
+        lease: LeaseState::always_held(),
+        quote_handler: Arc::new(Box::new(options.quote_handler)),
+        quote_risk_limit_percent: 20,
+        gas_price_cache: Arc::new(GasPriceCache::new(
+            Duration::from_secs(15),
+            Arc::new(Box::new(MockProviders::new())),
+        )),
     }
 }
+
+fn no_quotes() -> MockQuotes {
+    let mut quote_handler = MockQuotes::new();
+    quote_handler.expect_find().returning(|_| Ok(None));
+    quote_handler
+}
+
+fn accepted_quotes() -> MockQuotes {
+    let mut quote_handler = MockQuotes::new();
+    quote_handler.expect_find().returning(|signature| {
+        Ok(Some(Document::new(
+            "quote".to_string(),
+            1234567890u64,
+            1234567890u64,
+            AcceptedQuote {
+                signature: signature.to_string(),
+                quote_id: "a1".to_string(),
+                minimum_gas_fee: "1000".to_string(),
+            },
+        )))
+    });
+    quote_handler
+}
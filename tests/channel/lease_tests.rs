@@ -0,0 +1,198 @@
+use crate::channel::MockProducers;
+use crate::common::{create_default_context, default_transact_request_data};
+use crate::handler::MockLeases;
+use ethers_signers::LocalWallet;
+use mystiko_relayer::channel::lease::{recover_unsent_transactions, LeaseKeeper, LeaseState};
+use mystiko_relayer::channel::producer::ProducerHandler;
+use mystiko_relayer::channel::{contains_sender, read_senders, SenderInfo, SenderSet};
+use mystiko_relayer::configs::lease::LeaseConfig;
+use mystiko_relayer::context::Context;
+use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::handler::account::{AccountHandler, UpdateAccountOptions};
+use mystiko_relayer::handler::transaction::{TransactionHandler, UpdateTransactionOptions};
+use mystiko_relayer::handler::types::current_timestamp;
+use mystiko_relayer::service::admin::REQUEUE_STUCK_AFTER_MS;
+use mystiko_relayer::signer::AccountSigner;
+use mystiko_relayer_types::TransactStatus;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+// the signer of the testnet config, stored for chain id 5 and for chain id 97 by the tests
+const ADDRESS: &str = "0x4d870a75d6552a0199610a460a65116b552de0d9";
+
+#[test]
+fn test_lease_state() {
+    assert!(LeaseState::always_held().is_held());
+    let lease = LeaseState::not_held();
+    assert!(!lease.is_held());
+    // clones share the state, the sender and the consumer see the same lease
+    let shared = lease.clone();
+    lease.hold_until(u64::MAX);
+    assert!(shared.is_held());
+    lease.hold_until(1);
+    assert!(!shared.is_held());
+    lease.hold_until(u64::MAX);
+    shared.lose();
+    assert!(!lease.is_held());
+}
+
+#[actix_rt::test]
+async fn test_lease_keeper_heartbeat() {
+    let lease_0 = LeaseState::not_held();
+    let lease_1 = LeaseState::not_held();
+    let senders = Arc::new(SenderSet::new(HashSet::from([
+        sender_info(5, ADDRESS, lease_0.clone(), MockProducers::new()),
+        sender_info(97, ADDRESS, lease_1.clone(), MockProducers::new()),
+    ])));
+    let mut handler = MockLeases::new();
+    handler
+        .expect_try_acquire()
+        .withf(|_, _, owner, ttl_ms| owner == "relayer-a" && *ttl_ms == 30000)
+        .returning(|chain_id, _, _, _| match chain_id {
+            5 => Ok(true),
+            _ => Ok(false),
+        });
+    handler
+        .expect_release()
+        .withf(|_, _, owner| owner == "relayer-a")
+        .times(2)
+        .returning(|_, _, _| Ok(()));
+    let config = LeaseConfig::builder()
+        .enabled(true)
+        .instance_id("relayer-a".to_string())
+        .build();
+    let keeper = LeaseKeeper::new(create_context(handler).await, senders, &config);
+    assert_eq!(keeper.owner(), "relayer-a");
+
+    keeper.heartbeat().await;
+    assert!(lease_0.is_held());
+    assert!(!lease_1.is_held());
+
+    keeper.release_all().await;
+    assert!(!lease_0.is_held());
+}
+
+#[actix_rt::test]
+async fn test_lease_keeper_keeps_lease_on_error() {
+    let lease = LeaseState::not_held();
+    lease.hold_until(u64::MAX);
+    let senders = Arc::new(SenderSet::new(HashSet::from([sender_info(
+        5,
+        ADDRESS,
+        lease.clone(),
+        MockProducers::new(),
+    )])));
+    let mut handler = MockLeases::new();
+    handler
+        .expect_try_acquire()
+        .returning(|_, _, _, _| Err(RelayerServerError::QueueSendError("database is down".to_string())));
+    let keeper = LeaseKeeper::new(create_context(handler).await, senders, &LeaseConfig::default());
+    assert!(keeper.owner().starts_with("relayer-"));
+
+    // a failed renewal lets the lease lapse on its own expiry
+    keeper.heartbeat().await;
+    assert!(lease.is_held());
+}
+
+#[actix_rt::test]
+async fn test_lease_keeper_reload_accounts() {
+    let lease = LeaseState::not_held();
+    let senders = Arc::new(SenderSet::new(HashSet::from([
+        sender_info(5, ADDRESS, lease.clone(), MockProducers::new()),
+        sender_info(97, ADDRESS, lease.clone(), MockProducers::new()),
+    ])));
+    let mut handler = MockLeases::new();
+    handler.expect_try_acquire().returning(|_, _, _, _| Ok(false));
+    let context = create_context(handler).await;
+    let keeper = LeaseKeeper::new(context.clone(), senders.clone(), &LeaseConfig::default());
+
+    // changed through the admin api of another instance
+    let options = UpdateAccountOptions::builder()
+        .available(false)
+        .supported_erc20_tokens(vec!["USDT".to_string()])
+        .build();
+    context
+        .account_handler
+        .update_by_address(5, ADDRESS, &options)
+        .await
+        .unwrap();
+    context.account_handler.delete_by_address(97, ADDRESS).await.unwrap();
+
+    keeper.heartbeat().await;
+    let sender = read_senders(&senders)
+        .iter()
+        .find(|sender| sender.chain_id == 5)
+        .map(|sender| (sender.available, sender.supported_erc20_tokens.clone()))
+        .unwrap();
+    assert_eq!(sender, (false, vec!["usdt".to_string()]));
+    // retired on the other instance
+    assert!(!contains_sender(&senders, 97, ADDRESS));
+}
+
+#[actix_rt::test]
+async fn test_recover_unsent_transactions() {
+    let context = create_context(MockLeases::new()).await;
+    let handler = &context.transaction_handler;
+    let queued = handler
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+    // a recent claim is still sent by its consumer
+    let claimed = handler
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+    handler
+        .update_by_id(
+            &claimed.id,
+            &UpdateTransactionOptions::builder()
+                .status(TransactStatus::Pending)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let queued_id = queued.id.clone();
+    let mut producer = MockProducers::new();
+    producer
+        .expect_requeue()
+        .withf(move |id, data| id == queued_id && data.chain_id == 5)
+        .times(1)
+        .returning(|_, _| Ok(()));
+    let lease = LeaseState::not_held();
+    lease.hold_until(u64::MAX);
+    let senders = Arc::new(SenderSet::new(HashSet::from([sender_info(
+        5, ADDRESS, lease, producer,
+    )])));
+
+    // the lease was just taken over, every queued job is taken
+    recover_unsent_transactions(context.clone(), senders.clone(), 5, current_timestamp()).await;
+    let recovered = handler.find_by_id(&queued.id).await.unwrap().unwrap();
+    assert_eq!(recovered.data.status, TransactStatus::Queued);
+
+    // the sweep of a later heartbeat only takes jobs left alone for a while
+    let queued_before = current_timestamp().saturating_sub(REQUEUE_STUCK_AFTER_MS);
+    recover_unsent_transactions(context, senders, 5, queued_before).await;
+}
+
+async fn create_context(handler: MockLeases) -> Arc<Context> {
+    let mut context = create_default_context().await;
+    let mut account = context.server_config.load().accounts.values().next().cloned().unwrap();
+    account.chain_id = 97;
+    context.account_handler.create(&account).await.unwrap();
+    context.lease_handler = Arc::new(Box::new(handler));
+    Arc::new(context)
+}
+
+fn sender_info(chain_id: u64, address: &str, lease: LeaseState, producer: MockProducers) -> SenderInfo {
+    SenderInfo {
+        chain_id,
+        address: address.to_string(),
+        signer: AccountSigner::Local(LocalWallet::new(&mut rand::thread_rng())),
+        available: true,
+        supported_erc20_tokens: vec![],
+        producer: Arc::new(Box::new(producer) as Box<dyn ProducerHandler<Error = RelayerServerError>>),
+        drained: Arc::new(Notify::new()),
+        lease,
+    }
+}
@@ -1,17 +1,23 @@
 use async_trait::async_trait;
+use ethers_signers::LocalWallet;
 use mockall::mock;
 use mystiko_relayer::channel::consumer::ConsumerHandler;
+use mystiko_relayer::channel::lease::LeaseState;
 use mystiko_relayer::channel::producer::ProducerHandler;
 use mystiko_relayer::channel::SenderInfo;
 use mystiko_relayer::database::transaction::Transaction;
 use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::signer::AccountSigner;
 use mystiko_relayer_types::TransactRequestData;
 use mystiko_storage::Document;
 use std::sync::Arc;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::Notify;
 
 mod consumer_tests;
+mod lease_tests;
 mod producer_tests;
+mod webhook_tests;
 
 struct MockSenderAndReceiver {
     sender: Sender<(String, TransactRequestData)>,
@@ -28,21 +34,33 @@ fn create_default_sender_and_receiver() -> MockSenderAndReceiver {
 fn test_compare_sender_info() {
     let sender_0 = SenderInfo {
         chain_id: 1,
-        private_key: "0x00000".to_string(),
+        address: "0x00000".to_string(),
+        signer: AccountSigner::Local(LocalWallet::new(&mut rand::thread_rng())),
+        available: true,
         supported_erc20_tokens: vec![],
         producer: Arc::new(MockProducers::new()),
+        drained: Arc::new(Notify::new()),
+        lease: LeaseState::always_held(),
     };
     let sender_1 = SenderInfo {
         chain_id: 1,
-        private_key: "0x00000".to_string(),
+        address: "0x00000".to_string(),
+        signer: AccountSigner::Local(LocalWallet::new(&mut rand::thread_rng())),
+        available: true,
         supported_erc20_tokens: vec![],
         producer: Arc::new(MockProducers::new()),
+        drained: Arc::new(Notify::new()),
+        lease: LeaseState::always_held(),
     };
     let sender_2 = SenderInfo {
         chain_id: 2,
-        private_key: "0x00000".to_string(),
+        address: "0x00000".to_string(),
+        signer: AccountSigner::Local(LocalWallet::new(&mut rand::thread_rng())),
+        available: true,
         supported_erc20_tokens: vec![],
         producer: Arc::new(MockProducers::new()),
+        drained: Arc::new(Notify::new()),
+        lease: LeaseState::always_held(),
     };
     assert!(sender_0.eq(&sender_1));
     assert!(sender_0.ne(&sender_2));
@@ -55,6 +73,7 @@ mock! {
     impl ProducerHandler for Producers {
         type Error = RelayerServerError;
         async fn send(&self, data: TransactRequestData) -> Result<Document<Transaction>, RelayerServerError>;
+        async fn requeue(&self, id: &str, data: TransactRequestData) -> Result<(), RelayerServerError>;
     }
 }
 
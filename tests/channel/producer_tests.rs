@@ -118,6 +118,28 @@ async fn test_producer_send_failed() {
     assert!(result.is_err());
 }
 
+#[actix_rt::test]
+async fn test_producer_requeue() {
+    let mock = create_default_sender_and_receiver();
+    let mut receiver = mock.receiver;
+    let options = MockOptions::builder()
+        .sender(mock.sender)
+        .handler(MockTransactions::new())
+        .build();
+
+    let producer = setup(options).await;
+    let result = producer.requeue("123456", default_transact_request_data(5)).await;
+    assert!(result.is_ok());
+    let (id, data) = receiver.recv().await.unwrap();
+    assert_eq!(id, "123456");
+    assert_eq!(data.chain_id, 5);
+
+    // closed queue
+    drop(receiver);
+    let result = producer.requeue("123456", default_transact_request_data(5)).await;
+    assert!(result.is_err());
+}
+
 #[derive(Debug, TypedBuilder)]
 struct MockOptions {
     sender: Sender<(String, TransactRequestData)>,
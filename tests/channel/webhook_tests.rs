@@ -0,0 +1,433 @@
+use crate::common::{create_default_database_in_memory, default_transaction};
+use crate::handler::MockTransactions;
+use mockito::{Matcher, Server};
+use mystiko_protos::core::v1::SpendType;
+use mystiko_relayer::channel::webhook::{
+    is_public_address, resolve_callback_url, sign_webhook, WebhookHandlerRef, WebhookNotifier, WebhookPayload,
+    WEBHOOK_EVENT_TRANSACTION_STATUS, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+use mystiko_relayer::configs::webhook::WebhookConfig;
+use mystiko_relayer::database::transaction::Transaction;
+use mystiko_relayer::database::webhook::WebhookDelivery;
+use mystiko_relayer::handler::transaction::status::{TransactionStatusBus, TransactionStatusEvent};
+use mystiko_relayer::handler::webhook::{Webhook, WebhookHandler};
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::Document;
+use serde_json::json;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SECRET: &str = "webhook-secret";
+
+#[test]
+fn test_sign_webhook() {
+    let signature = sign_webhook(SECRET, 1700000000, b"{}");
+    assert_eq!(signature.len(), 64);
+    assert_eq!(signature, sign_webhook(SECRET, 1700000000, b"{}"));
+    assert_ne!(signature, sign_webhook(SECRET, 1700000001, b"{}"));
+    assert_ne!(signature, sign_webhook("other-secret", 1700000000, b"{}"));
+    assert_ne!(signature, sign_webhook(SECRET, 1700000000, b"{ }"));
+}
+
+#[test]
+fn test_is_public_address() {
+    for address in ["8.8.8.8", "1.1.1.1", "2606:4700:4700::1111"] {
+        assert!(is_public_address(&address.parse::<IpAddr>().unwrap()), "{}", address);
+    }
+    for address in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "255.255.255.255",
+        "::1",
+        "::",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(!is_public_address(&address.parse::<IpAddr>().unwrap()), "{}", address);
+    }
+}
+
+#[actix_rt::test]
+async fn test_resolve_callback_url() {
+    let mut config = webhook_config(None, 1);
+    config.callback_allowed_hosts = vec![];
+    let target = resolve_callback_url(&config, "https://8.8.8.8/hooks").await.unwrap();
+    assert_eq!(target.host, "8.8.8.8");
+    assert_eq!(target.addresses, vec!["8.8.8.8:443".parse().unwrap()]);
+    assert!(resolve_callback_url(&config, "http://127.0.0.1:8080/hooks")
+        .await
+        .is_err());
+    assert!(resolve_callback_url(&config, "http://localhost:8080/hooks")
+        .await
+        .is_err());
+    assert!(resolve_callback_url(&config, "http://[fe80::1]/hooks").await.is_err());
+
+    // allowlisted hosts may be private, every other host is rejected
+    config.callback_allowed_hosts = vec!["127.0.0.1".to_string()];
+    let target = resolve_callback_url(&config, "http://127.0.0.1:8080/hooks")
+        .await
+        .unwrap();
+    assert!(target.addresses.is_empty());
+    assert!(resolve_callback_url(&config, "https://8.8.8.8/hooks").await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_deliver_to_global_and_callback_url() {
+    let mut server = Server::new_async().await;
+    let global = server
+        .mock("POST", "/hooks")
+        .match_header("content-type", "application/json")
+        .match_body(Matcher::PartialJson(json!({
+            "event": WEBHOOK_EVENT_TRANSACTION_STATUS,
+            "transaction": {"uuid": "1", "status": TransactStatus::Succeeded, "transactionHash": "0x123"},
+        })))
+        .match_request(verify_signature)
+        .with_status(200)
+        .expect(1)
+        .create_async()
+        .await;
+    let callback = server
+        .mock("POST", "/callback")
+        .match_request(verify_signature)
+        .with_status(204)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let webhook_handler = webhook_handler().await;
+    let callback_url = format!("{}/callback", server.url());
+    webhook_handler.save_callback("0x01", &callback_url).await.unwrap();
+    let config = webhook_config(Some(format!("{}/hooks", server.url())), 3);
+    let notifier = Arc::new(
+        WebhookNotifier::new(config, Arc::new(Box::new(mock_transactions())), webhook_handler.clone()).unwrap(),
+    );
+    let status_bus = TransactionStatusBus::default();
+    tokio::spawn(notifier.clone().run(status_bus.subscribe_all()));
+    notifier
+        .enqueue(&status_event("1", TransactStatus::Succeeded, Some("0x123")))
+        .await;
+
+    let deliveries = wait_for_deliveries(&webhook_handler, "1", 2).await;
+    global.assert_async().await;
+    callback.assert_async().await;
+    assert!(deliveries
+        .iter()
+        .all(|delivery| delivery.data.delivered && delivery.data.attempt == 1));
+    assert_eq!(deliveries[0].data.url, format!("{}/hooks", server.url()));
+    assert_eq!(deliveries[0].data.response_status, Some(200));
+    assert_eq!(deliveries[1].data.url, callback_url);
+    assert_eq!(deliveries[1].data.response_status, Some(204));
+    assert_eq!(json!(deliveries[1].data.status), json!(TransactStatus::Succeeded));
+}
+
+#[actix_rt::test]
+async fn test_retry_with_backoff() {
+    let mut server = Server::new_async().await;
+    let hooks = server
+        .mock("POST", "/hooks")
+        .with_status(500)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let webhook_handler = webhook_handler().await;
+    let notifier = global_notifier(Some(format!("{}/hooks", server.url())), 3, &webhook_handler);
+    let status_bus = TransactionStatusBus::default();
+    let started_at = std::time::Instant::now();
+    tokio::spawn(notifier.clone().run(status_bus.subscribe_all()));
+    notifier
+        .enqueue(&status_event("1", TransactStatus::Pending, None))
+        .await;
+
+    let deliveries = wait_for_deliveries(&webhook_handler, "1", 1).await;
+    // 10 ms and 20 ms between the three attempts
+    assert!(started_at.elapsed() >= Duration::from_millis(30));
+    hooks.assert_async().await;
+    let delivery = &deliveries[0].data;
+    assert_eq!(delivery.attempt, 3);
+    assert!(!delivery.delivered);
+    assert_eq!(delivery.response_status, Some(500));
+    assert_eq!(json!(delivery.status), json!(TransactStatus::Pending));
+}
+
+#[actix_rt::test]
+async fn test_unreachable_url() {
+    let webhook_handler = webhook_handler().await;
+    let notifier = global_notifier(Some("http://127.0.0.1:1/hooks".to_string()), 2, &webhook_handler);
+    let status_bus = TransactionStatusBus::default();
+    tokio::spawn(notifier.clone().run(status_bus.subscribe_all()));
+    notifier.enqueue(&status_event("1", TransactStatus::Failed, None)).await;
+
+    let deliveries = wait_for_deliveries(&webhook_handler, "1", 1).await;
+    let delivery = &deliveries[0].data;
+    assert_eq!(delivery.attempt, 2);
+    assert!(delivery.response_status.is_none() && delivery.error_message.is_some());
+}
+
+#[actix_rt::test]
+async fn test_deliver_in_order() {
+    let mut server = Server::new_async().await;
+    let pending = server
+        .mock("POST", "/hooks")
+        .match_body(Matcher::PartialJson(
+            json!({"transaction": {"status": TransactStatus::Pending}}),
+        ))
+        .with_status(500)
+        .expect(2)
+        .create_async()
+        .await;
+    let succeeded = server
+        .mock("POST", "/hooks")
+        .match_body(Matcher::PartialJson(
+            json!({"transaction": {"status": TransactStatus::Succeeded}}),
+        ))
+        .with_status(200)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let webhook_handler = webhook_handler().await;
+    let notifier = global_notifier(Some(format!("{}/hooks", server.url())), 2, &webhook_handler);
+    notifier
+        .enqueue(&status_event("1", TransactStatus::Pending, None))
+        .await;
+    notifier
+        .enqueue(&status_event("1", TransactStatus::Succeeded, Some("0x123")))
+        .await;
+
+    // only the oldest change of a transaction is attempted, the later one waits for it
+    notifier.dispatch().await;
+    let deliveries = webhook_handler.find_deliveries("1").await.unwrap();
+    assert_eq!(deliveries.len(), 2);
+    assert_eq!(json!(deliveries[1].data.status), json!(TransactStatus::Succeeded));
+    assert_eq!(deliveries[1].data.attempt, 0);
+
+    let status_bus = TransactionStatusBus::default();
+    tokio::spawn(notifier.clone().run(status_bus.subscribe_all()));
+    let deliveries = wait_for_deliveries(&webhook_handler, "1", 2).await;
+    pending.assert_async().await;
+    succeeded.assert_async().await;
+    let (pending, succeeded) = (&deliveries[0], &deliveries[1]);
+    assert!(!pending.data.delivered && pending.data.attempt == 2);
+    assert!(succeeded.data.delivered && succeeded.data.attempt == 1);
+    assert!(succeeded.updated_at >= pending.updated_at);
+}
+
+#[actix_rt::test]
+async fn test_resume_pending_deliveries() {
+    let mut server = Server::new_async().await;
+    let hooks = server
+        .mock("POST", "/hooks")
+        .match_request(verify_signature)
+        .with_status(200)
+        .expect(1)
+        .create_async()
+        .await;
+
+    // enqueued before a restart, never dispatched
+    let webhook_handler = webhook_handler().await;
+    let url = format!("{}/hooks", server.url());
+    global_notifier(Some(url.clone()), 3, &webhook_handler)
+        .enqueue(&status_event("1", TransactStatus::Pending, None))
+        .await;
+
+    let notifier = global_notifier(Some(url), 3, &webhook_handler);
+    let status_bus = TransactionStatusBus::default();
+    tokio::spawn(notifier.run(status_bus.subscribe_all()));
+    let deliveries = wait_for_deliveries(&webhook_handler, "1", 1).await;
+    hooks.assert_async().await;
+    assert!(deliveries[0].data.delivered);
+}
+
+#[actix_rt::test]
+async fn test_run_on_status_bus() {
+    let mut server = Server::new_async().await;
+    let hooks = server
+        .mock("POST", "/hooks")
+        .match_request(verify_signature)
+        .with_status(200)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let webhook_handler = webhook_handler().await;
+    let notifier = global_notifier(Some(format!("{}/hooks", server.url())), 1, &webhook_handler);
+    let status_bus = TransactionStatusBus::default();
+    tokio::spawn(notifier.run(status_bus.subscribe_all()));
+
+    // every transaction is delivered, not only watched ones, a repeated change only once
+    status_bus.publish(status_event("1", TransactStatus::Pending, None));
+    status_bus.publish(status_event("1", TransactStatus::Pending, None));
+    status_bus.publish(status_event("2", TransactStatus::Queued, None));
+    wait_for_deliveries(&webhook_handler, "1", 1).await;
+    wait_for_deliveries(&webhook_handler, "2", 1).await;
+    hooks.assert_async().await;
+}
+
+#[actix_rt::test]
+async fn test_enqueue_recent_after_lag() {
+    let mut server = Server::new_async().await;
+    let hooks = server
+        .mock("POST", "/hooks")
+        .with_status(200)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let events = vec![
+        status_event("1", TransactStatus::Pending, None),
+        status_event("2", TransactStatus::Succeeded, Some("0x123")),
+        status_event("3", TransactStatus::Failed, None),
+    ];
+    let mut transaction_handler = MockTransactions::new();
+    let updated = events.clone();
+    transaction_handler
+        .expect_find()
+        .withf(|options| options.updated_after.is_some())
+        .times(1)
+        .returning(move |_| Ok(updated.iter().map(transaction_document).collect()));
+    let webhook_handler = webhook_handler().await;
+    let mut config = webhook_config(Some(format!("{}/hooks", server.url())), 1);
+    config.allow_callback_url = false;
+    let notifier = Arc::new(
+        WebhookNotifier::new(config, Arc::new(Box::new(transaction_handler)), webhook_handler.clone()).unwrap(),
+    );
+
+    // the oldest changes are dropped before the notifier reads them
+    let status_bus = TransactionStatusBus::new(1);
+    let subscription = status_bus.subscribe_all();
+    for event in events {
+        status_bus.publish(event);
+    }
+    tokio::spawn(notifier.run(subscription));
+
+    for id in ["1", "2", "3"] {
+        wait_for_deliveries(&webhook_handler, id, 1).await;
+    }
+    hooks.assert_async().await;
+}
+
+fn verify_signature(request: &mockito::Request) -> bool {
+    let header = |name: &str| {
+        request
+            .header(name)
+            .first()
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let (Some(timestamp), Some(signature)) = (header(WEBHOOK_TIMESTAMP_HEADER), header(WEBHOOK_SIGNATURE_HEADER))
+    else {
+        return false;
+    };
+    let Ok(body) = request.body() else {
+        return false;
+    };
+    let Ok(payload) = serde_json::from_slice::<WebhookPayload>(body) else {
+        return false;
+    };
+    payload.timestamp.to_string() == timestamp
+        && signature == format!("sha256={}", sign_webhook(SECRET, payload.timestamp, body))
+}
+
+fn webhook_config(url: Option<String>, max_attempts: u32) -> WebhookConfig {
+    WebhookConfig::builder()
+        .enabled(true)
+        .url(url)
+        .secret(SECRET.to_string())
+        .allow_callback_url(true)
+        .callback_allowed_hosts(vec!["127.0.0.1".to_string()])
+        .max_attempts(max_attempts)
+        .initial_backoff_ms(10u64)
+        .max_backoff_ms(100u64)
+        .timeout_ms(1000u64)
+        .build()
+}
+
+fn status_event(id: &str, status: TransactStatus, transaction_hash: Option<&str>) -> TransactionStatusEvent {
+    TransactionStatusEvent {
+        id: id.to_string(),
+        chain_id: 5,
+        spend_type: SpendType::Withdraw,
+        status,
+        transaction_hash: transaction_hash.map(|hash| hash.to_string()),
+        error_message: None,
+    }
+}
+
+fn mock_transactions() -> MockTransactions {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .withf(|id| id == "1")
+        .returning(|id| {
+            let mut transaction = default_transaction();
+            transaction.signature = "0x01".to_string();
+            Ok(Some(Document::new(
+                id.to_string(),
+                1234567890u64,
+                1234567890u64,
+                transaction,
+            )))
+        });
+    transaction_handler
+}
+
+fn transaction_document(event: &TransactionStatusEvent) -> Document<Transaction> {
+    let mut transaction = default_transaction();
+    transaction.chain_id = event.chain_id;
+    transaction.spend_type = event.spend_type;
+    transaction.status = event.status.clone();
+    transaction.transaction_hash = event.transaction_hash.clone();
+    transaction.error_message = event.error_message.clone();
+    Document::new(event.id.clone(), 1234567890u64, 1234567890u64, transaction)
+}
+
+async fn webhook_handler() -> WebhookHandlerRef {
+    let db = Arc::new(create_default_database_in_memory().await);
+    Arc::new(Box::new(Webhook::new(db)))
+}
+
+// posts to the global url only
+fn global_notifier(
+    url: Option<String>,
+    max_attempts: u32,
+    webhook_handler: &WebhookHandlerRef,
+) -> Arc<WebhookNotifier> {
+    let mut config = webhook_config(url, max_attempts);
+    config.allow_callback_url = false;
+    Arc::new(
+        WebhookNotifier::new(
+            config,
+            Arc::new(Box::new(MockTransactions::new())),
+            webhook_handler.clone(),
+        )
+        .unwrap(),
+    )
+}
+
+// waits until the transaction has this many deliveries and none of them is pending
+async fn wait_for_deliveries(
+    webhook_handler: &WebhookHandlerRef,
+    transaction_id: &str,
+    count: usize,
+) -> Vec<Document<WebhookDelivery>> {
+    for _ in 0..100 {
+        let deliveries = webhook_handler.find_deliveries(transaction_id).await.unwrap();
+        if deliveries.len() == count
+            && deliveries
+                .iter()
+                .all(|delivery| delivery.data.next_attempt_at.is_none())
+        {
+            return deliveries;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("deliveries of transaction {} are not finished", transaction_id);
+}
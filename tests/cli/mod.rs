@@ -0,0 +1,226 @@
+use crate::common::{create_default_server_config, default_transact_request_data};
+use clap::Parser;
+use mockito::{Matcher, Server};
+use mystiko_relayer::cli::{
+    check_config, list_transactions, migrate_database, requeue_transaction, show_transaction, Cli, Command,
+    ConfigCommand, TxCommand, TxListArgs, TxRequeueArgs, DEFAULT_SERVER_CONFIG_PATH,
+};
+use mystiko_relayer::configs::auth::{ApiKeyConfig, ApiKeyScope};
+use mystiko_relayer::configs::server::REDACTED;
+use mystiko_relayer::database::init_sqlite_database;
+use mystiko_relayer::handler::transaction::{Transaction, TransactionHandler};
+use mystiko_relayer::service::auth::{
+    sign_request, API_KEY_HEADER, API_KEY_ID_HEADER, API_NONCE_HEADER, API_SIGNATURE_HEADER, API_TIMESTAMP_HEADER,
+};
+use mystiko_relayer_types::TransactStatus;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_commands() {
+    let cli = Cli::try_parse_from(["mystiko_relayer"]).unwrap();
+    assert_eq!(cli.config, DEFAULT_SERVER_CONFIG_PATH);
+    assert!(cli.command.is_none());
+
+    let cli = Cli::try_parse_from(["mystiko_relayer", "config", "check", "--config", "relayer.toml"]).unwrap();
+    assert_eq!(cli.config, "relayer.toml");
+    assert!(matches!(cli.command, Some(Command::Config(ConfigCommand::Check))));
+
+    let status = serde_json::to_value(TransactStatus::Failed).unwrap();
+    let cli = Cli::try_parse_from([
+        "mystiko_relayer",
+        "tx",
+        "list",
+        "--chain-id",
+        "5",
+        "--status",
+        status.as_str().unwrap(),
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::Tx(TxCommand::List(args))) => {
+            assert_eq!(args.chain_id, Some(5));
+            assert_eq!(args.status, Some(TransactStatus::Failed));
+            assert_eq!(args.offset, 0);
+        }
+        command => panic!("unexpected command {:?}", command),
+    }
+
+    // the config path as the only argument still starts the server
+    let cli = Cli::try_parse_from(["mystiko_relayer", "./relayer.toml"]).unwrap();
+    assert_eq!(cli.config_path(), "./relayer.toml");
+    assert!(cli.command.is_none());
+    assert!(Cli::try_parse_from(["mystiko_relayer", "./relayer.toml", "--config", "other.toml"]).is_err());
+
+    assert!(Cli::try_parse_from(["mystiko_relayer", "tx", "requeue", "some-id"]).is_err());
+    assert!(Cli::try_parse_from(["mystiko_relayer", "tx", "list", "--status", "unknown"]).is_err());
+}
+
+#[actix_rt::test]
+async fn test_check_config() {
+    let server_config = create_default_server_config(true).await;
+    let output = check_config(Arc::new(server_config)).await.unwrap();
+    assert_eq!(output["accounts"]["0"]["private_key"], json!(REDACTED));
+    assert_eq!(output["admin"]["tokens"]["operator"], json!(REDACTED));
+    assert_eq!(output["settings"]["coin_market_cap_api_key"], json!(REDACTED));
+    assert_eq!(output["settings"]["port"], json!(8081));
+
+    let mut server_config = create_default_server_config(true).await;
+    server_config.accounts.get_mut(&0).unwrap().chain_id = 51111;
+    let error = check_config(Arc::new(server_config)).await.unwrap_err();
+    assert!(error.to_string().contains("chain id 51111 not found in relayer config"));
+}
+
+#[actix_rt::test]
+async fn test_transactions_from_database() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("relayer.sqlite").to_str().unwrap().to_string();
+    let mut server_config = create_default_server_config(true).await;
+    let args = TxListArgs {
+        chain_id: None,
+        status: None,
+        offset: 0,
+        limit: 10,
+    };
+    assert!(list_transactions(&server_config, &args).await.is_err());
+
+    server_config.settings.sqlite_db_path = Some(db_path.clone());
+    assert!(list_transactions(&server_config, &args).await.is_err());
+
+    // reading does not migrate an existing database
+    std::fs::write(&db_path, b"").unwrap();
+    assert!(list_transactions(&server_config, &args).await.is_err());
+    assert_eq!(std::fs::metadata(&db_path).unwrap().len(), 0);
+    migrate_database(&server_config).await.unwrap();
+    assert_eq!(list_transactions(&server_config, &args).await.unwrap().total, 0);
+
+    let database = init_sqlite_database(Some(db_path)).await.unwrap();
+    let transaction = Transaction::new(Arc::new(database))
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+
+    let list = list_transactions(&server_config, &args).await.unwrap();
+    assert_eq!(list.total, 1);
+    assert_eq!(list.transactions[0].id, transaction.id);
+    let filtered = TxListArgs {
+        chain_id: Some(97),
+        ..args
+    };
+    assert_eq!(list_transactions(&server_config, &filtered).await.unwrap().total, 0);
+
+    let shown = show_transaction(&server_config, &transaction.id).await.unwrap();
+    assert_eq!(shown.status, TransactStatus::Queued);
+    assert!(show_transaction(&server_config, "unknown").await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_requeue_transaction() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/admin/transactions/tx-id/requeue")
+        .match_header("authorization", "Bearer admin-token")
+        .match_body(Matcher::Json(json!({"reason": "rpc outage"})))
+        .with_body(json!({"code": 0, "data": {"transaction": {"id": "tx-id"}}}).to_string())
+        .create_async()
+        .await;
+    let mut server_config = create_default_server_config(true).await;
+    let args = TxRequeueArgs {
+        id: "tx-id".to_string(),
+        reason: "rpc outage".to_string(),
+        operator: None,
+        url: Some(server.url()),
+        client_cert: None,
+        client_key: None,
+        ca_cert: None,
+    };
+    let output = requeue_transaction(&server_config, &args).await.unwrap();
+    assert_eq!(output["data"]["transaction"]["id"], json!("tx-id"));
+    mock.assert_async().await;
+
+    let unknown_operator = TxRequeueArgs {
+        operator: Some("unknown".to_string()),
+        ..args
+    };
+    assert!(requeue_transaction(&server_config, &unknown_operator).await.is_err());
+    server_config.admin.enabled = false;
+    assert!(requeue_transaction(&server_config, &unknown_operator).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_requeue_transaction_signed() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/admin/transactions/tx-id/requeue")
+        .match_header("authorization", "Bearer admin-token")
+        .match_header(API_KEY_ID_HEADER, "ops")
+        .match_header(API_KEY_HEADER, Matcher::Missing)
+        .match_request(|request| {
+            let header = |name: &str| {
+                request
+                    .header(name)
+                    .first()
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let Ok(timestamp) = header(API_TIMESTAMP_HEADER).parse::<u64>() else {
+                return false;
+            };
+            let body = request.body().cloned().unwrap_or_default();
+            let signature = sign_request(
+                "ops-key-0123456789abcdef",
+                timestamp,
+                &header(API_NONCE_HEADER),
+                "POST",
+                "/api/admin/transactions/tx-id/requeue",
+                &body,
+            );
+            header(API_SIGNATURE_HEADER) == signature
+        })
+        .with_body(json!({"code": 0, "data": {"transaction": {"id": "tx-id"}}}).to_string())
+        .create_async()
+        .await;
+    let mut server_config = create_default_server_config(true).await;
+    server_config.auth.enabled = true;
+    server_config.auth.keys = HashMap::from([(
+        "ops".to_string(),
+        ApiKeyConfig::builder()
+            .key("ops-key-0123456789abcdef")
+            .scope(ApiKeyScope::Admin)
+            .build(),
+    )]);
+    let args = TxRequeueArgs {
+        id: "tx-id".to_string(),
+        reason: "rpc outage".to_string(),
+        operator: None,
+        url: Some(server.url()),
+        client_cert: None,
+        client_key: None,
+        ca_cert: None,
+    };
+    let output = requeue_transaction(&server_config, &args).await.unwrap();
+    assert_eq!(output["data"]["transaction"]["id"], json!("tx-id"));
+    mock.assert_async().await;
+
+    // a client certificate needs its key, unreadable files are reported
+    assert!(Cli::try_parse_from([
+        "mystiko_relayer",
+        "tx",
+        "requeue",
+        "tx-id",
+        "--reason",
+        "rpc outage",
+        "--client-cert",
+        "client.pem",
+    ])
+    .is_err());
+    let args = TxRequeueArgs {
+        client_cert: Some("./tests/files/missing/client.pem".to_string()),
+        client_key: Some("./tests/files/missing/client.key".to_string()),
+        ..args
+    };
+    assert!(requeue_transaction(&server_config, &args).await.is_err());
+}
@@ -0,0 +1,2 @@
+mod cli;
+mod common;
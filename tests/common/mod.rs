@@ -31,6 +31,8 @@ pub const SERVER_CONFIG_MAINNET: &str = "tests/files/configs/config_mainnet.toml
 #[allow(dead_code)]
 pub const RELAYER_CONFIG_PATH: &str = "tests/files/relayer_config.json";
 #[allow(dead_code)]
+pub const MYSTIKO_CONFIG_PATH: &str = "tests/files/mystiko_config.json";
+#[allow(dead_code)]
 pub const SERVER_CONFIG_INVALID_ID: &str = "tests/files/configs/config_invalid_id.toml";
 #[allow(dead_code)]
 pub const SERVER_CONFIG_INVALID_SYMBOL: &str = "tests/files/configs/config_invalid_symbol.toml";
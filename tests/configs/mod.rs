@@ -1,11 +1,31 @@
 use crate::common::{
-    create_default_server_config, RELAYER_CONFIG_PATH, SERVER_CONFIG_INVALID_ID, SERVER_CONFIG_INVALID_SYMBOL,
-    SERVER_CONFIG_INVALID_VERSION,
+    create_default_context, create_default_database_in_memory, create_default_server_config, MYSTIKO_CONFIG_PATH,
+    RELAYER_CONFIG_PATH, SERVER_CONFIG_INVALID_ID, SERVER_CONFIG_INVALID_SYMBOL, SERVER_CONFIG_INVALID_VERSION,
+    SERVER_CONFIG_TESTNET,
 };
-use mystiko_relayer::configs::account::AccountConfig;
+use ethers_signers::{LocalWallet, Signer};
+use mystiko_config::MystikoConfig;
+use mystiko_relayer::channel::SenderSet;
+use mystiko_relayer::configs::account::{AccountConfig, RemoteSignerConfig};
+use mystiko_relayer::configs::chain::ChainConfig;
+use mystiko_relayer::configs::cors::{origin_matches, CorsConfig};
+use mystiko_relayer::configs::lease::LeaseConfig;
 use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::price_cache::PriceCacheConfig;
+use mystiko_relayer::configs::quote::QuoteConfig;
+use mystiko_relayer::configs::reload::{restart_required_changes, ConfigReloader};
+use mystiko_relayer::configs::remote::{RemoteConfigRefresher, SharedConfig};
 use mystiko_relayer::configs::server::Settings;
+use mystiko_relayer::configs::validation::{validate_sqlite_db_path, ConfigError};
+use mystiko_relayer::configs::webhook::{validate_webhook_url, WebhookConfig};
+use mystiko_relayer::context::Context;
+use mystiko_relayer::logger::init_logger;
 use mystiko_relayer_config::wrapper::relayer::RelayerConfig;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::tempdir;
+use validator::Validate;
 
 #[actix_rt::test]
 async fn test_find_accounts_success() {
@@ -32,15 +52,21 @@ async fn test_find_account_none() {
 async fn test_invalid_0() {
     let server_config = load_server_config(Some(SERVER_CONFIG_INVALID_ID));
     assert!(server_config.is_ok());
-    let relayer_config = RelayerConfig::from_json_file(RELAYER_CONFIG_PATH).await;
-    assert!(relayer_config.is_ok());
-    let server_config = server_config.unwrap();
-    let relayer_config = relayer_config.unwrap();
-    let validate = server_config.validation(&relayer_config);
+    let (relayer_config, mystiko_config) = load_test_configs().await;
+    let validate = server_config.unwrap().validation(&relayer_config, &mystiko_config);
     assert!(validate.is_err());
     assert_eq!(
-        validate.unwrap_err().to_string().as_str(),
-        "chain id 51111 not found in relayer config"
+        validate.unwrap_err().errors(),
+        &[
+            ConfigError::ChainNotInRelayerConfig {
+                account: 2,
+                chain_id: 51111
+            },
+            ConfigError::ChainNotInMystikoConfig {
+                account: 2,
+                chain_id: 51111
+            },
+        ]
     );
 }
 
@@ -48,30 +74,156 @@ async fn test_invalid_0() {
 async fn test_invalid_1() {
     let server_config = load_server_config(Some(SERVER_CONFIG_INVALID_SYMBOL));
     assert!(server_config.is_ok());
-    let relayer_config = RelayerConfig::from_json_file(RELAYER_CONFIG_PATH).await;
-    assert!(relayer_config.is_ok());
-    let server_config = server_config.unwrap();
-    let relayer_config = relayer_config.unwrap();
-    let validate = server_config.validation(&relayer_config);
+    let (relayer_config, mystiko_config) = load_test_configs().await;
+    let validate = server_config.unwrap().validation(&relayer_config, &mystiko_config);
     assert!(validate.is_err());
+    let error = validate.unwrap_err();
     assert_eq!(
-        validate.unwrap_err().to_string().as_str(),
-        "chain_id 5 token TEST not found in relayer chain config"
+        error.errors(),
+        &[ConfigError::UnsupportedToken {
+            account: 0,
+            chain_id: 5,
+            token: "TEST".to_string()
+        }]
     );
+    assert!(error
+        .to_string()
+        .contains("accounts.0: chain_id 5 token TEST not found in relayer chain config"));
 }
 
 #[actix_rt::test]
 async fn test_invalid_2() {
     let server_config = load_server_config(Some(SERVER_CONFIG_INVALID_VERSION));
     assert!(server_config.is_ok());
-    let relayer_config = RelayerConfig::from_json_file(RELAYER_CONFIG_PATH).await;
-    assert!(relayer_config.is_ok());
-    let server_config = server_config.unwrap();
-    let relayer_config = relayer_config.unwrap();
-    let validate = server_config.validation(&relayer_config);
+    let (relayer_config, mystiko_config) = load_test_configs().await;
+    let validate = server_config.unwrap().validation(&relayer_config, &mystiko_config);
     assert!(validate.is_err());
 }
 
+#[actix_rt::test]
+async fn test_validation_lists_every_problem() {
+    let mut server_config = create_default_server_config(true).await;
+    let (relayer_config, mystiko_config) = load_test_configs().await;
+    assert!(server_config.validation(&relayer_config, &mystiko_config).is_ok());
+
+    let duplicate = server_config.accounts.get(&0).unwrap().clone();
+    server_config.accounts.insert(7, duplicate);
+    let mut invalid_key = server_config.accounts.get(&1).unwrap().clone();
+    invalid_key.private_key = "not a key".to_string();
+    server_config.accounts.insert(8, invalid_key);
+    server_config.chains.insert(80001, ChainConfig::default());
+    server_config.settings.sqlite_db_path = Some("./tests/files/missing/db.sqlite".to_string());
+
+    let error = server_config.validation(&relayer_config, &mystiko_config).unwrap_err();
+    assert_eq!(error.errors().len(), 3);
+    assert!(error.errors().contains(&ConfigError::DuplicateAccount {
+        account: 7,
+        duplicate_of: 0,
+        chain_id: 5
+    }));
+    assert!(error
+        .errors()
+        .iter()
+        .any(|error| matches!(error, ConfigError::InvalidSigner { account: 8, .. })));
+    assert!(error
+        .errors()
+        .iter()
+        .any(|error| matches!(error, ConfigError::InvalidSqliteDbPath { .. })));
+    assert!(error.to_string().starts_with("invalid server config, 3 problem(s):"));
+
+    // a chain without accounts is only a warning
+    assert_eq!(
+        server_config.warnings(),
+        vec![ConfigError::UnusedChain { chain_id: 80001 }]
+    );
+    let mut server_config = create_default_server_config(true).await;
+    server_config.chains.insert(80001, ChainConfig::default());
+    assert!(server_config.validation(&relayer_config, &mystiko_config).is_ok());
+}
+
+#[actix_rt::test]
+async fn test_validation_duplicate_signer_across_kinds() {
+    let (relayer_config, mystiko_config) = load_test_configs().await;
+    let dir = tempdir().unwrap();
+    let (wallet, name) =
+        LocalWallet::new_keystore(dir.path(), &mut rand::thread_rng(), "keystore-password", None).unwrap();
+    let password_path = dir.path().join("password");
+    std::fs::write(&password_path, "keystore-password").unwrap();
+    let keystore_account = AccountConfig::builder()
+        .chain_id(5u64)
+        .keystore_path(dir.path().join(name).to_string_lossy().to_string())
+        .keystore_password_file(password_path.to_string_lossy().to_string())
+        .build();
+    let private_key_account = AccountConfig::builder()
+        .chain_id(5u64)
+        .private_key(hex::encode(wallet.signer().to_bytes()))
+        .build();
+
+    // the same wallet as keystore and as plaintext key
+    let mut server_config = create_default_server_config(true).await;
+    server_config.accounts.insert(7, keystore_account);
+    server_config.accounts.insert(8, private_key_account);
+    let error = server_config.validation(&relayer_config, &mystiko_config).unwrap_err();
+    assert_eq!(
+        error.errors(),
+        &[ConfigError::DuplicateAccount {
+            account: 8,
+            duplicate_of: 7,
+            chain_id: 5
+        }]
+    );
+
+    // the plain address of a keystore is compared without decrypting it
+    let address = "0x5e2a5ae2e2e0a6d0a6a7ee3b3b3b8f2c1d4e6f70";
+    let keystore_path = dir.path().join("with-address.json");
+    std::fs::write(
+        &keystore_path,
+        format!(
+            "{{\"address\":\"{}\",\"crypto\":{{}}}}",
+            address.trim_start_matches("0x")
+        ),
+    )
+    .unwrap();
+    let keystore_account = AccountConfig::builder()
+        .chain_id(5u64)
+        .keystore_path(keystore_path.to_string_lossy().to_string())
+        .keystore_password_file(password_path.to_string_lossy().to_string())
+        .build();
+    let remote_account = AccountConfig::builder()
+        .chain_id(5u64)
+        .remote_signer(
+            RemoteSignerConfig::builder()
+                .url("http://127.0.0.1:8545")
+                .address(address)
+                .build(),
+        )
+        .build();
+    let mut server_config = create_default_server_config(true).await;
+    server_config.accounts.insert(7, keystore_account);
+    server_config.accounts.insert(8, remote_account);
+    let error = server_config.validation(&relayer_config, &mystiko_config).unwrap_err();
+    assert_eq!(
+        error.errors(),
+        &[ConfigError::DuplicateAccount {
+            account: 8,
+            duplicate_of: 7,
+            chain_id: 5
+        }]
+    );
+}
+
+#[test]
+fn test_validate_sqlite_db_path() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("relayer.sqlite");
+    assert!(validate_sqlite_db_path(db_path.to_str().unwrap()).is_ok());
+    assert!(validate_sqlite_db_path("relayer.sqlite").is_ok());
+    assert!(validate_sqlite_db_path(dir.path().to_str().unwrap()).is_err());
+    let missing = dir.path().join("missing").join("relayer.sqlite");
+    assert!(validate_sqlite_db_path(missing.to_str().unwrap()).is_err());
+    assert!(validate_sqlite_db_path("").is_err());
+}
+
 #[test]
 fn test_settings_default_param() {
     let settings = Settings::builder().build();
@@ -86,8 +238,366 @@ fn test_account_config_default_param() {
     assert!(account.available);
 }
 
+#[test]
+fn test_cors_config() {
+    let cors = CorsConfig::default();
+    assert!(cors.allows_any_origin());
+    assert_eq!(cors.allowed_methods, vec!["GET", "POST"]);
+    assert_eq!(cors.allowed_headers, vec!["content-type"]);
+    assert!(cors.validate().is_ok());
+
+    assert!(origin_matches(
+        "https://wallet.mystiko.network/",
+        "https://Wallet.mystiko.network"
+    ));
+    assert!(origin_matches(
+        "https://*.mystiko.network",
+        "https://app.mystiko.network"
+    ));
+    assert!(origin_matches("http://localhost:*", "http://localhost:3000"));
+    assert!(!origin_matches("https://*.mystiko.network", "https://mystiko.network"));
+    assert!(!origin_matches(
+        "https://*.mystiko.network",
+        "https://app.mystiko.network.evil.com"
+    ));
+
+    let cors = CorsConfig::builder().allow_credentials(true).build();
+    assert!(cors.validate().is_err());
+    let cors = CorsConfig::builder()
+        .allowed_origins(vec!["wallet.mystiko.network".to_string()])
+        .build();
+    assert!(cors.validate().is_err());
+    let cors = CorsConfig::builder()
+        .allowed_headers(vec!["bad header".to_string()])
+        .build();
+    assert!(cors.validate().is_err());
+}
+
+#[test]
+fn test_lease_config() {
+    let lease = LeaseConfig::default();
+    assert!(!lease.enabled);
+    assert_eq!(lease.ttl_ms, 30000);
+    assert_eq!(lease.heartbeat_interval_ms, 10000);
+    assert!(lease.validate().is_ok());
+
+    let lease = LeaseConfig::builder().ttl_ms(15000u64).build();
+    assert!(lease.validate().is_err());
+    let lease = LeaseConfig::builder()
+        .ttl_ms(500u64)
+        .heartbeat_interval_ms(100u64)
+        .build();
+    assert!(lease.validate().is_err());
+    let lease = LeaseConfig::builder().instance_id("".to_string()).build();
+    assert!(lease.validate().is_err());
+}
+
+#[test]
+fn test_webhook_config() {
+    let webhook = WebhookConfig::default();
+    assert!(!webhook.enabled);
+    assert!(!webhook.allow_callback_url);
+    assert!(webhook.callback_allowed_hosts.is_empty());
+    assert_eq!(webhook.max_attempts, 5);
+    assert!(webhook.validate().is_ok());
+
+    // the signing secret is required once enabled
+    let webhook = WebhookConfig::builder().enabled(true).build();
+    assert!(webhook.validate().is_err());
+    let webhook = WebhookConfig::builder()
+        .enabled(true)
+        .secret("webhook-secret".to_string())
+        .url(Some("https://example.com/hooks".to_string()))
+        .build();
+    assert!(webhook.validate().is_ok());
+    let webhook = WebhookConfig::builder()
+        .url(Some("ftp://example.com/hooks".to_string()))
+        .build();
+    assert!(webhook.validate().is_err());
+    let webhook = WebhookConfig::builder().max_attempts(0u32).build();
+    assert!(webhook.validate().is_err());
+
+    let webhook = WebhookConfig::builder()
+        .initial_backoff_ms(100u64)
+        .max_backoff_ms(500u64)
+        .build();
+    assert_eq!(webhook.backoff_ms(1), 100);
+    assert_eq!(webhook.backoff_ms(2), 200);
+    assert_eq!(webhook.backoff_ms(3), 400);
+    assert_eq!(webhook.backoff_ms(4), 500);
+    assert_eq!(webhook.backoff_ms(40), 500);
+
+    let webhook = WebhookConfig::builder()
+        .callback_allowed_hosts(vec!["Hooks.Example.com".to_string()])
+        .build();
+    assert!(webhook.is_allowed_callback_host("hooks.example.com"));
+    assert!(!webhook.is_allowed_callback_host("example.com"));
+
+    assert!(validate_webhook_url("http://127.0.0.1:8080/hooks").is_ok());
+    assert!(validate_webhook_url("not a url").is_err());
+    assert!(validate_webhook_url(&format!("https://example.com/{}", "a".repeat(255))).is_err());
+}
+
+#[test]
+fn test_quote_config() {
+    let quote = QuoteConfig::default();
+    assert!(!quote.enabled);
+    assert_eq!(quote.ttl_ms, 120000);
+    assert_eq!(quote.risk_limit_percent, 20);
+    assert!(quote.validate().is_ok());
+
+    // a signing secret of at least 16 characters is required once enabled
+    let quote = QuoteConfig::builder().enabled(true).secret("short").build();
+    assert!(quote.validate().is_err());
+    let quote = QuoteConfig::builder()
+        .enabled(true)
+        .secret("quote-secret-0123456789")
+        .build();
+    assert!(quote.validate().is_ok());
+    let quote = QuoteConfig::builder().ttl_ms(10u64).build();
+    assert!(quote.validate().is_err());
+    let quote = QuoteConfig::builder().risk_limit_percent(101u32).build();
+    assert!(quote.validate().is_err());
+}
+
+#[test]
+fn test_price_cache_config() {
+    let price_cache = PriceCacheConfig::default();
+    assert_eq!(price_cache.gas_price_ttl_ms, 15000);
+    assert_eq!(price_cache.token_price_ttl_ms, 60000);
+    assert_eq!(price_cache.refresh_interval_ms, 10000);
+    assert!(price_cache.validate().is_ok());
+
+    // a zero ttl disables the cache
+    let price_cache = PriceCacheConfig::builder()
+        .gas_price_ttl_ms(0u64)
+        .token_price_ttl_ms(0u64)
+        .build();
+    assert!(price_cache.validate().is_ok());
+    let price_cache = PriceCacheConfig::builder().refresh_interval_ms(10u64).build();
+    assert!(price_cache.validate().is_err());
+    let price_cache = PriceCacheConfig::builder().gas_price_ttl_ms(3600000u64).build();
+    assert!(price_cache.validate().is_err());
+
+    // entries are refreshed before they expire
+    let price_cache = PriceCacheConfig::builder().gas_price_ttl_ms(10000u64).build();
+    assert!(price_cache.validate().is_err());
+    let price_cache = PriceCacheConfig::builder()
+        .token_price_ttl_ms(5000u64)
+        .refresh_interval_ms(5000u64)
+        .build();
+    assert!(price_cache.validate().is_err());
+    let price_cache = PriceCacheConfig::builder()
+        .gas_price_ttl_ms(0u64)
+        .refresh_interval_ms(20000u64)
+        .build();
+    assert!(price_cache.validate().is_ok());
+}
+
+#[test]
+fn test_account_keystore() {
+    let dir = tempdir().unwrap();
+    let (wallet, name) =
+        LocalWallet::new_keystore(dir.path(), &mut rand::thread_rng(), "keystore-password", None).unwrap();
+    let keystore_path = dir.path().join(name).to_string_lossy().to_string();
+    let password_path = dir.path().join("password");
+    std::fs::write(&password_path, "keystore-password\n").unwrap();
+    let expected_address = format!("0x{}", hex::encode(wallet.address()));
+
+    let account = AccountConfig::builder()
+        .chain_id(5u64)
+        .keystore_path(keystore_path.clone())
+        .keystore_password_file(password_path.to_string_lossy().to_string())
+        .build();
+    assert!(account.validate().is_ok());
+    assert_eq!(account.address().unwrap(), expected_address);
+
+    std::env::set_var("RELAYER_TEST_KEYSTORE_PASSWORD", "keystore-password");
+    let account = AccountConfig::builder()
+        .chain_id(5u64)
+        .keystore_path(keystore_path.clone())
+        .keystore_password_env("RELAYER_TEST_KEYSTORE_PASSWORD".to_string())
+        .build();
+    assert_eq!(account.address().unwrap(), expected_address);
+
+    std::fs::write(&password_path, "wrong-password").unwrap();
+    let account = AccountConfig::builder()
+        .chain_id(5u64)
+        .keystore_path(keystore_path.clone())
+        .keystore_password_file(password_path.to_string_lossy().to_string())
+        .build();
+    assert!(account.wallet().is_err());
+
+    // exactly one signer source, keystores need a password source
+    let account = AccountConfig::builder().chain_id(5u64).build();
+    assert!(account.validate().is_err());
+    let account = AccountConfig::builder()
+        .chain_id(5u64)
+        .keystore_path(keystore_path.clone())
+        .build();
+    assert!(account.validate().is_err());
+    let account = AccountConfig::builder()
+        .chain_id(5u64)
+        .private_key("eac14b2f62136a72601430dfb9abce2f4b98dabef428f5f92869ef85997e8f84")
+        .keystore_path(keystore_path)
+        .keystore_password_env("RELAYER_TEST_KEYSTORE_PASSWORD".to_string())
+        .build();
+    assert!(account.validate().is_err());
+    assert!(!format!("{:?}", account).contains("eac14b2f"));
+}
+
+#[test]
+fn test_account_load_signer() {
+    let dir = tempdir().unwrap();
+    let (wallet, name) =
+        LocalWallet::new_keystore(dir.path(), &mut rand::thread_rng(), "keystore-password", None).unwrap();
+    let keystore_path = dir.path().join(name).to_string_lossy().to_string();
+    let password_path = dir.path().join("password");
+    std::fs::write(&password_path, "keystore-password").unwrap();
+    let expected_address = format!("0x{}", hex::encode(wallet.address()));
+
+    // decrypted once, later calls do not read the keystore again
+    let mut account = AccountConfig::builder()
+        .chain_id(5u64)
+        .keystore_path(keystore_path.clone())
+        .keystore_password_file(password_path.to_string_lossy().to_string())
+        .build();
+    account.load_signer().unwrap();
+    std::fs::remove_file(&keystore_path).unwrap();
+    assert_eq!(account.address().unwrap(), expected_address);
+    assert_eq!(account.wallet().unwrap().address(), wallet.address());
+
+    // the plaintext key is cleared, the account stays valid
+    let private_key = "eac14b2f62136a72601430dfb9abce2f4b98dabef428f5f92869ef85997e8f84";
+    let mut account = AccountConfig::builder().chain_id(5u64).private_key(private_key).build();
+    let expected_address = account.address().unwrap();
+    account.load_signer().unwrap();
+    assert!(account.private_key.is_empty());
+    assert!(account.has_private_key());
+    assert!(account.validate().is_ok());
+    assert_eq!(account.address().unwrap(), expected_address);
+
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.load_signers().unwrap();
+    assert!(server_config
+        .accounts
+        .values()
+        .all(|account| account.private_key.is_empty() && account.address().is_ok()));
+}
+
 #[test]
 fn load_server_config_from_env() {
     let result = load_server_config(None);
     assert!(result.is_ok());
 }
+
+#[actix_rt::test]
+async fn test_restart_required_changes() {
+    let server_config = create_default_server_config(true).await;
+    let mut new_config = server_config.clone();
+    new_config.settings.log_level = "warn".to_string();
+    new_config.accounts.get_mut(&0).unwrap().available = false;
+    new_config.chains.clear();
+    assert!(restart_required_changes(&server_config, &new_config).is_empty());
+
+    new_config.settings.port += 1;
+    new_config.accounts.remove(&1);
+    let changes = restart_required_changes(&server_config, &new_config);
+    assert_eq!(
+        changes,
+        vec![
+            "settings.host/port".to_string(),
+            "accounts (added, removed or signer changed)".to_string()
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_reload_server_config() {
+    assert!(init_logger(log::LevelFilter::Debug));
+    let context = Arc::new(create_default_context().await);
+    let config = std::fs::read_to_string(SERVER_CONFIG_TESTNET).unwrap();
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, &config).unwrap();
+    let reloader = ConfigReloader::new(
+        Some(path.to_str().unwrap().to_string()),
+        context.clone(),
+        Arc::new(SenderSet::new(HashSet::new())),
+    );
+    assert!(reloader.reload().await.is_ok());
+
+    // enable the chain 97 account and change the log level
+    let changed = config
+        .replace("log_level = \"debug\"", "log_level = \"warn\"")
+        .replace("available = false", "available = true");
+    std::fs::write(&path, &changed).unwrap();
+    assert!(reloader.reload().await.is_ok());
+    assert_eq!(log::max_level(), log::LevelFilter::Warn);
+    assert_eq!(reloader.current().settings.log_level, "warn");
+    // handlers read the reloaded config from the context
+    assert_eq!(context.server_config.load().settings.log_level, "warn");
+    let account = context
+        .account_handler
+        .find_by_address(97, "0x4d870a75d6552a0199610a460a65116b552de0d9")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(account.data.available);
+
+    // changing the port requires a restart
+    std::fs::write(&path, changed.replace("port = 8081", "port = 8082")).unwrap();
+    let result = reloader.reload().await;
+    assert!(result.unwrap_err().to_string().contains("settings.host/port"));
+    assert_eq!(context.server_config.load().settings.port, 8081);
+}
+
+#[test]
+fn test_shared_config() {
+    let config = SharedConfig::new(Arc::new(1u32));
+    assert_eq!(*config.load(), 1);
+    let previous = config.store(Arc::new(2u32));
+    assert_eq!(*previous, 1);
+    assert_eq!(*config.load(), 2);
+}
+
+#[actix_rt::test]
+async fn test_refresh_remote_configs() {
+    let relayer_config = std::fs::read_to_string(RELAYER_CONFIG_PATH).unwrap();
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("relayer_config.json");
+    std::fs::write(&path, &relayer_config).unwrap();
+    let mut server_config = create_default_server_config(true).await;
+    server_config.options.relayer_config_path = Some(path.to_str().unwrap().to_string());
+    let database = Arc::new(create_default_database_in_memory().await);
+    let context = Arc::new(Context::new(Arc::new(server_config), database).await.unwrap());
+    let refresher = RemoteConfigRefresher::new(context.clone(), Duration::from_secs(60));
+
+    // fee change is picked up
+    let changed = relayer_config.replacen(
+        "\"relayerFeeOfTenThousandth\":25",
+        "\"relayerFeeOfTenThousandth\":40",
+        1,
+    );
+    std::fs::write(&path, &changed).unwrap();
+    assert!(refresher.refresh().await.is_ok());
+    let fee = |context: &Context| {
+        context.relayer_config.load().find_chain_config(5).and_then(|chain| {
+            chain
+                .find_contract("ETH")
+                .map(|contract| contract.relayer_fee_of_ten_thousandth())
+        })
+    };
+    assert_eq!(fee(&context), Some(40));
+
+    // a config missing the chain of an account is rejected and the current one kept
+    std::fs::write(&path, changed.replace("\"chainId\":5,", "\"chainId\":55,")).unwrap();
+    assert!(refresher.refresh().await.is_err());
+    assert_eq!(fee(&context), Some(40));
+}
+
+async fn load_test_configs() -> (RelayerConfig, MystikoConfig) {
+    let relayer_config = RelayerConfig::from_json_file(RELAYER_CONFIG_PATH).await.unwrap();
+    let mystiko_config = MystikoConfig::from_json_file(MYSTIKO_CONFIG_PATH).await.unwrap();
+    (relayer_config, mystiko_config)
+}
@@ -1,14 +1,17 @@
 use crate::common::{create_default_context, create_default_server_config};
 use mockito::Server;
+use mystiko_relayer::configs::cache::{load_remote_config, RemoteConfigSource};
 use mystiko_relayer::context::create_config;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
 
 #[actix_rt::test]
 async fn test_create_default_context() {
     let context = create_default_context().await;
-    assert_eq!(context.server_config.settings.api_version.get(&0).unwrap(), "v2");
-    assert_eq!(context.relayer_config.version(), "0.0.1");
-    assert_eq!(context.mystiko_config.version(), "0.2.0");
+    assert_eq!(context.server_config.load().settings.api_version.get(&0).unwrap(), "v2");
+    assert_eq!(context.relayer_config.load().version(), "0.0.1");
+    assert_eq!(context.mystiko_config.load().version(), "0.2.0");
     assert!(context.providers.get_provider(5).await.is_ok());
 }
 
@@ -272,3 +275,93 @@ fn mainnet_relayer_config_json_string() -> String {
     "#;
     relayer_config.to_string()
 }
+
+#[actix_rt::test]
+async fn test_create_config_with_remote_cache() {
+    let mut server = Server::new_async().await;
+    let cache_dir = tempdir().unwrap();
+
+    let mut server_config = create_default_server_config(true).await;
+    server_config.options.mystiko_config_path = None;
+    server_config.options.relayer_config_path = None;
+    server_config.options.relayer_remote_config_base_url = Some(format!("{}/relayer_config", server.url()));
+    server_config.options.mystiko_remote_config_base_url = Some(format!("{}/config", server.url()));
+    server_config.options.remote_config_cache_dir = Some(cache_dir.path().to_str().unwrap().to_string());
+
+    let mock_0 = server
+        .mock("GET", "/relayer_config/production/testnet/latest.json")
+        .with_body(testnet_relayer_config_json_string())
+        .create_async()
+        .await;
+    let mock_1 = server
+        .mock("GET", "/config/production/testnet/latest.json")
+        .with_body("{\"version\": \"0.2.0\"}")
+        .create_async()
+        .await;
+    let result = create_config(Arc::new(server_config.clone())).await;
+    assert!(result.is_ok());
+    mock_0.assert_async().await;
+    mock_1.assert_async().await;
+    assert!(cache_dir.path().join("relayer_config_production_testnet.json").exists());
+    assert!(cache_dir.path().join("mystiko_config_production_testnet.json").exists());
+
+    // the remote is down, the cached copies are used
+    mock_0.remove_async().await;
+    mock_1.remove_async().await;
+    let mock_2 = server
+        .mock("GET", "/relayer_config/production/testnet/latest.json")
+        .with_status(503)
+        .create_async()
+        .await;
+    let _mock_3 = server
+        .mock("GET", "/config/production/testnet/latest.json")
+        .with_status(503)
+        .create_async()
+        .await;
+    let (relayer_config, mystiko_config) = create_config(Arc::new(server_config.clone())).await.unwrap();
+    assert!(relayer_config.find_chain_config(5).is_some());
+    assert_eq!(mystiko_config.version(), "0.2.0");
+
+    // a broken config is not cached and does not replace the cached copy
+    mock_2.remove_async().await;
+    let _mock_4 = server
+        .mock("GET", "/relayer_config/production/testnet/latest.json")
+        .with_body("{broken")
+        .create_async()
+        .await;
+    let (relayer_config, _) = create_config(Arc::new(server_config.clone())).await.unwrap();
+    assert!(relayer_config.find_chain_config(5).is_some());
+
+    // without a cached copy the outage is an error
+    server_config.options.remote_config_cache_dir = Some(cache_dir.path().join("empty").to_str().unwrap().to_string());
+    assert!(create_config(Arc::new(server_config)).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_remote_config_fetch_timeout() {
+    // accepts connections and never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((connection, _)) = listener.accept().await {
+            connections.push(connection);
+        }
+    });
+
+    let cache_dir = tempdir().unwrap();
+    let source = RemoteConfigSource::builder()
+        .name("relayer_config")
+        .base_url(format!("http://{}/relayer_config", address))
+        .cache_dir(cache_dir.path())
+        .fetch_timeout(Duration::from_millis(500))
+        .build();
+    std::fs::write(source.cache_path(), "cached").unwrap();
+
+    let started = Instant::now();
+    let config = load_remote_config(&source, |path| async move { Ok(std::fs::read_to_string(path)?) })
+        .await
+        .unwrap();
+    assert_eq!(config, "cached");
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
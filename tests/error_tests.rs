@@ -31,4 +31,33 @@ fn test_get_error_code() {
         error: "err".to_string(),
     });
     assert_eq!(code, ResponseCode::Failed);
+    let code = get_error_code(&ResponseError::Unauthorized);
+    assert_eq!(code, ResponseCode::Failed);
+    let code = get_error_code(&ResponseError::InvalidTransactionStatus {
+        id: "id".to_string(),
+        status: "Succeeded".to_string(),
+    });
+    assert_eq!(code, ResponseCode::Failed);
+    let code = get_error_code(&ResponseError::TransactionRequestNotFound { id: "id".to_string() });
+    assert_eq!(code, ResponseCode::TransactionNotFound);
+    let code = get_error_code(&ResponseError::CancelTransactionFailed {
+        error: "err".to_string(),
+    });
+    assert_eq!(code, ResponseCode::Failed);
+    let code = get_error_code(&ResponseError::AccountNotFound {
+        chain_id: 5,
+        address: "0x00".to_string(),
+    });
+    assert_eq!(code, ResponseCode::AccountNotFoundInDatabase);
+    let code = get_error_code(&ResponseError::AccountAlreadyExists {
+        chain_id: 5,
+        address: "0x00".to_string(),
+    });
+    assert_eq!(code, ResponseCode::Failed);
+    let code = get_error_code(&ResponseError::InvalidAccount {
+        error: "err".to_string(),
+    });
+    assert_eq!(code, ResponseCode::ValidateError);
+    let code = get_error_code(&ResponseError::RateLimited { retry_after_secs: 1 });
+    assert_eq!(code, ResponseCode::Failed);
 }
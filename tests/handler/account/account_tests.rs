@@ -1,7 +1,7 @@
 use crate::common::{create_default_database_in_memory, create_default_server_config};
 use mystiko_relayer::configs::account::AccountConfig;
 use mystiko_relayer::handler::account::handler::Account;
-use mystiko_relayer::handler::account::AccountHandler;
+use mystiko_relayer::handler::account::{AccountHandler, UpdateAccountOptions};
 use std::sync::Arc;
 
 #[actix_rt::test]
@@ -38,3 +38,74 @@ async fn test_find_by_chain_id_success() {
     assert_eq!(account[0].data.balance_check_interval_ms, 500000);
     assert!(!account[0].data.insufficient_balances);
 }
+
+#[actix_rt::test]
+async fn test_create_update_and_delete_by_address() {
+    let db = create_default_database_in_memory().await;
+    let handler = Account::new(Arc::new(db), &[]).await.unwrap();
+    assert!(handler.find_all().await.unwrap().is_empty());
+
+    let server_config = create_default_server_config(true).await;
+    let account_config = server_config.accounts.values().next().cloned().unwrap();
+    let account = handler.create(&account_config).await.unwrap();
+    assert_eq!(account.data.chain_id, account_config.chain_id);
+    assert_eq!(handler.find_all().await.unwrap().len(), 1);
+
+    let address = account.data.chain_address.to_uppercase();
+    let found = handler
+        .find_by_address(account_config.chain_id, &address)
+        .await
+        .unwrap();
+    assert_eq!(found.unwrap().id, account.id);
+    assert!(handler.find_by_address(97, &address).await.unwrap().is_none());
+
+    let options = UpdateAccountOptions::builder()
+        .available(false)
+        .supported_erc20_tokens(vec!["0xABC".to_string()])
+        .build();
+    let updated = handler
+        .update_by_address(account_config.chain_id, &address, &options)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!updated.data.available);
+    assert_eq!(updated.data.supported_erc20_tokens, ["0xabc"]);
+    assert!(handler
+        .update_by_address(97, &address, &options)
+        .await
+        .unwrap()
+        .is_none());
+
+    handler
+        .delete_by_address(account_config.chain_id, &address)
+        .await
+        .unwrap();
+    assert!(handler.find_all().await.unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_init_data_keeps_other_accounts() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let server_config = create_default_server_config(true).await;
+    let accounts = server_config.accounts.values().cloned().collect::<Vec<AccountConfig>>();
+    let handler = Account::new(db.clone(), &accounts).await.unwrap();
+    let account = handler.find_by_chain_id(5).await.unwrap().remove(0);
+
+    // an account added at runtime, or configured on another instance
+    let mut other_config = accounts[0].clone();
+    other_config.chain_id = 97;
+    handler.create(&other_config).await.unwrap();
+    let options = UpdateAccountOptions::builder().available(false).build();
+    handler
+        .update_by_address(5, &account.data.chain_address, &options)
+        .await
+        .unwrap();
+
+    // a restart upserts the configured accounts and leaves the others alone
+    let handler = Account::new(db, &accounts).await.unwrap();
+    assert_eq!(handler.find_all().await.unwrap().len(), 2);
+    let restarted = handler.find_by_chain_id(5).await.unwrap().remove(0);
+    assert_eq!(restarted.id, account.id);
+    assert!(restarted.data.available);
+    assert_eq!(handler.find_by_chain_id(97).await.unwrap().len(), 1);
+}
@@ -0,0 +1,93 @@
+use crate::common::create_default_database_in_memory;
+use mystiko_relayer::configs::auth::{hash_api_key, ApiKeyConfig, ApiKeyScope, AuthConfig};
+use mystiko_relayer::handler::api_key::{ApiKey, ApiKeyHandler};
+use mystiko_relayer::handler::types::current_timestamp;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[actix_rt::test]
+async fn test_init_and_record_usage() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let auth = auth_config(vec![
+        ("partner", "partner-key-0123456789", ApiKeyScope::Public),
+        ("ops", "ops-key-0123456789abc", ApiKeyScope::Admin),
+    ]);
+    let handler = ApiKey::new(db.clone(), &auth).await.unwrap();
+    assert_eq!(handler.find_all().await.unwrap().len(), 2);
+    let partner = handler.find_by_name("partner").await.unwrap().unwrap();
+    assert_eq!(partner.data.key_hash, hash_api_key("partner-key-0123456789"));
+    assert_ne!(partner.data.key_hash, "partner-key-0123456789");
+    assert_eq!(partner.data.scope, "public");
+    assert_eq!(partner.data.usage_count, 0);
+    assert!(partner.data.last_used_at.is_none());
+
+    handler.record_usage("partner").await.unwrap();
+    handler.record_usage("partner").await.unwrap();
+    handler.record_usage("unknown").await.unwrap();
+    // counted in memory until flushed
+    let partner = handler.find_by_name("partner").await.unwrap().unwrap();
+    assert_eq!(partner.data.usage_count, 0);
+    handler.flush_usage().await.unwrap();
+    let partner = handler.find_by_name("partner").await.unwrap().unwrap();
+    assert_eq!(partner.data.usage_count, 2);
+    assert!(partner.data.last_used_at.is_some());
+    handler.flush_usage().await.unwrap();
+    let partner = handler.find_by_name("partner").await.unwrap().unwrap();
+    assert_eq!(partner.data.usage_count, 2);
+
+    // counters survive a restart, removed keys are dropped
+    let auth = auth_config(vec![("partner", "partner-key-0123456789", ApiKeyScope::Admin)]);
+    let handler = ApiKey::new(db, &auth).await.unwrap();
+    let keys = handler.find_all().await.unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].data.usage_count, 2);
+    assert_eq!(keys[0].data.scope, "admin");
+}
+
+#[actix_rt::test]
+async fn test_use_nonce() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let auth = auth_config(vec![("partner", "partner-key-0123456789", ApiKeyScope::Public)]);
+    let handler = ApiKey::new(db, &auth).await.unwrap();
+    let expires_at = current_timestamp() + 60_000;
+    assert!(handler.use_nonce("partner", "nonce-0001", expires_at).await.unwrap());
+    assert!(!handler.use_nonce("partner", "nonce-0001", expires_at).await.unwrap());
+    assert!(handler.use_nonce("ops", "nonce-0001", expires_at).await.unwrap());
+    // an expired nonce can not be replayed anyway, its timestamp is rejected
+    assert!(handler.use_nonce("partner", "nonce-0002", 0).await.unwrap());
+    assert!(handler.use_nonce("partner", "nonce-0002", expires_at).await.unwrap());
+}
+
+#[actix_rt::test]
+async fn test_use_nonce_across_instances() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let auth = auth_config(vec![("partner", "partner-key-0123456789", ApiKeyScope::Public)]);
+    let handler = ApiKey::new(db.clone(), &auth).await.unwrap();
+    let other_handler = ApiKey::new(db.clone(), &auth).await.unwrap();
+    let expires_at = current_timestamp() + 60_000;
+    assert!(handler.use_nonce("partner", "nonce-0001", expires_at).await.unwrap());
+    assert!(!other_handler
+        .use_nonce("partner", "nonce-0001", expires_at)
+        .await
+        .unwrap());
+    assert_eq!(db.api_key_nonces.find_all().await.unwrap().len(), 1);
+
+    // expired nonces are deleted by the next check
+    assert!(handler.use_nonce("partner", "nonce-0002", 0).await.unwrap());
+    let other_handler = ApiKey::new(db.clone(), &auth).await.unwrap();
+    assert!(other_handler
+        .use_nonce("partner", "nonce-0003", expires_at)
+        .await
+        .unwrap());
+    let nonces = db.api_key_nonces.find_all().await.unwrap();
+    assert_eq!(nonces.len(), 2);
+    assert!(nonces.iter().all(|nonce| nonce.data.nonce != "nonce-0002"));
+}
+
+fn auth_config(keys: Vec<(&str, &str, ApiKeyScope)>) -> AuthConfig {
+    let keys = keys
+        .into_iter()
+        .map(|(name, key, scope)| (name.to_string(), ApiKeyConfig::builder().key(key).scope(scope).build()))
+        .collect::<HashMap<_, _>>();
+    AuthConfig::builder().enabled(true).keys(keys).build()
+}
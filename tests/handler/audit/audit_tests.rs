@@ -0,0 +1,33 @@
+use crate::common::create_default_database_in_memory;
+use mystiko_relayer::handler::audit::{AuditAction, AuditHandler, AuditLog, CreateAuditLogOptions};
+use std::sync::Arc;
+
+#[actix_rt::test]
+async fn test_create_and_find_by_target() {
+    let db = create_default_database_in_memory().await;
+    let handler = AuditLog::new(Arc::new(db));
+    let options = CreateAuditLogOptions::builder()
+        .target("123456")
+        .action(AuditAction::Cancel)
+        .operator("operator")
+        .reason("stuck in mempool")
+        .detail("replacement transaction 0x01".to_string())
+        .build();
+    let log = handler.create(&options).await.unwrap();
+    assert_eq!(log.data.action, "cancel");
+    assert_eq!(log.data.operator, "operator");
+
+    let options = CreateAuditLogOptions::builder()
+        .target("654321")
+        .action(AuditAction::Requeue)
+        .operator("operator")
+        .reason("rpc outage")
+        .build();
+    handler.create(&options).await.unwrap();
+
+    let logs = handler.find_by_target("123456").await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].data.reason, "stuck in mempool");
+    assert_eq!(logs[0].data.detail.as_deref(), Some("replacement transaction 0x01"));
+    assert!(handler.find_by_target("unknown").await.unwrap().is_empty());
+}
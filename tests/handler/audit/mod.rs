@@ -0,0 +1 @@
+mod audit_tests;
@@ -0,0 +1,82 @@
+use crate::common::create_default_database_in_memory;
+use mystiko_relayer::handler::lease::{Lease, LeaseHandler};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+const ADDRESS: &str = "0xAbCd000000000000000000000000000000000001";
+
+#[actix_rt::test]
+async fn test_acquire_and_renew() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Lease::new(db, Duration::ZERO);
+    assert!(handler.find(5, ADDRESS).await.unwrap().is_none());
+
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-a", 60000).await.unwrap());
+    let lease = handler.find(5, &ADDRESS.to_lowercase()).await.unwrap().unwrap();
+    assert_eq!(lease.data.lease_key, format!("5:{}", ADDRESS.to_lowercase()));
+    assert_eq!(lease.data.owner, "relayer-a");
+
+    // the owner renews, everybody else is rejected until the lease expires
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-a", 60000).await.unwrap());
+    let renewed = handler.find(5, ADDRESS).await.unwrap().unwrap();
+    assert!(renewed.data.expires_at >= lease.data.expires_at);
+    assert!(!handler.try_acquire(5, ADDRESS, "relayer-b", 60000).await.unwrap());
+
+    // leases are per chain
+    assert!(handler.try_acquire(97, ADDRESS, "relayer-b", 60000).await.unwrap());
+}
+
+#[actix_rt::test]
+async fn test_take_over_expired_lease() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Lease::new(db, Duration::ZERO);
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-a", 10).await.unwrap());
+    sleep(Duration::from_millis(20)).await;
+
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-b", 60000).await.unwrap());
+    assert_eq!(handler.find(5, ADDRESS).await.unwrap().unwrap().data.owner, "relayer-b");
+    assert!(!handler.try_acquire(5, ADDRESS, "relayer-a", 60000).await.unwrap());
+}
+
+#[actix_rt::test]
+async fn test_take_over_after_clock_skew() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Lease::new(db, Duration::from_secs(60));
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-a", 10).await.unwrap());
+    sleep(Duration::from_millis(20)).await;
+
+    // expired on this clock, but still within the skew another instance may have
+    assert!(!handler.try_acquire(5, ADDRESS, "relayer-b", 60000).await.unwrap());
+    assert_eq!(handler.find(5, ADDRESS).await.unwrap().unwrap().data.owner, "relayer-a");
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-a", 60000).await.unwrap());
+}
+
+#[actix_rt::test]
+async fn test_concurrent_take_over() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Lease::new(db, Duration::ZERO);
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-a", 10).await.unwrap());
+    sleep(Duration::from_millis(20)).await;
+
+    // only one of the takers gets the lease
+    let (taken_b, taken_c) = tokio::join!(
+        handler.try_acquire(5, ADDRESS, "relayer-b", 60000),
+        handler.try_acquire(5, ADDRESS, "relayer-c", 60000)
+    );
+    assert!(taken_b.unwrap() ^ taken_c.unwrap());
+}
+
+#[actix_rt::test]
+async fn test_release() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Lease::new(db, Duration::ZERO);
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-a", 60000).await.unwrap());
+
+    // only the owner can release its lease
+    handler.release(5, ADDRESS, "relayer-b").await.unwrap();
+    assert!(handler.find(5, ADDRESS).await.unwrap().is_some());
+    handler.release(5, ADDRESS, "relayer-a").await.unwrap();
+    assert!(handler.find(5, ADDRESS).await.unwrap().is_none());
+    assert!(handler.try_acquire(5, ADDRESS, "relayer-b", 60000).await.unwrap());
+}
@@ -1,15 +1,32 @@
 use async_trait::async_trait;
 use mockall::mock;
+use mystiko_relayer::configs::account::AccountConfig;
 use mystiko_relayer::database::account::Account;
+use mystiko_relayer::database::api_key::ApiKey;
+use mystiko_relayer::database::audit::AuditLog;
+use mystiko_relayer::database::lease::AccountLease;
+use mystiko_relayer::database::quote::AcceptedQuote;
 use mystiko_relayer::database::transaction::Transaction;
+use mystiko_relayer::database::webhook::{TransactionCallback, WebhookDelivery};
 use mystiko_relayer::error::RelayerServerError;
-use mystiko_relayer::handler::account::AccountHandler;
-use mystiko_relayer::handler::transaction::{TransactionHandler, UpdateTransactionOptions};
-use mystiko_relayer_types::TransactRequestData;
+use mystiko_relayer::handler::account::{AccountHandler, UpdateAccountOptions};
+use mystiko_relayer::handler::api_key::ApiKeyHandler;
+use mystiko_relayer::handler::audit::{AuditHandler, CreateAuditLogOptions};
+use mystiko_relayer::handler::lease::LeaseHandler;
+use mystiko_relayer::handler::quote::QuoteHandler;
+use mystiko_relayer::handler::transaction::{QueryTransactionOptions, TransactionHandler, UpdateTransactionOptions};
+use mystiko_relayer::handler::webhook::{CreateWebhookDeliveryOptions, WebhookHandler};
+use mystiko_relayer_types::{TransactRequestData, TransactStatus};
 use mystiko_storage::Document;
+use num_bigint::BigUint;
 
 mod account;
+mod api_key;
+mod audit;
+mod lease;
+mod quote;
 mod transaction;
+mod webhook;
 
 mock! {
     #[derive(Debug)]
@@ -20,12 +37,37 @@ mock! {
         type Error = RelayerServerError;
         async fn create_by_request(&self, data: TransactRequestData) -> Result<Document<Transaction>, RelayerServerError>;
         async fn find_by_id(&self, id: &str) -> Result<Option<Document<Transaction>>, RelayerServerError>;
+        async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Document<Transaction>>, RelayerServerError>;
+        async fn find_request_by_id(&self, id: &str) -> Result<Option<TransactRequestData>, RelayerServerError>;
+        async fn find_by_transaction_hash(&self, transaction_hash: &str) -> Result<Vec<Document<Transaction>>, RelayerServerError>;
+        async fn find_by_signature(&self, signature: &str) -> Result<Vec<Document<Transaction>>, RelayerServerError>;
+        async fn find_by_serial_number(&self, serial_number: &BigUint) -> Result<Vec<Document<Transaction>>, RelayerServerError>;
         async fn update_by_id(
             &self,
             id: &str,
             options: &UpdateTransactionOptions,
         ) -> Result<Option<Document<Transaction>>, RelayerServerError>;
+        async fn update_by_id_if_status(
+            &self,
+            id: &str,
+            expected: &[TransactStatus],
+            options: &UpdateTransactionOptions,
+        ) -> Result<Option<Document<Transaction>>, RelayerServerError>;
+        async fn update_by_id_if_claimed(
+            &self,
+            id: &str,
+            claimed_before: u64,
+            options: &UpdateTransactionOptions,
+        ) -> Result<Option<Document<Transaction>>, RelayerServerError>;
+        async fn find_unsent(
+            &self,
+            chain_id: u64,
+            queued_before: u64,
+            claimed_before: u64,
+        ) -> Result<Vec<Document<Transaction>>, RelayerServerError>;
         async fn is_repeated_transaction(&self, signature: &str) -> Result<bool, RelayerServerError>;
+        async fn find(&self, options: &QueryTransactionOptions) -> Result<Vec<Document<Transaction>>, RelayerServerError>;
+        async fn count(&self, options: &QueryTransactionOptions) -> Result<u64, RelayerServerError>;
     }
 }
 
@@ -36,6 +78,98 @@ mock! {
     #[async_trait]
     impl AccountHandler<Document<Account>> for Accounts {
         type Error = RelayerServerError;
+        async fn find_all(&self) -> Result<Vec<Document<Account>>, RelayerServerError>;
         async fn find_by_chain_id(&self, chain_id: u64) -> Result<Vec<Document<Account>>, RelayerServerError>;
+        async fn find_by_address(&self, chain_id: u64, address: &str) -> Result<Option<Document<Account>>, RelayerServerError>;
+        async fn create(&self, account: &AccountConfig) -> Result<Document<Account>, RelayerServerError>;
+        async fn update_by_address(
+            &self,
+            chain_id: u64,
+            address: &str,
+            options: &UpdateAccountOptions,
+        ) -> Result<Option<Document<Account>>, RelayerServerError>;
+        async fn delete_by_address(&self, chain_id: u64, address: &str) -> Result<(), RelayerServerError>;
+    }
+}
+
+mock! {
+    #[derive(Debug)]
+    pub Audits {}
+
+    #[async_trait]
+    impl AuditHandler<Document<AuditLog>> for Audits {
+        type Error = RelayerServerError;
+        async fn create(&self, options: &CreateAuditLogOptions) -> Result<Document<AuditLog>, RelayerServerError>;
+        async fn find_by_target(&self, target: &str) -> Result<Vec<Document<AuditLog>>, RelayerServerError>;
+    }
+}
+
+mock! {
+    #[derive(Debug)]
+    pub ApiKeys {}
+
+    #[async_trait]
+    impl ApiKeyHandler<Document<ApiKey>> for ApiKeys {
+        type Error = RelayerServerError;
+        async fn find_all(&self) -> Result<Vec<Document<ApiKey>>, RelayerServerError>;
+        async fn find_by_name(&self, name: &str) -> Result<Option<Document<ApiKey>>, RelayerServerError>;
+        async fn record_usage(&self, name: &str) -> Result<(), RelayerServerError>;
+        async fn flush_usage(&self) -> Result<(), RelayerServerError>;
+        async fn use_nonce(&self, name: &str, nonce: &str, expires_at: u64) -> Result<bool, RelayerServerError>;
+    }
+}
+
+mock! {
+    #[derive(Debug)]
+    pub Leases {}
+
+    #[async_trait]
+    impl LeaseHandler<Document<AccountLease>> for Leases {
+        type Error = RelayerServerError;
+        async fn try_acquire(&self, chain_id: u64, address: &str, owner: &str, ttl_ms: u64) -> Result<bool, RelayerServerError>;
+        async fn release(&self, chain_id: u64, address: &str, owner: &str) -> Result<(), RelayerServerError>;
+        async fn find(&self, chain_id: u64, address: &str) -> Result<Option<Document<AccountLease>>, RelayerServerError>;
+    }
+}
+
+mock! {
+    #[derive(Debug)]
+    pub Webhooks {}
+
+    #[async_trait]
+    impl WebhookHandler<Document<TransactionCallback>, Document<WebhookDelivery>> for Webhooks {
+        type Error = RelayerServerError;
+        async fn save_callback(&self, signature: &str, url: &str) -> Result<Document<TransactionCallback>, RelayerServerError>;
+        async fn delete_callback(&self, signature: &str) -> Result<(), RelayerServerError>;
+        async fn find_callback(&self, signature: &str) -> Result<Option<Document<TransactionCallback>>, RelayerServerError>;
+        async fn enqueue_delivery(
+            &self,
+            options: &CreateWebhookDeliveryOptions,
+        ) -> Result<Document<WebhookDelivery>, RelayerServerError>;
+        async fn find_pending_deliveries(&self) -> Result<Vec<Document<WebhookDelivery>>, RelayerServerError>;
+        async fn claim_delivery(
+            &self,
+            id: &str,
+            next_attempt_at: u64,
+            claimed_until: u64,
+        ) -> Result<Option<Document<WebhookDelivery>>, RelayerServerError>;
+        async fn update_delivery(
+            &self,
+            delivery: &Document<WebhookDelivery>,
+        ) -> Result<Document<WebhookDelivery>, RelayerServerError>;
+        async fn find_deliveries(&self, transaction_id: &str) -> Result<Vec<Document<WebhookDelivery>>, RelayerServerError>;
+    }
+}
+
+mock! {
+    #[derive(Debug)]
+    pub Quotes {}
+
+    #[async_trait]
+    impl QuoteHandler<Document<AcceptedQuote>> for Quotes {
+        type Error = RelayerServerError;
+        async fn save(&self, signature: &str, quote_id: &str, minimum_gas_fee: &str) -> Result<Document<AcceptedQuote>, RelayerServerError>;
+        async fn find(&self, signature: &str) -> Result<Option<Document<AcceptedQuote>>, RelayerServerError>;
+        async fn delete(&self, signature: &str) -> Result<(), RelayerServerError>;
     }
 }
@@ -0,0 +1,27 @@
+use crate::common::create_default_database_in_memory;
+use mystiko_relayer::handler::quote::{Quote, QuoteHandler};
+use std::sync::Arc;
+
+#[actix_rt::test]
+async fn test_save_and_delete_quote() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Quote::new(db);
+    assert!(handler.find("0x01").await.unwrap().is_none());
+
+    let quote = handler.save("0x01", "a1", "1000").await.unwrap();
+    assert_eq!(quote.data.signature, "0x01");
+    assert_eq!(quote.data.quote_id, "a1");
+    assert_eq!(quote.data.minimum_gas_fee, "1000");
+
+    // a resubmitted signature replaces the quote
+    let replaced = handler.save("0x01", "b2", "2000").await.unwrap();
+    assert_eq!(replaced.id, quote.id);
+    let found = handler.find("0x01").await.unwrap().unwrap();
+    assert_eq!(found.data.quote_id, "b2");
+    assert_eq!(found.data.minimum_gas_fee, "2000");
+    assert!(handler.find("0x02").await.unwrap().is_none());
+
+    handler.delete("0x01").await.unwrap();
+    assert!(handler.find("0x01").await.unwrap().is_none());
+    handler.delete("0x01").await.unwrap();
+}
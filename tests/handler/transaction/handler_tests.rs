@@ -1,8 +1,13 @@
 use crate::common::{create_default_database_in_memory, default_transact_request_data};
 use mystiko_protos::core::v1::SpendType;
-use mystiko_relayer::handler::transaction::{Transaction, TransactionHandler, UpdateTransactionOptions};
+use mystiko_relayer::database::BACKFILL_SERIAL_NUMBERS;
+use mystiko_relayer::handler::transaction::{
+    QueryTransactionOptions, SortOrder, Transaction, TransactionHandler, TransactionSortBy, UpdateTransactionOptions,
+};
 use mystiko_relayer_types::TransactStatus;
 use mystiko_types::{BridgeType, CircuitType};
+use mystiko_utils::convert::u256_to_biguint;
+use num_bigint::BigUint;
 use std::sync::Arc;
 
 #[actix_rt::test]
@@ -41,6 +46,34 @@ async fn test_find_by_id() {
     assert_eq!(transaction_0, transaction_1);
 }
 
+#[actix_rt::test]
+async fn test_find_by_ids() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let transaction_0 = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    let transaction_1 = handler
+        .create_by_request(default_transact_request_data(97))
+        .await
+        .unwrap();
+    handler
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+
+    let ids = vec![
+        transaction_1.id.clone(),
+        "unknown".to_string(),
+        transaction_0.id.clone(),
+    ];
+    let mut transactions = handler.find_by_ids(&ids).await.unwrap();
+    transactions.sort_by_key(|transaction| transaction.data.chain_id);
+    assert_eq!(transactions, vec![transaction_1, transaction_0]);
+    assert!(handler.find_by_ids(&[]).await.unwrap().is_empty());
+}
+
 #[actix_rt::test]
 async fn test_update_by_id() {
     let chain_id = 99;
@@ -66,6 +99,263 @@ async fn test_update_by_id() {
     assert_eq!(transaction_1.data.transaction_hash.unwrap(), "transaction_hash");
 }
 
+#[actix_rt::test]
+async fn test_update_by_id_if_status() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let transaction = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    let claim = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Pending)
+        .build();
+
+    let claimed = handler
+        .update_by_id_if_status(transaction.id.as_str(), &[TransactStatus::Queued], &claim)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.data.status, TransactStatus::Pending);
+    assert_eq!(claimed.data.signature, transaction.data.signature);
+    // a second claim of the same job loses
+    assert!(handler
+        .update_by_id_if_status(transaction.id.as_str(), &[TransactStatus::Queued], &claim)
+        .await
+        .unwrap()
+        .is_none());
+
+    let fail = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Failed)
+        .error_message("cancelled".to_string())
+        .build();
+    let failed = handler
+        .update_by_id_if_status(transaction.id.as_str(), &[TransactStatus::Pending], &fail)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(failed.data.status, TransactStatus::Failed);
+    assert_eq!(failed.data.error_message, Some("cancelled".to_string()));
+    assert!(handler
+        .update_by_id_if_status("unknown", &[TransactStatus::Queued], &claim)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[actix_rt::test]
+async fn test_update_by_id_if_claimed() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let transaction = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    let requeue = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Queued)
+        .build();
+    // queued, not claimed
+    assert!(handler
+        .update_by_id_if_claimed(transaction.id.as_str(), u64::MAX, &requeue)
+        .await
+        .unwrap()
+        .is_none());
+
+    let claimed = handler
+        .update_by_id_if_status(
+            transaction.id.as_str(),
+            &[TransactStatus::Queued],
+            &UpdateTransactionOptions::builder()
+                .status(TransactStatus::Pending)
+                .build(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    // claimed after the given time
+    assert!(handler
+        .update_by_id_if_claimed(transaction.id.as_str(), claimed.updated_at - 1, &requeue)
+        .await
+        .unwrap()
+        .is_none());
+    let requeued = handler
+        .update_by_id_if_claimed(transaction.id.as_str(), claimed.updated_at, &requeue)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(requeued.data.status, TransactStatus::Queued);
+
+    // sent, it has a hash
+    handler
+        .update_by_id(
+            transaction.id.as_str(),
+            &UpdateTransactionOptions::builder()
+                .status(TransactStatus::Pending)
+                .transaction_hash("0xabcdef".to_string())
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert!(handler
+        .update_by_id_if_claimed(transaction.id.as_str(), u64::MAX, &requeue)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[actix_rt::test]
+async fn test_find_unsent() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let queued = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    let claimed = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    let claimed = handler
+        .update_by_id(
+            &claimed.id,
+            &UpdateTransactionOptions::builder()
+                .status(TransactStatus::Pending)
+                .build(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let sent = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    handler
+        .update_by_id(
+            &sent.id,
+            &UpdateTransactionOptions::builder()
+                .status(TransactStatus::Pending)
+                .transaction_hash("0xabcdef".to_string())
+                .build(),
+        )
+        .await
+        .unwrap();
+    handler
+        .create_by_request(default_transact_request_data(97))
+        .await
+        .unwrap();
+
+    let unsent = handler.find_unsent(99, u64::MAX, u64::MAX).await.unwrap();
+    assert_eq!(
+        unsent
+            .iter()
+            .map(|transaction| transaction.id.clone())
+            .collect::<Vec<_>>(),
+        vec![queued.id.clone(), claimed.id.clone()]
+    );
+    // each kind is matched against its own time
+    let unsent = handler.find_unsent(99, u64::MAX, claimed.updated_at - 1).await.unwrap();
+    assert_eq!(unsent, vec![queued.clone()]);
+    let unsent = handler.find_unsent(99, queued.updated_at - 1, u64::MAX).await.unwrap();
+    assert_eq!(unsent, vec![claimed]);
+    assert!(handler.find_unsent(5, u64::MAX, u64::MAX).await.unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_find_by_transaction_hash_and_signature() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let request = default_transact_request_data(99);
+    let signature = request.signature.clone();
+    let failed = handler.create_by_request(request).await.unwrap();
+    let failed = handler
+        .update_by_id(
+            &failed.id,
+            &UpdateTransactionOptions::builder()
+                .status(TransactStatus::Failed)
+                .transaction_hash("0xabcdef".to_string())
+                .build(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let resubmitted = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    let resubmitted = handler
+        .update_by_id(
+            &resubmitted.id,
+            &UpdateTransactionOptions::builder()
+                .transaction_hash("123456".to_string())
+                .build(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    // hashes are matched case insensitive, with or without the 0x prefix
+    for hash in ["0xabcdef", "0xABCDEF", "abcdef"] {
+        let found = handler.find_by_transaction_hash(hash).await.unwrap();
+        assert_eq!(found, vec![failed.clone()]);
+    }
+    assert_eq!(
+        handler.find_by_transaction_hash("0x123456").await.unwrap(),
+        vec![resubmitted.clone()]
+    );
+    assert!(handler.find_by_transaction_hash("0x01").await.unwrap().is_empty());
+
+    let mut found = handler.find_by_signature(&signature).await.unwrap();
+    found.sort_by_key(|transaction| transaction.created_at);
+    assert_eq!(found.len(), 2);
+    assert!(handler.find_by_signature("0x01").await.unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_find_by_serial_number() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let request = default_transact_request_data(99);
+    let serial_number = u256_to_biguint(&request.contract_param.serial_numbers[0]);
+    let transaction = handler.create_by_request(request).await.unwrap();
+    let mut other = default_transact_request_data(97);
+    other.contract_param.serial_numbers = vec![];
+    handler.create_by_request(other).await.unwrap();
+
+    let found = handler.find_by_serial_number(&serial_number).await.unwrap();
+    assert_eq!(found, vec![transaction]);
+    assert!(handler
+        .find_by_serial_number(&BigUint::from(1u32))
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[actix_rt::test]
+async fn test_backfill_serial_numbers() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Transaction::new(db.clone());
+    let request = default_transact_request_data(99);
+    let serial_number = u256_to_biguint(&request.contract_param.serial_numbers[0]);
+    let transaction = handler.create_by_request(request).await.unwrap();
+    // stored before the serial number collection existed
+    db.serial_numbers.delete_all().await.unwrap();
+    assert!(handler.find_by_serial_number(&serial_number).await.unwrap().is_empty());
+    // the backfill already ran when the database was created, it runs once
+    db.migrate().await.unwrap();
+    assert!(handler.find_by_serial_number(&serial_number).await.unwrap().is_empty());
+
+    db.data_migrations.delete_all().await.unwrap();
+    db.migrate().await.unwrap();
+    assert!(db.is_data_migrated(BACKFILL_SERIAL_NUMBERS).await.unwrap());
+    let found = handler.find_by_serial_number(&serial_number).await.unwrap();
+    assert_eq!(found, vec![transaction]);
+    let backfilled = db.serial_numbers.find_all().await.unwrap().len();
+    // a replica starting at the same time does not write them twice
+    db.data_migrations.delete_all().await.unwrap();
+    db.migrate().await.unwrap();
+    assert_eq!(db.serial_numbers.find_all().await.unwrap().len(), backfilled);
+}
+
 #[actix_rt::test]
 async fn test_is_repeated_transaction() {
     let chain_id = 99;
@@ -83,3 +373,181 @@ async fn test_is_repeated_transaction() {
     assert!(result.is_ok());
     assert!(!result.unwrap());
 }
+
+#[actix_rt::test]
+async fn test_find_and_count() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let transaction_0 = handler
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+    let transaction_1 = handler
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+    let transaction_2 = handler
+        .create_by_request(default_transact_request_data(97))
+        .await
+        .unwrap();
+    handler
+        .update_by_id(
+            transaction_1.id.as_str(),
+            &UpdateTransactionOptions::builder()
+                .status(TransactStatus::Failed)
+                .error_message("Relayer fee amount not enough".to_string())
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    let options = QueryTransactionOptions::builder().build();
+    assert_eq!(handler.count(&options).await.unwrap(), 3);
+
+    let options = QueryTransactionOptions::builder().chain_id(5).build();
+    assert_eq!(handler.count(&options).await.unwrap(), 2);
+
+    let options = QueryTransactionOptions::builder()
+        .chain_id(5)
+        .status(TransactStatus::Queued)
+        .build();
+    let transactions = handler.find(&options).await.unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].id, transaction_0.id);
+
+    let options = QueryTransactionOptions::builder().error_message("FEE AMOUNT").build();
+    let transactions = handler.find(&options).await.unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].id, transaction_1.id);
+
+    let options = QueryTransactionOptions::builder()
+        .spend_type(SpendType::Transfer)
+        .build();
+    assert_eq!(handler.count(&options).await.unwrap(), 0);
+
+    let options = QueryTransactionOptions::builder().asset_symbol("eth").build();
+    assert_eq!(handler.count(&options).await.unwrap(), 3);
+
+    let options = QueryTransactionOptions::builder()
+        .created_after(transaction_2.created_at + 1)
+        .build();
+    assert_eq!(handler.count(&options).await.unwrap(), 0);
+
+    let updated = handler.find_by_id(transaction_1.id.as_str()).await.unwrap().unwrap();
+    let options = QueryTransactionOptions::builder()
+        .updated_after(updated.updated_at)
+        .build();
+    let transactions = handler.find(&options).await.unwrap();
+    assert!(transactions
+        .iter()
+        .any(|transaction| transaction.id == transaction_1.id));
+    let options = QueryTransactionOptions::builder()
+        .updated_after(updated.updated_at + 1)
+        .build();
+    assert!(handler
+        .find(&options)
+        .await
+        .unwrap()
+        .iter()
+        .all(|transaction| transaction.id != transaction_1.id));
+
+    let options = QueryTransactionOptions::builder()
+        .sort_by(TransactionSortBy::ChainId)
+        .order(SortOrder::Desc)
+        .limit(1)
+        .build();
+    let transactions = handler.find(&options).await.unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].id, transaction_2.id);
+
+    let options = QueryTransactionOptions::builder()
+        .sort_by(TransactionSortBy::ChainId)
+        .order(SortOrder::Asc)
+        .offset(2)
+        .limit(10)
+        .build();
+    let transactions = handler.find(&options).await.unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].id, transaction_2.id);
+}
+
+#[actix_rt::test]
+async fn test_find_by_error_message_paged() {
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    for _ in 0..4 {
+        let transaction = handler
+            .create_by_request(default_transact_request_data(5))
+            .await
+            .unwrap();
+        handler
+            .update_by_id(
+                transaction.id.as_str(),
+                &UpdateTransactionOptions::builder()
+                    .status(TransactStatus::Failed)
+                    .error_message("nonce too low".to_string())
+                    .build(),
+            )
+            .await
+            .unwrap();
+    }
+    handler
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+
+    let options = QueryTransactionOptions::builder()
+        .error_message("NONCE")
+        .order(SortOrder::Asc)
+        .build();
+    let failed = handler.find(&options).await.unwrap();
+    assert_eq!(failed.len(), 4);
+    let options = QueryTransactionOptions::builder()
+        .error_message("NONCE")
+        .order(SortOrder::Asc)
+        .offset(1)
+        .limit(2)
+        .build();
+    assert_eq!(handler.find(&options).await.unwrap(), failed[1..3].to_vec());
+    assert_eq!(handler.count(&options).await.unwrap(), 4);
+}
+
+#[actix_rt::test]
+async fn test_find_by_mixed_case_asset_symbol() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Transaction::new(db.clone());
+    let mut data = default_transact_request_data(5);
+    data.asset_symbol = "mUSD".to_string();
+    let transaction = handler.create_by_request(data).await.unwrap();
+    assert_eq!(transaction.data.asset_symbol, "MUSD");
+    let options = QueryTransactionOptions::builder().asset_symbol("Musd").build();
+    assert_eq!(handler.count(&options).await.unwrap(), 1);
+
+    // stored before symbols were uppercased
+    let mut stored = transaction.clone();
+    stored.data.asset_symbol = "mUsd".to_string();
+    db.transactions.update(&stored).await.unwrap();
+    assert_eq!(handler.count(&options).await.unwrap(), 0);
+    db.migrate().await.unwrap();
+    assert_eq!(handler.count(&options).await.unwrap(), 0);
+    db.data_migrations.delete_all().await.unwrap();
+    db.migrate().await.unwrap();
+    assert_eq!(handler.count(&options).await.unwrap(), 1);
+}
+
+#[actix_rt::test]
+async fn test_find_request_by_id() {
+    let chain_id = 99;
+    let db = create_default_database_in_memory().await;
+    let handler = Transaction::new(Arc::new(db));
+    let data = default_transact_request_data(chain_id);
+    let transaction = handler.create_by_request(data.clone()).await.unwrap();
+    let request = handler.find_request_by_id(transaction.id.as_str()).await.unwrap();
+    assert!(request.is_some());
+    let request = request.unwrap();
+    assert_eq!(request.chain_id, chain_id);
+    assert_eq!(request.signature, data.signature);
+    assert_eq!(request.contract_param, data.contract_param);
+    let request = handler.find_request_by_id("unknown").await.unwrap();
+    assert!(request.is_none());
+}
@@ -1 +1,2 @@
 mod handler_tests;
+mod status_tests;
@@ -0,0 +1,97 @@
+use crate::common::{create_default_database_in_memory, default_transact_request_data};
+use mystiko_protos::core::v1::SpendType;
+use mystiko_relayer::handler::transaction::status::{StatusUpdate, TransactionStatusBus, TransactionStatusEvent};
+use mystiko_relayer::handler::transaction::{Transaction, TransactionHandler, UpdateTransactionOptions};
+use mystiko_relayer_types::TransactStatus;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[actix_rt::test]
+async fn test_update_publishes_status() {
+    let db = create_default_database_in_memory().await;
+    let status_bus = Arc::new(TransactionStatusBus::default());
+    let handler = Transaction::with_status_bus(Arc::new(db), status_bus.clone());
+    let transaction = handler
+        .create_by_request(default_transact_request_data(99))
+        .await
+        .unwrap();
+    let mut subscription = status_bus.subscribe(&transaction.id);
+
+    let options = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Pending)
+        .transaction_hash("0x123".to_string())
+        .build();
+    handler.update_by_id(&transaction.id, &options).await.unwrap();
+    let expected = TransactionStatusEvent {
+        id: transaction.id.clone(),
+        chain_id: 99,
+        spend_type: SpendType::Withdraw,
+        status: TransactStatus::Pending,
+        transaction_hash: Some("0x123".to_string()),
+        error_message: None,
+    };
+    assert_eq!(subscription.next().await, StatusUpdate::Event(expected));
+
+    // an update without changes is not published
+    handler.update_by_id(&transaction.id, &options).await.unwrap();
+    assert!(timeout(Duration::from_millis(50), subscription.next()).await.is_err());
+
+    let options = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Succeeded)
+        .build();
+    handler.update_by_id(&transaction.id, &options).await.unwrap();
+    match subscription.next().await {
+        StatusUpdate::Event(event) => {
+            assert_eq!(event.status, TransactStatus::Succeeded);
+            assert!(event.is_final());
+        }
+        update => panic!("unexpected update {:?}", update),
+    }
+}
+
+#[actix_rt::test]
+async fn test_subscription() {
+    let status_bus = TransactionStatusBus::new(2);
+    let mut subscription = status_bus.subscribe_none();
+    assert!(subscription.watch("1"));
+    assert!(!subscription.watch("1"));
+    assert!(subscription.is_watching("1"));
+
+    // events of other transactions are skipped
+    status_bus.publish(status_event("2", TransactStatus::Pending));
+    status_bus.publish(status_event("1", TransactStatus::Pending));
+    assert_eq!(
+        subscription.next().await,
+        StatusUpdate::Event(status_event("1", TransactStatus::Pending))
+    );
+
+    // several transactions on one subscription
+    subscription.watch("3");
+    status_bus.publish(status_event("3", TransactStatus::Failed));
+    assert_eq!(
+        subscription.next().await,
+        StatusUpdate::Event(status_event("3", TransactStatus::Failed))
+    );
+    assert!(subscription.unwatch("3"));
+    assert_eq!(subscription.ids().len(), 1);
+
+    for _ in 0..3 {
+        status_bus.publish(status_event("2", TransactStatus::Pending));
+    }
+    assert_eq!(subscription.next().await, StatusUpdate::Lagged);
+
+    drop(status_bus);
+    assert_eq!(subscription.next().await, StatusUpdate::Closed);
+}
+
+fn status_event(id: &str, status: TransactStatus) -> TransactionStatusEvent {
+    TransactionStatusEvent {
+        id: id.to_string(),
+        chain_id: 99,
+        spend_type: SpendType::Withdraw,
+        status,
+        transaction_hash: None,
+        error_message: None,
+    }
+}
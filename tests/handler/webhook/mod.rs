@@ -0,0 +1,75 @@
+use crate::common::create_default_database_in_memory;
+use mystiko_relayer::handler::webhook::{CreateWebhookDeliveryOptions, Webhook, WebhookHandler};
+use std::sync::Arc;
+
+#[actix_rt::test]
+async fn test_save_and_delete_callback() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Webhook::new(db);
+    assert!(handler.find_callback("0x01").await.unwrap().is_none());
+
+    let callback = handler.save_callback("0x01", "https://example.com/a").await.unwrap();
+    assert_eq!(callback.data.signature, "0x01");
+    assert_eq!(callback.data.url, "https://example.com/a");
+
+    // a resubmitted signature replaces the callback
+    let replaced = handler.save_callback("0x01", "https://example.com/b").await.unwrap();
+    assert_eq!(replaced.id, callback.id);
+    let found = handler.find_callback("0x01").await.unwrap().unwrap();
+    assert_eq!(found.data.url, "https://example.com/b");
+
+    handler.delete_callback("0x01").await.unwrap();
+    assert!(handler.find_callback("0x01").await.unwrap().is_none());
+    handler.delete_callback("0x01").await.unwrap();
+}
+
+#[actix_rt::test]
+async fn test_enqueue_and_claim_deliveries() {
+    let db = Arc::new(create_default_database_in_memory().await);
+    let handler = Webhook::new(db);
+    for (transaction_id, status) in [("1", "pending"), ("1", "succeeded"), ("2", "queued")] {
+        let options = CreateWebhookDeliveryOptions::builder()
+            .transaction_id(transaction_id)
+            .url("https://example.com/a")
+            .status(status)
+            .payload(format!("{{\"uuid\":\"{}\"}}", transaction_id))
+            .build();
+        let delivery = handler.enqueue_delivery(&options).await.unwrap();
+        assert_eq!(delivery.data.attempt, 0);
+        assert!(!delivery.data.delivered);
+        assert!(delivery.data.next_attempt_at.is_some());
+    }
+
+    let pending = handler.find_pending_deliveries().await.unwrap();
+    assert_eq!(pending.len(), 3);
+    let first = &pending[0];
+    let next_attempt_at = first.data.next_attempt_at.unwrap();
+    let claimed = handler
+        .claim_delivery(&first.id, next_attempt_at, next_attempt_at + 1000)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.data.next_attempt_at, Some(next_attempt_at + 1000));
+    // the claim only lands once
+    assert!(handler
+        .claim_delivery(&first.id, next_attempt_at, next_attempt_at + 2000)
+        .await
+        .unwrap()
+        .is_none());
+
+    let mut delivered = claimed;
+    delivered.data.attempt = 1;
+    delivered.data.delivered = true;
+    delivered.data.response_status = Some(200);
+    delivered.data.next_attempt_at = None;
+    handler.update_delivery(&delivered).await.unwrap();
+    assert_eq!(handler.find_pending_deliveries().await.unwrap().len(), 2);
+
+    let deliveries = handler.find_deliveries("1").await.unwrap();
+    assert_eq!(deliveries.len(), 2);
+    assert!(deliveries[0].data.delivered);
+    assert_eq!(deliveries[0].data.response_status, Some(200));
+    assert_eq!(deliveries[0].data.payload, "{\"uuid\":\"1\"}");
+    assert!(!deliveries[1].data.delivered);
+    assert!(handler.find_deliveries("3").await.unwrap().is_empty());
+}
@@ -0,0 +1,191 @@
+use crate::common::{MockProviders, MockTokenPrice};
+use async_trait::async_trait;
+use ethers_core::types::{Block, FeeHistory, TxHash, U256};
+use ethers_providers::ProviderError;
+use futures::future::join_all;
+use mystiko_ethers::{JsonRpcClientWrapper, JsonRpcParams, Provider, ProviderWrapper};
+use mystiko_relayer::price_cache::{swap_amount, GasPriceCache, TokenPriceCache};
+use mystiko_server_utils::token_price::{PriceMiddleware, PriceMiddlewareError};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct SlowProvider {
+    gas_price: U256,
+}
+
+#[async_trait]
+impl JsonRpcClientWrapper for SlowProvider {
+    async fn request(&self, method: &str, _params: JsonRpcParams) -> Result<Value, ProviderError> {
+        // keeps the refresh in flight while the other callers arrive
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        if method == "eth_getBlockByNumber" {
+            Ok(serde_json::json!(Block::<TxHash>::default()))
+        } else if method == "eth_feeHistory" {
+            let history = FeeHistory {
+                base_fee_per_gas: vec![],
+                gas_used_ratio: vec![],
+                oldest_block: Default::default(),
+                reward: vec![],
+            };
+            Ok(serde_json::json!(history))
+        } else if method == "eth_gasPrice" {
+            Ok(serde_json::json!(self.gas_price))
+        } else {
+            panic!("Unexpected method: {}", method);
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_token_price_cache() {
+    let mut token_price = MockTokenPrice::new();
+    token_price
+        .expect_price()
+        .withf(|symbol| symbol == "ETH")
+        .times(1)
+        .returning(|_| Ok(2000.0));
+    token_price
+        .expect_price()
+        .withf(|symbol| symbol == "MTT")
+        .times(1)
+        .returning(|_| Ok(0.5));
+    // swaps of any amount are derived from the cached prices
+    token_price.expect_swap().never();
+    let cache = TokenPriceCache::new(Duration::from_secs(60), Box::new(token_price));
+
+    for _ in 0..3 {
+        let swapped = cache.swap("ETH", 18, U256::exp10(18), "MTT", 18).await.unwrap();
+        assert_eq!(swapped, U256::from(4000) * U256::exp10(18));
+        let swapped = cache.swap("ETH", 18, U256::from(3), "MTT", 6).await.unwrap();
+        assert!(swapped.is_zero());
+        let swapped = cache.swap("eth", 18, U256::exp10(17), "mtt", 6).await.unwrap();
+        assert_eq!(swapped, U256::from(400) * U256::exp10(6));
+    }
+    assert_eq!(cache.price("ETH").await.unwrap(), 2000.0);
+    assert_eq!(cache.price("eth").await.unwrap(), 2000.0);
+}
+
+#[actix_rt::test]
+async fn test_token_price_cache_swap_expired() {
+    let failing = Arc::new(AtomicBool::new(false));
+    let mut token_price = MockTokenPrice::new();
+    let price_failing = failing.clone();
+    token_price.expect_price().times(5).returning(move |_| {
+        if price_failing.load(Ordering::SeqCst) {
+            Err(PriceMiddlewareError::InternalError)
+        } else {
+            Ok(2.0)
+        }
+    });
+    let cache = TokenPriceCache::new(Duration::ZERO, Box::new(token_price));
+
+    assert_eq!(
+        cache.swap("ETH", 18, U256::exp10(18), "MTT", 18).await.unwrap(),
+        U256::exp10(18)
+    );
+    assert_eq!(
+        cache.swap("ETH", 18, U256::exp10(18), "MTT", 18).await.unwrap(),
+        U256::exp10(18)
+    );
+    failing.store(true, Ordering::SeqCst);
+    assert!(cache.swap("ETH", 18, U256::exp10(18), "MTT", 18).await.is_err());
+}
+
+#[test]
+fn test_swap_amount() {
+    assert_eq!(
+        swap_amount(2000.0, 18, U256::exp10(18), 1.0, 6).unwrap(),
+        U256::from(2000) * U256::exp10(6)
+    );
+    // truncated to the units of asset_b
+    assert_eq!(swap_amount(1.0, 6, U256::from(1999999), 2.0, 0).unwrap(), U256::zero());
+    assert_eq!(swap_amount(1.0, 6, U256::from(2000000), 2.0, 0).unwrap(), U256::one());
+    assert!(swap_amount(0.0, 18, U256::one(), 1.0, 18).is_err());
+    assert!(swap_amount(1.0, 18, U256::one(), f64::NAN, 18).is_err());
+}
+
+#[actix_rt::test]
+async fn test_token_price_cache_expired() {
+    let mut token_price = MockTokenPrice::new();
+    token_price.expect_price().times(2).returning(|_| Ok(1.0));
+    let cache = TokenPriceCache::new(Duration::ZERO, Box::new(token_price));
+
+    assert_eq!(cache.price("ETH").await.unwrap(), 1.0);
+    assert_eq!(cache.price("ETH").await.unwrap(), 1.0);
+}
+
+#[actix_rt::test]
+async fn test_token_price_cache_refresh() {
+    let failing = Arc::new(AtomicBool::new(false));
+    let mut token_price = MockTokenPrice::new();
+    let price_failing = failing.clone();
+    token_price.expect_price().returning(move |_| {
+        if price_failing.load(Ordering::SeqCst) {
+            Err(PriceMiddlewareError::InternalError)
+        } else {
+            Ok(3.0)
+        }
+    });
+    let cache = TokenPriceCache::new(Duration::from_secs(60), Box::new(token_price));
+    assert_eq!(cache.price("ETH").await.unwrap(), 3.0);
+
+    // a failed refresh keeps the cached price
+    failing.store(true, Ordering::SeqCst);
+    cache.refresh_all().await;
+    assert_eq!(cache.price("ETH").await.unwrap(), 3.0);
+    assert!(cache.price("MTT").await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_gas_price_cache() {
+    let mut providers = MockProviders::new();
+    providers
+        .expect_get_provider()
+        .returning(|chain_id| Err(anyhow::anyhow!("No provider for chain_id {}", chain_id)));
+    let cache = GasPriceCache::new(Duration::from_secs(60), Arc::new(Box::new(providers)));
+
+    // nothing cached, the provider is queried
+    assert!(cache.gas_price(5, true).await.is_err());
+
+    // prices sent with by the consumers are served until they expire
+    cache.insert(5, true, U256::from(1000)).await;
+    assert_eq!(cache.gas_price(5, true).await.unwrap(), U256::from(1000));
+
+    // a failed refresh keeps the cached price
+    cache.refresh_all().await;
+    assert_eq!(cache.gas_price(5, true).await.unwrap(), U256::from(1000));
+}
+
+#[actix_rt::test]
+async fn test_gas_price_cache_expired() {
+    let mut providers = MockProviders::new();
+    providers
+        .expect_get_provider()
+        .times(1)
+        .returning(|chain_id| Err(anyhow::anyhow!("No provider for chain_id {}", chain_id)));
+    let cache = GasPriceCache::new(Duration::ZERO, Arc::new(Box::new(providers)));
+
+    cache.insert(5, false, U256::from(1000)).await;
+    assert!(cache.gas_price(5, false).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_gas_price_cache_single_flight() {
+    let mut providers = MockProviders::new();
+    providers.expect_get_provider().times(1).returning(|_| {
+        let provider = SlowProvider {
+            gas_price: U256::from(2000),
+        };
+        Ok(Arc::new(Provider::new(ProviderWrapper::new(Box::new(provider)))))
+    });
+    let cache = GasPriceCache::new(Duration::from_secs(60), Arc::new(Box::new(providers)));
+
+    // concurrent callers after expiry share one refresh
+    let gas_prices = join_all((0..5).map(|_| cache.gas_price(5, false))).await;
+    for gas_price in gas_prices {
+        assert_eq!(gas_price.unwrap(), U256::from(2000));
+    }
+}
@@ -0,0 +1,295 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::MockTokenPrice;
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app, MockOptions};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::test::{call_and_read_body_json, TestRequest};
+use anyhow::anyhow;
+use ethers_signers::LocalWallet;
+use mystiko_relayer::configs::account::RemoteSignerConfig;
+use mystiko_relayer::database::account::Account;
+use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::service::admin::request::{AccountActionRequest, AddAccountRequest, UpdateAccountTokensRequest};
+use mystiko_relayer::service::admin::response::{AccountListResponse, AccountResponse};
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_storage::Document;
+use std::collections::HashMap;
+use tempfile::tempdir;
+
+const CHAIN_ID: u64 = 5;
+const ADDRESS: &str = "0x000000";
+const ADMIN_TOKEN: &str = "Bearer admin-token";
+
+#[actix_rt::test]
+async fn test_list_accounts() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_find_all()
+        .returning(|| Ok(vec![account(CHAIN_ID, ADDRESS, true), account(97, "0x111111", true)]));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/admin/accounts")
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .to_request();
+    let response: ApiResponse<AccountListResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let accounts = response.data.unwrap().accounts;
+    assert_eq!(accounts.len(), 2);
+    assert!(accounts[0].active);
+    assert!(!accounts[1].active);
+}
+
+#[actix_rt::test]
+async fn test_disable_and_enable_account() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_update_by_address()
+        .withf(|chain_id, address, options| {
+            *chain_id == CHAIN_ID && address == ADDRESS && options.available == Some(false)
+        })
+        .times(1)
+        .returning(|chain_id, address, _| Ok(Some(account(chain_id, address, false))));
+    account_handler
+        .expect_update_by_address()
+        .withf(|_, _, options| options.available == Some(true))
+        .times(1)
+        .returning(|chain_id, address, _| Ok(Some(account(chain_id, address, true))));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+
+    let uri = format!("/api/admin/accounts/{}/{}/disable", CHAIN_ID, ADDRESS);
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, action_request(&uri)).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(!response.data.unwrap().available);
+
+    let uri = format!("/api/admin/accounts/{}/{}/enable", CHAIN_ID, ADDRESS);
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, action_request(&uri)).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(response.data.unwrap().available);
+}
+
+#[actix_rt::test]
+async fn test_disable_unknown_account() {
+    let mut account_handler = MockAccounts::new();
+    account_handler.expect_update_by_address().returning(|_, _, _| Ok(None));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+    let uri = format!("/api/admin/accounts/{}/0x123456/disable", CHAIN_ID);
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, action_request(&uri)).await;
+    assert_eq!(response.code, ResponseCode::AccountNotFoundInDatabase as i32);
+}
+
+#[actix_rt::test]
+async fn test_disable_account_without_sender() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_update_by_address()
+        .returning(|chain_id, address, _| Ok(Some(account(chain_id, address, false))));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+    // stored but not running in this instance
+    let uri = format!("/api/admin/accounts/{}/0x123456/disable", CHAIN_ID);
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, action_request(&uri)).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(!response.data.unwrap().active);
+}
+
+#[actix_rt::test]
+async fn test_disable_account_with_database_error() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_update_by_address()
+        .times(1)
+        .returning(|_, _, _| Err(RelayerServerError::AnyhowError(anyhow!("database down"))));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+
+    let uri = format!("/api/admin/accounts/{}/{}/disable", CHAIN_ID, ADDRESS);
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, action_request(&uri)).await;
+    assert_eq!(response.code, ResponseCode::DatabaseError as i32);
+}
+
+#[actix_rt::test]
+async fn test_update_account_tokens() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_update_by_address()
+        .withf(|_, _, options| options.supported_erc20_tokens == Some(vec!["MTT".to_string()]))
+        .times(1)
+        .returning(|chain_id, address, _| Ok(Some(account(chain_id, address, true))));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+
+    let uri = format!("/api/admin/accounts/{}/{}/tokens", CHAIN_ID, ADDRESS);
+    let response: ApiResponse<AccountResponse> =
+        call_and_read_body_json(&app, tokens_request(&uri, vec!["MTT".to_string()])).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+
+    let response: ApiResponse<AccountResponse> =
+        call_and_read_body_json(&app, tokens_request(&uri, vec!["UNKNOWN".to_string()])).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_retire_account() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_update_by_address()
+        .withf(|_, _, options| options.available == Some(false))
+        .times(1)
+        .returning(|chain_id, address, _| Ok(Some(account(chain_id, address, false))));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+
+    let uri = format!("/api/admin/accounts/{}/{}/retire", CHAIN_ID, ADDRESS);
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, action_request(&uri)).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(!response.data.unwrap().active);
+
+    // the sender is gone once retired
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, action_request(&uri)).await;
+    assert_eq!(response.code, ResponseCode::AccountNotFoundInDatabase as i32);
+}
+
+#[actix_rt::test]
+async fn test_add_account_without_keystore() {
+    let app = create_app(mock_options(MockAccounts::new())).await.unwrap();
+    // a plaintext key in the body is ignored, the keystore is required
+    let request = TestRequest::post()
+        .uri("/api/admin/accounts")
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .set_json(serde_json::json!({
+            "chainId": CHAIN_ID,
+            "privateKey": "0x0d9c6b1b8e1d9d8ebf5ab32aa5c15a7c26ad0a4e2c1b4e41f3e2a1c2d3b4a596",
+            "reason": "new account",
+        }))
+        .to_request();
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_add_existing_remote_account() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_find_by_address()
+        .withf(|chain_id, address| *chain_id == CHAIN_ID && address == "0x00000000000000000000000000000000000000aa")
+        .returning(|chain_id, address| Ok(Some(account(chain_id, address, true))));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+    let request = add_request(AddAccountRequest {
+        remote_signer: Some(
+            RemoteSignerConfig::builder()
+                .url("http://127.0.0.1:9000".to_string())
+                .address("0x00000000000000000000000000000000000000AA".to_string())
+                .build(),
+        ),
+        ..add_account_request()
+    });
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+    assert!(response.message.unwrap().contains("already exists"));
+}
+
+#[actix_rt::test]
+async fn test_add_account_with_invalid_keystore() {
+    let app = create_app(mock_options(MockAccounts::new())).await.unwrap();
+    let request = add_request(AddAccountRequest {
+        keystore_path: Some("./tests/files/missing.json".to_string()),
+        keystore_password_env: Some("RELAYER_TEST_ADD_ACCOUNT_PASSWORD".to_string()),
+        ..add_account_request()
+    });
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_add_existing_account() {
+    let dir = tempdir().unwrap();
+    let name = LocalWallet::new_keystore(dir.path(), &mut rand::thread_rng(), "keystore-password", None)
+        .unwrap()
+        .1;
+    let password_path = dir.path().join("password");
+    std::fs::write(&password_path, "keystore-password").unwrap();
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_find_by_address()
+        .returning(|chain_id, address| Ok(Some(account(chain_id, address, true))));
+    let app = create_app(mock_options(account_handler)).await.unwrap();
+    let request = add_request(AddAccountRequest {
+        keystore_path: Some(dir.path().join(name).to_string_lossy().to_string()),
+        keystore_password_file: Some(password_path.to_string_lossy().to_string()),
+        supported_erc20_tokens: vec!["MTT".to_string()],
+        ..add_account_request()
+    });
+    let response: ApiResponse<AccountResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+    assert!(response.message.unwrap().contains("already exists"));
+}
+
+fn mock_options(account_handler: MockAccounts) -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler: MockTransactions::new(),
+        account_handler,
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
+
+fn action_request(uri: &str) -> actix_http::Request {
+    TestRequest::post()
+        .uri(uri)
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .set_json(AccountActionRequest {
+            reason: "maintenance".to_string(),
+        })
+        .to_request()
+}
+
+fn tokens_request(uri: &str, supported_erc20_tokens: Vec<String>) -> actix_http::Request {
+    TestRequest::put()
+        .uri(uri)
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .set_json(UpdateAccountTokensRequest {
+            supported_erc20_tokens,
+            reason: "maintenance".to_string(),
+        })
+        .to_request()
+}
+
+fn add_account_request() -> AddAccountRequest {
+    AddAccountRequest {
+        chain_id: CHAIN_ID,
+        keystore_path: None,
+        keystore_password_file: None,
+        keystore_password_env: None,
+        remote_signer: None,
+        available: None,
+        supported_erc20_tokens: vec![],
+        balance_alarm_threshold: None,
+        balance_check_interval_ms: None,
+        reason: "new account".to_string(),
+    }
+}
+
+fn add_request(request: AddAccountRequest) -> actix_http::Request {
+    TestRequest::post()
+        .uri("/api/admin/accounts")
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .set_json(request)
+        .to_request()
+}
+
+fn account(chain_id: u64, address: &str, available: bool) -> Document<Account> {
+    Document::new(
+        format!("{}:{}", chain_id, address),
+        1234567890u64,
+        1234567890u64,
+        Account {
+            chain_address: address.to_string(),
+            chain_id,
+            available,
+            supported_erc20_tokens: vec!["mtt".to_string()],
+            balance_alarm_threshold: 0.05,
+            balance_check_interval_ms: 500000,
+            insufficient_balances: false,
+        },
+    )
+}
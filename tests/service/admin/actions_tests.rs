@@ -0,0 +1,325 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{default_transact_request_data, default_transaction, MockTokenPrice};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app, MockOptions};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::test::{call_and_read_body_json, TestRequest};
+use mystiko_relayer::database::transaction::Transaction;
+use mystiko_relayer::handler::types::current_timestamp;
+use mystiko_relayer::service::admin::request::TransactionActionRequest;
+use mystiko_relayer::service::admin::response::TransactionActionResponse;
+use mystiko_relayer::service::admin::REQUEUE_STUCK_AFTER_MS;
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::Document;
+use std::collections::HashMap;
+
+const CHAIN_ID: u64 = 5;
+const ADMIN_TOKEN: &str = "Bearer admin-token";
+
+#[actix_rt::test]
+async fn test_requeue_success() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .withf(|id| id == "123456")
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Failed))));
+    transaction_handler
+        .expect_find_request_by_id()
+        .withf(|id| id == "123456")
+        .returning(|_| Ok(Some(default_transact_request_data(CHAIN_ID))));
+    transaction_handler
+        .expect_update_by_id_if_status()
+        .withf(|id, expected, options| {
+            id == "123456" && expected == [TransactStatus::Failed] && options.status == Some(TransactStatus::Queued)
+        })
+        .times(1)
+        .returning(|id, _, _| Ok(Some(transaction_with_status(id, TransactStatus::Queued))));
+    let mut producer = MockProducers::new();
+    producer
+        .expect_requeue()
+        .withf(|id, data| id == "123456" && data.chain_id == CHAIN_ID)
+        .times(1)
+        .returning(|_, _| Ok(()));
+    let app = create_app(mock_options(transaction_handler, producer)).await.unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/requeue");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let data = response.data.unwrap();
+    assert_eq!(data.transaction.id, "123456");
+    assert!(data.replacement_transaction_hash.is_none());
+}
+
+#[actix_rt::test]
+async fn test_requeue_with_invalid_status() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Succeeded))));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/requeue");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+    assert!(response.message.unwrap().contains("action not allowed"));
+}
+
+#[actix_rt::test]
+async fn test_requeue_recently_queued() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler.expect_find_by_id().returning(|id| {
+        let mut transaction = transaction_with_status(id, TransactStatus::Queued);
+        transaction.updated_at = current_timestamp();
+        Ok(Some(transaction))
+    });
+    transaction_handler.expect_update_by_id_if_status().never();
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/requeue");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+    assert!(response.message.unwrap().contains("action not allowed"));
+}
+
+#[actix_rt::test]
+async fn test_requeue_stale_claim() {
+    let mut transaction_handler = MockTransactions::new();
+    // claimed by a consumer that never sent it
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Pending))));
+    transaction_handler
+        .expect_find_request_by_id()
+        .returning(|_| Ok(Some(default_transact_request_data(CHAIN_ID))));
+    transaction_handler
+        .expect_update_by_id_if_claimed()
+        .withf(|id, claimed_before, options| {
+            id == "123456"
+                && *claimed_before <= current_timestamp() - REQUEUE_STUCK_AFTER_MS
+                && options.status == Some(TransactStatus::Queued)
+        })
+        .times(1)
+        .returning(|id, _, _| Ok(Some(transaction_with_status(id, TransactStatus::Queued))));
+    let mut producer = MockProducers::new();
+    producer.expect_requeue().times(1).returning(|_, _| Ok(()));
+    let app = create_app(mock_options(transaction_handler, producer)).await.unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/requeue");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+}
+
+#[actix_rt::test]
+async fn test_requeue_recent_claim() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler.expect_find_by_id().returning(|id| {
+        let mut transaction = transaction_with_status(id, TransactStatus::Pending);
+        transaction.updated_at = current_timestamp();
+        Ok(Some(transaction))
+    });
+    transaction_handler.expect_update_by_id_if_claimed().never();
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/requeue");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+    assert!(response.message.unwrap().contains("action not allowed"));
+}
+
+#[actix_rt::test]
+async fn test_requeue_without_request() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Queued))));
+    transaction_handler.expect_find_request_by_id().returning(|_| Ok(None));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/requeue");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::TransactionNotFound as i32);
+}
+
+#[actix_rt::test]
+async fn test_fail_success() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Pending))));
+    transaction_handler
+        .expect_update_by_id()
+        .withf(|_, options| {
+            options.status == Some(TransactStatus::Failed)
+                && options.error_message.as_deref() == Some("stuck (marked failed by operator)")
+        })
+        .returning(|id, _| Ok(Some(transaction_with_status(id, TransactStatus::Failed))));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/fail");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert_eq!(response.data.unwrap().transaction.status, TransactStatus::Failed);
+}
+
+#[actix_rt::test]
+async fn test_cancel_queued_success() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Queued))));
+    transaction_handler
+        .expect_update_by_id_if_status()
+        .withf(|_, expected, options| {
+            expected == [TransactStatus::Queued]
+                && options.status == Some(TransactStatus::Failed)
+                && options.error_message.as_deref() == Some("cancelled by operator: stuck")
+        })
+        .returning(|id, _, _| Ok(Some(transaction_with_status(id, TransactStatus::Failed))));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/cancel");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(response.data.unwrap().replacement_transaction_hash.is_none());
+}
+
+#[actix_rt::test]
+async fn test_cancel_queued_after_claim() {
+    let mut transaction_handler = MockTransactions::new();
+    let mut statuses = vec![TransactStatus::Pending, TransactStatus::Queued];
+    transaction_handler
+        .expect_find_by_id()
+        .returning(move |id| Ok(Some(transaction_with_status(id, statuses.pop().unwrap()))));
+    // the consumer claimed the job between the read and the cancel
+    transaction_handler
+        .expect_update_by_id_if_status()
+        .times(1)
+        .returning(|_, _, _| Ok(None));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/cancel");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+    assert!(response.message.unwrap().contains("Pending"));
+}
+
+#[actix_rt::test]
+async fn test_cancel_stale_claim() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Pending))));
+    transaction_handler.expect_update_by_id_if_status().never();
+    transaction_handler
+        .expect_update_by_id_if_claimed()
+        .withf(|_, _, options| {
+            options.status == Some(TransactStatus::Failed)
+                && options.error_message.as_deref() == Some("cancelled by operator: stuck")
+        })
+        .times(1)
+        .returning(|id, _, _| Ok(Some(transaction_with_status(id, TransactStatus::Failed))));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/cancel");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(response.data.unwrap().replacement_transaction_hash.is_none());
+}
+
+#[actix_rt::test]
+async fn test_cancel_stale_claim_after_send() {
+    let mut transaction_handler = MockTransactions::new();
+    let mut sent = transaction_with_status("123456", TransactStatus::Pending);
+    sent.data.transaction_hash = Some("0x1234".to_string());
+    let mut transactions = vec![sent, transaction_with_status("123456", TransactStatus::Pending)];
+    transaction_handler
+        .expect_find_by_id()
+        .returning(move |_| Ok(Some(transactions.pop().unwrap())));
+    // the consumer sent the job between the read and the cancel
+    transaction_handler
+        .expect_update_by_id_if_claimed()
+        .times(1)
+        .returning(|_, _, _| Ok(None));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/cancel");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+}
+
+#[actix_rt::test]
+async fn test_cancel_with_invalid_status() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Failed))));
+    let app = create_app(mock_options(transaction_handler, MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = action_request("/api/admin/transactions/123456/cancel");
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Failed as i32);
+}
+
+#[actix_rt::test]
+async fn test_action_without_reason() {
+    let app = create_app(mock_options(MockTransactions::new(), MockProducers::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/admin/transactions/123456/fail")
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .set_json(TransactionActionRequest { reason: "".to_string() })
+        .to_request();
+    let response: ApiResponse<TransactionActionResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+fn mock_options(transaction_handler: MockTransactions, producer: MockProducers) -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer,
+    }
+}
+
+fn action_request(uri: &str) -> actix_http::Request {
+    TestRequest::post()
+        .uri(uri)
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .set_json(TransactionActionRequest {
+            reason: "stuck".to_string(),
+        })
+        .to_request()
+}
+
+fn transaction_with_status(id: &str, status: TransactStatus) -> Document<Transaction> {
+    let mut transaction = default_transaction();
+    transaction.status = status;
+    Document::new(id.to_string(), 1234567890u64, 1234567891u64, transaction)
+}
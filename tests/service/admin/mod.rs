@@ -0,0 +1,3 @@
+mod accounts_tests;
+mod actions_tests;
+mod transactions_tests;
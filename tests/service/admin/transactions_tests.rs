@@ -0,0 +1,142 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{default_transaction, MockTokenPrice};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app, MockOptions, CHAIN_ID};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::StatusCode;
+use actix_web::test::{call_and_read_body_json, call_service, TestRequest};
+use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::handler::transaction::{SortOrder, TransactionSortBy};
+use mystiko_relayer::service::admin::response::TransactionListResponse;
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::{Document, StorageError};
+use std::collections::HashMap;
+
+const ADMIN_TOKEN: &str = "Bearer admin-token";
+
+#[actix_rt::test]
+async fn test_success() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_count()
+        .withf(|options| {
+            options.chain_id == Some(5)
+                && options.status == Some(TransactStatus::Failed)
+                && options.error_message.as_deref() == Some("fee")
+                && options.sort_by == TransactionSortBy::UpdatedAt
+                && options.order == SortOrder::Asc
+        })
+        .returning(|_| Ok(3));
+    transaction_handler
+        .expect_find()
+        .withf(|options| options.offset == Some(1) && options.limit == Some(2))
+        .returning(|_| {
+            Ok(vec![
+                Document::new("1".to_string(), 1234567890u64, 1234567891u64, default_transaction()),
+                Document::new("2".to_string(), 1234567892u64, 1234567893u64, default_transaction()),
+            ])
+        });
+    let options = MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    };
+    let app = create_app(options).await.unwrap();
+
+    let status = serde_json::to_value(TransactStatus::Failed).unwrap();
+    let uri = format!(
+        "/api/admin/transactions?chainId=5&status={}&error=fee&sortBy=updated_at&order=asc&offset=1&limit=2",
+        status.as_str().unwrap()
+    );
+    let request = TestRequest::get()
+        .uri(&uri)
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .to_request();
+    let response: ApiResponse<TransactionListResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let data = response.data.unwrap();
+    assert_eq!(data.total, 3);
+    assert_eq!(data.offset, 1);
+    assert_eq!(data.limit, 2);
+    assert_eq!(data.transactions.len(), 2);
+    assert_eq!(data.transactions[0].id, "1");
+    assert_eq!(data.transactions[1].created_at, 1234567892u64);
+}
+
+#[actix_rt::test]
+async fn test_without_token() {
+    let options = MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler: MockTransactions::new(),
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    };
+    let app = create_app(options).await.unwrap();
+
+    let request = TestRequest::get().uri("/api/admin/transactions").to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let request = TestRequest::get()
+        .uri("/api/admin/transactions")
+        .insert_header((AUTHORIZATION, "Bearer wrong-token"))
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn test_with_invalid_limit() {
+    let options = MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler: MockTransactions::new(),
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    };
+    let app = create_app(options).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/admin/transactions?limit=1000")
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .to_request();
+    let response: ApiResponse<TransactionListResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_with_database_error() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler.expect_count().returning(|_| {
+        Err(RelayerServerError::StorageError(StorageError::NoSuchColumnError(
+            "mock_error".to_string(),
+        )))
+    });
+    let options = MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    };
+    let app = create_app(options).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/admin/transactions")
+        .insert_header((AUTHORIZATION, ADMIN_TOKEN))
+        .to_request();
+    let response: ApiResponse<TransactionListResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::DatabaseError as i32);
+}
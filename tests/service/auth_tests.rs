@@ -0,0 +1,140 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{MockTokenPrice, SERVER_CONFIG_TESTNET};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app_with_config, MockOptions, CHAIN_ID};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, TestRequest};
+use mystiko_relayer::configs::auth::{ApiKeyConfig, ApiKeyScope};
+use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::server::ServerConfig;
+use mystiko_relayer::service::auth::{
+    sign_request, API_KEY_HEADER, API_KEY_ID_HEADER, API_NONCE_HEADER, API_SIGNATURE_HEADER, API_TIMESTAMP_HEADER,
+};
+use mystiko_relayer_types::RegisterInfoRequest;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PUBLIC_KEY: &str = "partner-key-0123456789";
+const ADMIN_KEY: &str = "ops-key-0123456789abcdef";
+
+#[actix_rt::test]
+async fn test_static_api_key() {
+    let (options, server_config) = auth_options();
+    let app = create_app_with_config(options, server_config).await.unwrap();
+
+    let request = TestRequest::get().uri("/handshake").to_request();
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    let request = TestRequest::get()
+        .uri("/handshake")
+        .insert_header((API_KEY_HEADER, "wrong-key-0123456789"))
+        .to_request();
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    let request = TestRequest::get()
+        .uri("/handshake")
+        .insert_header((API_KEY_HEADER, PUBLIC_KEY))
+        .to_request();
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_api_key_scope() {
+    let (options, server_config) = auth_options();
+    let app = create_app_with_config(options, server_config).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/admin/accounts")
+        .insert_header((API_KEY_HEADER, PUBLIC_KEY))
+        .insert_header((AUTHORIZATION, "Bearer admin-token"))
+        .to_request();
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    let request = TestRequest::get()
+        .uri("/api/admin/accounts")
+        .insert_header((API_KEY_HEADER, ADMIN_KEY))
+        .insert_header((AUTHORIZATION, "Bearer admin-token"))
+        .to_request();
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+
+    // admin keys can call the public endpoints too
+    let request = TestRequest::get()
+        .uri("/handshake")
+        .insert_header((API_KEY_HEADER, ADMIN_KEY))
+        .to_request();
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_signed_request() {
+    let (options, server_config) = auth_options();
+    let app = create_app_with_config(options, server_config).await.unwrap();
+    let body = serde_json::to_vec(&RegisterInfoRequest::builder().chain_id(1234u64).build()).unwrap();
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let signature = sign_request(PUBLIC_KEY, timestamp, "nonce-0001", "POST", "/api/v2/info", &body);
+
+    let request = signed_request(&body, timestamp, "nonce-0001", &signature);
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+
+    // the same signed request is not accepted twice
+    let request = signed_request(&body, timestamp, "nonce-0001", &signature);
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    // tampered body
+    let signature = sign_request(PUBLIC_KEY, timestamp, "nonce-0002", "POST", "/api/v2/info", &body);
+    let request = signed_request(b"{\"chainId\":1}", timestamp, "nonce-0002", &signature);
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    // tampered nonce
+    let request = signed_request(&body, timestamp, "nonce-0003", &signature);
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    // expired timestamp
+    let timestamp = timestamp - 3600;
+    let signature = sign_request(PUBLIC_KEY, timestamp, "nonce-0004", "POST", "/api/v2/info", &body);
+    let request = signed_request(&body, timestamp, "nonce-0004", &signature);
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+fn signed_request(body: &[u8], timestamp: u64, nonce: &str, signature: &str) -> actix_http::Request {
+    TestRequest::post()
+        .uri("/api/v2/info")
+        .insert_header(("content-type", "application/json"))
+        .insert_header((API_KEY_ID_HEADER, "partner"))
+        .insert_header((API_TIMESTAMP_HEADER, timestamp.to_string()))
+        .insert_header((API_NONCE_HEADER, nonce.to_string()))
+        .insert_header((API_SIGNATURE_HEADER, signature.to_string()))
+        .set_payload(body.to_vec())
+        .to_request()
+}
+
+fn auth_options() -> (MockOptions, ServerConfig) {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.auth.enabled = true;
+    server_config.auth.keys = HashMap::from([
+        (
+            "partner".to_string(),
+            ApiKeyConfig::builder()
+                .key(PUBLIC_KEY)
+                .scope(ApiKeyScope::Public)
+                .build(),
+        ),
+        (
+            "ops".to_string(),
+            ApiKeyConfig::builder().key(ADMIN_KEY).scope(ApiKeyScope::Admin).build(),
+        ),
+    ]);
+    let mut account_handler = MockAccounts::new();
+    account_handler.expect_find_all().returning(|| Ok(vec![]));
+    let options = MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler: MockTransactions::new(),
+        account_handler,
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    };
+    (options, server_config)
+}
@@ -0,0 +1,93 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{MockTokenPrice, SERVER_CONFIG_TESTNET};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app_with_config, MockOptions, CHAIN_ID};
+use actix_web::http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, AUTHORIZATION, ORIGIN,
+};
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, TestRequest};
+use mystiko_relayer::configs::cors::CorsConfig;
+use mystiko_relayer::configs::load_server_config;
+use std::collections::HashMap;
+
+#[actix_rt::test]
+async fn test_default_cors() {
+    let server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    assert_eq!(server_config.settings.cors, CorsConfig::default());
+    let app = create_app_with_config(mock_options(), server_config).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/handshake")
+        .insert_header((ORIGIN, "https://any.example.com"))
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    // the authorization header is not allowed by default
+    let request = TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri("/api/v2/info")
+        .insert_header((ORIGIN, "https://any.example.com"))
+        .insert_header((ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+        .insert_header((ACCESS_CONTROL_REQUEST_HEADERS, "authorization"))
+        .to_request();
+    assert!(call_service(&app, request).await.status().is_client_error());
+}
+
+#[actix_rt::test]
+async fn test_configured_cors() {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.settings.cors = CorsConfig::builder()
+        .allowed_origins(vec![
+            "https://wallet.mystiko.network".to_string(),
+            "https://*.preview.mystiko.network".to_string(),
+        ])
+        .allowed_headers(vec!["content-type".to_string(), "authorization".to_string()])
+        .allow_credentials(true)
+        .max_age_secs(600usize)
+        .build();
+    let app = create_app_with_config(mock_options(), server_config).await.unwrap();
+
+    for origin in [
+        "https://wallet.mystiko.network",
+        "https://pr-12.preview.mystiko.network",
+    ] {
+        let request = TestRequest::default()
+            .method(actix_web::http::Method::OPTIONS)
+            .uri("/api/v2/info")
+            .insert_header((ORIGIN, origin))
+            .insert_header((ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+            .insert_header((ACCESS_CONTROL_REQUEST_HEADERS, "authorization"))
+            .to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), origin);
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(), "true");
+        assert_eq!(headers.get(ACCESS_CONTROL_MAX_AGE).unwrap(), "600");
+        let allowed_headers = headers.get(ACCESS_CONTROL_ALLOW_HEADERS).unwrap().to_str().unwrap();
+        assert!(allowed_headers.contains(AUTHORIZATION.as_str()));
+    }
+
+    let request = TestRequest::get()
+        .uri("/handshake")
+        .insert_header((ORIGIN, "https://evil.example.com"))
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+}
+
+fn mock_options() -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler: MockTransactions::new(),
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
@@ -1,9 +1,10 @@
 use crate::channel::{MockConsumers, MockProducers};
 use crate::common::{MockProviders, MockTokenPrice, SERVER_CONFIG_TESTNET};
-use crate::handler::{MockAccounts, MockTransactions};
+use crate::handler::{MockAccounts, MockApiKeys, MockAudits, MockLeases, MockQuotes, MockTransactions, MockWebhooks};
 use actix_http::body::BoxBody;
 use actix_http::Request;
 use actix_web::dev::{Service, ServiceResponse};
+use actix_web::middleware::from_fn;
 use actix_web::test::{call_and_read_body_json, init_service, TestRequest};
 use actix_web::web::{scope, Data};
 use actix_web::{App, Error};
@@ -11,26 +12,53 @@ use anyhow::Result;
 use async_trait::async_trait;
 use ethers_core::types::{Block, FeeHistory, TxHash, U256};
 use ethers_providers::ProviderError;
+use ethers_signers::LocalWallet;
 use log::LevelFilter;
-use mystiko_ethers::{JsonRpcClientWrapper, JsonRpcParams, Provider, ProviderWrapper};
+use mystiko_ethers::{JsonRpcClientWrapper, JsonRpcParams, Provider, ProviderWrapper, Providers};
 use mystiko_relayer::channel::consumer::ConsumerHandler;
+use mystiko_relayer::channel::lease::LeaseState;
 use mystiko_relayer::channel::producer::ProducerHandler;
-use mystiko_relayer::channel::SenderInfo;
+use mystiko_relayer::channel::{SenderInfo, SenderSet};
 use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::remote::SharedConfig;
+use mystiko_relayer::configs::server::ServerConfig;
 use mystiko_relayer::context::{create_config, Context};
+use mystiko_relayer::database::audit::AuditLog;
 use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::handler::transaction::status::TransactionStatusBus;
+use mystiko_relayer::price_cache::GasPriceCache;
+use mystiko_relayer::service::admin::handler::{
+    add_account, cancel_transaction, disable_account, enable_account, fail_transaction, list_accounts,
+    list_transactions, requeue_transaction, retire_account, update_account_tokens,
+};
+use mystiko_relayer::service::auth::authenticate;
+use mystiko_relayer::service::cors::create_cors;
 use mystiko_relayer::service::handshake;
+use mystiko_relayer::service::rate_limit::{rate_limit, RateLimiter};
+use mystiko_relayer::service::tls::require_admin_client_certificate;
 use mystiko_relayer::service::v1::handler::{chain_status, job_status, transact_v1};
-use mystiko_relayer::service::v2::handler::{info, transact, transaction_status};
+use mystiko_relayer::service::v2::handler::{
+    chains, info, transact, transaction_status, transaction_status_batch, transaction_status_stream,
+    transactions_by_hash, transactions_by_serial_number, transactions_by_signature,
+};
+use mystiko_relayer::service::v2::ws::ws;
+use mystiko_relayer::signer::AccountSigner;
 use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
 use mystiko_relayer_types::HandshakeResponse;
+use mystiko_storage::Document;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
 use typed_builder::TypedBuilder;
 
+mod admin;
+mod auth_tests;
+mod cors_tests;
+mod rate_limit_tests;
+mod tls_tests;
 mod v1;
 mod v2;
 
@@ -49,7 +77,70 @@ struct MockOptions {
 async fn create_app(
     options: MockOptions,
 ) -> Result<impl Service<Request, Response = ServiceResponse<BoxBody>, Error = Error> + Sized> {
-    let server_config = Arc::new(load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap());
+    let server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    create_app_with_config(options, server_config).await
+}
+
+async fn create_app_with_config(
+    options: MockOptions,
+    server_config: ServerConfig,
+) -> Result<impl Service<Request, Response = ServiceResponse<BoxBody>, Error = Error> + Sized> {
+    create_app_with_status_bus(options, server_config, Arc::new(TransactionStatusBus::default())).await
+}
+
+async fn create_app_with_status_bus(
+    options: MockOptions,
+    server_config: ServerConfig,
+    status_bus: Arc<TransactionStatusBus>,
+) -> Result<impl Service<Request, Response = ServiceResponse<BoxBody>, Error = Error> + Sized> {
+    create_app_with_handlers(
+        options,
+        server_config,
+        status_bus,
+        MockWebhooks::new(),
+        MockQuotes::new(),
+    )
+    .await
+}
+
+async fn create_app_with_webhooks(
+    options: MockOptions,
+    server_config: ServerConfig,
+    webhook_handler: MockWebhooks,
+) -> Result<impl Service<Request, Response = ServiceResponse<BoxBody>, Error = Error> + Sized> {
+    create_app_with_handlers(
+        options,
+        server_config,
+        Arc::new(TransactionStatusBus::default()),
+        webhook_handler,
+        MockQuotes::new(),
+    )
+    .await
+}
+
+async fn create_app_with_quotes(
+    options: MockOptions,
+    server_config: ServerConfig,
+    quote_handler: MockQuotes,
+) -> Result<impl Service<Request, Response = ServiceResponse<BoxBody>, Error = Error> + Sized> {
+    create_app_with_handlers(
+        options,
+        server_config,
+        Arc::new(TransactionStatusBus::default()),
+        MockWebhooks::new(),
+        quote_handler,
+    )
+    .await
+}
+
+async fn create_app_with_handlers(
+    options: MockOptions,
+    server_config: ServerConfig,
+    status_bus: Arc<TransactionStatusBus>,
+    webhook_handler: MockWebhooks,
+    quote_handler: MockQuotes,
+) -> Result<impl Service<Request, Response = ServiceResponse<BoxBody>, Error = Error> + Sized> {
+    let server_config = Arc::new(server_config);
     let (relayer_config, mystiko_config) = create_config(server_config.clone()).await.unwrap();
 
     // try init logger
@@ -79,28 +170,67 @@ async fn create_app(
             .ok_or(anyhow::anyhow!("No provider for chain_id {}", chain_id))
     });
     let signer_providers = MockProviders::new();
+    let mut audit_handler = MockAudits::new();
+    audit_handler.expect_create().returning(|options| {
+        Ok(Document::new(
+            "audit".to_string(),
+            1234567890u64,
+            1234567890u64,
+            AuditLog {
+                target: options.target.clone(),
+                action: options.action.to_string(),
+                operator: options.operator.clone(),
+                reason: options.reason.clone(),
+                detail: options.detail.clone(),
+            },
+        ))
+    });
+
+    let mut api_key_handler = MockApiKeys::new();
+    api_key_handler.expect_record_usage().returning(|_| Ok(()));
+    let mut used_nonces = HashSet::new();
+    api_key_handler
+        .expect_use_nonce()
+        .returning(move |name, nonce, _| Ok(used_nonces.insert((name.to_string(), nonce.to_string()))));
 
+    let providers: Arc<Box<dyn Providers>> = Arc::new(Box::new(providers));
+    let gas_price_cache = Arc::new(GasPriceCache::new(
+        Duration::from_millis(server_config.price_cache.gas_price_ttl_ms),
+        providers.clone(),
+    ));
     let context = Context {
-        server_config,
-        relayer_config,
-        mystiko_config,
-        providers: Arc::new(Box::new(providers)),
+        server_config: Arc::new(SharedConfig::new(server_config)),
+        relayer_config: Arc::new(SharedConfig::new(relayer_config)),
+        mystiko_config: Arc::new(SharedConfig::new(mystiko_config)),
+        providers: providers.clone(),
         signer_providers: Arc::new(Box::new(signer_providers)),
         transaction_handler: Arc::new(Box::new(options.transaction_handler)),
+        status_bus,
         account_handler: Arc::new(Box::new(options.account_handler)),
+        audit_handler: Arc::new(Box::new(audit_handler)),
+        api_key_handler: Arc::new(Box::new(api_key_handler)),
+        lease_handler: Arc::new(Box::new(MockLeases::new())),
+        webhook_handler: Arc::new(Box::new(webhook_handler)),
+        quote_handler: Arc::new(Box::new(quote_handler)),
         token_price: Arc::new(RwLock::new(Box::new(options.token_price))),
+        gas_price_cache,
+        token_price_cache: None,
     };
 
     let mut senders = HashSet::new();
     senders.insert(SenderInfo {
         chain_id: options.chain_id,
-        private_key: "0x000000".to_string(),
+        address: "0x000000".to_string(),
+        signer: AccountSigner::Local(LocalWallet::new(&mut rand::thread_rng())),
+        available: true,
         supported_erc20_tokens: vec!["MTT".to_string(), "ETH".to_string()],
         producer: Arc::new(Box::new(options.producer) as Box<dyn ProducerHandler<Error = RelayerServerError>>),
+        drained: Arc::new(Notify::new()),
+        lease: LeaseState::always_held(),
     });
 
     let consumers = vec![Box::new(options.consumer) as Box<dyn ConsumerHandler>];
-    let senders = Arc::new(senders);
+    let senders = Arc::new(SenderSet::new(senders));
     // spawn consumer
     for mut consumer in consumers {
         tokio::spawn(async move {
@@ -108,11 +238,18 @@ async fn create_app(
         });
     }
 
+    let rate_limiter = Arc::new(RateLimiter::new(context.server_config.load().rate_limit.clone()));
+
     // run http server
     let app = init_service(
         App::new()
+            .wrap(from_fn(require_admin_client_certificate))
+            .wrap(from_fn(rate_limit))
+            .wrap(from_fn(authenticate))
+            .wrap(create_cors(&context.server_config.load().settings.cors))
             .app_data(Data::new(Arc::new(context.clone())))
             .app_data(Data::new(senders.clone()))
+            .app_data(Data::new(rate_limiter))
             .service(handshake)
             // v1
             .service(chain_status)
@@ -120,9 +257,29 @@ async fn create_app(
             .service(transact_v1)
             .service(
                 scope("/api/v2")
+                    .service(chains)
                     .service(info)
                     .service(transact)
-                    .service(transaction_status),
+                    .service(transaction_status)
+                    .service(transaction_status_batch)
+                    .service(transaction_status_stream)
+                    .service(transactions_by_hash)
+                    .service(transactions_by_signature)
+                    .service(transactions_by_serial_number)
+                    .service(ws),
+            )
+            .service(
+                scope("/api/admin")
+                    .service(list_transactions)
+                    .service(requeue_transaction)
+                    .service(fail_transaction)
+                    .service(cancel_transaction)
+                    .service(list_accounts)
+                    .service(add_account)
+                    .service(enable_account)
+                    .service(disable_account)
+                    .service(update_account_tokens)
+                    .service(retire_account),
             ),
     )
     .await;
@@ -0,0 +1,190 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{MockTokenPrice, SERVER_CONFIG_TESTNET};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app_with_config, MockOptions, CHAIN_ID};
+use actix_web::http::header::RETRY_AFTER;
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, TestRequest};
+use mystiko_relayer::configs::auth::{ApiKeyConfig, ApiKeyScope};
+use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::rate_limit::{RateLimitConfig, TokenBucketConfig};
+use mystiko_relayer::configs::server::ServerConfig;
+use mystiko_relayer::service::auth::API_KEY_HEADER;
+use mystiko_relayer::service::rate_limit::TokenBucket;
+use mystiko_relayer_types::RegisterInfoRequest;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+const PUBLIC_KEY: &str = "partner-key-0123456789";
+
+#[test]
+fn test_token_bucket() {
+    let config = TokenBucketConfig::builder()
+        .capacity(2u32)
+        .refill_per_second(0.5)
+        .build();
+    let now = Instant::now();
+    let mut bucket = TokenBucket::new(&config, now);
+    assert!(bucket.try_acquire(now).is_ok());
+    assert!(bucket.try_acquire(now).is_ok());
+    assert_eq!(bucket.try_acquire(now), Err(Duration::from_secs(2)));
+
+    let later = now + Duration::from_secs(1);
+    assert_eq!(bucket.try_acquire(later), Err(Duration::from_secs(1)));
+    let later = now + Duration::from_secs(2);
+    assert!(bucket.try_acquire(later).is_ok());
+
+    // refilled tokens never exceed the capacity
+    let later = now + Duration::from_secs(3600);
+    assert!(bucket.try_acquire(later).is_ok());
+    assert!(bucket.try_acquire(later).is_ok());
+    assert!(bucket.try_acquire(later).is_err());
+}
+
+#[actix_rt::test]
+async fn test_rate_limit_client() {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.rate_limit = RateLimitConfig::builder()
+        .enabled(true)
+        .client(
+            TokenBucketConfig::builder()
+                .capacity(1u32)
+                .refill_per_second(0.01)
+                .build(),
+        )
+        .build();
+    let app = create_app_with_config(mock_options(), server_config).await.unwrap();
+
+    let request = handshake_request("10.0.0.1:1000");
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+    let request = handshake_request("10.0.0.1:2000");
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "100");
+
+    // other clients have their own bucket
+    let request = handshake_request("10.0.0.2:1000");
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_rate_limit_api_key() {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    enable_auth(&mut server_config);
+    server_config.rate_limit = RateLimitConfig::builder()
+        .enabled(true)
+        .client(
+            TokenBucketConfig::builder()
+                .capacity(1u32)
+                .refill_per_second(0.01)
+                .build(),
+        )
+        .build();
+    let app = create_app_with_config(mock_options(), server_config).await.unwrap();
+
+    // an api key shares its bucket across addresses
+    let request = TestRequest::get()
+        .uri("/handshake")
+        .peer_addr("10.0.0.1:1000".parse::<SocketAddr>().unwrap())
+        .insert_header((API_KEY_HEADER, PUBLIC_KEY))
+        .to_request();
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+    let request = TestRequest::get()
+        .uri("/handshake")
+        .peer_addr("10.0.0.2:1000".parse::<SocketAddr>().unwrap())
+        .insert_header((API_KEY_HEADER, PUBLIC_KEY))
+        .to_request();
+    assert_eq!(
+        call_service(&app, request).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+#[actix_rt::test]
+async fn test_rate_limit_chain() {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.rate_limit = RateLimitConfig::builder()
+        .enabled(true)
+        .chain(
+            TokenBucketConfig::builder()
+                .capacity(1u32)
+                .refill_per_second(0.01)
+                .build(),
+        )
+        .build();
+    let app = create_app_with_config(mock_options(), server_config).await.unwrap();
+
+    let request = info_request(1234, "10.0.0.1:1000");
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+    let request = info_request(1234, "10.0.0.1:1000");
+    assert_eq!(
+        call_service(&app, request).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    // other chains and other clients of the same chain have their own buckets
+    let request = info_request(4321, "10.0.0.1:1000");
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+    let request = info_request(1234, "10.0.0.2:1000");
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+    // requests without a chain id are not limited by chain
+    let request = handshake_request("10.0.0.2:1000");
+    assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_rate_limit_disabled() {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.rate_limit = RateLimitConfig::builder()
+        .enabled(false)
+        .client(
+            TokenBucketConfig::builder()
+                .capacity(1u32)
+                .refill_per_second(0.01)
+                .build(),
+        )
+        .build();
+    let app = create_app_with_config(mock_options(), server_config).await.unwrap();
+    for _ in 0..3 {
+        let request = handshake_request("10.0.0.1:1000");
+        assert_eq!(call_service(&app, request).await.status(), StatusCode::OK);
+    }
+}
+
+fn handshake_request(peer_addr: &str) -> actix_http::Request {
+    TestRequest::get()
+        .uri("/handshake")
+        .peer_addr(peer_addr.parse::<SocketAddr>().unwrap())
+        .to_request()
+}
+
+fn info_request(chain_id: u64, peer_addr: &str) -> actix_http::Request {
+    TestRequest::post()
+        .uri("/api/v2/info")
+        .peer_addr(peer_addr.parse::<SocketAddr>().unwrap())
+        .set_json(RegisterInfoRequest::builder().chain_id(chain_id).build())
+        .to_request()
+}
+
+fn enable_auth(server_config: &mut ServerConfig) {
+    server_config.auth.enabled = true;
+    server_config.auth.keys = HashMap::from([(
+        "partner".to_string(),
+        ApiKeyConfig::builder()
+            .key(PUBLIC_KEY)
+            .scope(ApiKeyScope::Public)
+            .build(),
+    )]);
+}
+
+fn mock_options() -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler: MockTransactions::new(),
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
@@ -0,0 +1,125 @@
+use crate::common::{create_default_database_in_memory, SERVER_CONFIG_TESTNET};
+use actix_web::middleware::from_fn;
+use actix_web::web::{get, Data};
+use actix_web::{App, HttpServer};
+use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::tls::TlsConfig;
+use mystiko_relayer::context::Context;
+use mystiko_relayer::service::handshake;
+use mystiko_relayer::service::tls::{
+    create_tls_config, require_admin_client_certificate, tls_on_connect, TlsCertResolver,
+};
+use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa};
+use reqwest::StatusCode;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+#[test]
+fn test_tls_cert_reload() {
+    let dir = tempdir().unwrap();
+    let ca = create_ca();
+    let config = write_cert(dir.path(), &ca, "localhost", None);
+    let resolver = TlsCertResolver::new(config.clone()).unwrap();
+    let first = resolver.certificate().cert[0].clone();
+    assert!(!resolver.reload_if_changed().unwrap());
+
+    write_cert(dir.path(), &ca, "localhost", None);
+    assert!(resolver.reload_if_changed().unwrap());
+    let second = resolver.certificate().cert[0].clone();
+    assert_ne!(first, second);
+
+    // a broken certificate keeps the current one
+    std::fs::write(&config.cert_path, "not a certificate").unwrap();
+    assert!(resolver.reload_if_changed().is_err());
+    assert_eq!(resolver.certificate().cert[0], second);
+}
+
+#[actix_rt::test]
+async fn test_tls_server_with_admin_client_certificate() {
+    let dir = tempdir().unwrap();
+    let ca = create_ca();
+    let ca_path = dir.path().join("ca.pem");
+    std::fs::write(&ca_path, ca.serialize_pem().unwrap()).unwrap();
+    let tls = write_cert(
+        dir.path(),
+        &ca,
+        "localhost",
+        Some(ca_path.to_string_lossy().to_string()),
+    );
+    let (client_cert, client_key) = sign_cert(&ca, "admin");
+
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.settings.tls = Some(tls.clone());
+    let database = Arc::new(create_default_database_in_memory().await);
+    let context = Arc::new(Context::new(Arc::new(server_config), database).await.unwrap());
+
+    let (tls_config, _) = create_tls_config(&tls).unwrap();
+    let server = HttpServer::new(move || {
+        App::new()
+            .wrap(from_fn(require_admin_client_certificate))
+            .app_data(Data::new(context.clone()))
+            .service(handshake)
+            .route("/api/admin/ping", get().to(|| async { "pong" }))
+    })
+    .on_connect(tls_on_connect)
+    .workers(1)
+    .bind_rustls_021(("127.0.0.1", 0), tls_config)
+    .unwrap();
+    let address = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_rt::spawn(server);
+
+    let root = reqwest::Certificate::from_pem(ca.serialize_pem().unwrap().as_bytes()).unwrap();
+    let client = reqwest::Client::builder()
+        .add_root_certificate(root.clone())
+        .resolve("localhost", address)
+        .build()
+        .unwrap();
+    let identity = reqwest::Identity::from_pem(format!("{}{}", client_cert, client_key).as_bytes()).unwrap();
+    let admin_client = reqwest::Client::builder()
+        .add_root_certificate(root)
+        .identity(identity)
+        .resolve("localhost", address)
+        .build()
+        .unwrap();
+    let url = |path: &str| format!("https://localhost:{}{}", address.port(), path);
+
+    let response = client.get(url("/handshake")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = client.get(url("/api/admin/ping")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = admin_client.get(url("/api/admin/ping")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    handle.stop(false).await;
+}
+
+fn create_ca() -> Certificate {
+    let mut params = CertificateParams::new(vec![]);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.distinguished_name.push(DnType::CommonName, "relayer test ca");
+    Certificate::from_params(params).unwrap()
+}
+
+fn sign_cert(ca: &Certificate, name: &str) -> (String, String) {
+    let cert = Certificate::from_params(CertificateParams::new(vec![name.to_string()])).unwrap();
+    (
+        cert.serialize_pem_with_signer(ca).unwrap(),
+        cert.serialize_private_key_pem(),
+    )
+}
+
+fn write_cert(dir: &Path, ca: &Certificate, name: &str, admin_client_ca_path: Option<String>) -> TlsConfig {
+    let (cert, key) = sign_cert(ca, name);
+    let cert_path = dir.join("cert.pem");
+    let key_path = dir.join("key.pem");
+    std::fs::write(&cert_path, cert).unwrap();
+    std::fs::write(&key_path, key).unwrap();
+    TlsConfig::builder()
+        .cert_path(cert_path.to_string_lossy().to_string())
+        .key_path(key_path.to_string_lossy().to_string())
+        .admin_client_ca_path(admin_client_ca_path)
+        .build()
+}
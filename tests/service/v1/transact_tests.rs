@@ -1,11 +1,13 @@
 use crate::channel::{MockConsumers, MockProducers};
-use crate::common::{default_transaction, MockTokenPrice};
+use crate::common::{default_transaction, MockTokenPrice, SERVER_CONFIG_TESTNET};
 use crate::handler::{MockAccounts, MockTransactions};
-use crate::service::{create_app, MockOptions};
+use crate::service::{create_app, create_app_with_status_bus, MockOptions};
 use actix_web::test::{call_and_read_body_json, TestRequest};
 use anyhow::anyhow;
 use ethers_core::types::U256;
+use mystiko_relayer::configs::load_server_config;
 use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::handler::transaction::status::{TransactionStatusBus, TransactionStatusEvent};
 use mystiko_relayer::service::v1::request::{
     G1PointStruct, G2PointStruct, ProofStruct, TransactRequestV1, TransactionTypeV1,
 };
@@ -15,6 +17,8 @@ use mystiko_relayer_types::TransactStatus;
 use mystiko_storage::{Document, StorageError};
 use mystiko_types::{BridgeType, CircuitType};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 const CHAIN_ID: u64 = 5;
 
@@ -68,6 +72,70 @@ async fn test_success() {
     assert_eq!(response.code, ResponseCode::Successful as i32);
 }
 
+#[actix_rt::test]
+async fn test_wait_for_transaction_hash() {
+    let data = transact_request_v1();
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_is_repeated_transaction()
+        .returning(|_| Ok(false));
+    transaction_handler
+        .expect_find_by_id()
+        .withf(|id| id == "123456")
+        .returning(|id| {
+            let mut transaction = default_transaction();
+            transaction.status = TransactStatus::Queued;
+            transaction.transaction_hash = None;
+            Ok(Some(Document::new(
+                id.to_string(),
+                1234567890u64,
+                1234567891u64,
+                transaction,
+            )))
+        });
+    let mut producer = MockProducers::new();
+    producer.expect_send().returning(|_| {
+        Ok(Document::new(
+            "123456".to_string(),
+            1234567890u64,
+            1234567891u64,
+            default_transaction(),
+        ))
+    });
+    let options = MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer,
+    };
+    let status_bus = Arc::new(TransactionStatusBus::default());
+    let server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    let app = create_app_with_status_bus(options, server_config, status_bus.clone())
+        .await
+        .unwrap();
+
+    // the consumer sends the transaction while the request waits on the status bus
+    let publisher = tokio::spawn(async move {
+        let mut transaction = default_transaction();
+        transaction.status = TransactStatus::Pending;
+        transaction.transaction_hash = Some("0xabc".to_string());
+        let document = Document::new("123456".to_string(), 1234567890u64, 1234567892u64, transaction);
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            status_bus.publish(TransactionStatusEvent::from(&document));
+        }
+    });
+
+    let request = TestRequest::post().uri("/transact").set_json(data).to_request();
+    let response: ApiResponse<TransactResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert_eq!(response.data.unwrap().hash, "0xabc");
+    publisher.abort();
+}
+
 #[actix_rt::test]
 async fn test_main_success() {
     let mut data = transact_request_v1();
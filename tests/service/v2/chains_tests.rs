@@ -0,0 +1,210 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::MockTokenPrice;
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app, MockOptions, MockProvider};
+use actix_web::test::{call_and_read_body_json, TestRequest};
+use ethereum_types::U256;
+use mystiko_relayer::database::account::Account;
+use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::service::v2::response::ChainInfo;
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_storage::{Document, StorageError};
+use mystiko_types::CircuitType;
+use std::collections::HashMap;
+
+const CHAIN_ID: u64 = 5;
+const UNKNOWN_CHAIN_ID: u64 = 123456789;
+
+// a swapped fee of 10000 for the largest gas cost of chain id 5 in tests/files/relayer_config.json, 803183
+// for the main asset and 803645 for erc20 tokens
+const MAIN_SCALED_FEES: [(CircuitType, &str); 6] = [
+    (CircuitType::Transaction1x0, "6234"),
+    (CircuitType::Transaction1x1, "7690"),
+    (CircuitType::Transaction1x2, "8780"),
+    (CircuitType::Transaction2x0, "7456"),
+    (CircuitType::Transaction2x1, "8820"),
+    (CircuitType::Transaction2x2, "10000"),
+];
+const ERC20_SCALED_FEES: [(CircuitType, &str); 6] = [
+    (CircuitType::Transaction1x0, "6384"),
+    (CircuitType::Transaction1x1, "7837"),
+    (CircuitType::Transaction1x2, "8779"),
+    (CircuitType::Transaction2x0, "7604"),
+    (CircuitType::Transaction2x1, "9059"),
+    (CircuitType::Transaction2x2, "10000"),
+];
+
+#[actix_rt::test]
+async fn test_chains() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_find_all()
+        .returning(|| Ok(vec![account(UNKNOWN_CHAIN_ID, true), account(CHAIN_ID, true)]));
+    let app = create_app(mock_options(account_handler, HashMap::new(), MockTokenPrice::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::get().uri("/api/v2/chains").to_request();
+    let response: ApiResponse<Vec<ChainInfo>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let chains = response.data.unwrap();
+    assert_eq!(chains.len(), 2);
+
+    let chain = &chains[0];
+    assert_eq!(chain.chain_id, CHAIN_ID);
+    assert!(chain.support);
+    assert!(chain.available);
+    assert!(chain.relayer_contract_address.is_some());
+    assert!(chain
+        .contracts
+        .iter()
+        .any(|contract| contract.asset_symbol.eq_ignore_ascii_case("mtt")));
+    assert!(chain
+        .contracts
+        .iter()
+        .all(|contract| contract.minimum_gas_fees.is_none()));
+
+    let chain = &chains[1];
+    assert_eq!(chain.chain_id, UNKNOWN_CHAIN_ID);
+    assert!(!chain.support);
+    assert!(!chain.available);
+    assert!(chain.relayer_contract_address.is_none());
+    assert!(chain.contracts.is_empty());
+}
+
+#[actix_rt::test]
+async fn test_chains_with_fees() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_find_all()
+        .returning(|| Ok(vec![account(CHAIN_ID, true)]));
+    let mut token_price = MockTokenPrice::new();
+    token_price
+        .expect_swap()
+        .returning(|_, _, _, _, _| Ok(U256::from(10000)));
+    let provider = MockProvider::builder()
+        .base_fee_per_gas(U256::from(100000))
+        .max_fee_per_gas(U256::from(1000000))
+        .priority_fee(U256::from(10000))
+        .gas_price(U256::from(10000))
+        .build();
+    let mut providers = HashMap::new();
+    providers.insert(CHAIN_ID, provider);
+    let app = create_app(mock_options(account_handler, providers, token_price))
+        .await
+        .unwrap();
+
+    let request = TestRequest::get().uri("/api/v2/chains?fees=true").to_request();
+    let response: ApiResponse<Vec<ChainInfo>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let chains = response.data.unwrap();
+    assert_eq!(chains.len(), 1);
+    assert!(!chains[0].contracts.is_empty());
+    for contract in &chains[0].contracts {
+        // the largest fee is the swapped one, the others are scaled from it by gas cost and rounded up
+        let expected = if contract.asset_symbol.eq_ignore_ascii_case("eth") {
+            MAIN_SCALED_FEES
+        } else {
+            ERC20_SCALED_FEES
+        };
+        let fees = contract
+            .minimum_gas_fees
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|fee| (fee.circuit_type, fee.minimum_gas_fee.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(fees, expected, "fees of {}", contract.asset_symbol);
+    }
+}
+
+#[actix_rt::test]
+async fn test_chains_without_gas_price() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_find_all()
+        .returning(|| Ok(vec![account(CHAIN_ID, true)]));
+    // no provider for the chain, its fees are left out
+    let app = create_app(mock_options(account_handler, HashMap::new(), MockTokenPrice::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::get().uri("/api/v2/chains?fees=true").to_request();
+    let response: ApiResponse<Vec<ChainInfo>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let chains = response.data.unwrap();
+    assert!(!chains[0].contracts.is_empty());
+    assert!(chains[0]
+        .contracts
+        .iter()
+        .all(|contract| contract.minimum_gas_fees.is_none()));
+}
+
+#[actix_rt::test]
+async fn test_chains_unavailable() {
+    let mut account_handler = MockAccounts::new();
+    account_handler
+        .expect_find_all()
+        .returning(|| Ok(vec![account(CHAIN_ID, false)]));
+    let app = create_app(mock_options(account_handler, HashMap::new(), MockTokenPrice::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::get().uri("/api/v2/chains").to_request();
+    let response: ApiResponse<Vec<ChainInfo>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let chains = response.data.unwrap();
+    assert!(chains[0].support);
+    assert!(!chains[0].available);
+    assert!(chains[0].contracts.is_empty());
+}
+
+#[actix_rt::test]
+async fn test_chains_database_error() {
+    let mut account_handler = MockAccounts::new();
+    account_handler.expect_find_all().returning(|| {
+        Err(RelayerServerError::StorageError(StorageError::NoSuchColumnError(
+            "error".to_string(),
+        )))
+    });
+    let app = create_app(mock_options(account_handler, HashMap::new(), MockTokenPrice::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::get().uri("/api/v2/chains").to_request();
+    let response: ApiResponse<Vec<ChainInfo>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::DatabaseError as i32);
+}
+
+fn account(chain_id: u64, available: bool) -> Document<Account> {
+    Document::new(
+        format!("account-{}", chain_id),
+        1234567890u64,
+        1234567891u64,
+        Account {
+            chain_address: "0x1234567890".to_string(),
+            chain_id,
+            available,
+            supported_erc20_tokens: vec!["mtt".to_string()],
+            balance_alarm_threshold: 0.0,
+            balance_check_interval_ms: 0,
+            insufficient_balances: false,
+        },
+    )
+}
+
+fn mock_options(
+    account_handler: MockAccounts,
+    providers: HashMap<u64, MockProvider>,
+    token_price: MockTokenPrice,
+) -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers,
+        transaction_handler: MockTransactions::new(),
+        account_handler,
+        token_price,
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
@@ -0,0 +1,311 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{default_transaction, MockTokenPrice, SERVER_CONFIG_TESTNET};
+use crate::handler::{MockAccounts, MockQuotes, MockTransactions};
+use crate::service::v2::transact_tests::{mock_options, transact_request_data};
+use crate::service::{create_app, create_app_with_quotes, MockOptions, MockProvider};
+use actix_http::body::BoxBody;
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::test::{call_and_read_body_json, TestRequest};
+use actix_web::Error;
+use ethereum_types::U256;
+use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::quote::QuoteConfig;
+use mystiko_relayer::configs::server::ServerConfig;
+use mystiko_relayer::database::account::Account;
+use mystiko_relayer::database::quote::AcceptedQuote;
+use mystiko_relayer::service::quote::FeeQuote;
+use mystiko_relayer::service::v2::request::{RegisterInfoRequestV2, TransactRequestV2};
+use mystiko_relayer::service::v2::response::RegisterInfoResponseV2;
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_relayer_types::{RegisterInfoRequest, RegisterOptions, RelayTransactResponse, TransactRequestData};
+use mystiko_storage::Document;
+use mystiko_types::CircuitType;
+use std::collections::HashMap;
+
+const CHAIN_ID: u64 = 5;
+const QUOTE_SECRET: &str = "quote-secret-0123456789";
+// below the relayer fee amount of transact_request_data
+const MINIMUM_GAS_FEE: &str = "1000000000000000";
+
+#[test]
+fn test_sign_and_verify() {
+    let config = quote_config();
+    let quote = FeeQuote::new(&config, CHAIN_ID, "MTT", CircuitType::Transaction1x0, MINIMUM_GAS_FEE);
+    assert_eq!(quote.quote_id.len(), 32);
+    assert_eq!(quote.signature, quote.sign(QUOTE_SECRET));
+    assert!(quote.verify(QUOTE_SECRET).is_ok());
+    assert!(quote.verify("another-secret-0123456789").is_err());
+
+    let mut tampered = quote.clone();
+    tampered.minimum_gas_fee = "1".to_string();
+    assert!(tampered.verify(QUOTE_SECRET).is_err());
+
+    // the asset symbol is signed case insensitively
+    let mut lowercase = quote.clone();
+    lowercase.asset_symbol = "mtt".to_string();
+    assert!(lowercase.verify(QUOTE_SECRET).is_ok());
+
+    let mut expired = quote;
+    expired.expires_at = 1;
+    expired.signature = expired.sign(QUOTE_SECRET);
+    assert!(expired.verify(QUOTE_SECRET).unwrap_err().contains("expired"));
+}
+
+#[actix_rt::test]
+async fn test_info_with_quote() {
+    let app = create_app_with_quotes(info_options(), quote_server_config(), MockQuotes::new())
+        .await
+        .unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(info_request(true))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let data = response.data.unwrap();
+    let contract = &data.info.contracts.unwrap()[0];
+    let quote = data.quote.unwrap();
+    assert_eq!(quote.chain_id, CHAIN_ID);
+    assert!(quote.asset_symbol.eq_ignore_ascii_case("mtt"));
+    assert_eq!(quote.circuit_type, CircuitType::Transaction1x0);
+    assert_eq!(Some(quote.minimum_gas_fee.clone()), contract.minimum_gas_fee);
+    assert!(quote.verify(QUOTE_SECRET).is_ok());
+}
+
+#[actix_rt::test]
+async fn test_info_without_quote() {
+    let app = create_app_with_quotes(info_options(), quote_server_config(), MockQuotes::new())
+        .await
+        .unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(info_request(false))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(response.data.unwrap().quote.is_none());
+}
+
+#[actix_rt::test]
+async fn test_info_quote_disabled() {
+    let app = create_app(info_options()).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(info_request(true))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_transact_with_quote() {
+    let data = transact_request_data();
+    let signature = data.signature.clone();
+    let quote = FeeQuote::new(
+        &quote_config(),
+        CHAIN_ID,
+        "MTT",
+        CircuitType::Transaction1x0,
+        MINIMUM_GAS_FEE,
+    );
+    let quote_id = quote.quote_id.clone();
+    let mut quote_handler = MockQuotes::new();
+    quote_handler
+        .expect_save()
+        .withf(move |sig, id, fee| sig == signature && id == quote_id && fee == MINIMUM_GAS_FEE)
+        .times(1)
+        .returning(|signature, quote_id, minimum_gas_fee| {
+            Ok(Document::new(
+                "quote".to_string(),
+                1234567890u64,
+                1234567890u64,
+                AcceptedQuote {
+                    signature: signature.to_string(),
+                    quote_id: quote_id.to_string(),
+                    minimum_gas_fee: minimum_gas_fee.to_string(),
+                },
+            ))
+        });
+    let app = create_app_with_quotes(transact_options(1), quote_server_config(), quote_handler)
+        .await
+        .unwrap();
+
+    let response = transact(&app, data, Some(quote)).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert_eq!(response.data.unwrap().uuid, "123456");
+}
+
+#[actix_rt::test]
+async fn test_transact_without_quote() {
+    let data = transact_request_data();
+    let signature = data.signature.clone();
+    let mut quote_handler = MockQuotes::new();
+    quote_handler.expect_save().never();
+    // a resubmitted signature must not reuse an older quote
+    quote_handler
+        .expect_delete()
+        .withf(move |sig| sig == signature)
+        .times(1)
+        .returning(|_| Ok(()));
+    let app = create_app_with_quotes(transact_options(1), quote_server_config(), quote_handler)
+        .await
+        .unwrap();
+
+    let response = transact(&app, data, None).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+}
+
+#[actix_rt::test]
+async fn test_transact_with_invalid_quote() {
+    let config = quote_config();
+    let quote = FeeQuote::new(&config, CHAIN_ID, "MTT", CircuitType::Transaction1x0, MINIMUM_GAS_FEE);
+
+    let mut expired = quote.clone();
+    expired.expires_at = 1;
+    expired.signature = expired.sign(QUOTE_SECRET);
+    let mut tampered = quote.clone();
+    tampered.minimum_gas_fee = "1".to_string();
+    let other_asset = FeeQuote::new(&config, CHAIN_ID, "ETH", CircuitType::Transaction1x0, MINIMUM_GAS_FEE);
+    let other_circuit = FeeQuote::new(&config, CHAIN_ID, "MTT", CircuitType::Transaction2x0, MINIMUM_GAS_FEE);
+    let other_chain = FeeQuote::new(&config, 97, "MTT", CircuitType::Transaction1x0, MINIMUM_GAS_FEE);
+    // more than the relayer fee amount of the request
+    let insufficient_fee = FeeQuote::new(
+        &config,
+        CHAIN_ID,
+        "MTT",
+        CircuitType::Transaction1x0,
+        "5000000000000000",
+    );
+
+    for quote in [
+        expired,
+        tampered,
+        other_asset,
+        other_circuit,
+        other_chain,
+        insufficient_fee,
+    ] {
+        let mut quote_handler = MockQuotes::new();
+        quote_handler.expect_save().never();
+        let app = create_app_with_quotes(transact_options(0), quote_server_config(), quote_handler)
+            .await
+            .unwrap();
+        let response = transact(&app, transact_request_data(), Some(quote)).await;
+        assert_eq!(response.code, ResponseCode::ValidateError as i32);
+    }
+}
+
+#[actix_rt::test]
+async fn test_transact_quote_disabled() {
+    let config = quote_config();
+    let quote = FeeQuote::new(&config, CHAIN_ID, "MTT", CircuitType::Transaction1x0, MINIMUM_GAS_FEE);
+    let app = create_app(transact_options(0)).await.unwrap();
+
+    let response = transact(&app, transact_request_data(), Some(quote)).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+async fn transact<S>(app: &S, data: TransactRequestData, quote: Option<FeeQuote>) -> ApiResponse<RelayTransactResponse>
+where
+    S: Service<Request, Response = ServiceResponse<BoxBody>, Error = Error>,
+{
+    let request = TransactRequestV2 {
+        data,
+        callback_url: None,
+        quote,
+    };
+    let request = TestRequest::post()
+        .uri("/api/v2/transact")
+        .set_json(request)
+        .to_request();
+    call_and_read_body_json(app, request).await
+}
+
+fn transact_options(sent: usize) -> MockOptions {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_is_repeated_transaction()
+        .returning(|_| Ok(false));
+    let mut producer = MockProducers::new();
+    producer.expect_send().times(sent).returning(|_| {
+        Ok(Document::new(
+            "123456".to_string(),
+            1234567890u64,
+            1234567891u64,
+            default_transaction(),
+        ))
+    });
+    mock_options(transaction_handler, producer)
+}
+
+fn info_options() -> MockOptions {
+    let mut account_handler = MockAccounts::new();
+    account_handler.expect_find_by_chain_id().returning(|chain_id| {
+        Ok(vec![Document::new(
+            "123456".to_string(),
+            1234567890u64,
+            1234567891u64,
+            Account {
+                chain_address: "0x1234567890".to_string(),
+                chain_id,
+                available: true,
+                supported_erc20_tokens: vec!["mtt".to_string()],
+                balance_alarm_threshold: 0.0,
+                balance_check_interval_ms: 0,
+                insufficient_balances: false,
+            },
+        )])
+    });
+    let mut token_price = MockTokenPrice::new();
+    token_price
+        .expect_swap()
+        .returning(|_, _, _, _, _| Ok(U256::from(10000)));
+    let provider = MockProvider::builder()
+        .base_fee_per_gas(U256::from(100000))
+        .max_fee_per_gas(U256::from(1000000))
+        .priority_fee(U256::from(10000))
+        .gas_price(U256::from(10000))
+        .build();
+    let mut providers = HashMap::new();
+    providers.insert(CHAIN_ID, provider);
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers,
+        transaction_handler: MockTransactions::new(),
+        account_handler,
+        token_price,
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
+
+fn info_request(quote: bool) -> RegisterInfoRequestV2 {
+    RegisterInfoRequestV2 {
+        data: RegisterInfoRequest::builder()
+            .chain_id(CHAIN_ID)
+            .options(
+                RegisterOptions::builder()
+                    .asset_symbol("mtt")
+                    .circuit_type(CircuitType::Transaction1x0)
+                    .show_unavailable(false)
+                    .build(),
+            )
+            .build(),
+        quote,
+        all_fees: false,
+    }
+}
+
+fn quote_config() -> QuoteConfig {
+    QuoteConfig::builder().enabled(true).secret(QUOTE_SECRET).build()
+}
+
+fn quote_server_config() -> ServerConfig {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.quote = quote_config();
+    server_config
+}
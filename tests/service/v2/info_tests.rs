@@ -6,12 +6,16 @@ use actix_web::test::{call_and_read_body_json, TestRequest};
 use ethereum_types::U256;
 use mystiko_relayer::database::account::Account;
 use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::service::v2::request::RegisterInfoRequestV2;
+use mystiko_relayer::service::v2::response::RegisterInfoResponseV2;
 use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
 use mystiko_relayer_types::{RegisterInfoRequest, RegisterInfoResponse, RegisterOptions};
 use mystiko_server_utils::token_price::PriceMiddlewareError;
 use mystiko_storage::{Document, StorageError};
 use mystiko_types::CircuitType;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 const CHAIN_ID: u64 = 5;
 
@@ -614,3 +618,252 @@ async fn test_with_minimum_gas_fee_error() {
     let response: ApiResponse<RegisterInfoResponse> = call_and_read_body_json(&app, request).await;
     assert_eq!(response.code, ResponseCode::GetMinimumGasFeeFailed as i32);
 }
+
+#[actix_rt::test]
+async fn test_all_fees() {
+    let swaps = Arc::new(AtomicUsize::new(0));
+    let counted = swaps.clone();
+    let mut token_price = MockTokenPrice::new();
+    token_price.expect_swap().returning(move |_, _, amount, _, _| {
+        counted.fetch_add(1, Ordering::SeqCst);
+        Ok(amount + U256::from(1))
+    });
+    let provider = MockProvider::builder()
+        .base_fee_per_gas(U256::from(100000))
+        .max_fee_per_gas(U256::from(1000000))
+        .priority_fee(U256::from(10000))
+        .gas_price(U256::from(10000))
+        .build();
+    let mut providers = HashMap::new();
+    providers.insert(CHAIN_ID, provider);
+    let app = create_app(all_fees_options(providers, token_price)).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(all_fees_request(CHAIN_ID))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let data = response.data.unwrap();
+    let contracts = data.info.contracts.unwrap();
+    assert!(contracts.iter().all(|contract| contract.minimum_gas_fee.is_none()));
+    let fees = data.minimum_gas_fees.unwrap();
+    assert_eq!(fees.len(), contracts.len());
+    for (asset_fees, contract) in fees.iter().zip(contracts.iter()) {
+        assert_eq!(asset_fees.asset_symbol, contract.asset_symbol);
+        let minimum_gas_fees = asset_fees.minimum_gas_fees.as_ref().unwrap();
+        assert!(minimum_gas_fees
+            .iter()
+            .any(|fee| fee.circuit_type == CircuitType::Transaction1x0));
+        assert!(minimum_gas_fees
+            .iter()
+            .all(|fee| U256::from_dec_str(&fee.minimum_gas_fee).unwrap() > U256::zero()));
+    }
+    // one swap per asset, not per circuit type
+    assert_eq!(swaps.load(Ordering::SeqCst), fees.len());
+}
+
+#[actix_rt::test]
+async fn test_all_fees_not_requested() {
+    let app = create_app(all_fees_options(HashMap::new(), MockTokenPrice::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(RegisterInfoRequest::builder().chain_id(CHAIN_ID).build())
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert!(response.data.unwrap().minimum_gas_fees.is_none());
+}
+
+#[actix_rt::test]
+async fn test_all_fees_unsupported_chain() {
+    let app = create_app(all_fees_options(HashMap::new(), MockTokenPrice::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(all_fees_request(123456789))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let data = response.data.unwrap();
+    assert!(!data.info.support);
+    assert!(data.minimum_gas_fees.is_none());
+}
+
+#[actix_rt::test]
+async fn test_all_fees_with_gas_price_error() {
+    let app = create_app(all_fees_options(HashMap::new(), MockTokenPrice::new()))
+        .await
+        .unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(all_fees_request(CHAIN_ID))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::GetGasPriceError as i32);
+}
+
+#[actix_rt::test]
+async fn test_all_fees_with_minimum_gas_fee_error() {
+    let mut token_price = MockTokenPrice::new();
+    token_price
+        .expect_swap()
+        .returning(|_, _, _, _, _| Err(PriceMiddlewareError::InternalError));
+    let provider = MockProvider::builder()
+        .base_fee_per_gas(U256::from(100000))
+        .max_fee_per_gas(U256::from(1000000))
+        .priority_fee(U256::from(10000))
+        .gas_price(U256::from(10000))
+        .build();
+    let mut providers = HashMap::new();
+    providers.insert(CHAIN_ID, provider);
+    let app = create_app(all_fees_options(providers, token_price)).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(all_fees_request(CHAIN_ID))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let fees = response.data.unwrap().minimum_gas_fees.unwrap();
+    assert!(!fees.is_empty());
+    assert!(fees.iter().all(|asset_fees| asset_fees.minimum_gas_fees.is_none()));
+}
+
+#[actix_rt::test]
+async fn test_all_fees_with_one_asset_error() {
+    let mut token_price = MockTokenPrice::new();
+    token_price.expect_swap().returning(|_, _, amount, asset_b, _| {
+        if asset_b.eq_ignore_ascii_case("mtt") {
+            Err(PriceMiddlewareError::InternalError)
+        } else {
+            Ok(amount)
+        }
+    });
+    let provider = MockProvider::builder()
+        .base_fee_per_gas(U256::from(100000))
+        .max_fee_per_gas(U256::from(1000000))
+        .priority_fee(U256::from(10000))
+        .gas_price(U256::from(10000))
+        .build();
+    let mut providers = HashMap::new();
+    providers.insert(CHAIN_ID, provider);
+    let app = create_app(all_fees_options(providers, token_price)).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(all_fees_request(CHAIN_ID))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let fees = response.data.unwrap().minimum_gas_fees.unwrap();
+    assert!(fees.len() > 1);
+    for asset_fees in fees {
+        assert_eq!(
+            asset_fees.minimum_gas_fees.is_none(),
+            asset_fees.asset_symbol.eq_ignore_ascii_case("mtt"),
+            "{}",
+            asset_fees.asset_symbol
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_all_fees_agree_with_options() {
+    let mut token_price = MockTokenPrice::new();
+    // not proportional, a fee swapped on its own would differ from a scaled one
+    token_price
+        .expect_swap()
+        .returning(|_, _, amount, _, _| Ok(amount / U256::from(3) + U256::from(7)));
+    let provider = MockProvider::builder()
+        .base_fee_per_gas(U256::from(100000))
+        .max_fee_per_gas(U256::from(1000000))
+        .priority_fee(U256::from(10000))
+        .gas_price(U256::from(10000))
+        .build();
+    let mut providers = HashMap::new();
+    providers.insert(CHAIN_ID, provider);
+    let app = create_app(all_fees_options(providers, token_price)).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/info")
+        .set_json(all_fees_request(CHAIN_ID))
+        .to_request();
+    let response: ApiResponse<RegisterInfoResponseV2> = call_and_read_body_json(&app, request).await;
+    let fees = response.data.unwrap().minimum_gas_fees.unwrap();
+    let fees = fees
+        .iter()
+        .find(|asset_fees| asset_fees.asset_symbol.eq_ignore_ascii_case("mtt"))
+        .and_then(|asset_fees| asset_fees.minimum_gas_fees.as_ref())
+        .unwrap();
+    assert!(!fees.is_empty());
+    for fee in fees {
+        let request = TestRequest::post()
+            .uri("/api/v2/info")
+            .set_json(
+                RegisterInfoRequest::builder()
+                    .chain_id(CHAIN_ID)
+                    .options(
+                        RegisterOptions::builder()
+                            .asset_symbol("MTT")
+                            .circuit_type(fee.circuit_type)
+                            .show_unavailable(false)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .to_request();
+        let response: ApiResponse<RegisterInfoResponse> = call_and_read_body_json(&app, request).await;
+        assert_eq!(response.code, ResponseCode::Successful as i32);
+        let contracts = response.data.unwrap().contracts.unwrap();
+        assert_eq!(
+            contracts[0].minimum_gas_fee.as_deref(),
+            Some(fee.minimum_gas_fee.as_str()),
+            "{:?}",
+            fee.circuit_type
+        );
+    }
+}
+
+fn all_fees_request(chain_id: u64) -> RegisterInfoRequestV2 {
+    RegisterInfoRequestV2 {
+        data: RegisterInfoRequest::builder().chain_id(chain_id).build(),
+        quote: false,
+        all_fees: true,
+    }
+}
+
+fn all_fees_options(providers: HashMap<u64, MockProvider>, token_price: MockTokenPrice) -> MockOptions {
+    let mut account_handler = MockAccounts::new();
+    account_handler.expect_find_by_chain_id().returning(|chain_id| {
+        Ok(vec![Document::new(
+            "123456".to_string(),
+            1234567890u64,
+            1234567891u64,
+            Account {
+                chain_address: "0x1234567890".to_string(),
+                chain_id,
+                available: true,
+                supported_erc20_tokens: vec!["mtt".to_string()],
+                balance_alarm_threshold: 0.0,
+                balance_check_interval_ms: 0,
+                insufficient_balances: false,
+            },
+        )])
+    });
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers,
+        transaction_handler: MockTransactions::new(),
+        account_handler,
+        token_price,
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
@@ -1,3 +1,9 @@
+mod chains_tests;
+mod fee_quote_tests;
 mod info_tests;
 mod transact_tests;
+mod transaction_lookup_tests;
+mod transaction_status_batch_tests;
+mod transaction_status_stream_tests;
 mod transaction_status_tests;
+mod ws_tests;
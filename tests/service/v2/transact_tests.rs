@@ -1,14 +1,20 @@
 use crate::channel::{MockConsumers, MockProducers};
+use crate::common::SERVER_CONFIG_TESTNET;
 use crate::common::{default_transaction, MockTokenPrice};
-use crate::handler::{MockAccounts, MockTransactions};
-use crate::service::{create_app, MockOptions};
+use crate::handler::{MockAccounts, MockTransactions, MockWebhooks};
+use crate::service::{create_app, create_app_with_webhooks, MockOptions};
 use actix_web::test::{call_and_read_body_json, TestRequest};
 use anyhow::anyhow;
 use ethereum_types::U256;
 use ethers_core::types::Bytes;
 use mystiko_abi::commitment_pool::TransactRequest;
 use mystiko_protos::core::v1::SpendType;
+use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::server::ServerConfig;
+use mystiko_relayer::configs::webhook::WebhookConfig;
+use mystiko_relayer::database::webhook::TransactionCallback;
 use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::service::v2::request::TransactRequestV2;
 use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
 use mystiko_relayer_types::{RelayTransactResponse, TransactRequestData, TransactStatus};
 use mystiko_storage::{Document, StorageError};
@@ -299,7 +305,205 @@ async fn test_with_send_error() {
     assert_eq!(response.code, ResponseCode::TransactionChannelError as i32);
 }
 
-fn transact_request_data() -> TransactRequestData {
+#[actix_rt::test]
+async fn test_callback_url() {
+    let data = transact_request_data();
+    let signature = data.signature.clone();
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_is_repeated_transaction()
+        .returning(|_| Ok(false));
+    let mut producer = MockProducers::new();
+    producer.expect_send().times(1).returning(|_| {
+        Ok(Document::new(
+            "123456".to_string(),
+            1234567890u64,
+            1234567891u64,
+            default_transaction(),
+        ))
+    });
+    let mut webhook_handler = MockWebhooks::new();
+    webhook_handler
+        .expect_save_callback()
+        .withf(move |sig, url| sig == signature && url == "https://example.com/hooks")
+        .times(1)
+        .returning(|signature, url| {
+            Ok(Document::new(
+                "callback".to_string(),
+                1234567890u64,
+                1234567890u64,
+                TransactionCallback {
+                    signature: signature.to_string(),
+                    url: url.to_string(),
+                },
+            ))
+        });
+    let app = create_app_with_webhooks(
+        mock_options(transaction_handler, producer),
+        webhook_server_config(),
+        webhook_handler,
+    )
+    .await
+    .unwrap();
+
+    let request = TransactRequestV2 {
+        data,
+        callback_url: Some("https://example.com/hooks".to_string()),
+        quote: None,
+    };
+    let request = TestRequest::post()
+        .uri("/api/v2/transact")
+        .set_json(request)
+        .to_request();
+    let response: ApiResponse<RelayTransactResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert_eq!(response.data.unwrap().uuid, "123456");
+}
+
+#[actix_rt::test]
+async fn test_invalid_callback_url() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_is_repeated_transaction()
+        .returning(|_| Ok(false));
+    let mut producer = MockProducers::new();
+    producer.expect_send().never();
+    let mut webhook_handler = MockWebhooks::new();
+    webhook_handler.expect_save_callback().never();
+    let app = create_app_with_webhooks(
+        mock_options(transaction_handler, producer),
+        webhook_server_config(),
+        webhook_handler,
+    )
+    .await
+    .unwrap();
+
+    let request = TransactRequestV2 {
+        data: transact_request_data(),
+        callback_url: Some("ftp://example.com/hooks".to_string()),
+        quote: None,
+    };
+    let request = TestRequest::post()
+        .uri("/api/v2/transact")
+        .set_json(request)
+        .to_request();
+    let response: ApiResponse<RelayTransactResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_non_public_callback_url() {
+    let mut server_config = webhook_server_config();
+    server_config.webhook.callback_allowed_hosts = vec![];
+    for callback_url in [
+        "http://127.0.0.1:8080/hooks",
+        "http://169.254.169.254/latest/meta-data",
+        "http://10.0.0.1/hooks",
+        "http://[::1]/hooks",
+        "http://[::ffff:192.168.1.1]/hooks",
+    ] {
+        let mut transaction_handler = MockTransactions::new();
+        transaction_handler
+            .expect_is_repeated_transaction()
+            .returning(|_| Ok(false));
+        let mut producer = MockProducers::new();
+        producer.expect_send().never();
+        let mut webhook_handler = MockWebhooks::new();
+        webhook_handler.expect_save_callback().never();
+        let app = create_app_with_webhooks(
+            mock_options(transaction_handler, producer),
+            server_config.clone(),
+            webhook_handler,
+        )
+        .await
+        .unwrap();
+
+        let request = TransactRequestV2 {
+            data: transact_request_data(),
+            callback_url: Some(callback_url.to_string()),
+            quote: None,
+        };
+        let request = TestRequest::post()
+            .uri("/api/v2/transact")
+            .set_json(request)
+            .to_request();
+        let response: ApiResponse<RelayTransactResponse> = call_and_read_body_json(&app, request).await;
+        assert_eq!(response.code, ResponseCode::ValidateError as i32, "{}", callback_url);
+    }
+}
+
+#[actix_rt::test]
+async fn test_callback_url_not_allowlisted() {
+    let mut producer = MockProducers::new();
+    producer.expect_send().never();
+    let mut webhook_handler = MockWebhooks::new();
+    webhook_handler.expect_save_callback().never();
+    let app = create_app_with_webhooks(
+        mock_options(MockTransactions::new(), producer),
+        webhook_server_config(),
+        webhook_handler,
+    )
+    .await
+    .unwrap();
+
+    let request = TransactRequestV2 {
+        data: transact_request_data(),
+        callback_url: Some("https://example.org/hooks".to_string()),
+        quote: None,
+    };
+    let request = TestRequest::post()
+        .uri("/api/v2/transact")
+        .set_json(request)
+        .to_request();
+    let response: ApiResponse<RelayTransactResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_callback_url_without_webhooks() {
+    let mut producer = MockProducers::new();
+    producer.expect_send().never();
+    let app = create_app(mock_options(MockTransactions::new(), producer))
+        .await
+        .unwrap();
+
+    let request = TransactRequestV2 {
+        data: transact_request_data(),
+        callback_url: Some("https://example.com/hooks".to_string()),
+        quote: None,
+    };
+    let request = TestRequest::post()
+        .uri("/api/v2/transact")
+        .set_json(request)
+        .to_request();
+    let response: ApiResponse<RelayTransactResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+pub fn mock_options(transaction_handler: MockTransactions, producer: MockProducers) -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer,
+    }
+}
+
+fn webhook_server_config() -> ServerConfig {
+    let mut server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    server_config.webhook = WebhookConfig::builder()
+        .enabled(true)
+        .secret("webhook-secret".to_string())
+        .allow_callback_url(true)
+        .callback_allowed_hosts(vec!["example.com".to_string()])
+        .build();
+    server_config
+}
+
+pub fn transact_request_data() -> TransactRequestData {
     TransactRequestData {
         contract_param: TransactRequest {
             proof: Default::default(),
@@ -0,0 +1,128 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{default_transaction, MockTokenPrice};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app, MockOptions, CHAIN_ID};
+use actix_web::test::{call_and_read_body_json, TestRequest};
+use mystiko_relayer::database::transaction::Transaction;
+use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_relayer_types::{RelayTransactStatusResponse, TransactStatus};
+use mystiko_storage::{Document, StorageError};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+
+#[actix_rt::test]
+async fn test_by_transaction_hash() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_transaction_hash()
+        .withf(|hash| hash == "0xabc")
+        .returning(|_| Ok(vec![transaction("1", 1, TransactStatus::Succeeded)]));
+    transaction_handler
+        .expect_find_by_transaction_hash()
+        .returning(|_| Ok(vec![]));
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    let request = TestRequest::get().uri("/api/v2/transaction/hash/0xabc").to_request();
+    let response: ApiResponse<Vec<RelayTransactStatusResponse>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let transactions = response.data.unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].uuid, "1");
+    assert_eq!(transactions[0].status, TransactStatus::Succeeded);
+
+    let request = TestRequest::get().uri("/api/v2/transaction/hash/0xdef").to_request();
+    let response: ApiResponse<Vec<RelayTransactStatusResponse>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::TransactionNotFound as i32);
+}
+
+#[actix_rt::test]
+async fn test_by_signature() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_signature()
+        .withf(|signature| signature == "0x1234")
+        .returning(|_| {
+            Ok(vec![
+                transaction("1", 1, TransactStatus::Failed),
+                transaction("2", 2, TransactStatus::Pending),
+            ])
+        });
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    // a resubmitted job comes before the failed one
+    let request = TestRequest::get()
+        .uri("/api/v2/transaction/signature/0x1234")
+        .to_request();
+    let response: ApiResponse<Vec<RelayTransactStatusResponse>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let ids = response
+        .data
+        .unwrap()
+        .into_iter()
+        .map(|transaction| transaction.uuid)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["2", "1"]);
+}
+
+#[actix_rt::test]
+async fn test_by_serial_number() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_serial_number()
+        .withf(|serial_number| *serial_number == BigUint::from(255u32))
+        .times(2)
+        .returning(|_| Ok(vec![transaction("1", 1, TransactStatus::Queued)]));
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    for uri in [
+        "/api/v2/transaction/serial_number/0xff",
+        "/api/v2/transaction/serial_number/255",
+    ] {
+        let request = TestRequest::get().uri(uri).to_request();
+        let response: ApiResponse<Vec<RelayTransactStatusResponse>> = call_and_read_body_json(&app, request).await;
+        assert_eq!(response.code, ResponseCode::Successful as i32);
+        assert_eq!(response.data.unwrap()[0].uuid, "1");
+    }
+
+    let request = TestRequest::get()
+        .uri("/api/v2/transaction/serial_number/0xzz")
+        .to_request();
+    let response: ApiResponse<Vec<RelayTransactStatusResponse>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_with_error() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler.expect_find_by_signature().returning(|_| {
+        Err(RelayerServerError::StorageError(StorageError::NoSuchColumnError(
+            "mock_error".to_string(),
+        )))
+    });
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/v2/transaction/signature/0x1234")
+        .to_request();
+    let response: ApiResponse<Vec<RelayTransactStatusResponse>> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::DatabaseError as i32);
+}
+
+fn transaction(id: &str, created_at: u64, status: TransactStatus) -> Document<Transaction> {
+    let mut transaction = default_transaction();
+    transaction.status = status;
+    Document::new(id.to_string(), created_at, created_at, transaction)
+}
+
+fn mock_options(transaction_handler: MockTransactions) -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
@@ -0,0 +1,105 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{default_transaction, MockTokenPrice};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app, MockOptions, CHAIN_ID};
+use actix_web::test::{call_and_read_body_json, TestRequest};
+use mystiko_relayer::database::transaction::Transaction;
+use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::service::v2::response::TransactionStatusBatchResponse;
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::{Document, StorageError};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[actix_rt::test]
+async fn test_success() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_ids()
+        .withf(|ids| ids == ["3", "1", "2"])
+        .times(1)
+        .returning(|_| {
+            // the storage order is not the request order
+            Ok(vec![
+                transaction("1", TransactStatus::Pending),
+                transaction("3", TransactStatus::Succeeded),
+            ])
+        });
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/transaction/status")
+        .set_json(json!({"ids": ["3", "1", "2", "3"]}))
+        .to_request();
+    let response: ApiResponse<TransactionStatusBatchResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    let response = response.data.unwrap();
+    assert_eq!(
+        response
+            .transactions
+            .iter()
+            .map(|transaction| (transaction.uuid.as_str(), transaction.status.clone()))
+            .collect::<Vec<_>>(),
+        vec![("3", TransactStatus::Succeeded), ("1", TransactStatus::Pending)]
+    );
+    assert_eq!(response.not_found, vec!["2"]);
+}
+
+#[actix_rt::test]
+async fn test_invalid_ids() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler.expect_find_by_ids().never();
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/transaction/status")
+        .set_json(json!({"ids": []}))
+        .to_request();
+    let response: ApiResponse<TransactionStatusBatchResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+
+    let ids = (0..101).map(|id| id.to_string()).collect::<Vec<_>>();
+    let request = TestRequest::post()
+        .uri("/api/v2/transaction/status")
+        .set_json(json!({ "ids": ids }))
+        .to_request();
+    let response: ApiResponse<TransactionStatusBatchResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_database_error() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler.expect_find_by_ids().returning(|_| {
+        Err(RelayerServerError::StorageError(StorageError::NoSuchColumnError(
+            "mock_error".to_string(),
+        )))
+    });
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    let request = TestRequest::post()
+        .uri("/api/v2/transaction/status")
+        .set_json(json!({"ids": ["1"]}))
+        .to_request();
+    let response: ApiResponse<TransactionStatusBatchResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::DatabaseError as i32);
+}
+
+fn transaction(id: &str, status: TransactStatus) -> Document<Transaction> {
+    let mut transaction = default_transaction();
+    transaction.status = status;
+    Document::new(id.to_string(), 1234567890u64, 1234567891u64, transaction)
+}
+
+fn mock_options(transaction_handler: MockTransactions) -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
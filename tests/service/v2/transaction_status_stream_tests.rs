@@ -0,0 +1,123 @@
+use crate::channel::{MockConsumers, MockProducers};
+use crate::common::{default_transaction, MockTokenPrice, SERVER_CONFIG_TESTNET};
+use crate::handler::{MockAccounts, MockTransactions};
+use crate::service::{create_app, create_app_with_status_bus, MockOptions, CHAIN_ID};
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::test::{call_and_read_body_json, call_service, read_body, TestRequest};
+use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::database::transaction::Transaction;
+use mystiko_relayer::handler::transaction::status::{TransactionStatusBus, TransactionStatusEvent};
+use mystiko_relayer::service::v2::stream::TEXT_EVENT_STREAM;
+use mystiko_relayer_types::response::{ApiResponse, ResponseCode};
+use mystiko_relayer_types::{RelayTransactStatusResponse, TransactStatus};
+use mystiko_storage::Document;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[actix_rt::test]
+async fn test_stream_final_status() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .withf(|id| id == "1")
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Succeeded))));
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/v2/transaction/status/1/stream")
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), TEXT_EVENT_STREAM);
+    // a final status closes the stream right away
+    let events = parse_events(&read_body(response).await);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].uuid, "1");
+    assert_eq!(events[0].status, TransactStatus::Succeeded);
+}
+
+#[actix_rt::test]
+async fn test_stream_status_changes() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler
+        .expect_find_by_id()
+        .withf(|id| id == "1")
+        .returning(|id| Ok(Some(transaction_with_status(id, TransactStatus::Queued))));
+    let status_bus = Arc::new(TransactionStatusBus::default());
+    let server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    let app = create_app_with_status_bus(mock_options(transaction_handler), server_config, status_bus.clone())
+        .await
+        .unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/v2/transaction/status/1/stream")
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert!(response.status().is_success());
+
+    let pending = status_event("1", TransactStatus::Pending, Some("0x123"));
+    status_bus.publish(status_event("2", TransactStatus::Failed, None));
+    status_bus.publish(pending.clone());
+    // repeated events are sent once
+    status_bus.publish(pending);
+    status_bus.publish(status_event("1", TransactStatus::Succeeded, Some("0x123")));
+    status_bus.publish(status_event("1", TransactStatus::Failed, Some("0x123")));
+
+    let events = parse_events(&read_body(response).await);
+    let statuses = events.iter().map(|event| event.status.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        vec![
+            TransactStatus::Queued,
+            TransactStatus::Pending,
+            TransactStatus::Succeeded
+        ]
+    );
+    assert_eq!(events[1].transaction_hash, Some("0x123".to_string()));
+}
+
+#[actix_rt::test]
+async fn test_stream_not_found() {
+    let mut transaction_handler = MockTransactions::new();
+    transaction_handler.expect_find_by_id().returning(|_| Ok(None));
+    let app = create_app(mock_options(transaction_handler)).await.unwrap();
+
+    let request = TestRequest::get()
+        .uri("/api/v2/transaction/status/2/stream")
+        .to_request();
+    let response: ApiResponse<RelayTransactStatusResponse> = call_and_read_body_json(&app, request).await;
+    assert_eq!(response.code, ResponseCode::TransactionNotFound as i32);
+}
+
+fn mock_options(transaction_handler: MockTransactions) -> MockOptions {
+    MockOptions {
+        chain_id: CHAIN_ID,
+        providers: HashMap::new(),
+        transaction_handler,
+        account_handler: MockAccounts::new(),
+        token_price: MockTokenPrice::new(),
+        consumer: MockConsumers::new(),
+        producer: MockProducers::new(),
+    }
+}
+
+fn transaction_with_status(id: &str, status: TransactStatus) -> Document<Transaction> {
+    let mut transaction = default_transaction();
+    transaction.status = status;
+    transaction.transaction_hash = None;
+    Document::new(id.to_string(), 1234567890u64, 1234567891u64, transaction)
+}
+
+fn status_event(id: &str, status: TransactStatus, transaction_hash: Option<&str>) -> TransactionStatusEvent {
+    let mut event = TransactionStatusEvent::from(&transaction_with_status(id, status));
+    event.transaction_hash = transaction_hash.map(|hash| hash.to_string());
+    event
+}
+
+fn parse_events(body: &[u8]) -> Vec<RelayTransactStatusResponse> {
+    String::from_utf8_lossy(body)
+        .split("\n\n")
+        .filter_map(|frame| frame.strip_prefix("event: status\ndata: "))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect()
+}
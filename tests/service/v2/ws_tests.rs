@@ -0,0 +1,242 @@
+use crate::channel::MockProducers;
+use crate::common::{
+    create_default_database_in_memory, default_transact_request_data, default_transaction, SERVER_CONFIG_TESTNET,
+};
+use crate::service::v2::transact_tests::transact_request_data;
+use actix_web::web::{scope, Data};
+use actix_web::{App, HttpServer};
+use ethers_signers::LocalWallet;
+use futures::{SinkExt, StreamExt};
+use mystiko_relayer::channel::lease::LeaseState;
+use mystiko_relayer::channel::producer::ProducerHandler;
+use mystiko_relayer::channel::{SenderInfo, SenderSet};
+use mystiko_relayer::configs::load_server_config;
+use mystiko_relayer::configs::rate_limit::{RateLimitConfig, TokenBucketConfig};
+use mystiko_relayer::context::Context;
+use mystiko_relayer::error::RelayerServerError;
+use mystiko_relayer::handler::transaction::status::TransactionStatusEvent;
+use mystiko_relayer::handler::transaction::{TransactionHandler, UpdateTransactionOptions};
+use mystiko_relayer::service::rate_limit::RateLimiter;
+use mystiko_relayer::service::v2::ws::{ws, WsMessage, WsMessageType};
+use mystiko_relayer::signer::AccountSigner;
+use mystiko_relayer_types::response::ResponseCode;
+use mystiko_relayer_types::TransactStatus;
+use mystiko_storage::Document;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[actix_rt::test]
+async fn test_subscribe_transactions() {
+    let (context, address) = start_server(MockProducers::new()).await;
+    let mut client = connect(address).await;
+    let transaction = context
+        .transaction_handler
+        .create_by_request(default_transact_request_data(5))
+        .await
+        .unwrap();
+
+    send(
+        &mut client,
+        json!({"type": "subscribe", "requestId": "r1", "ids": [transaction.id, "unknown"]}),
+    )
+    .await;
+    let ack = next_message(&mut client).await;
+    assert_eq!(ack.message_type, WsMessageType::Subscribe);
+    assert_eq!(ack.request_id, Some("r1".to_string()));
+    let status = next_message(&mut client).await;
+    assert_eq!(status.message_type, WsMessageType::Status);
+    assert_eq!(status.data.unwrap()["status"], json!(TransactStatus::Queued));
+    let error = next_message(&mut client).await;
+    assert_eq!(error.message_type, WsMessageType::Error);
+    assert_eq!(error.code, ResponseCode::TransactionNotFound as i32);
+
+    let options = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Pending)
+        .transaction_hash("0x123".to_string())
+        .build();
+    context
+        .transaction_handler
+        .update_by_id(&transaction.id, &options)
+        .await
+        .unwrap();
+    let status = next_message(&mut client).await.data.unwrap();
+    assert_eq!(status["uuid"], json!(transaction.id));
+    assert_eq!(status["status"], json!(TransactStatus::Pending));
+    assert_eq!(status["transactionHash"], json!("0x123"));
+
+    // unsubscribed transactions are not pushed anymore
+    send(&mut client, json!({"type": "unsubscribe", "ids": [transaction.id]})).await;
+    assert_eq!(next_message(&mut client).await.message_type, WsMessageType::Unsubscribe);
+    let options = UpdateTransactionOptions::builder()
+        .status(TransactStatus::Succeeded)
+        .build();
+    context
+        .transaction_handler
+        .update_by_id(&transaction.id, &options)
+        .await
+        .unwrap();
+    send(&mut client, json!({"type": "subscribe_info", "data": {"chainId": 5}})).await;
+    let info = next_message(&mut client).await;
+    assert_eq!(info.message_type, WsMessageType::Info);
+    assert_eq!(info.data.unwrap()["chainId"], json!(5));
+}
+
+#[actix_rt::test]
+async fn test_transact() {
+    let mut producer = MockProducers::new();
+    producer.expect_send().returning(|_| {
+        let mut transaction = default_transaction();
+        transaction.status = TransactStatus::Queued;
+        Ok(Document::new(
+            "tx-1".to_string(),
+            1234567890u64,
+            1234567890u64,
+            transaction,
+        ))
+    });
+    let (context, address) = start_server(producer).await;
+    let mut client = connect(address).await;
+
+    send(
+        &mut client,
+        json!({"type": "transact", "requestId": "r1", "data": transact_request_data()}),
+    )
+    .await;
+    let response = next_message(&mut client).await;
+    assert_eq!(response.message_type, WsMessageType::Transact);
+    assert_eq!(response.code, ResponseCode::Successful as i32);
+    assert_eq!(response.data.unwrap()["uuid"], json!("tx-1"));
+
+    // the submitted job is followed without a subscribe
+    let mut transaction = default_transaction();
+    transaction.status = TransactStatus::Succeeded;
+    let document = Document::new("tx-1".to_string(), 1234567890u64, 1234567891u64, transaction);
+    context.status_bus.publish(TransactionStatusEvent::from(&document));
+    let status = next_message(&mut client).await;
+    assert_eq!(status.message_type, WsMessageType::Status);
+    assert_eq!(status.data.unwrap()["status"], json!(TransactStatus::Succeeded));
+
+    // same checks as the http api
+    context
+        .transaction_handler
+        .create_by_request(transact_request_data())
+        .await
+        .unwrap();
+    send(
+        &mut client,
+        json!({"type": "transact", "data": transact_request_data()}),
+    )
+    .await;
+    let error = next_message(&mut client).await;
+    assert_eq!(error.message_type, WsMessageType::Error);
+    assert_eq!(error.code, ResponseCode::RepeatedTransaction as i32);
+
+    client.send(Message::Text("not json".to_string())).await.unwrap();
+    let error = next_message(&mut client).await;
+    assert_eq!(error.code, ResponseCode::ValidateError as i32);
+}
+
+#[actix_rt::test]
+async fn test_rate_limit_messages() {
+    let limiter = RateLimiter::new(
+        RateLimitConfig::builder()
+            .enabled(true)
+            .client(
+                TokenBucketConfig::builder()
+                    .capacity(2u32)
+                    .refill_per_second(0.01)
+                    .build(),
+            )
+            .build(),
+    );
+    let (_, address) = start_server_with_limiter(MockProducers::new(), Some(limiter)).await;
+    let mut client = connect(address).await;
+
+    // every message takes a token, not only the handshake
+    for request_id in ["r1", "r2"] {
+        send(
+            &mut client,
+            json!({"type": "unsubscribe", "requestId": request_id, "ids": ["tx-1"]}),
+        )
+        .await;
+        assert_eq!(next_message(&mut client).await.message_type, WsMessageType::Unsubscribe);
+    }
+    send(
+        &mut client,
+        json!({"type": "transact", "requestId": "r3", "data": transact_request_data()}),
+    )
+    .await;
+    let error = next_message(&mut client).await;
+    assert_eq!(error.message_type, WsMessageType::Error);
+    assert_eq!(error.request_id, Some("r3".to_string()));
+    assert!(error.message.unwrap().starts_with("too many requests"));
+}
+
+async fn start_server(producer: MockProducers) -> (Arc<Context>, std::net::SocketAddr) {
+    start_server_with_limiter(producer, None).await
+}
+
+async fn start_server_with_limiter(
+    producer: MockProducers,
+    limiter: Option<RateLimiter>,
+) -> (Arc<Context>, std::net::SocketAddr) {
+    let server_config = load_server_config(Some(SERVER_CONFIG_TESTNET)).unwrap();
+    let database = Arc::new(create_default_database_in_memory().await);
+    let context = Arc::new(Context::new(Arc::new(server_config), database).await.unwrap());
+    let senders = Arc::new(SenderSet::new(HashSet::from([SenderInfo {
+        chain_id: 5,
+        address: "0x000000".to_string(),
+        signer: AccountSigner::Local(LocalWallet::new(&mut rand::thread_rng())),
+        available: true,
+        supported_erc20_tokens: vec!["MTT".to_string()],
+        producer: Arc::new(Box::new(producer) as Box<dyn ProducerHandler<Error = RelayerServerError>>),
+        drained: Arc::new(Notify::new()),
+        lease: LeaseState::always_held(),
+    }])));
+
+    let app_context = context.clone();
+    let limiter = limiter.map(Arc::new);
+    let server = HttpServer::new(move || {
+        let app = App::new()
+            .app_data(Data::new(app_context.clone()))
+            .app_data(Data::new(senders.clone()));
+        match &limiter {
+            Some(limiter) => app.app_data(Data::new(limiter.clone())),
+            None => app,
+        }
+        .service(scope("/api/v2").service(ws))
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let address = server.addrs()[0];
+    actix_rt::spawn(server.run());
+    (context, address)
+}
+
+async fn connect(address: std::net::SocketAddr) -> Client {
+    let (client, _) = connect_async(format!("ws://{}/api/v2/ws", address)).await.unwrap();
+    client
+}
+
+async fn send(client: &mut Client, message: serde_json::Value) {
+    client.send(Message::Text(message.to_string())).await.unwrap();
+}
+
+// skips the heartbeat pings of the server
+async fn next_message(client: &mut Client) -> WsMessage {
+    loop {
+        match client.next().await.unwrap().unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Close(frame) => panic!("connection closed {:?}", frame),
+            _ => continue,
+        }
+    }
+}
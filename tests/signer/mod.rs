@@ -0,0 +1,177 @@
+use crate::common::{MockProvider, MockTxManager};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{Address, Bytes, TransactionRequest, TxHash, U256};
+use ethers_core::utils::rlp::Rlp;
+use ethers_signers::{LocalWallet, Signer};
+use mockito::{Matcher, Mock, Server, ServerGuard};
+use mystiko_ethers::{Provider, ProviderWrapper};
+use mystiko_relayer::configs::account::{AccountConfig, RemoteSignerConfig};
+use mystiko_relayer::signer::{AccountSigner, RemoteSigner, RemoteSignerError, RemoteSignerTxManager};
+use mystiko_server_utils::tx_manager::{TransactionData, TransactionMiddleware};
+use serde_json::{json, Value};
+use validator::Validate;
+
+const CHAIN_ID: u64 = 5;
+
+#[actix_rt::test]
+async fn test_remote_signer_sign_transaction() {
+    let mut server = Server::new_async().await;
+    let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(CHAIN_ID);
+    let _accounts = mock_accounts(&mut server, wallet.address()).await;
+    let _sign = mock_sign_transaction(&mut server, wallet.clone()).await;
+
+    let signer = RemoteSigner::new(&remote_config(&server, wallet.address()))
+        .unwrap()
+        .with_chain_id(CHAIN_ID);
+    signer.verify().await.unwrap();
+
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to(Address::random())
+        .value(U256::from(1000))
+        .gas(21000u64)
+        .gas_price(U256::from(10))
+        .nonce(3u64)
+        .into();
+    let raw = signer.sign_transaction_raw(&tx).await.unwrap();
+    let (signed_tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+    assert_eq!(signature.recover(signed_tx.sighash()).unwrap(), wallet.address());
+    assert_eq!(signed_tx.nonce(), Some(&U256::from(3)));
+
+    let signature = signer.sign_transaction(&tx).await.unwrap();
+    let mut expected_tx = tx.clone();
+    expected_tx.set_chain_id(CHAIN_ID);
+    assert_eq!(signature.recover(expected_tx.sighash()).unwrap(), wallet.address());
+}
+
+#[actix_rt::test]
+async fn test_remote_signer_rejects_other_key() {
+    let mut server = Server::new_async().await;
+    let address = Address::random();
+    let _accounts = mock_accounts(&mut server, Address::random()).await;
+    let other_wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(CHAIN_ID);
+    let _sign = mock_sign_transaction(&mut server, other_wallet).await;
+
+    let signer = RemoteSigner::new(&remote_config(&server, address))
+        .unwrap()
+        .with_chain_id(CHAIN_ID);
+    assert!(matches!(
+        signer.verify().await,
+        Err(RemoteSignerError::AccountNotFound(_))
+    ));
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to(Address::random())
+        .gas(21000u64)
+        .gas_price(U256::from(10))
+        .nonce(0u64)
+        .into();
+    assert!(matches!(
+        signer.sign_transaction_raw(&tx).await,
+        Err(RemoteSignerError::InvalidResponse(_))
+    ));
+}
+
+#[actix_rt::test]
+async fn test_remote_signer_rpc_error() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/")
+        .with_body(json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "locked"}}).to_string())
+        .create_async()
+        .await;
+    let signer = RemoteSigner::new(&remote_config(&server, Address::random())).unwrap();
+    match signer.sign_message("hello").await {
+        Err(RemoteSignerError::RpcError { code, message }) => {
+            assert_eq!(code, -32000);
+            assert_eq!(message, "locked");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn test_remote_signer_tx_manager_send() {
+    let mut server = Server::new_async().await;
+    let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(CHAIN_ID);
+    let _sign = mock_sign_transaction(&mut server, wallet.clone()).await;
+    let signer = RemoteSigner::new(&remote_config(&server, wallet.address()))
+        .unwrap()
+        .with_chain_id(CHAIN_ID);
+
+    let tx_hash = TxHash::random();
+    let mut provider = MockProvider::new();
+    provider.expect_request().returning(move |method, _| match method {
+        "eth_getTransactionCount" => Ok(json!("0x7")),
+        "eth_sendRawTransaction" => Ok(json!(tx_hash)),
+        _ => panic!("unexpected method {}", method),
+    });
+    let provider = Provider::new(ProviderWrapper::new(Box::new(provider)));
+    let mut inner = MockTxManager::new();
+    inner.expect_tx_eip1559().returning(|| false);
+    inner.expect_gas_price().returning(|_| Ok(U256::from(20)));
+
+    let tx_manager = RemoteSignerTxManager::new(inner, signer);
+    let data = TransactionData::builder()
+        .to(Address::random())
+        .data(vec![1u8, 2, 3].into())
+        .value(U256::zero())
+        .gas(U256::from(100000))
+        .max_price(U256::from(10))
+        .build();
+    assert_eq!(tx_manager.send(&data, &provider).await.unwrap(), tx_hash);
+}
+
+#[test]
+fn test_remote_signer_account_config() {
+    let address = Address::random();
+    let account = AccountConfig::builder()
+        .chain_id(CHAIN_ID)
+        .remote_signer(
+            RemoteSignerConfig::builder()
+                .url("http://127.0.0.1:9000")
+                .address(format!("{:?}", address))
+                .build(),
+        )
+        .build();
+    assert!(account.validate().is_ok());
+    assert_eq!(account.address().unwrap(), format!("{:?}", address));
+    assert!(matches!(account.signer().unwrap(), AccountSigner::Remote(_)));
+    assert!(account.wallet().is_err());
+
+    let mut account = account;
+    account.private_key = "eac14b2f62136a72601430dfb9abce2f4b98dabef428f5f92869ef85997e8f84".to_string();
+    assert!(account.validate().is_err());
+}
+
+fn remote_config(server: &ServerGuard, address: Address) -> RemoteSignerConfig {
+    RemoteSignerConfig::builder()
+        .url(server.url())
+        .address(format!("{:?}", address))
+        .build()
+}
+
+async fn mock_accounts(server: &mut ServerGuard, address: Address) -> Mock {
+    server
+        .mock("POST", "/")
+        .match_body(Matcher::PartialJson(json!({"method": "eth_accounts"})))
+        .with_body(json!({"jsonrpc": "2.0", "id": 1, "result": [address]}).to_string())
+        .create_async()
+        .await
+}
+
+// signs like a web3signer instance holding the wallet key
+async fn mock_sign_transaction(server: &mut ServerGuard, wallet: LocalWallet) -> Mock {
+    server
+        .mock("POST", "/")
+        .match_body(Matcher::PartialJson(json!({"method": "eth_signTransaction"})))
+        .with_body_from_request(move |request| {
+            let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+            let tx: TypedTransaction = serde_json::from_value(body["params"][0].clone()).unwrap();
+            let signature = wallet.sign_transaction_sync(&tx).unwrap();
+            let raw = Bytes::from(tx.rlp_signed(&signature).to_vec());
+            json!({"jsonrpc": "2.0", "id": body["id"], "result": raw})
+                .to_string()
+                .into_bytes()
+        })
+        .create_async()
+        .await
+}